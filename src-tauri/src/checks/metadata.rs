@@ -0,0 +1,184 @@
+use crate::checks::most_common;
+use crate::checks::ranking::MapFile;
+use crate::models::beatmapset::BeatmapMetadata;
+use serde::Serialize;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct FieldValue {
+    pub value: String,
+    pub files: Vec<String>,
+}
+
+// A [Metadata] field that isn't the same in every difficulty, with each value found.
+#[derive(Serialize, Debug, Clone)]
+pub struct FieldInconsistency {
+    pub field: &'static str,
+    pub values: Vec<FieldValue>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct FileRename {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct MetadataReport {
+    // The value most difficulties use for each field.
+    pub canonical: BeatmapMetadata,
+    pub inconsistencies: Vec<FieldInconsistency>,
+    // Files whose names don't match the canonical metadata.
+    pub renames: Vec<FileRename>,
+}
+
+pub fn metadata_report(maps: &[MapFile], osb_files: &[String]) -> MetadataReport {
+    // BeatmapSetID is left to the ranking checks, since it isn't something this fixer sets.
+    let fields: Vec<Vec<(&'static str, String)>> = maps
+        .iter()
+        .map(|map| {
+            map.beatmap
+                .metadata
+                .shared_fields()
+                .into_iter()
+                .filter(|(name, _)| *name != "BeatmapSetID")
+                .collect()
+        })
+        .collect();
+
+    let mut inconsistencies = Vec::new();
+    if let Some(first) = fields.first() {
+        for (field, (name, _)) in first.iter().enumerate() {
+            let mut values: Vec<FieldValue> = Vec::new();
+            for (map, map_fields) in maps.iter().zip(&fields) {
+                let value = &map_fields[field].1;
+                match values.iter_mut().find(|v| &v.value == value) {
+                    Some(existing) => existing.files.push(map.name.clone()),
+                    None => values.push(FieldValue {
+                        value: value.clone(),
+                        files: vec![map.name.clone()],
+                    }),
+                }
+            }
+            if values.len() > 1 {
+                values.sort_by_key(|v| std::cmp::Reverse(v.files.len()));
+                inconsistencies.push(FieldInconsistency {
+                    field: name,
+                    values,
+                });
+            }
+        }
+    }
+
+    let all: Vec<BeatmapMetadata> = maps
+        .iter()
+        .map(|map| BeatmapMetadata::from(&map.beatmap.metadata))
+        .collect();
+    let pick = |get: fn(&BeatmapMetadata) -> &str| {
+        most_common(all.iter().map(get))
+            .unwrap_or_default()
+            .to_string()
+    };
+    let canonical = BeatmapMetadata {
+        title: pick(|m| &m.title),
+        title_unicode: pick(|m| &m.title_unicode),
+        artist: pick(|m| &m.artist),
+        artist_unicode: pick(|m| &m.artist_unicode),
+        creator: pick(|m| &m.creator),
+        source: pick(|m| &m.source),
+        tags: pick(|m| &m.tags),
+    };
+
+    MetadataReport {
+        renames: renames(maps, osb_files, &canonical),
+        canonical,
+        inconsistencies,
+    }
+}
+
+// Difficulties are named after their metadata and version. A set's .osb is only
+// renamed when there's exactly one, since otherwise it's unclear which one osu! uses.
+pub fn renames(
+    maps: &[MapFile],
+    osb_files: &[String],
+    metadata: &BeatmapMetadata,
+) -> Vec<FileRename> {
+    let mut renames: Vec<FileRename> = maps
+        .iter()
+        .map(|map| FileRename {
+            from: map.name.clone(),
+            to: metadata.osu_file_name(&map.beatmap.metadata.version),
+        })
+        .collect();
+    if let [osb] = osb_files {
+        renames.push(FileRename {
+            from: osb.clone(),
+            to: metadata.osb_file_name(),
+        });
+    }
+    renames.retain(|rename| rename.from != rename.to);
+    renames
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::beatmap::Beatmap;
+
+    fn map(name: &str, title: &str, version: &str, set_id: i32) -> MapFile {
+        let content = format!(
+            "osu file format v14\n\n[Metadata]\nTitle:{}\nArtist:Artist\nCreator:Mapper\n\
+             Version:{}\nBeatmapSetID:{}\n",
+            title, version, set_id
+        );
+        MapFile::new(name.to_string(), Beatmap::parse(&content).unwrap())
+    }
+
+    #[test]
+    fn reports_fields_the_fixer_can_set() {
+        let maps = [
+            map("Artist - Title (Mapper) [Easy].osu", "Title", "Easy", 1),
+            map("Artist - Title (Mapper) [Hard].osu", "Title", "Hard", 1),
+            map("old name.osu", "Title (TV Size)", "Insane", 2),
+        ];
+        let report = metadata_report(&maps, &[]);
+        assert_eq!(report.canonical.title, "Title");
+
+        let fields: Vec<&str> = report.inconsistencies.iter().map(|i| i.field).collect();
+        assert_eq!(fields, ["Title"]);
+        let values = &report.inconsistencies[0].values;
+        assert_eq!(values[0].value, "Title");
+        assert_eq!(values[0].files.len(), 2);
+        assert_eq!(values[1].files, ["old name.osu"]);
+
+        let renames: Vec<(&str, &str)> = report
+            .renames
+            .iter()
+            .map(|r| (r.from.as_str(), r.to.as_str()))
+            .collect();
+        assert_eq!(
+            renames,
+            [("old name.osu", "Artist - Title (Mapper) [Insane].osu")]
+        );
+    }
+
+    #[test]
+    fn renames_a_single_osb_only() {
+        let maps = [map(
+            "Artist - Title (Mapper) [Easy].osu",
+            "Title",
+            "Easy",
+            1,
+        )];
+        let metadata = BeatmapMetadata::from(&maps[0].beatmap.metadata);
+
+        let one = renames(&maps, &["storyboard.osb".to_string()], &metadata);
+        assert_eq!(one.len(), 1);
+        assert_eq!(one[0].to, "Artist - Title (Mapper).osb");
+
+        let two = ["a.osb".to_string(), "b.osb".to_string()];
+        assert!(renames(&maps, &two, &metadata).is_empty());
+
+        let named = ["Artist - Title (Mapper).osb".to_string()];
+        assert!(renames(&maps, &named, &metadata).is_empty());
+    }
+}
@@ -0,0 +1,90 @@
+pub mod metadata;
+pub mod ranking;
+pub mod spread;
+pub mod storyboard;
+
+use serde::Serialize;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Problem,
+    Warning,
+    Minor,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub check: &'static str,
+    pub message: String,
+    pub file: String,
+    pub time: Option<i32>,
+    // The time as osu!'s editor writes it, e.g. `01:02:345 (1,2)`, so it can be pasted
+    // into the editor to jump there.
+    pub timestamp: Option<String>,
+}
+
+pub fn editor_time(time: i32) -> String {
+    let sign = if time < 0 { "-" } else { "" };
+    let time = time.unsigned_abs();
+    format!(
+        "{}{:02}:{:02}:{:03}",
+        sign,
+        time / 60000,
+        time / 1000 % 60,
+        time % 1000
+    )
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, check: &'static str, file: &str, message: String) -> Self {
+        Diagnostic {
+            severity,
+            check,
+            message,
+            file: file.to_string(),
+            time: None,
+            timestamp: None,
+        }
+    }
+
+    pub fn at(mut self, time: i32) -> Self {
+        self.time = Some(time);
+        self.timestamp = Some(editor_time(time));
+        self
+    }
+
+    // Like `at`, also naming the objects involved the way the editor does.
+    pub fn at_objects(mut self, time: i32, objects: &str) -> Self {
+        self.time = Some(time);
+        self.timestamp = Some(format!("{} ({})", editor_time(time), objects));
+        self
+    }
+}
+
+// Most severe first, then by file and time so related diagnostics sit together.
+pub fn sort_diagnostics(diagnostics: &mut [Diagnostic]) {
+    diagnostics.sort_by(|a, b| {
+        a.severity
+            .cmp(&b.severity)
+            .then_with(|| a.file.cmp(&b.file))
+            .then_with(|| a.time.cmp(&b.time))
+    });
+}
+
+// The value most difficulties agree on; ties go to whichever comes first.
+pub fn most_common<'a>(values: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+    for value in values {
+        match counts.iter_mut().find(|(v, _)| *v == value) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((value, 1)),
+        }
+    }
+    counts
+        .iter()
+        .rev()
+        .max_by_key(|(_, count)| *count)
+        .map(|(value, _)| *value)
+}
@@ -0,0 +1,830 @@
+use crate::checks::{editor_time, most_common, sort_diagnostics, Diagnostic, Severity};
+use crate::difficulty::difficulty_range;
+use crate::models::beatmap::{Beatmap, Event};
+use crate::models::hitobject::{HitObject, HitObjectKind};
+use crate::utils::curve::{CurveType, Vec2};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+const SNAP_DIVISORS: [u32; 8] = [1, 2, 3, 4, 6, 8, 12, 16];
+// osu! rounds object times to whole milliseconds, so 1ms off is still snapped.
+const SNAP_TOLERANCE: f64 = 2.0;
+
+const MIN_DRAIN_TIME: f64 = 30.0;
+
+// The area visible at 4:3, in osu!pixels relative to the playfield.
+const SCREEN_LEFT: f64 = -64.0;
+const SCREEN_RIGHT: f64 = 576.0;
+const SCREEN_TOP: f64 = -56.0;
+const SCREEN_BOTTOM: f64 = 424.0;
+
+// Auto spins at 477 rpm.
+const AUTO_SPINS_PER_SECOND: f64 = 477.0 / 60.0;
+
+pub trait MapCheck: Send + Sync {
+    fn id(&self) -> &'static str;
+    fn description(&self) -> &'static str;
+
+    fn check_map(&self, _map: &MapFile, _diagnostics: &mut Vec<Diagnostic>) {}
+
+    // Checks comparing difficulties override this instead of `check_map`.
+    fn check_set(&self, maps: &[MapFile], diagnostics: &mut Vec<Diagnostic>) {
+        for map in maps {
+            self.check_map(map, diagnostics);
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct CheckInfo {
+    pub id: String,
+    pub description: String,
+}
+
+static REGISTRY: Lazy<Vec<Box<dyn MapCheck>>> = Lazy::new(|| {
+    vec![
+        Box::new(Unsnapped),
+        Box::new(BreakOverlap),
+        Box::new(ShortSpinner),
+        Box::new(SliderAnchors),
+        Box::new(InconsistentMetadata),
+        Box::new(KiaiToggles),
+        Box::new(PreviewTime),
+        Box::new(DrainTime),
+        Box::new(Offscreen),
+    ]
+});
+
+pub fn available_checks() -> Vec<CheckInfo> {
+    REGISTRY
+        .iter()
+        .map(|c| CheckInfo {
+            id: c.id().to_string(),
+            description: c.description().to_string(),
+        })
+        .collect()
+}
+
+pub struct MapFile {
+    pub name: String,
+    pub beatmap: Beatmap,
+    combo_numbers: Vec<u32>,
+}
+
+impl MapFile {
+    pub fn new(name: String, beatmap: Beatmap) -> Self {
+        let mut combo_numbers = Vec::with_capacity(beatmap.hit_objects.len());
+        let mut combo = 0;
+        for (i, object) in beatmap.hit_objects.iter().enumerate() {
+            let after_spinner = i > 0 && beatmap.hit_objects[i - 1].is_spinner();
+            combo = if i == 0 || object.new_combo || after_spinner {
+                1
+            } else {
+                combo + 1
+            };
+            combo_numbers.push(combo);
+        }
+        MapFile {
+            name,
+            beatmap,
+            combo_numbers,
+        }
+    }
+
+    // How the editor refers to an object: its combo number, or time|column in mania.
+    fn reference(&self, index: usize) -> String {
+        let object = &self.beatmap.hit_objects[index];
+        if self.beatmap.general.mode == 3 {
+            let key_count = self.beatmap.difficulty.circle_size.round().max(1.0) as u32;
+            format!("{}|{}", object.time as i32, object.column(key_count))
+        } else {
+            self.combo_numbers[index].to_string()
+        }
+    }
+
+    fn diagnostic(
+        &self,
+        severity: Severity,
+        check: &'static str,
+        index: usize,
+        time: f64,
+        message: String,
+    ) -> Diagnostic {
+        Diagnostic::new(severity, check, &self.name, message)
+            .at_objects(time as i32, &self.reference(index))
+    }
+
+    fn version(&self) -> &str {
+        &self.beatmap.metadata.version
+    }
+}
+
+// Every .osu file directly inside the set folder, sorted by name.
+pub fn load_set(folder: &Path) -> Result<Vec<MapFile>, String> {
+    let entries = fs::read_dir(folder).map_err(|e| format!("Failed to read directory: {}", e))?;
+    let mut names: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().and_then(|ext| ext.to_str()) == Some("osu"))
+        .filter_map(|e| e.file_name().to_str().map(|s| s.to_string()))
+        .collect();
+    names.sort();
+
+    names
+        .into_iter()
+        .map(|name| {
+            let beatmap = Beatmap::from_file(&folder.join(&name))?;
+            Ok(MapFile::new(name, beatmap))
+        })
+        .collect()
+}
+
+pub fn run_checks(maps: &[MapFile], disabled: &[String]) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for check in REGISTRY.iter() {
+        if !disabled.iter().any(|id| id == check.id()) {
+            check.check_set(maps, &mut diagnostics);
+        }
+    }
+    sort_diagnostics(&mut diagnostics);
+    diagnostics
+}
+
+// Times an object has to land on the beat: its start, and for long objects each
+// repeat and the end.
+fn snapped_times(beatmap: &Beatmap, object: &HitObject) -> Vec<(&'static str, f64)> {
+    let mut times = vec![("Circle", object.time)];
+    match &object.kind {
+        HitObjectKind::Circle if beatmap.general.mode == 3 => times[0].0 = "Note",
+        HitObjectKind::Circle => {}
+        HitObjectKind::Slider(slider) => {
+            times[0].0 = "Slider head";
+            let span = beatmap.slider_duration(object) / slider.slides.max(1) as f64;
+            for repeat in 1..slider.slides {
+                times.push(("Slider repeat", object.time + span * repeat as f64));
+            }
+            times.push(("Slider tail", beatmap.end_time(object)));
+        }
+        HitObjectKind::Spinner { end_time } => {
+            times[0].0 = "Spinner start";
+            times.push(("Spinner end", *end_time));
+        }
+        HitObjectKind::Hold { end_time } => {
+            times[0].0 = "Hold note head";
+            times.push(("Hold note tail", *end_time));
+        }
+    }
+    times
+}
+
+pub struct Unsnapped;
+
+impl MapCheck for Unsnapped {
+    fn id(&self) -> &'static str {
+        "unsnapped"
+    }
+
+    fn description(&self) -> &'static str {
+        "Objects off the 1/1 to 1/16 and 1/3, 1/6, 1/12 snaps of their red line"
+    }
+
+    fn check_map(&self, map: &MapFile, diagnostics: &mut Vec<Diagnostic>) {
+        let timing = &map.beatmap.timing_points;
+        if timing.red_lines().next().is_none() {
+            return;
+        }
+
+        for (index, object) in map.beatmap.hit_objects.iter().enumerate() {
+            for (part, time) in snapped_times(&map.beatmap, object) {
+                let (offset, divisor) = SNAP_DIVISORS
+                    .iter()
+                    .map(|&divisor| (time - timing.nearest_snap(time, divisor), divisor))
+                    .min_by(|a, b| a.0.abs().total_cmp(&b.0.abs()))
+                    .unwrap_or((0.0, 1));
+                if offset.abs() >= SNAP_TOLERANCE {
+                    diagnostics.push(map.diagnostic(
+                        Severity::Problem,
+                        self.id(),
+                        index,
+                        time,
+                        format!(
+                            "{} is unsnapped by {:+.0} ms from the nearest 1/{}",
+                            part, offset, divisor
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+pub struct BreakOverlap;
+
+impl MapCheck for BreakOverlap {
+    fn id(&self) -> &'static str {
+        "break_overlap"
+    }
+
+    fn description(&self) -> &'static str {
+        "Objects inside a break"
+    }
+
+    fn check_map(&self, map: &MapFile, diagnostics: &mut Vec<Diagnostic>) {
+        let beatmap = &map.beatmap;
+        for event in &beatmap.events {
+            let Event::Break {
+                start_time,
+                end_time,
+            } = event
+            else {
+                continue;
+            };
+            let (start, end) = (*start_time as f64, *end_time as f64);
+            for (index, object) in beatmap.hit_objects.iter().enumerate() {
+                if object.time < end && beatmap.end_time(object) > start {
+                    diagnostics.push(map.diagnostic(
+                        Severity::Problem,
+                        self.id(),
+                        index,
+                        object.time,
+                        format!(
+                            "Object overlaps the break from {} to {}",
+                            editor_time(*start_time),
+                            editor_time(*end_time)
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+pub struct ShortSpinner;
+
+impl MapCheck for ShortSpinner {
+    fn id(&self) -> &'static str {
+        "short_spinner"
+    }
+
+    fn description(&self) -> &'static str {
+        "Spinners too short for auto to earn any bonus"
+    }
+
+    fn check_map(&self, map: &MapFile, diagnostics: &mut Vec<Diagnostic>) {
+        if map.beatmap.general.mode != 0 {
+            return;
+        }
+        let spins_needed_per_second =
+            difficulty_range(map.beatmap.difficulty.overall_difficulty, 3.0, 5.0, 7.5);
+
+        for (index, object) in map.beatmap.hit_objects.iter().enumerate() {
+            let HitObjectKind::Spinner { end_time } = object.kind else {
+                continue;
+            };
+            let seconds = (end_time - object.time) / 1000.0;
+            // Each full spin past the ones needed to clear the spinner is worth 1000 bonus.
+            let spare_spins = seconds * (AUTO_SPINS_PER_SECOND - spins_needed_per_second);
+            if spare_spins < 1.0 {
+                diagnostics.push(map.diagnostic(
+                    Severity::Warning,
+                    self.id(),
+                    index,
+                    object.time,
+                    format!(
+                        "Spinner lasts {:.0} ms, too short for auto to earn 1000 bonus at OD {}",
+                        end_time - object.time,
+                        map.beatmap.difficulty.overall_difficulty
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+pub struct SliderAnchors;
+
+impl MapCheck for SliderAnchors {
+    fn id(&self) -> &'static str {
+        "slider_anchors"
+    }
+
+    fn description(&self) -> &'static str {
+        "Zero-length sliders and sliders with stacked anchors"
+    }
+
+    fn check_map(&self, map: &MapFile, diagnostics: &mut Vec<Diagnostic>) {
+        for (index, object) in map.beatmap.hit_objects.iter().enumerate() {
+            let HitObjectKind::Slider(slider) = &object.kind else {
+                continue;
+            };
+            if slider.pixel_length(object.position) < 1.0
+                || slider.calculated_length(object.position) < 1.0
+            {
+                diagnostics.push(map.diagnostic(
+                    Severity::Problem,
+                    self.id(),
+                    index,
+                    object.time,
+                    "Slider has zero length".to_string(),
+                ));
+                continue;
+            }
+
+            // Two anchors in the same place make a red anchor in a bezier slider, so
+            // only a third is redundant there. Other curve types have no use for them.
+            let allowed = if slider.curve_type == CurveType::Bezier {
+                2
+            } else {
+                1
+            };
+            let points = slider.path_points(object.position);
+            let longest_stack = longest_run(&points);
+            if longest_stack > allowed {
+                diagnostics.push(map.diagnostic(
+                    Severity::Warning,
+                    self.id(),
+                    index,
+                    object.time,
+                    format!("Slider has {} anchors on the same spot", longest_stack),
+                ));
+            }
+        }
+    }
+}
+
+fn longest_run(points: &[Vec2]) -> usize {
+    let mut longest = 1;
+    let mut run = 1;
+    for pair in points.windows(2) {
+        run = if pair[0] == pair[1] { run + 1 } else { 1 };
+        longest = longest.max(run);
+    }
+    longest
+}
+
+// Tags are compared as a set of words, since their order doesn't matter.
+fn normalize_tags(tags: &str) -> String {
+    let mut words: Vec<&str> = tags.split_whitespace().collect();
+    words.sort_unstable();
+    words.dedup();
+    words.join(" ")
+}
+
+pub struct InconsistentMetadata;
+
+impl MapCheck for InconsistentMetadata {
+    fn id(&self) -> &'static str {
+        "inconsistent_metadata"
+    }
+
+    fn description(&self) -> &'static str {
+        "Metadata that differs between difficulties"
+    }
+
+    fn check_set(&self, maps: &[MapFile], diagnostics: &mut Vec<Diagnostic>) {
+        let fields: Vec<Vec<(&'static str, String)>> = maps
+            .iter()
+            .map(|map| {
+                map.beatmap
+                    .metadata
+                    .shared_fields()
+                    .into_iter()
+                    .map(|(name, value)| match name {
+                        "Tags" => (name, normalize_tags(&value)),
+                        _ => (name, value),
+                    })
+                    .collect()
+            })
+            .collect();
+        let Some(first) = fields.first() else {
+            return;
+        };
+
+        for (field, (name, _)) in first.iter().enumerate() {
+            let Some(common) = most_common(fields.iter().map(|f| f[field].1.as_str())) else {
+                continue;
+            };
+            for (map, map_fields) in maps.iter().zip(&fields) {
+                if map_fields[field].1 != common {
+                    diagnostics.push(Diagnostic::new(
+                        Severity::Problem,
+                        self.id(),
+                        &map.name,
+                        format!(
+                            "[{}] has {} \"{}\" where other difficulties have \"{}\"",
+                            map.version(),
+                            name,
+                            map_fields[field].1,
+                            common
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+pub struct KiaiToggles;
+
+impl MapCheck for KiaiToggles {
+    fn id(&self) -> &'static str {
+        "kiai_toggles"
+    }
+
+    fn description(&self) -> &'static str {
+        "Kiai switched on and off within a beat"
+    }
+
+    fn check_map(&self, map: &MapFile, diagnostics: &mut Vec<Diagnostic>) {
+        let timing = &map.beatmap.timing_points;
+        let mut kiai = false;
+        let mut last_toggle: Option<f64> = None;
+        for point in timing.iter() {
+            if point.kiai() == kiai {
+                continue;
+            }
+            kiai = point.kiai();
+            if let Some(last) = last_toggle {
+                if point.time - last < timing.beat_length_at(last) {
+                    diagnostics.push(
+                        Diagnostic::new(
+                            Severity::Warning,
+                            self.id(),
+                            &map.name,
+                            format!(
+                                "Kiai is toggled {} {:.0} ms after being toggled {}, causing a flash",
+                                if kiai { "on" } else { "off" },
+                                point.time - last,
+                                if kiai { "off" } else { "on" }
+                            ),
+                        )
+                        .at(point.time as i32),
+                    );
+                }
+            }
+            last_toggle = Some(point.time);
+        }
+    }
+}
+
+pub struct PreviewTime;
+
+impl MapCheck for PreviewTime {
+    fn id(&self) -> &'static str {
+        "preview_time"
+    }
+
+    fn description(&self) -> &'static str {
+        "Missing or inconsistent preview points"
+    }
+
+    fn check_set(&self, maps: &[MapFile], diagnostics: &mut Vec<Diagnostic>) {
+        for map in maps {
+            if map.beatmap.general.preview_time < 0 {
+                diagnostics.push(Diagnostic::new(
+                    Severity::Warning,
+                    self.id(),
+                    &map.name,
+                    format!("[{}] has no preview point", map.version()),
+                ));
+            }
+        }
+
+        let times: Vec<String> = maps
+            .iter()
+            .map(|map| map.beatmap.general.preview_time.to_string())
+            .collect();
+        let Some(common) = most_common(times.iter().map(|t| t.as_str())) else {
+            return;
+        };
+        for (map, time) in maps.iter().zip(&times) {
+            if time != common {
+                let preview_time = map.beatmap.general.preview_time;
+                let mut diagnostic = Diagnostic::new(
+                    Severity::Problem,
+                    self.id(),
+                    &map.name,
+                    format!(
+                        "[{}] previews from a different point than the other difficulties",
+                        map.version()
+                    ),
+                );
+                if preview_time >= 0 {
+                    diagnostic = diagnostic.at(preview_time);
+                }
+                diagnostics.push(diagnostic);
+            }
+        }
+    }
+}
+
+pub struct DrainTime;
+
+impl MapCheck for DrainTime {
+    fn id(&self) -> &'static str {
+        "drain_time"
+    }
+
+    fn description(&self) -> &'static str {
+        "Difficulties with under 30 seconds of drain time"
+    }
+
+    fn check_map(&self, map: &MapFile, diagnostics: &mut Vec<Diagnostic>) {
+        let drain = map.beatmap.drain_time();
+        if drain < MIN_DRAIN_TIME {
+            diagnostics.push(Diagnostic::new(
+                Severity::Problem,
+                self.id(),
+                &map.name,
+                format!(
+                    "[{}] has {:.1} seconds of drain time, under the {} second minimum",
+                    map.version(),
+                    drain,
+                    MIN_DRAIN_TIME
+                ),
+            ));
+        }
+    }
+}
+
+pub struct Offscreen;
+
+impl MapCheck for Offscreen {
+    fn id(&self) -> &'static str {
+        "offscreen"
+    }
+
+    fn description(&self) -> &'static str {
+        "Circles and slider bodies that go past the edge of a 4:3 screen"
+    }
+
+    fn check_map(&self, map: &MapFile, diagnostics: &mut Vec<Diagnostic>) {
+        if map.beatmap.general.mode != 0 {
+            return;
+        }
+        let radius = 54.4 - 4.48 * map.beatmap.difficulty.circle_size;
+        let offscreen = |p: &Vec2| {
+            p.x - radius < SCREEN_LEFT
+                || p.x + radius > SCREEN_RIGHT
+                || p.y - radius < SCREEN_TOP
+                || p.y + radius > SCREEN_BOTTOM
+        };
+
+        for (index, object) in map.beatmap.hit_objects.iter().enumerate() {
+            let message = match &object.kind {
+                HitObjectKind::Circle if offscreen(&object.position) => "Circle",
+                HitObjectKind::Slider(slider)
+                    if offscreen(&object.position)
+                        || slider.path(object.position).iter().any(offscreen) =>
+                {
+                    "Slider"
+                }
+                _ => continue,
+            };
+            diagnostics.push(map.diagnostic(
+                Severity::Problem,
+                self.id(),
+                index,
+                object.time,
+                format!("{} is partially offscreen", message),
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED_LINE: &str = "1000,500,4,2,0,100,1,0";
+
+    // A 120 BPM map at OD 8 and CS 4 with the red line at 1000ms.
+    fn map(mode: u8, events: &str, timing: &str, objects: &str) -> MapFile {
+        let content = format!(
+            "osu file format v14\n\n[General]\nPreviewTime: 1000\nMode: {}\n\n\
+             [Metadata]\nTitle:Song\nArtist:Someone\nCreator:Mapper\nVersion:Hard\nTags:a b c\n\n\
+             [Difficulty]\nHPDrainRate:5\nCircleSize:4\nOverallDifficulty:8\nApproachRate:9\n\
+             SliderMultiplier:1.4\nSliderTickRate:1\n\n[Events]\n{}\n\n\
+             [TimingPoints]\n{}\n\n[HitObjects]\n{}\n",
+            mode, events, timing, objects
+        );
+        MapFile::new("map.osu".to_string(), Beatmap::parse(&content).unwrap())
+    }
+
+    fn circles(mode: u8, times: &[i32]) -> MapFile {
+        let objects: Vec<String> = times
+            .iter()
+            .map(|time| format!("256,192,{},1,0,0:0:0:0:", time))
+            .collect();
+        map(mode, "", RED_LINE, &objects.join("\n"))
+    }
+
+    fn run(check: &dyn MapCheck, maps: &[MapFile]) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        check.check_set(maps, &mut diagnostics);
+        diagnostics
+    }
+
+    #[test]
+    fn snapped_objects_pass() {
+        // 1/3 and 1/16 of a 500ms beat, rounded to whole milliseconds like the editor does.
+        let map = circles(0, &[1000, 1167, 1333, 1031, 1094, 1969, 2042]);
+        assert!(run(&Unsnapped, &[map]).is_empty());
+    }
+
+    #[test]
+    fn two_ms_drift_fails() {
+        let diagnostics = run(&Unsnapped, &[circles(0, &[1000, 1502, 1998])]);
+        let messages: Vec<&str> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "Circle is unsnapped by +2 ms from the nearest 1/1",
+                "Circle is unsnapped by -2 ms from the nearest 1/1"
+            ]
+        );
+    }
+
+    #[test]
+    fn slider_ends_are_checked() {
+        // One beat is 140px at SV 1.4, so 150px ends the slider 4ms past the nearest 1/16.
+        let slider = map(0, "", RED_LINE, "100,100,1000,2,0,L|250:100,1,150");
+        let diagnostics = run(&Unsnapped, &[slider]);
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].message.starts_with("Slider tail"));
+    }
+
+    #[test]
+    fn timestamps_name_the_combo() {
+        let diagnostics = run(&Unsnapped, &[circles(0, &[1000, 61502])]);
+        assert_eq!(diagnostics[0].time, Some(61502));
+        assert_eq!(diagnostics[0].timestamp.as_deref(), Some("01:01:502 (2)"));
+
+        let new_combo = map(
+            0,
+            "",
+            RED_LINE,
+            "256,192,1000,1,0,0:0:0:0:\n256,192,1500,5,0,0:0:0:0:\n256,192,2003,1,0,0:0:0:0:",
+        );
+        let diagnostics = run(&Unsnapped, &[new_combo]);
+        assert_eq!(diagnostics[0].timestamp.as_deref(), Some("00:02:003 (2)"));
+    }
+
+    #[test]
+    fn mania_timestamps_name_time_and_column() {
+        let diagnostics = run(&Unsnapped, &[circles(3, &[1000, 1502])]);
+        assert_eq!(
+            diagnostics[0].message,
+            "Note is unsnapped by +2 ms from the nearest 1/1"
+        );
+        assert_eq!(
+            diagnostics[0].timestamp.as_deref(),
+            Some("00:01:502 (1502|2)")
+        );
+    }
+
+    #[test]
+    fn run_checks_skips_disabled() {
+        let maps = [circles(0, &[1000, 1502])];
+        let all = run_checks(&maps, &[]);
+        assert!(all.iter().any(|d| d.check == "unsnapped"));
+        assert!(all.iter().any(|d| d.check == "drain_time"));
+
+        let disabled = run_checks(&maps, &["unsnapped".to_string()]);
+        assert!(!disabled.iter().any(|d| d.check == "unsnapped"));
+        assert!(disabled.iter().any(|d| d.check == "drain_time"));
+
+        let ids: Vec<String> = available_checks().into_iter().map(|c| c.id).collect();
+        assert!(run_checks(&maps, &ids).is_empty());
+    }
+
+    #[test]
+    fn objects_inside_breaks() {
+        let objects = "256,192,1000,1,0,0:0:0:0:\n256,192,6000,1,0,0:0:0:0:\n\
+                       256,192,4500,12,0,5500,0:0:0:0:\n256,192,9000,1,0,0:0:0:0:";
+        let diagnostics = run(&BreakOverlap, &[map(0, "2,5000,8000", RED_LINE, objects)]);
+        let times: Vec<Option<i32>> = diagnostics.iter().map(|d| d.time).collect();
+        assert_eq!(times, [Some(6000), Some(4500)]);
+        assert_eq!(
+            diagnostics[0].message,
+            "Object overlaps the break from 00:05:000 to 00:08:000"
+        );
+    }
+
+    #[test]
+    fn short_spinners() {
+        // At OD 8 auto spins 1.45 times a second more than it needs to.
+        let spinner = |length: i32| {
+            let object = format!("256,192,1000,12,0,{},0:0:0:0:", 1000 + length);
+            map(0, "", RED_LINE, &object)
+        };
+        assert_eq!(run(&ShortSpinner, &[spinner(500)]).len(), 1);
+        assert!(run(&ShortSpinner, &[spinner(1000)]).is_empty());
+    }
+
+    #[test]
+    fn slider_anchors() {
+        let slider = |path: &str| map(0, "", RED_LINE, &format!("100,100,1000,2,0,{},1,100", path));
+        let messages = |path: &str| -> Vec<String> {
+            run(&SliderAnchors, &[slider(path)])
+                .into_iter()
+                .map(|d| d.message)
+                .collect()
+        };
+        // A doubled anchor is a red anchor in a bezier, so only a third is flagged.
+        assert!(messages("B|150:150|150:150|200:100").is_empty());
+        assert_eq!(
+            messages("B|150:150|150:150|150:150|200:100"),
+            ["Slider has 3 anchors on the same spot"]
+        );
+        assert_eq!(
+            messages("L|150:150|150:150"),
+            ["Slider has 2 anchors on the same spot"]
+        );
+        assert_eq!(
+            run(
+                &SliderAnchors,
+                &[map(0, "", RED_LINE, "100,100,1000,2,0,L|100:100,1,0")]
+            )[0]
+            .message,
+            "Slider has zero length"
+        );
+    }
+
+    #[test]
+    fn metadata_differences() {
+        let [mut a, b, mut c] = [
+            circles(0, &[1000]),
+            circles(0, &[1000]),
+            circles(0, &[1000]),
+        ];
+        a.beatmap.metadata.tags = "c b a".to_string();
+        c.beatmap.metadata.title = "Song (TV Size)".to_string();
+        c.name = "other.osu".to_string();
+
+        let diagnostics = run(&InconsistentMetadata, &[a, b, c]);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].file, "other.osu");
+        assert_eq!(
+            diagnostics[0].message,
+            "[Hard] has Title \"Song (TV Size)\" where other difficulties have \"Song\""
+        );
+    }
+
+    #[test]
+    fn kiai_flashes() {
+        let kiai = |off: i32| {
+            let timing = format!(
+                "{}\n2000,-100,4,2,0,100,0,1\n{},-100,4,2,0,100,0,0",
+                RED_LINE, off
+            );
+            map(0, "", &timing, "256,192,1000,1,0,0:0:0:0:")
+        };
+        let diagnostics = run(&KiaiToggles, &[kiai(2250)]);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].time, Some(2250));
+        assert!(run(&KiaiToggles, &[kiai(2500)]).is_empty());
+    }
+
+    #[test]
+    fn preview_points() {
+        let [a, b, mut c] = [
+            circles(0, &[1000]),
+            circles(0, &[1000]),
+            circles(0, &[1000]),
+        ];
+        c.beatmap.general.preview_time = -1;
+        let diagnostics = run(&PreviewTime, &[a, b, c]);
+        let severities: Vec<Severity> = diagnostics.iter().map(|d| d.severity).collect();
+        assert_eq!(severities, [Severity::Warning, Severity::Problem]);
+        assert_eq!(diagnostics[1].time, None);
+    }
+
+    #[test]
+    fn drain_time_excludes_breaks() {
+        let objects = "256,192,1000,1,0,0:0:0:0:\n256,192,40000,1,0,0:0:0:0:";
+        assert!(run(&DrainTime, &[map(0, "", RED_LINE, objects)]).is_empty());
+        let with_break = map(0, "2,5000,15000", RED_LINE, objects);
+        assert_eq!(
+            run(&DrainTime, &[with_break])[0].message,
+            "[Hard] has 29.0 seconds of drain time, under the 30 second minimum"
+        );
+    }
+
+    #[test]
+    fn offscreen_objects() {
+        let objects = "0,0,1000,1,0,0:0:0:0:\n-40,192,1500,1,0,0:0:0:0:\n\
+                       256,100,2000,2,0,L|256:-30,1,130";
+        let diagnostics = run(&Offscreen, &[map(0, "", RED_LINE, objects)]);
+        let messages: Vec<&str> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "Circle is partially offscreen",
+                "Slider is partially offscreen"
+            ]
+        );
+        assert!(run(&Offscreen, &[circles(3, &[1000])]).is_empty());
+    }
+}
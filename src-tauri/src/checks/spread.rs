@@ -0,0 +1,354 @@
+use crate::checks::{sort_diagnostics, Diagnostic, Severity};
+use crate::difficulty;
+use crate::models::beatmap::Beatmap;
+use crate::models::hitobject::HitObjectKind;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+// Star rating each tier starts at, from the ranking criteria. Taiko and mania put
+// their Normal and Hard boundary a little higher than standard and catch.
+const STANDARD_TIERS: [f64; 5] = [2.0, 2.7, 4.0, 5.3, 6.5];
+const TAIKO_MANIA_TIERS: [f64; 5] = [2.0, 2.8, 4.0, 5.3, 6.5];
+
+const TIER_NAMES: [[&str; 6]; 4] = [
+    ["Easy", "Normal", "Hard", "Insane", "Expert", "Expert+"],
+    [
+        "Kantan",
+        "Futsuu",
+        "Muzukashii",
+        "Oni",
+        "Inner Oni",
+        "Ura Oni",
+    ],
+    ["Cup", "Salad", "Platter", "Rain", "Overdose", "Deluge"],
+    ["Easy", "Normal", "Hard", "Insane", "Expert", "Expert+"],
+];
+
+// Sets shorter than these drain times (in seconds) need their easiest difficulty to be
+// at or below Normal, Hard and Insane respectively.
+const LOWEST_TIER_DRAIN_LIMITS: [(f64, usize); 3] = [(210.0, 1), (255.0, 2), (300.0, 3)];
+
+// Every difficulty should play most of what the longest one does.
+const MIN_DRAIN_RATIO: f64 = 0.8;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SpreadDifficulty {
+    pub file: String,
+    pub version: String,
+    pub mode: u8,
+    pub star_rating: f64,
+    pub tier: usize,
+    pub tier_name: &'static str,
+    pub drain_time: f64,
+    pub total_length: f64,
+    pub circle_count: u32,
+    pub slider_count: u32,
+    pub spinner_count: u32,
+    pub hold_count: u32,
+    pub approach_rate: f64,
+    pub overall_difficulty: f64,
+    pub circle_size: f64,
+    pub hp_drain_rate: f64,
+    // Objects per second of drain time.
+    pub note_density: f64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SpreadReport {
+    // Grouped by mode, easiest first within each mode.
+    pub difficulties: Vec<SpreadDifficulty>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+fn tier_of(mode: u8, star_rating: f64) -> usize {
+    let bounds = match mode {
+        1 | 3 => &TAIKO_MANIA_TIERS,
+        _ => &STANDARD_TIERS,
+    };
+    bounds.iter().filter(|&&bound| star_rating >= bound).count()
+}
+
+fn mode_name(mode: u8) -> &'static str {
+    match mode {
+        1 => "osu!taiko",
+        2 => "osu!catch",
+        3 => "osu!mania",
+        _ => "osu!standard",
+    }
+}
+
+// The tier a difficulty's name claims, from the usual naming for each tier. Names only
+// match whole words, so "Harmonic" isn't read as Oni or "Hardcore" as Hard.
+fn named_tier(mode: u8, version: &str) -> Option<usize> {
+    let words: Vec<String> = version
+        .split(|c: char| !c.is_alphanumeric() && c != '+')
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect();
+    // A trailing "+" still counts, so "Hard+" reads as Hard.
+    let has_name = |name: &str| {
+        let name: Vec<String> = name.split(' ').map(|word| word.to_lowercase()).collect();
+        words.windows(name.len()).any(|window| {
+            window
+                .iter()
+                .zip(&name)
+                .all(|(word, part)| word == part || word.strip_suffix('+') == Some(part))
+        })
+    };
+
+    let names = &TIER_NAMES[mode.min(3) as usize];
+    // Longer names first so "Inner Oni" isn't read as "Oni" and "Expert+" not as "Expert".
+    let mut tiers: Vec<usize> = (0..names.len()).collect();
+    tiers.sort_by_key(|&tier| std::cmp::Reverse(names[tier].len()));
+    tiers
+        .into_iter()
+        .find(|&tier| has_name(names[tier]))
+        .or_else(|| {
+            [("beginner", 0), ("advanced", 2), ("extra", 4)]
+                .into_iter()
+                .find(|(name, _)| has_name(name))
+                .map(|(_, tier)| tier)
+        })
+}
+
+fn measure(file: String, beatmap: &Beatmap, star_rating: f64) -> SpreadDifficulty {
+    let mode = beatmap.general.mode;
+    let objects = &beatmap.hit_objects;
+    let count = |f: fn(&HitObjectKind) -> bool| objects.iter().filter(|o| f(&o.kind)).count();
+
+    let drain_time = beatmap.drain_time();
+    let end = beatmap.last_object_end();
+
+    let tier = tier_of(mode, star_rating);
+    SpreadDifficulty {
+        file,
+        version: beatmap.metadata.version.clone(),
+        mode,
+        star_rating,
+        tier,
+        tier_name: TIER_NAMES[mode.min(3) as usize][tier],
+        drain_time,
+        total_length: end / 1000.0,
+        circle_count: count(|k| matches!(k, HitObjectKind::Circle)) as u32,
+        slider_count: count(|k| matches!(k, HitObjectKind::Slider(_))) as u32,
+        spinner_count: count(|k| matches!(k, HitObjectKind::Spinner { .. })) as u32,
+        hold_count: count(|k| matches!(k, HitObjectKind::Hold { .. })) as u32,
+        approach_rate: beatmap.difficulty.approach_rate,
+        overall_difficulty: beatmap.difficulty.overall_difficulty,
+        circle_size: beatmap.difficulty.circle_size,
+        hp_drain_rate: beatmap.difficulty.hp_drain_rate,
+        note_density: if drain_time > 0.0 {
+            objects.len() as f64 / drain_time
+        } else {
+            0.0
+        },
+    }
+}
+
+fn format_drain(seconds: f64) -> String {
+    let seconds = seconds.round() as i64;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+// Settings that should never go down as difficulties get harder.
+fn settings_of(difficulty: &SpreadDifficulty) -> Vec<(&'static str, f64)> {
+    let mut settings = vec![
+        ("OD", difficulty.overall_difficulty),
+        ("HP", difficulty.hp_drain_rate),
+    ];
+    if matches!(difficulty.mode, 0 | 2) {
+        settings.push(("AR", difficulty.approach_rate));
+    }
+    settings
+}
+
+// Checks one mode's difficulties, which must already be sorted easiest first.
+fn check_mode(difficulties: &[SpreadDifficulty], diagnostics: &mut Vec<Diagnostic>) {
+    let Some(easiest) = difficulties.first() else {
+        return;
+    };
+    let mode = easiest.mode;
+    let names = &TIER_NAMES[mode.min(3) as usize];
+
+    let longest = difficulties
+        .iter()
+        .map(|d| d.drain_time)
+        .fold(0.0, f64::max);
+    if let Some(&(_, max_tier)) = LOWEST_TIER_DRAIN_LIMITS
+        .iter()
+        .find(|(limit, _)| longest < *limit)
+    {
+        if easiest.tier > max_tier {
+            diagnostics.push(Diagnostic::new(
+                Severity::Problem,
+                "spread_lowest",
+                &easiest.file,
+                format!(
+                    "The easiest {} difficulty is {} ({:.2}★), but a set with {} of drain time needs one at {} or below",
+                    mode_name(mode),
+                    easiest.tier_name,
+                    easiest.star_rating,
+                    format_drain(longest),
+                    names[max_tier]
+                ),
+            ));
+        }
+    }
+
+    for difficulty in difficulties {
+        if difficulty.drain_time < longest * MIN_DRAIN_RATIO {
+            diagnostics.push(Diagnostic::new(
+                Severity::Warning,
+                "spread_drain",
+                &difficulty.file,
+                format!(
+                    "[{}] drains for {}, less than {:.0}% of the longest difficulty's {}",
+                    difficulty.version,
+                    format_drain(difficulty.drain_time),
+                    MIN_DRAIN_RATIO * 100.0,
+                    format_drain(longest)
+                ),
+            ));
+        }
+
+        if let Some(named) = named_tier(mode, &difficulty.version) {
+            if named != difficulty.tier {
+                diagnostics.push(Diagnostic::new(
+                    Severity::Minor,
+                    "spread_name",
+                    &difficulty.file,
+                    format!(
+                        "[{}] is named for {} but rates as {} ({:.2}★)",
+                        difficulty.version,
+                        names[named],
+                        difficulty.tier_name,
+                        difficulty.star_rating
+                    ),
+                ));
+            }
+        }
+    }
+
+    for pair in difficulties.windows(2) {
+        let (easier, harder) = (&pair[0], &pair[1]);
+        if harder.tier > easier.tier + 1 {
+            let skipped: Vec<&str> = names[easier.tier + 1..harder.tier].to_vec();
+            diagnostics.push(Diagnostic::new(
+                Severity::Problem,
+                "spread_gap",
+                &harder.file,
+                format!(
+                    "[{}] ({:.2}★) follows [{}] ({:.2}★), skipping {}",
+                    harder.version,
+                    harder.star_rating,
+                    easier.version,
+                    easier.star_rating,
+                    skipped.join(", ")
+                ),
+            ));
+        }
+
+        if let (Some(easier_named), Some(harder_named)) = (
+            named_tier(mode, &easier.version),
+            named_tier(mode, &harder.version),
+        ) {
+            if easier_named > harder_named {
+                diagnostics.push(Diagnostic::new(
+                    Severity::Warning,
+                    "spread_order",
+                    &harder.file,
+                    format!(
+                        "[{}] ({:.2}★) is harder than [{}] ({:.2}★), though its name suggests it should be easier",
+                        harder.version, harder.star_rating, easier.version, easier.star_rating
+                    ),
+                ));
+            }
+        }
+
+        let easier_settings = settings_of(easier);
+        for (name, value) in settings_of(harder) {
+            let Some(&(_, easier_value)) = easier_settings.iter().find(|(n, _)| *n == name) else {
+                continue;
+            };
+            if value < easier_value {
+                diagnostics.push(Diagnostic::new(
+                    Severity::Warning,
+                    "spread_settings",
+                    &harder.file,
+                    format!(
+                        "[{}] has lower {} ({}) than the easier [{}] ({})",
+                        harder.version, name, value, easier.version, easier_value
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+pub fn analyze_spread(folder: &Path) -> Result<SpreadReport, String> {
+    let entries = fs::read_dir(folder).map_err(|e| format!("Failed to read directory: {}", e))?;
+    let mut files: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().and_then(|ext| ext.to_str()) == Some("osu"))
+        .filter_map(|e| e.file_name().to_str().map(|s| s.to_string()))
+        .collect();
+    files.sort();
+
+    let mut difficulties = Vec::new();
+    for file in files {
+        let beatmap = Beatmap::from_file(&folder.join(&file))?;
+        let attributes = difficulty::calculate(&beatmap, beatmap.general.mode, 0)?;
+        difficulties.push(measure(file, &beatmap, attributes.star_rating()));
+    }
+    difficulties.sort_by(|a, b| {
+        a.mode
+            .cmp(&b.mode)
+            .then_with(|| a.star_rating.total_cmp(&b.star_rating))
+    });
+
+    // Hybrid sets are spread separately for each mode.
+    let mut diagnostics = Vec::new();
+    for mode in 0..4 {
+        let start = difficulties.partition_point(|d| d.mode < mode);
+        let end = difficulties.partition_point(|d| d.mode <= mode);
+        check_mode(&difficulties[start..end], &mut diagnostics);
+    }
+    sort_diagnostics(&mut diagnostics);
+
+    Ok(SpreadReport {
+        difficulties,
+        diagnostics,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_match_whole_words() {
+        assert_eq!(named_tier(0, "Hard"), Some(2));
+        assert_eq!(named_tier(0, "Someone's Insane"), Some(3));
+        assert_eq!(named_tier(0, "[Hard-ish]"), Some(2));
+        assert_eq!(named_tier(0, "Hard+"), Some(2));
+        assert_eq!(named_tier(0, "Expert+"), Some(5));
+        assert_eq!(named_tier(0, "EXPERT"), Some(4));
+        assert_eq!(named_tier(0, "Extra Stage"), Some(4));
+        assert_eq!(named_tier(1, "Inner Oni"), Some(4));
+        assert_eq!(named_tier(1, "Mapper's Ura Oni"), Some(5));
+        assert_eq!(named_tier(1, "Oni"), Some(3));
+        assert_eq!(named_tier(2, "Lumi's Rain"), Some(3));
+    }
+
+    #[test]
+    fn names_inside_other_words_dont_match() {
+        assert_eq!(named_tier(0, "Hardcore"), None);
+        assert_eq!(named_tier(0, "Normality"), None);
+        assert_eq!(named_tier(1, "Harmonic"), None);
+        assert_eq!(named_tier(1, "Inner Onion"), None);
+        assert_eq!(named_tier(2, "Terrain"), None);
+        assert_eq!(named_tier(2, "Cupcake"), None);
+        assert_eq!(named_tier(0, "Extravaganza"), None);
+    }
+}
@@ -0,0 +1,543 @@
+use crate::checks::{sort_diagnostics, Diagnostic, Severity};
+use crate::models::beatmap::Beatmap;
+use crate::models::storyboard::{Command, Element, Storyboard};
+use crate::utils::files::list_files;
+use crate::utils::image::image_dimensions;
+use crate::utils::references::normalize_path;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+// Storyboard coordinates span 640x480; widescreen storyboards add 107 pixels each side.
+const SCREEN_WIDTH: f64 = 640.0;
+const SCREEN_HEIGHT: f64 = 480.0;
+const WIDESCREEN_MARGIN: f64 = 107.0;
+
+const MAX_IMAGE_WIDTH: u32 = 1920;
+const MAX_IMAGE_HEIGHT: u32 = 1080;
+
+struct Source {
+    name: String,
+    storyboard: Storyboard,
+    widescreen: Option<bool>,
+}
+
+fn describe(element: &Element) -> String {
+    let kind = match element {
+        Element::Sprite { .. } => "Sprite",
+        Element::Animation { .. } => "Animation",
+        Element::Sample { .. } => "Sample",
+        Element::Video { .. } => "Video",
+    };
+    format!("{} \"{}\"", kind, element.path())
+}
+
+fn flatten<'a>(commands: &'a [Command], output: &mut Vec<&'a Command>) {
+    for command in commands {
+        output.push(command);
+        flatten(command.children(), output);
+    }
+}
+
+fn first_time(element: &Element) -> Option<i32> {
+    match element {
+        Element::Sample { time, .. } => Some(*time),
+        Element::Video { start_time, .. } => Some(*start_time),
+        _ => element.commands().iter().map(|c| c.start_time()).min(),
+    }
+}
+
+// Properties a command writes to. Move sets both axes, so it clashes with MoveX and MoveY.
+fn properties(command: &Command) -> &'static [&'static str] {
+    match command {
+        Command::Fade(_) => &["opacity"],
+        Command::Move(_) => &["x", "y"],
+        Command::MoveX(_) => &["x"],
+        Command::MoveY(_) => &["y"],
+        Command::Scale(_) => &["scale"],
+        Command::VectorScale(_) => &["vector scale"],
+        Command::Rotate(_) => &["rotation"],
+        Command::Colour(_) => &["colour"],
+        _ => &[],
+    }
+}
+
+// Every x and y an element is placed at, from its initial position and any move commands.
+fn positions(element: &Element, commands: &[&Command]) -> (Vec<f64>, Vec<f64>) {
+    let (mut xs, mut ys) = match element {
+        Element::Sprite { position, .. } | Element::Animation { position, .. } => {
+            (vec![position.x], vec![position.y])
+        }
+        _ => return (Vec::new(), Vec::new()),
+    };
+
+    let has_move = |f: fn(&Command) -> bool| commands.iter().any(|c| f(c));
+    if has_move(|c| matches!(c, Command::Move(_) | Command::MoveX(_))) {
+        xs.clear();
+    }
+    if has_move(|c| matches!(c, Command::Move(_) | Command::MoveY(_))) {
+        ys.clear();
+    }
+
+    for command in commands {
+        match command {
+            Command::Move(t) => {
+                xs.extend([t.from.x, t.to.x]);
+                ys.extend([t.from.y, t.to.y]);
+            }
+            Command::MoveX(t) => xs.extend([t.from, t.to]),
+            Command::MoveY(t) => ys.extend([t.from, t.to]),
+            _ => {}
+        }
+    }
+    (xs, ys)
+}
+
+fn max_scale(commands: &[&Command]) -> f64 {
+    commands
+        .iter()
+        .flat_map(|command| match command {
+            Command::Scale(t) => vec![t.from, t.to],
+            Command::VectorScale(t) => vec![t.from.x, t.from.y, t.to.x, t.to.y],
+            _ => Vec::new(),
+        })
+        .map(f64::abs)
+        .reduce(f64::max)
+        .unwrap_or(1.0)
+}
+
+fn visibility_problem(
+    element: &Element,
+    commands: &[&Command],
+    image_size: Option<(u32, u32)>,
+) -> Option<&'static str> {
+    if commands.is_empty() {
+        return Some("has no commands and is never shown");
+    }
+
+    let fades: Vec<_> = commands
+        .iter()
+        .filter_map(|c| match c {
+            Command::Fade(t) => Some(t),
+            _ => None,
+        })
+        .collect();
+    if !fades.is_empty() && fades.iter().all(|t| t.from <= 0.0 && t.to <= 0.0) {
+        return Some("is always fully transparent");
+    }
+
+    let scales: Vec<bool> = commands
+        .iter()
+        .filter_map(|c| match c {
+            Command::Scale(t) => Some(t.from == 0.0 && t.to == 0.0),
+            Command::VectorScale(t) => {
+                Some((t.from.x == 0.0 || t.from.y == 0.0) && (t.to.x == 0.0 || t.to.y == 0.0))
+            }
+            _ => None,
+        })
+        .collect();
+    if !scales.is_empty() && scales.iter().all(|zero| *zero) {
+        return Some("always has a scale of zero");
+    }
+
+    // Without the image size there's no telling how far a sprite reaches past its position.
+    let (width, height) = image_size?;
+    let reach = width.max(height) as f64 * max_scale(commands);
+    let (xs, ys) = positions(element, commands);
+    let outside = |values: &[f64], low: f64, high: f64| {
+        !values.is_empty()
+            && (values.iter().all(|v| *v < low - reach) || values.iter().all(|v| *v > high + reach))
+    };
+    if outside(&xs, -WIDESCREEN_MARGIN, SCREEN_WIDTH + WIDESCREEN_MARGIN)
+        || outside(&ys, 0.0, SCREEN_HEIGHT)
+    {
+        return Some("is always off-screen");
+    }
+    None
+}
+
+fn uses_widescreen(element: &Element) -> bool {
+    let mut commands = Vec::new();
+    flatten(element.commands(), &mut commands);
+    let (xs, _) = positions(element, &commands);
+    xs.iter().any(|x| {
+        (-WIDESCREEN_MARGIN..0.0).contains(x)
+            || (*x > SCREEN_WIDTH && *x <= SCREEN_WIDTH + WIDESCREEN_MARGIN)
+    })
+}
+
+fn check_timing(source: &str, label: &str, commands: &[Command], output: &mut Vec<Diagnostic>) {
+    for command in commands {
+        let start = command.start_time();
+        let end = match command {
+            Command::Loop { .. } => start,
+            _ => command.end_time(),
+        };
+        if end < start {
+            output.push(
+                Diagnostic::new(
+                    Severity::Problem,
+                    "end_before_start",
+                    source,
+                    format!(
+                        "{}: \"{}\" ends at {} before it starts",
+                        label,
+                        command.to_osu_string(),
+                        end
+                    ),
+                )
+                .at(start),
+            );
+        }
+        check_timing(source, label, command.children(), output);
+    }
+}
+
+// Loop and trigger bodies use their own relative timeline, so each command list is
+// checked separately.
+fn check_conflicts(source: &str, label: &str, commands: &[Command], output: &mut Vec<Diagnostic>) {
+    let mut by_property: HashMap<&str, Vec<(i32, i32)>> = HashMap::new();
+    for command in commands {
+        for property in properties(command) {
+            by_property
+                .entry(property)
+                .or_default()
+                .push((command.start_time(), command.end_time()));
+        }
+        check_conflicts(source, label, command.children(), output);
+    }
+
+    let mut sorted: Vec<_> = by_property.into_iter().collect();
+    sorted.sort_by_key(|(property, _)| *property);
+    for (property, mut spans) in sorted {
+        spans.sort();
+        let mut covered_until = i32::MIN;
+        for (start, end) in spans {
+            if start < covered_until {
+                output.push(
+                    Diagnostic::new(
+                        Severity::Problem,
+                        "conflicting_commands",
+                        source,
+                        format!("{}: overlapping commands change its {}", label, property),
+                    )
+                    .at(start),
+                );
+                break;
+            }
+            covered_until = covered_until.max(end);
+        }
+    }
+}
+
+fn check_element(
+    source: &str,
+    element: &Element,
+    on_disk: &HashMap<String, PathBuf>,
+    output: &mut Vec<Diagnostic>,
+) {
+    let label = describe(element);
+
+    let missing: Vec<String> = element
+        .files()
+        .into_iter()
+        .filter(|file| !on_disk.contains_key(&normalize_path(file)))
+        .collect();
+    if !missing.is_empty() {
+        let mut diagnostic = Diagnostic::new(
+            Severity::Problem,
+            "missing_file",
+            source,
+            format!(
+                "{} references missing file{} {}",
+                label,
+                if missing.len() == 1 { "" } else { "s" },
+                missing.join(", ")
+            ),
+        );
+        diagnostic.time = first_time(element);
+        output.push(diagnostic);
+    }
+
+    if !matches!(element, Element::Sprite { .. } | Element::Animation { .. }) {
+        return;
+    }
+
+    let mut commands = Vec::new();
+    flatten(element.commands(), &mut commands);
+    let image_size = element
+        .files()
+        .first()
+        .and_then(|file| on_disk.get(&normalize_path(file)))
+        .and_then(|path| image_dimensions(path));
+    if let Some(problem) = visibility_problem(element, &commands, image_size) {
+        let mut diagnostic = Diagnostic::new(
+            Severity::Warning,
+            "never_visible",
+            source,
+            format!("{} {}", label, problem),
+        );
+        diagnostic.time = first_time(element);
+        output.push(diagnostic);
+    }
+
+    check_timing(source, &label, element.commands(), output);
+    check_conflicts(source, &label, element.commands(), output);
+}
+
+pub fn check_storyboards(folder: &Path) -> Result<Vec<Diagnostic>, String> {
+    let files = list_files(folder)?;
+    let mut on_disk = HashMap::new();
+    let mut sources = Vec::new();
+
+    for path in &files {
+        let relative = path
+            .strip_prefix(folder)
+            .map_err(|_| format!("Invalid file path: {}", path.display()))?
+            .to_string_lossy()
+            .replace('\\', "/");
+        on_disk.insert(relative.to_lowercase(), path.clone());
+
+        if path.parent() != Some(folder) {
+            continue;
+        }
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("osb") => sources.push(Source {
+                name: relative,
+                storyboard: Storyboard::from_file(path)?,
+                widescreen: None,
+            }),
+            Some("osu") => {
+                let beatmap = Beatmap::from_file(path)?;
+                sources.push(Source {
+                    name: relative,
+                    storyboard: beatmap.storyboard(),
+                    widescreen: Some(beatmap.general.widescreen_storyboard),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    let mut diagnostics = Vec::new();
+    let mut checked_images = HashSet::new();
+    for source in &sources {
+        for element in &source.storyboard.elements {
+            check_element(&source.name, element, &on_disk, &mut diagnostics);
+
+            if !matches!(element, Element::Sprite { .. } | Element::Animation { .. }) {
+                continue;
+            }
+            for file in element.files() {
+                let key = normalize_path(&file);
+                if !checked_images.insert(key.clone()) {
+                    continue;
+                }
+                let Some((width, height)) = on_disk.get(&key).and_then(|p| image_dimensions(p))
+                else {
+                    continue;
+                };
+                if width > MAX_IMAGE_WIDTH || height > MAX_IMAGE_HEIGHT {
+                    diagnostics.push(Diagnostic::new(
+                        Severity::Warning,
+                        "oversized_image",
+                        &source.name,
+                        format!(
+                            "{} is {}x{}, larger than {}x{}",
+                            file, width, height, MAX_IMAGE_WIDTH, MAX_IMAGE_HEIGHT
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+
+    // A set's .osb applies to every difficulty, alongside the difficulty's own events.
+    let shared_widescreen = sources
+        .iter()
+        .filter(|s| s.widescreen.is_none())
+        .any(|s| s.storyboard.elements.iter().any(uses_widescreen));
+    for source in &sources {
+        if source.widescreen != Some(false) {
+            continue;
+        }
+        if shared_widescreen || source.storyboard.elements.iter().any(uses_widescreen) {
+            diagnostics.push(Diagnostic::new(
+                Severity::Warning,
+                "widescreen_storyboard",
+                &source.name,
+                "Storyboard uses the widescreen area but WidescreenStoryboard is disabled"
+                    .to_string(),
+            ));
+        }
+    }
+
+    sort_diagnostics(&mut diagnostics);
+    Ok(diagnostics)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut data = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        data.extend(width.to_be_bytes());
+        data.extend(height.to_be_bytes());
+        data
+    }
+
+    fn check(files: &[(&str, Vec<u8>)]) -> Vec<Diagnostic> {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, contents) in files {
+            let path = dir.path().join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        check_storyboards(dir.path()).unwrap()
+    }
+
+    fn osb(events: &str) -> (&'static str, Vec<u8>) {
+        ("set.osb", format!("[Events]\n{}\n", events).into_bytes())
+    }
+
+    fn osu(widescreen: bool, events: &str) -> (&'static str, Vec<u8>) {
+        let content = format!(
+            "osu file format v14\n\n[General]\nWidescreenStoryboard: {}\n\n[Events]\n{}\n",
+            widescreen as u8, events
+        );
+        ("map.osu", content.into_bytes())
+    }
+
+    fn sprite(commands: &str) -> (&'static str, Vec<u8>) {
+        osb(&format!(
+            "Sprite,Foreground,Centre,\"sb/dot.png\",320,240\n{}",
+            commands
+        ))
+    }
+
+    fn dot() -> (&'static str, Vec<u8>) {
+        ("sb/dot.png", png(10, 10))
+    }
+
+    fn messages<'a>(diagnostics: &'a [Diagnostic], check: &str) -> Vec<&'a str> {
+        diagnostics
+            .iter()
+            .filter(|d| d.check == check)
+            .map(|d| d.message.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn visible_sprite_has_no_diagnostics() {
+        let diagnostics = check(&[sprite(" F,0,0,1000,0,1\n M,0,0,1000,0,0,640,480"), dot()]);
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+    }
+
+    #[test]
+    fn missing_animation_frames() {
+        let animation = osb(
+            "Animation,Foreground,Centre,\"sb/star.png\",320,240,3,50,LoopForever\n F,0,0,1000,1",
+        );
+        let diagnostics = check(&[
+            animation,
+            ("sb/star0.png", png(10, 10)),
+            ("sb/Star2.PNG", png(10, 10)),
+        ]);
+        assert_eq!(
+            messages(&diagnostics, "missing_file"),
+            ["Animation \"sb/star.png\" references missing file sb/star1.png"]
+        );
+        assert_eq!(diagnostics[0].time, Some(0));
+    }
+
+    #[test]
+    fn never_visible() {
+        let problem = |commands: &str| {
+            let diagnostics = check(&[sprite(commands), dot()]);
+            messages(&diagnostics, "never_visible")
+                .into_iter()
+                .map(|m| m.trim_start_matches("Sprite \"sb/dot.png\" ").to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(problem(""), ["has no commands and is never shown"]);
+        assert_eq!(
+            problem(" F,0,0,1000,0\n S,0,0,1000,1"),
+            ["is always fully transparent"]
+        );
+        assert_eq!(
+            problem(" F,0,0,1000,1\n V,0,0,1000,0,1,1,0"),
+            ["always has a scale of zero"]
+        );
+        assert_eq!(
+            problem(" M,0,0,1000,900,240,1000,240"),
+            ["is always off-screen"]
+        );
+        // Scaled up, the same sprite reaches back onto the screen.
+        assert!(problem(" M,0,0,1000,900,240,1000,240\n S,0,0,1000,50").is_empty());
+        assert!(problem(" F,0,0,1000,0,1").is_empty());
+    }
+
+    #[test]
+    fn end_before_start() {
+        let diagnostics = check(&[sprite(" F,0,1000,500,1\n L,0,2\n  F,0,200,100,1"), dot()]);
+        assert_eq!(
+            messages(&diagnostics, "end_before_start"),
+            [
+                // Sorted by time, so the loop body's command comes first.
+                "Sprite \"sb/dot.png\": \"F,0,200,100,1\" ends at 100 before it starts",
+                "Sprite \"sb/dot.png\": \"F,0,1000,500,1\" ends at 500 before it starts",
+            ]
+        );
+    }
+
+    #[test]
+    fn move_conflicts_with_move_x() {
+        let diagnostics = check(&[
+            sprite(" M,0,0,1000,0,0,100,100\n MX,0,500,1500,0,100"),
+            dot(),
+        ]);
+        assert_eq!(
+            messages(&diagnostics, "conflicting_commands"),
+            ["Sprite \"sb/dot.png\": overlapping commands change its x"]
+        );
+        assert_eq!(diagnostics[0].time, Some(500));
+
+        // Back to back, and loop bodies on their own timeline, don't conflict.
+        let diagnostics = check(&[
+            sprite(" M,0,0,1000,0,0,100,100\n MY,0,1000,1500,0,100\n L,0,2\n  MX,0,0,500,0,1"),
+            dot(),
+        ]);
+        assert!(messages(&diagnostics, "conflicting_commands").is_empty());
+    }
+
+    #[test]
+    fn widescreen_without_flag() {
+        let wide = "Sprite,Foreground,Centre,\"sb/dot.png\",-50,240\n F,0,0,1000,1";
+        let diagnostics = check(&[osu(false, wide), dot()]);
+        assert_eq!(
+            messages(&diagnostics, "widescreen_storyboard"),
+            ["Storyboard uses the widescreen area but WidescreenStoryboard is disabled"]
+        );
+        assert_eq!(diagnostics[0].file, "map.osu");
+
+        assert!(check(&[osu(true, wide), dot()]).is_empty());
+
+        // The set's .osb plays on every difficulty, so it counts too.
+        let shared = sprite(" F,0,0,1000,1\n MX,0,0,1000,320,700");
+        let diagnostics = check(&[shared, osu(false, ""), dot()]);
+        assert_eq!(messages(&diagnostics, "widescreen_storyboard").len(), 1);
+    }
+
+    #[test]
+    fn oversized_image_is_reported_once() {
+        let events = "Sprite,Background,Centre,\"bg.png\",320,240\n F,0,0,1000,1\n\
+                      Sprite,Foreground,Centre,\"bg.png\",320,240\n F,0,0,1000,1";
+        let diagnostics = check(&[osb(events), ("bg.png", png(2560, 1440))]);
+        assert_eq!(
+            messages(&diagnostics, "oversized_image"),
+            ["bg.png is 2560x1440, larger than 1920x1080"]
+        );
+        assert!(check(&[sprite(" F,0,0,1000,1"), ("sb/dot.png", png(1920, 1080))]).is_empty());
+    }
+}
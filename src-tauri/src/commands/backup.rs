@@ -0,0 +1,40 @@
+use crate::utils::backup::{BackupStore, Snapshot};
+use crate::utils::files::app_data_path;
+use std::path::Path;
+
+pub fn backup_store(app: &tauri::AppHandle) -> Result<BackupStore, String> {
+    Ok(BackupStore::new(app_data_path(app, "backups")?))
+}
+
+#[tauri::command]
+pub fn list_backups(
+    app: tauri::AppHandle,
+    beatmap_folder: String,
+) -> Result<Vec<Snapshot>, String> {
+    backup_store(&app)?.list(Path::new(&beatmap_folder))
+}
+
+#[tauri::command]
+pub fn restore_backup(
+    app: tauri::AppHandle,
+    beatmap_folder: String,
+    backup_id: String,
+) -> Result<String, String> {
+    let snapshot = backup_store(&app)?.restore(Path::new(&beatmap_folder), &backup_id)?;
+
+    Ok(format!(
+        "Restored {} file{} from {}",
+        snapshot.files.len(),
+        if snapshot.files.len() != 1 { "s" } else { "" },
+        snapshot.operation
+    ))
+}
+
+#[tauri::command]
+pub fn delete_backup(
+    app: tauri::AppHandle,
+    beatmap_folder: String,
+    backup_id: String,
+) -> Result<(), String> {
+    backup_store(&app)?.delete(Path::new(&beatmap_folder), &backup_id)
+}
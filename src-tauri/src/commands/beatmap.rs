@@ -1,6 +1,9 @@
 use crate::models::beatmapset::Beatmapset;
-use crate::utils::parser::scrape;
-use rayon::prelude::*;
+use crate::models::collection_db::{collection_db_path, CollectionDb};
+use crate::models::songs_index::{
+    IndexStats, SetEntry, SongsIndex, SongsIndexState, SONGS_INDEX_FILE,
+};
+use crate::utils::files::app_data_path;
 use std::fs;
 use std::path::Path;
 use std::process::Command;
@@ -53,115 +56,94 @@ pub fn detect_osu_path() -> Result<String, String> {
     }
 }
 
+pub fn with_songs_index<T>(
+    app: &tauri::AppHandle,
+    state: &SongsIndexState,
+    base_path: &str,
+    f: impl FnOnce(&mut SongsIndex) -> T,
+) -> Result<T, String> {
+    let mut guard = state.lock()?;
+
+    if !matches!(guard.as_ref(), Some(index) if index.songs_path == base_path) {
+        let index_path = app_data_path(app, SONGS_INDEX_FILE)?;
+        let mut index = SongsIndex::load(&index_path, base_path);
+        index.refresh(false)?;
+        index.save(&index_path)?;
+        *guard = Some(index);
+    }
+
+    let index = guard
+        .as_mut()
+        .ok_or_else(|| "Songs index is unavailable".to_string())?;
+    Ok(f(index))
+}
+
+#[tauri::command]
+pub fn refresh_songs_index(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SongsIndexState>,
+    base_path: String,
+    full: bool,
+) -> Result<IndexStats, String> {
+    let index_path = app_data_path(&app, SONGS_INDEX_FILE)?;
+    let mut guard = state.lock()?;
+
+    let mut index = match guard.take() {
+        Some(index) if index.songs_path == base_path => index,
+        _ => SongsIndex::load(&index_path, &base_path),
+    };
+    let stats = index.refresh(full);
+    if stats.is_ok() {
+        index.save(&index_path)?;
+    }
+    *guard = Some(index);
+
+    stats
+}
+
 #[tauri::command]
 pub fn scan_songs_step(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SongsIndexState>,
     base_path: String,
     start_index: usize,
     step_size: usize,
     search_query: String,
+    collection: Option<String>,
 ) -> Result<(Vec<Beatmapset>, usize, bool), String> {
-    let base = Path::new(&base_path);
-
-    if !base.exists() {
-        return Err(format!("Folder not found: {}", base_path));
-    }
-
-    let entries = fs::read_dir(base).map_err(|e| format!("Failed to read directory: {}", e))?;
-
-    let mut folders_with_time: Vec<_> = entries
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().map(|ft| ft.is_dir()).unwrap_or(false))
-        .filter_map(|entry| {
-            let modified = fs::metadata(entry.path()).and_then(|m| m.modified()).ok()?;
-            Some((entry, modified))
-        })
-        .collect();
-
-    folders_with_time.par_sort_by(|a, b| b.1.cmp(&a.1));
-
-    let total = folders_with_time.len();
-    let mut results = Vec::new();
-    let mut found = 0;
-    let mut current_index = start_index;
-
-    for (idx, (entry, _modified)) in folders_with_time.iter().enumerate().skip(start_index) {
-        if found >= step_size {
-            current_index = idx;
-            break;
+    let collections = match &collection {
+        Some(_) => CollectionDb::from_file(&collection_db_path(Path::new(&base_path))?)?,
+        None => CollectionDb::default(),
+    };
+    let hashes = collection.as_deref().map(|name| collections.hashes(name));
+
+    with_songs_index(&app, &state, &base_path, |index| {
+        let mut matches = index.search(&search_query);
+        if let Some(hashes) = &hashes {
+            matches.retain(|set| set.in_collection(hashes));
         }
+        let results: Vec<Beatmapset> = matches
+            .iter()
+            .skip(start_index)
+            .take(step_size)
+            .map(|set| set.to_beatmapset())
+            .collect();
+
+        let next_index = (start_index + results.len()).min(matches.len());
+        (results, next_index, next_index < matches.len())
+    })
+}
 
-        current_index = idx + 1;
-
-        let folder_path = entry.path();
-        let folder_name = entry.file_name().to_string_lossy().to_string();
-
-        if let Ok(files) = fs::read_dir(&folder_path) {
-            for file in files.filter_map(|f| f.ok()) {
-                let path = file.path();
-                if let Some(ext) = path.extension() {
-                    if ext == "osu" {
-                        if let Ok(data) = fs::read_to_string(&path) {
-                            let title = scrape(&data, "Title:", "\n");
-                            let artist = scrape(&data, "Artist:", "\n");
-                            let creator = scrape(&data, "Creator:", "\n");
-                            let beatmap_id = scrape(&data, "BeatmapID:", "\n");
-                            let beatmapset_id = scrape(&data, "BeatmapSetID:", "\n");
-                            let bg_file = scrape(&data, "0,0,\"", "\"");
-
-                            let searchable = format!(
-                                "{} - {} | {} ({} {})",
-                                title, artist, creator, beatmap_id, beatmapset_id
-                            );
-
-                            if search_query.is_empty()
-                                || searchable
-                                    .to_lowercase()
-                                    .contains(&search_query.to_lowercase())
-                            {
-                                let display_title = if !title.is_empty() && !artist.is_empty() {
-                                    format!("{} - {}", artist, title)
-                                } else {
-                                    folder_name
-                                        .split_once(' ')
-                                        .map(|(_, rest)| rest.to_string())
-                                        .unwrap_or(folder_name.clone())
-                                };
-
-                                let beatmap = Beatmapset {
-                                    folder_name: folder_name.clone(),
-                                    title: display_title,
-                                    artist: if artist.is_empty() {
-                                        "Unknown".to_string()
-                                    } else {
-                                        artist
-                                    },
-                                    creator: if creator.is_empty() {
-                                        "Unknown".to_string()
-                                    } else {
-                                        creator
-                                    },
-                                    background_path: if !bg_file.is_empty() {
-                                        Some(format!("{}/{}", folder_path.display(), bg_file))
-                                    } else {
-                                        None
-                                    },
-                                    beatmap_id,
-                                    beatmap_set_id: beatmapset_id,
-                                };
-
-                                results.push(beatmap);
-                                found += 1;
-                            }
-                        }
-                        break;
-                    }
-                }
-            }
-        }
-    }
-
-    let has_more = current_index < total;
-    Ok((results, current_index, has_more))
+#[tauri::command]
+pub fn get_beatmapset_details(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SongsIndexState>,
+    base_path: String,
+    folder_name: String,
+) -> Result<Option<SetEntry>, String> {
+    with_songs_index(&app, &state, &base_path, |index| {
+        index.find(&folder_name).cloned()
+    })
 }
 
 #[tauri::command]
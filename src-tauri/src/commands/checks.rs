@@ -0,0 +1,44 @@
+use crate::checks::ranking::{available_checks, load_set, run_checks, CheckInfo};
+use crate::checks::spread::{analyze_spread, SpreadReport};
+use crate::checks::storyboard::check_storyboards;
+use crate::checks::Diagnostic;
+use std::path::Path;
+
+#[tauri::command]
+pub fn check_storyboard(beatmap_folder: String) -> Result<Vec<Diagnostic>, String> {
+    let folder = Path::new(&beatmap_folder);
+    if !folder.is_dir() {
+        return Err(format!("Folder not found: {}", beatmap_folder));
+    }
+    check_storyboards(folder)
+}
+
+#[tauri::command]
+pub fn analyze_difficulty_spread(beatmap_folder: String) -> Result<SpreadReport, String> {
+    let folder = Path::new(&beatmap_folder);
+    if !folder.is_dir() {
+        return Err(format!("Folder not found: {}", beatmap_folder));
+    }
+    analyze_spread(folder)
+}
+
+#[tauri::command]
+pub fn list_map_checks() -> Vec<CheckInfo> {
+    available_checks()
+}
+
+#[tauri::command]
+pub fn run_map_checks(
+    beatmap_folder: String,
+    disabled_checks: Vec<String>,
+) -> Result<Vec<Diagnostic>, String> {
+    let folder = Path::new(&beatmap_folder);
+    if !folder.is_dir() {
+        return Err(format!("Folder not found: {}", beatmap_folder));
+    }
+    let maps = load_set(folder)?;
+    if maps.is_empty() {
+        return Err("No .osu files found in beatmap".to_string());
+    }
+    Ok(run_checks(&maps, &disabled_checks))
+}
@@ -1,6 +1,13 @@
+use crate::commands::backup::backup_store;
+use crate::commands::collection::add_plan_to_collection;
+use crate::models::beatmap::Beatmap;
 use crate::models::beatmapset::BeatmapMetadata;
-use crate::utils::parser::scrape;
-use std::collections::HashMap;
+use crate::transforms::pipeline::Pipeline;
+use crate::utils::files::list_files;
+use crate::utils::plan::{ChangePlan, PlanResult, SkipReason};
+use crate::utils::references::SetReferences;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::Path;
 
@@ -25,21 +32,25 @@ fn get_next_beatmap_number(songs_path: &Path) -> Result<i32, String> {
     Ok(max_num + 1)
 }
 
-fn get_background_from_osu(content: &str) -> Option<String> {
-    for line in content.lines() {
-        let trimmed = line.trim();
-        if trimmed.starts_with("0,0,") || trimmed.starts_with("Background,") {
-            if let Some(start) = trimmed.find('"') {
-                if let Some(end) = trimmed[start + 1..].find('"') {
-                    return Some(trimmed[start + 1..start + 1 + end].to_string());
-                }
+pub fn new_beatmap_folder_name(songs_path: &Path, title: &str) -> Result<String, String> {
+    let beatmap_num = get_next_beatmap_number(songs_path)?;
+
+    let safe_title = title
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == ' ' {
+                c
+            } else {
+                '_'
             }
-        }
-    }
-    None
+        })
+        .collect::<String>()
+        .replace(' ', "_");
+
+    Ok(format!("beatmap-{}-{}", beatmap_num, safe_title))
 }
 
-fn is_skin_file(filename: &str) -> bool {
+pub fn is_skin_file(filename: &str) -> bool {
     let lower = filename.to_lowercase();
     let name_without_ext = lower.rsplit_once('.').map(|(n, _)| n).unwrap_or(&lower);
 
@@ -310,264 +321,20 @@ fn is_skin_file(filename: &str) -> bool {
     false
 }
 
-fn process_timing_points(lines: &[&str]) -> Vec<String> {
-    let mut result = Vec::new();
-    let mut in_kiai = false;
-    let mut last_bpm_line: Option<String> = None;
-
-    for line in lines {
-        let trimmed = line.trim();
-
-        if trimmed.is_empty() || trimmed.starts_with("//") {
-            continue;
-        }
-
-        let parts: Vec<&str> = trimmed.split(',').collect();
-        if parts.len() < 8 {
-            continue;
-        }
-
-        let time = parts[0].trim();
-        let beat_length = parts[1].trim();
-        let meter = parts[2].trim();
-        let uninherited = parts[6].trim();
-        let effects = parts[7].trim().parse::<i32>().unwrap_or(0);
-        let has_kiai = (effects & 1) == 1;
-
-        if uninherited == "1" {
-            let new_line = format!(
-                "{},{},{},1,0,100,1,{}",
-                time,
-                beat_length,
-                meter,
-                if has_kiai { "1" } else { "0" }
-            );
-            last_bpm_line = Some(new_line.clone());
-            result.push(new_line);
-            in_kiai = has_kiai;
-            continue;
-        }
-
-        if has_kiai != in_kiai {
-            if let Some(ref bpm_line) = last_bpm_line {
-                let bpm_parts: Vec<&str> = bpm_line.split(',').collect();
-                let bpm_beat_length = bpm_parts[1];
-                let bpm_meter = bpm_parts[2];
-
-                let new_line = format!(
-                    "{},{},{},1,0,100,0,{}",
-                    time,
-                    bpm_beat_length,
-                    bpm_meter,
-                    if has_kiai { "1" } else { "0" }
-                );
-                result.push(new_line);
-            }
-
-            in_kiai = has_kiai;
-        }
-    }
-
-    result
-}
-
-fn process_osu_content(
-    content: &str,
-    metadata: &BeatmapMetadata,
-    game_mode: u8,
-    difficulty: &str,
-    keep_timing_points: bool,
-    reset_sample_set: bool,
-    reset_difficulty: bool,
-    remove_colours: bool,
-    background_file: Option<&str>,
-) -> String {
-    let eol = if content.contains("\r\n") {
-        "\r\n"
-    } else {
-        "\n"
-    };
-
-    let lines: Vec<&str> = content.split(eol).collect();
-    let mut output = Vec::new();
-    let mut current_section = String::new();
-    let mut timing_lines = Vec::new();
-    let mut in_timing_section = false;
-    let mut skip_section = false;
-
-    for line in lines {
-        let trimmed = line.trim();
-
-        if trimmed.starts_with('[') && trimmed.ends_with(']') {
-            if current_section == "[TimingPoints]" && in_timing_section {
-                if keep_timing_points {
-                    let processed = process_timing_points(&timing_lines);
-                    for tp_line in processed {
-                        output.push(tp_line);
-                    }
-                }
-                timing_lines.clear();
-                in_timing_section = false;
-            }
-
-            current_section = trimmed.to_string();
-
-            if current_section == "[Editor]"
-                || current_section == "[HitObjects]"
-                || (current_section == "[Colours]" && remove_colours)
-            {
-                skip_section = true;
-                continue;
-            }
-
-            skip_section = false;
-            output.push(line.to_string());
-
-            if current_section == "[TimingPoints]" {
-                in_timing_section = true;
-            }
-
-            continue;
-        }
-
-        if skip_section {
-            continue;
-        }
-
-        if in_timing_section && keep_timing_points {
-            timing_lines.push(line);
-            continue;
-        }
-
-        if in_timing_section && !keep_timing_points {
-            continue;
-        }
-
-        if current_section == "[Events]" {
-            if trimmed.starts_with("//") || trimmed.is_empty() {
-                output.push(line.to_string());
-                continue;
-            }
-
-            if trimmed.starts_with("0,0,") || trimmed.starts_with("Background,") {
-                if let Some(bg) = background_file {
-                    output.push(format!("0,0,\"{}\",0,0", bg));
-                } else {
-                    output.push(line.to_string());
-                }
-                continue;
-            }
-
-            continue;
-        }
-
-        if current_section == "[General]" {
-            if trimmed.starts_with("Mode:") {
-                output.push(format!("Mode: {}", game_mode));
-                continue;
-            }
-            if reset_sample_set && trimmed.starts_with("SampleSet:") {
-                output.push("SampleSet: Normal".to_string());
-                continue;
-            }
-        }
-
-        if current_section == "[Metadata]" {
-            if trimmed.starts_with("Title:") {
-                output.push(format!("Title:{}", metadata.title));
-                continue;
-            }
-            if trimmed.starts_with("TitleUnicode:") {
-                output.push(format!("TitleUnicode:{}", metadata.title_unicode));
-                continue;
-            }
-            if trimmed.starts_with("Artist:") {
-                output.push(format!("Artist:{}", metadata.artist));
-                continue;
-            }
-            if trimmed.starts_with("ArtistUnicode:") {
-                output.push(format!("ArtistUnicode:{}", metadata.artist_unicode));
-                continue;
-            }
-            if trimmed.starts_with("Creator:") {
-                output.push(format!("Creator:{}", metadata.creator));
-                continue;
-            }
-            if trimmed.starts_with("Version:") {
-                output.push(format!("Version:{}", difficulty));
-                continue;
-            }
-            if trimmed.starts_with("Source:") {
-                output.push(format!("Source:{}", metadata.source));
-                continue;
-            }
-            if trimmed.starts_with("Tags:") {
-                output.push(format!("Tags:{}", metadata.tags));
-                continue;
-            }
-            if trimmed.starts_with("BeatmapID:") {
-                output.push("BeatmapID:-1".to_string());
-                continue;
-            }
-            if trimmed.starts_with("BeatmapSetID:") {
-                output.push("BeatmapSetID:-1".to_string());
-                continue;
-            }
-        }
-
-        if current_section == "[Difficulty]" && reset_difficulty {
-            if trimmed.starts_with("HPDrainRate:") {
-                output.push("HPDrainRate:5".to_string());
-                continue;
-            }
-            if trimmed.starts_with("CircleSize:") {
-                output.push("CircleSize:2".to_string());
-                continue;
-            }
-            if trimmed.starts_with("OverallDifficulty:") {
-                output.push("OverallDifficulty:5".to_string());
-                continue;
-            }
-            if trimmed.starts_with("ApproachRate:") {
-                output.push("ApproachRate:5".to_string());
-                continue;
-            }
-            if trimmed.starts_with("SliderMultiplier:") {
-                output.push("SliderMultiplier:1.4".to_string());
-                continue;
-            }
-            if trimmed.starts_with("SliderTickRate:") {
-                output.push("SliderTickRate:1".to_string());
-                continue;
-            }
-        }
-
-        output.push(line.to_string());
-    }
-
-    if in_timing_section && keep_timing_points && !timing_lines.is_empty() {
-        let processed = process_timing_points(&timing_lines);
-        for tp_line in processed {
-            output.push(tp_line);
-        }
-    }
-
-    output.join(eol)
-}
-
 #[tauri::command]
 pub fn clone_beatmap(
+    app: tauri::AppHandle,
     source_beatmap: String,
-    game_mode: u8,
     difficulties: Vec<String>,
     metadata: BeatmapMetadata,
-    keep_timing_points: bool,
+    pipeline: Pipeline,
     remove_skin_files: bool,
-    reset_sample_set: bool,
-    reset_difficulty: bool,
-    remove_colours: bool,
     songs_folder: String,
-) -> Result<String, String> {
+    collection: Option<String>,
+    dry_run: bool,
+) -> Result<PlanResult, String> {
+    pipeline.validate()?;
+
     let songs_path = Path::new(&songs_folder);
     let source_path = songs_path.join(&source_beatmap);
 
@@ -575,25 +342,11 @@ pub fn clone_beatmap(
         return Err(format!("Source beatmap not found: {}", source_beatmap));
     }
 
-    let beatmap_num = get_next_beatmap_number(songs_path)?;
-
-    let safe_title = metadata
-        .title
-        .chars()
-        .map(|c| {
-            if c.is_alphanumeric() || c == ' ' {
-                c
-            } else {
-                '_'
-            }
-        })
-        .collect::<String>()
-        .replace(' ', "_");
-
-    let new_folder_name = format!("beatmap-{}-{}", beatmap_num, safe_title);
+    let new_folder_name = new_beatmap_folder_name(songs_path, &metadata.title)?;
     let new_folder_path = songs_path.join(&new_folder_name);
 
-    fs::create_dir_all(&new_folder_path).map_err(|e| format!("Failed to create folder: {}", e))?;
+    let mut plan = ChangePlan::new().with_backup(backup_store(&app)?, &new_folder_path, "clone");
+    plan.create_dir(&new_folder_path);
 
     let osu_files: Vec<_> = fs::read_dir(&source_path)
         .map_err(|e| format!("Failed to read source: {}", e))?
@@ -613,67 +366,116 @@ pub fn clone_beatmap(
 
     let mut diff_backgrounds: HashMap<String, String> = HashMap::new();
     for osu_file in &osu_files {
-        let content = fs::read_to_string(osu_file.path())
-            .map_err(|e| format!("Failed to read .osu file: {}", e))?;
+        let beatmap = Beatmap::from_file(&osu_file.path())?;
 
-        let version = scrape(&content, "Version:", "\n");
-        if let Some(bg) = get_background_from_osu(&content) {
-            diff_backgrounds.insert(version, bg);
+        if let Some(bg) = beatmap.background() {
+            diff_backgrounds.insert(beatmap.metadata.version.clone(), bg.to_string());
         }
     }
 
-    let audio_extensions = ["mp3", "ogg", "wav"];
-    let image_extensions = ["jpg", "jpeg", "png"];
+    let template = Beatmap::from_file(&osu_files[0].path())?;
 
-    for entry in fs::read_dir(&source_path).map_err(|e| format!("Failed to read source: {}", e))? {
-        let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
-        let path = entry.path();
+    // The new set only needs what its generated difficulties and the storyboard point at.
+    let mut references = SetReferences::default();
+    let mut new_files = Vec::new();
+    for diff in &difficulties {
+        let background = diff_backgrounds.get(diff.as_str());
+
+        let mut new_beatmap = template.clone();
+        Pipeline::new()
+            .with("strip_hit_objects", Value::Null)
+            .with("strip_bookmarks", Value::Null)
+            .with("strip_events", json!({ "background": background }))
+            .with(
+                "set_metadata",
+                json!({
+                    "title": metadata.title,
+                    "title_unicode": metadata.title_unicode,
+                    "artist": metadata.artist,
+                    "artist_unicode": metadata.artist_unicode,
+                    "creator": metadata.creator,
+                    "version": diff,
+                    "source": metadata.source,
+                    "tags": metadata.tags,
+                    "beatmap_id": -1,
+                    "beatmap_set_id": -1,
+                }),
+            )
+            .then(&pipeline)
+            .apply(&mut new_beatmap)?;
+
+        let new_filename = metadata.osu_file_name(diff);
+
+        let contents = new_beatmap.to_osu_string();
+        references.add_osu(&new_filename, &contents)?;
+        new_files.push((new_folder_path.join(new_filename), contents));
+    }
+
+    let source_files = list_files(&source_path)?;
+    for path in &source_files {
+        if path.parent() == Some(source_path.as_path())
+            && path.extension().and_then(|e| e.to_str()) == Some("osb")
+        {
+            let content = fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            let name = path.file_name().unwrap().to_string_lossy();
+            references.add_osb(&name, &content);
+        }
+    }
+
+    let mut created_dirs = HashSet::new();
+    for path in &source_files {
+        // Difficulties are regenerated above rather than copied.
+        if path.extension().and_then(|e| e.to_str()) == Some("osu") {
+            continue;
+        }
 
-        if let Some(ext) = path.extension() {
-            let ext_str = ext.to_str().unwrap_or("").to_lowercase();
-            let file_name = path.file_name().unwrap().to_str().unwrap_or("");
+        let relative = path
+            .strip_prefix(&source_path)
+            .map_err(|_| format!("Invalid file path: {}", path.display()))?;
+        let relative_name = relative.to_string_lossy().replace('\\', "/");
+        let file_name = path.file_name().unwrap().to_str().unwrap_or("");
+        let skin_file = relative.parent() == Some(Path::new("")) && is_skin_file(file_name);
 
-            if remove_skin_files && is_skin_file(file_name) {
+        if !references.is_referenced(&relative_name) {
+            if !skin_file {
+                plan.skip(path, SkipReason::NotReferenced);
                 continue;
             }
+            if remove_skin_files {
+                plan.skip(path, SkipReason::SkinFile);
+                continue;
+            }
+        }
 
-            if audio_extensions.contains(&ext_str.as_str())
-                || image_extensions.contains(&ext_str.as_str())
-            {
-                let dest = new_folder_path.join(path.file_name().unwrap());
-                fs::copy(&path, &dest).map_err(|e| format!("Failed to copy file: {}", e))?;
+        let dest = new_folder_path.join(relative);
+        if let Some(parent) = dest.parent() {
+            if parent != new_folder_path && created_dirs.insert(parent.to_path_buf()) {
+                plan.create_dir(parent);
             }
         }
+        plan.copy(path, &dest);
     }
 
-    let template_path = osu_files[0].path();
-    let template_content = fs::read_to_string(&template_path)
-        .map_err(|e| format!("Failed to read template: {}", e))?;
-
-    for diff in &difficulties {
-        let background = diff_backgrounds.get(diff.as_str()).map(|s| s.as_str());
-
-        let new_content = process_osu_content(
-            &template_content,
-            &metadata,
-            game_mode,
-            diff,
-            keep_timing_points,
-            reset_sample_set,
-            reset_difficulty,
-            remove_colours,
-            background,
-        );
-
-        let new_filename = format!(
-            "{} - {} ({}) [{}].osu",
-            metadata.artist, metadata.title, metadata.creator, diff
-        );
-
-        let new_file_path = new_folder_path.join(new_filename);
-        fs::write(&new_file_path, new_content)
-            .map_err(|e| format!("Failed to write .osu file: {}", e))?;
+    for (path, contents) in new_files {
+        plan.write(&path, contents)?;
     }
 
-    Ok(format!("Successfully created beatmap: {}", new_folder_name))
+    let mut message = if dry_run {
+        format!("Would create beatmap: {}", new_folder_name)
+    } else {
+        format!("Successfully created beatmap: {}", new_folder_name)
+    };
+    let skipped = plan.skipped().len();
+    if skipped > 0 {
+        message.push_str(&format!(
+            "; left {} file{} behind",
+            skipped,
+            if skipped == 1 { "" } else { "s" }
+        ));
+    }
+    let hashes = plan.osu_hashes();
+    let mut result = plan.finish(dry_run, message)?;
+    add_plan_to_collection(&app, songs_path, collection, &hashes, &mut result)?;
+    Ok(result)
 }
@@ -0,0 +1,106 @@
+use crate::commands::backup::backup_store;
+use crate::commands::beatmap::list_osu_files;
+use crate::models::collection_db::{collection_db_path, CollectionDb, CollectionSummary};
+use crate::utils::files::{content_md5, write_atomic};
+use crate::utils::plan::PlanResult;
+use std::fs;
+use std::path::Path;
+
+// Adds the new hashes to the named collection and repoints any collection that held
+// an older version of the same files. collection.db is backed up before being replaced.
+pub fn update_collection(
+    app: &tauri::AppHandle,
+    songs_folder: &Path,
+    name: &str,
+    hashes: &[(Option<String>, String)],
+) -> Result<usize, String> {
+    let db_path = collection_db_path(songs_folder)?;
+    let mut db = CollectionDb::from_file(&db_path)?;
+    let original = db.clone();
+
+    for (old, new) in hashes {
+        if let Some(old) = old.as_deref().filter(|old| old != new) {
+            db.replace_hash(old, new);
+        }
+    }
+    let new_hashes: Vec<String> = hashes.iter().map(|(_, new)| new.clone()).collect();
+    let added = db.add(name, &new_hashes);
+
+    if db != original {
+        let osu_dir = db_path
+            .parent()
+            .ok_or_else(|| format!("Invalid collection path: {}", db_path.display()))?;
+        backup_store(app)?.snapshot(osu_dir, "collection", std::slice::from_ref(&db_path))?;
+        write_atomic(&db_path, db.to_bytes())?;
+    }
+    Ok(added)
+}
+
+pub fn add_plan_to_collection(
+    app: &tauri::AppHandle,
+    songs_folder: &Path,
+    collection: Option<String>,
+    hashes: &[(Option<String>, String)],
+    result: &mut PlanResult,
+) -> Result<(), String> {
+    let Some(name) = collection.filter(|name| !name.trim().is_empty()) else {
+        return Ok(());
+    };
+
+    if result.dry_run {
+        result
+            .message
+            .push_str(&format!("; would add to collection \"{}\"", name));
+        return Ok(());
+    }
+
+    update_collection(app, songs_folder, &name, hashes)?;
+    result
+        .message
+        .push_str(&format!("; added to collection \"{}\"", name));
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_collections(base_path: String) -> Result<Vec<CollectionSummary>, String> {
+    let db_path = collection_db_path(Path::new(&base_path))?;
+    Ok(CollectionDb::from_file(&db_path)?.summaries())
+}
+
+#[tauri::command]
+pub fn add_to_collection(
+    app: tauri::AppHandle,
+    base_path: String,
+    collection: String,
+    beatmap_folder: String,
+    osu_files: Vec<String>,
+) -> Result<String, String> {
+    if collection.trim().is_empty() {
+        return Err("Collection name is required".to_string());
+    }
+
+    let files = if osu_files.is_empty() {
+        list_osu_files(beatmap_folder.clone())?
+    } else {
+        osu_files
+    };
+
+    let folder = Path::new(&beatmap_folder);
+    let hashes = files
+        .iter()
+        .map(|file| {
+            let path = folder.join(file);
+            fs::read(&path)
+                .map(|data| (None, content_md5(data)))
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let added = update_collection(&app, Path::new(&base_path), &collection, &hashes)?;
+    Ok(format!(
+        "Added {} difficult{} to \"{}\"",
+        added,
+        if added != 1 { "ies" } else { "y" },
+        collection
+    ))
+}
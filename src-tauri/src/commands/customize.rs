@@ -0,0 +1,62 @@
+use crate::commands::backup::backup_store;
+use crate::commands::collection::add_plan_to_collection;
+use crate::commands::transform::{plan_pipeline_for_files, processed_message};
+use crate::transforms::pipeline::Pipeline;
+use crate::utils::plan::PlanResult;
+use serde::Deserialize;
+use serde_json::Value;
+use std::path::Path;
+
+#[derive(Deserialize, Debug, Default)]
+pub struct CustomizeOptions {
+    pub center_objects: bool,
+    pub remove_bookmarks: bool,
+    pub remove_new_combos: bool,
+    pub whistle_to_clap: bool,
+    pub create_backup: bool,
+}
+
+impl CustomizeOptions {
+    pub fn to_pipeline(&self) -> Pipeline {
+        let steps = [
+            (self.center_objects, "center_objects"),
+            (self.remove_bookmarks, "strip_bookmarks"),
+            (self.remove_new_combos, "strip_new_combos"),
+            (self.whistle_to_clap, "whistle_to_clap"),
+        ];
+
+        steps
+            .into_iter()
+            .filter(|(enabled, _)| *enabled)
+            .fold(Pipeline::new(), |pipeline, (_, name)| {
+                pipeline.with(name, Value::Null)
+            })
+    }
+}
+
+#[tauri::command]
+pub fn customize_beatmap(
+    app: tauri::AppHandle,
+    beatmap_folder: String,
+    osu_files: Vec<String>,
+    options: CustomizeOptions,
+    collection: Option<String>,
+    dry_run: bool,
+) -> Result<PlanResult, String> {
+    let plan = plan_pipeline_for_files(
+        &beatmap_folder,
+        osu_files,
+        &options.to_pipeline(),
+        options.create_backup,
+    )?
+    .with_backup(backup_store(&app)?, Path::new(&beatmap_folder), "customize");
+    let message = processed_message(&plan, dry_run);
+    let hashes = plan.osu_hashes();
+    let mut result = plan.finish(dry_run, message)?;
+
+    let songs_folder = Path::new(&beatmap_folder)
+        .parent()
+        .ok_or_else(|| format!("Invalid beatmap folder: {}", beatmap_folder))?;
+    add_plan_to_collection(&app, songs_folder, collection, &hashes, &mut result)?;
+    Ok(result)
+}
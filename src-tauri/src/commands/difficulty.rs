@@ -0,0 +1,42 @@
+use crate::difficulty::osu::performance::pp_at_accuracy;
+use crate::difficulty::{self, DifficultyAttributes};
+use crate::models::beatmap::Beatmap;
+use serde::Serialize;
+use std::path::Path;
+
+const PP_ACCURACIES: [f64; 4] = [0.95, 0.98, 0.99, 1.0];
+
+#[derive(Serialize, Debug, Clone)]
+pub struct AccuracyPp {
+    pub accuracy: f64,
+    pub pp: f64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DifficultyResult {
+    pub attributes: DifficultyAttributes,
+    pub pp: Vec<AccuracyPp>,
+}
+
+#[tauri::command]
+pub fn calculate_difficulty(
+    file_path: String,
+    mods: i32,
+    mode: Option<u8>,
+) -> Result<DifficultyResult, String> {
+    let beatmap = Beatmap::from_file(Path::new(&file_path))?;
+    let attributes = difficulty::calculate(&beatmap, mode.unwrap_or(beatmap.general.mode), mods)?;
+
+    // Performance is only calculated for osu!standard so far.
+    let pp = match &attributes {
+        DifficultyAttributes::Osu(osu) => PP_ACCURACIES
+            .iter()
+            .map(|&accuracy| AccuracyPp {
+                accuracy,
+                pp: pp_at_accuracy(osu, mods, accuracy),
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    Ok(DifficultyResult { attributes, pp })
+}
@@ -0,0 +1,134 @@
+use crate::commands::clone::is_skin_file;
+use crate::utils::files::list_files;
+use crate::utils::references::SetReferences;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io;
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+#[derive(Deserialize, Debug, Default)]
+pub struct ExportOptions {
+    pub exclude_skin_files: bool,
+    pub exclude_unused_files: bool,
+    pub exclude_backups: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ExportResult {
+    pub path: String,
+    pub size: u64,
+    pub file_count: usize,
+    pub excluded: Vec<String>,
+}
+
+// Leftovers from this app's own writes: `.backup` copies and interrupted atomic writes.
+pub fn is_backup_artefact(file_name: &str) -> bool {
+    let lower = file_name.to_lowercase();
+    lower.ends_with(".backup")
+        || lower.ends_with(".bak")
+        || (lower.starts_with('.') && lower.ends_with(".tmp"))
+}
+
+#[tauri::command]
+pub fn export_osz(
+    beatmap_folder: String,
+    output_path: String,
+    options: ExportOptions,
+) -> Result<ExportResult, String> {
+    let folder = Path::new(&beatmap_folder);
+    if !folder.is_dir() {
+        return Err(format!("Folder not found: {}", beatmap_folder));
+    }
+
+    let references = if options.exclude_skin_files || options.exclude_unused_files {
+        Some(SetReferences::scan(folder)?)
+    } else {
+        None
+    };
+
+    let files = list_files(folder)?;
+
+    let mut included = Vec::new();
+    let mut excluded = Vec::new();
+    for path in files {
+        let relative = path
+            .strip_prefix(folder)
+            .map_err(|_| format!("Invalid file path: {}", path.display()))?
+            .to_string_lossy()
+            .replace('\\', "/");
+        let file_name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default();
+
+        let referenced = references
+            .as_ref()
+            .is_some_and(|r| r.is_referenced(&relative));
+        // Custom hitsounds share their names with skin samples, so referenced ones are kept.
+        let skin = !relative.contains('/') && is_skin_file(file_name);
+
+        let exclude = (options.exclude_backups && is_backup_artefact(file_name))
+            || (options.exclude_skin_files && skin && !referenced)
+            || (options.exclude_unused_files && !referenced && !skin);
+
+        if exclude {
+            excluded.push(relative);
+        } else {
+            included.push((path, relative));
+        }
+    }
+
+    if included.is_empty() {
+        return Err("No files left to export".to_string());
+    }
+
+    let output = Path::new(&output_path);
+    let file_name = output
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| format!("Invalid output path: {}", output_path))?;
+    let temp_path = output.with_file_name(format!(".{}.tmp", file_name));
+
+    let write_archive = || -> Result<(), String> {
+        let file = File::create(&temp_path)
+            .map_err(|e| format!("Failed to create {}: {}", temp_path.display(), e))?;
+        let mut zip = ZipWriter::new(file);
+        let zip_options =
+            SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+        for (path, relative) in &included {
+            zip.start_file(relative.as_str(), zip_options)
+                .map_err(|e| format!("Failed to add {}: {}", relative, e))?;
+            let mut source = File::open(path)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            io::copy(&mut source, &mut zip)
+                .map_err(|e| format!("Failed to add {}: {}", relative, e))?;
+        }
+
+        zip.finish()
+            .map_err(|e| format!("Failed to write archive: {}", e))?;
+        Ok(())
+    };
+
+    if let Err(e) = write_archive() {
+        let _ = fs::remove_file(&temp_path);
+        return Err(e);
+    }
+    fs::rename(&temp_path, output).map_err(|e| {
+        let _ = fs::remove_file(&temp_path);
+        format!("Failed to replace {}: {}", output.display(), e)
+    })?;
+
+    let size = fs::metadata(output)
+        .map(|m| m.len())
+        .map_err(|e| format!("Failed to read {}: {}", output.display(), e))?;
+
+    Ok(ExportResult {
+        path: output.display().to_string(),
+        size,
+        file_count: included.len(),
+        excluded,
+    })
+}
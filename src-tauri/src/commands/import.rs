@@ -0,0 +1,305 @@
+use crate::commands::backup::backup_store;
+use crate::commands::clone::new_beatmap_folder_name;
+use crate::commands::watcher::{update_index, SONGS_INDEX_EVENT};
+use crate::models::beatmap::Beatmap;
+use crate::models::songs_index::IndexChanges;
+use crate::utils::files::sanitize_file_name;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use tauri::Emitter;
+use zip::ZipArchive;
+
+// Limit on the total extracted size of an archive, well above any real beatmap set.
+const MAX_EXTRACTED_SIZE: u64 = 2 * 1024 * 1024 * 1024;
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportConflict {
+    #[default]
+    Rename,
+    Merge,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ImportResult {
+    pub folder_name: String,
+    pub path: String,
+    pub file_count: usize,
+    pub merged: bool,
+    pub backup_id: Option<String>,
+    pub changes: IndexChanges,
+}
+
+fn folder_name_for(songs_path: &Path, beatmap: &Beatmap) -> Result<String, String> {
+    let metadata = &beatmap.metadata;
+    if metadata.beatmap_set_id > 0 {
+        Ok(sanitize_file_name(&format!(
+            "{} {} - {}",
+            metadata.beatmap_set_id, metadata.artist, metadata.title
+        )))
+    } else {
+        new_beatmap_folder_name(songs_path, &metadata.title)
+    }
+}
+
+// An existing folder for the same online set counts as a collision even when its
+// name differs, since osu! would otherwise show the set twice.
+fn existing_folder(songs_path: &Path, folder_name: &str, set_id: i32) -> Option<String> {
+    if songs_path.join(folder_name).exists() {
+        return Some(folder_name.to_string());
+    }
+    if set_id <= 0 {
+        return None;
+    }
+
+    let prefix = format!("{} ", set_id);
+    fs::read_dir(songs_path)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().is_dir())
+        .filter_map(|e| e.file_name().to_str().map(|s| s.to_string()))
+        .find(|name| name.starts_with(&prefix))
+}
+
+fn unique_folder_name(songs_path: &Path, folder_name: &str) -> String {
+    let mut suffix = 2;
+    let mut candidate = folder_name.to_string();
+    while songs_path.join(&candidate).exists() {
+        candidate = format!("{} ({})", folder_name, suffix);
+        suffix += 1;
+    }
+    candidate
+}
+
+fn extract_entries<R: Read + io::Seek>(
+    archive: &mut ZipArchive<R>,
+    entries: &[(usize, PathBuf)],
+    folder: &Path,
+    max_size: u64,
+) -> Result<(), String> {
+    let mut remaining = max_size;
+    for (i, relative) in entries {
+        let dest = folder.join(relative);
+        if !dest.starts_with(folder) {
+            return Err(format!("Unsafe path in archive: {}", relative.display()));
+        }
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create folder: {}", e))?;
+        }
+
+        let entry = archive
+            .by_index(*i)
+            .map_err(|e| format!("Failed to read archive: {}", e))?;
+        let mut output = File::create(&dest)
+            .map_err(|e| format!("Failed to create {}: {}", dest.display(), e))?;
+        // The declared sizes can't be trusted, so count what is actually written. Reading
+        // one byte past the limit tells an archive that fits exactly from one that doesn't.
+        let written = io::copy(&mut entry.take(remaining + 1), &mut output)
+            .map_err(|e| format!("Failed to extract {}: {}", relative.display(), e))?;
+        if written > remaining {
+            return Err(format!(
+                "Archive is larger than the {} MB import limit",
+                max_size / (1024 * 1024)
+            ));
+        }
+        remaining -= written;
+    }
+    Ok(())
+}
+
+// A new set is renamed into place in one go; a merge moves each file over the existing one.
+fn move_into_place(
+    extracted: &Path,
+    entries: &[(usize, PathBuf)],
+    target: &Path,
+) -> Result<(), String> {
+    if !target.exists() {
+        return fs::rename(extracted, target)
+            .map_err(|e| format!("Failed to move {} into place: {}", target.display(), e));
+    }
+
+    for (_, relative) in entries {
+        let dest = target.join(relative);
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create folder: {}", e))?;
+        }
+        fs::rename(extracted.join(relative), &dest)
+            .map_err(|e| format!("Failed to move {} into place: {}", relative.display(), e))?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn import_osz(
+    app: tauri::AppHandle,
+    base_path: String,
+    osz_path: String,
+    on_conflict: ImportConflict,
+) -> Result<ImportResult, String> {
+    let songs_path = Path::new(&base_path);
+    if !songs_path.is_dir() {
+        return Err(format!("Folder not found: {}", base_path));
+    }
+
+    let file = File::open(&osz_path).map_err(|e| format!("Failed to open {}: {}", osz_path, e))?;
+    let mut archive = ZipArchive::new(file).map_err(|e| format!("Invalid .osz archive: {}", e))?;
+
+    // enclosed_name rejects absolute paths and anything that would escape the folder.
+    let mut entries: Vec<(usize, PathBuf)> = Vec::new();
+    for i in 0..archive.len() {
+        let entry = archive
+            .by_index(i)
+            .map_err(|e| format!("Failed to read archive: {}", e))?;
+        if entry.is_dir() {
+            continue;
+        }
+        let relative = entry
+            .enclosed_name()
+            .ok_or_else(|| format!("Unsafe path in archive: {}", entry.name()))?;
+        entries.push((i, relative));
+    }
+
+    let mut template: Option<Beatmap> = None;
+    for (i, relative) in &entries {
+        let is_osu = relative.components().count() == 1
+            && relative
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("osu"));
+        if !is_osu {
+            continue;
+        }
+        let mut content = String::new();
+        archive
+            .by_index(*i)
+            .map_err(|e| format!("Failed to read archive: {}", e))?
+            .read_to_string(&mut content)
+            .map_err(|e| format!("Failed to read {}: {}", relative.display(), e))?;
+        if let Ok(beatmap) = Beatmap::parse(&content) {
+            template = Some(beatmap);
+            break;
+        }
+    }
+    let template = template.ok_or_else(|| "No valid .osu files found in archive".to_string())?;
+
+    let folder_name = folder_name_for(songs_path, &template)?;
+    if folder_name.is_empty() {
+        return Err("Could not determine a folder name for this beatmap".to_string());
+    }
+
+    let existing = existing_folder(songs_path, &folder_name, template.metadata.beatmap_set_id);
+    let (folder_name, merged) = match (existing, on_conflict) {
+        (Some(existing), ImportConflict::Merge) => (existing, true),
+        (Some(_), ImportConflict::Rename) => (unique_folder_name(songs_path, &folder_name), false),
+        (None, _) => (folder_name, false),
+    };
+    let target = songs_path.join(&folder_name);
+
+    // Extract next to the destination first, so a bad or oversized archive never leaves a
+    // half-written set in Songs, and the final move is a rename on the same drive. The
+    // index and watcher skip dot-prefixed folders, so this one is never seen as a set.
+    let extracted = tempfile::Builder::new()
+        .prefix(".import-")
+        .tempdir_in(songs_path)
+        .map_err(|e| format!("Failed to create temporary folder: {}", e))?;
+    extract_entries(&mut archive, &entries, extracted.path(), MAX_EXTRACTED_SIZE)?;
+
+    let targets: Vec<PathBuf> = entries
+        .iter()
+        .map(|(_, relative)| target.join(relative))
+        .collect();
+    let snapshot = backup_store(&app)?.snapshot(&target, "import", &targets)?;
+    move_into_place(extracted.path(), &entries, &target)?;
+
+    let changes = update_index(&app, songs_path, &HashSet::from([folder_name.clone()]))?;
+    if !changes.is_empty() {
+        let _ = app.emit(SONGS_INDEX_EVENT, changes.clone());
+    }
+
+    Ok(ImportResult {
+        folder_name,
+        path: target.display().to_string(),
+        file_count: entries.len(),
+        merged,
+        backup_id: Some(snapshot.id),
+        changes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+    use zip::write::SimpleFileOptions;
+    use zip::ZipWriter;
+
+    fn archive(files: &[(&str, &[u8])]) -> ZipArchive<Cursor<Vec<u8>>> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in files {
+            writer
+                .start_file(*name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(content).unwrap();
+        }
+        ZipArchive::new(writer.finish().unwrap()).unwrap()
+    }
+
+    fn entries(files: &[(&str, &[u8])]) -> Vec<(usize, PathBuf)> {
+        files
+            .iter()
+            .enumerate()
+            .map(|(i, (name, _))| (i, PathBuf::from(name)))
+            .collect()
+    }
+
+    #[test]
+    fn extracts_within_size_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let files: &[(&str, &[u8])] = &[("map.osu", b"12345"), ("sb/star.png", b"67890")];
+        extract_entries(&mut archive(files), &entries(files), dir.path(), 10).unwrap();
+        assert_eq!(fs::read(dir.path().join("sb/star.png")).unwrap(), b"67890");
+    }
+
+    #[test]
+    fn rejects_archives_over_size_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let files: &[(&str, &[u8])] = &[("map.osu", b"12345"), ("audio.mp3", b"678901")];
+        let error =
+            extract_entries(&mut archive(files), &entries(files), dir.path(), 10).unwrap_err();
+        assert!(error.contains("import limit"), "{}", error);
+    }
+
+    #[test]
+    fn renames_new_folder_into_place() {
+        let dir = tempfile::tempdir().unwrap();
+        let extracted = dir.path().join(".import-test");
+        fs::create_dir_all(extracted.join("sb")).unwrap();
+        fs::write(extracted.join("sb/star.png"), "").unwrap();
+        let entries = vec![(0, PathBuf::from("sb/star.png"))];
+
+        let target = dir.path().join("1 Artist - Title");
+        move_into_place(&extracted, &entries, &target).unwrap();
+        assert!(target.join("sb/star.png").is_file());
+        assert!(!extracted.exists());
+    }
+
+    #[test]
+    fn merges_into_existing_folder() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("1 Artist - Title");
+        fs::create_dir_all(&target).unwrap();
+        fs::write(target.join("map.osu"), "old").unwrap();
+        fs::write(target.join("keep.osu"), "kept").unwrap();
+
+        let extracted = dir.path().join(".import-test");
+        fs::create_dir_all(&extracted).unwrap();
+        fs::write(extracted.join("map.osu"), "new").unwrap();
+        let entries = vec![(0, PathBuf::from("map.osu"))];
+
+        move_into_place(&extracted, &entries, &target).unwrap();
+        assert_eq!(fs::read_to_string(target.join("map.osu")).unwrap(), "new");
+        assert_eq!(fs::read_to_string(target.join("keep.osu")).unwrap(), "kept");
+    }
+}
@@ -0,0 +1,235 @@
+use crate::checks::metadata::{metadata_report, renames, MetadataReport};
+use crate::checks::ranking::{load_set, MapFile};
+use crate::commands::backup::backup_store;
+use crate::commands::transform::processed_message;
+use crate::models::beatmapset::BeatmapMetadata;
+use crate::transforms::pipeline::Pipeline;
+use crate::utils::plan::{ChangePlan, FileAction, PlanResult};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+fn load_metadata_set(beatmap_folder: &str) -> Result<(Vec<MapFile>, Vec<String>), String> {
+    let folder = Path::new(beatmap_folder);
+    if !folder.is_dir() {
+        return Err(format!("Folder not found: {}", beatmap_folder));
+    }
+    let maps = load_set(folder)?;
+    if maps.is_empty() {
+        return Err("No .osu files found in beatmap".to_string());
+    }
+
+    let entries = fs::read_dir(folder).map_err(|e| format!("Failed to read directory: {}", e))?;
+    let mut osb_files: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().and_then(|ext| ext.to_str()) == Some("osb"))
+        .filter_map(|e| e.file_name().to_str().map(|s| s.to_string()))
+        .collect();
+    osb_files.sort();
+    Ok((maps, osb_files))
+}
+
+pub fn plan_metadata_fix(
+    folder: &Path,
+    maps: &[MapFile],
+    osb_files: &[String],
+    metadata: &BeatmapMetadata,
+    rename_files: bool,
+) -> Result<ChangePlan, String> {
+    let params = serde_json::to_value(metadata)
+        .map_err(|e| format!("Failed to serialize metadata: {}", e))?;
+    let pipeline = Pipeline::new().with("set_metadata", params);
+
+    let mut plan = ChangePlan::new();
+    for map in maps {
+        let mut beatmap = map.beatmap.clone();
+        pipeline
+            .apply(&mut beatmap)
+            .map_err(|e| format!("{}: {}", map.name, e))?;
+        plan.write(&folder.join(&map.name), beatmap.to_osu_string())?;
+    }
+
+    if rename_files {
+        let renames = renames(maps, osb_files, metadata);
+        let mut targets = HashSet::new();
+        for map in maps {
+            let target = metadata.osu_file_name(&map.beatmap.metadata.version);
+            if !targets.insert(target.to_lowercase()) {
+                return Err(format!(
+                    "More than one difficulty would be named {}",
+                    target
+                ));
+            }
+        }
+        for rename in &renames {
+            // Windows treats names differing only in case as the same file.
+            let same_file = rename.from.eq_ignore_ascii_case(&rename.to);
+            if folder.join(&rename.to).exists() && !same_file {
+                return Err(format!("{} already exists", rename.to));
+            }
+        }
+        for rename in &renames {
+            plan.rename(&folder.join(&rename.from), &folder.join(&rename.to));
+        }
+    }
+
+    Ok(plan)
+}
+
+#[tauri::command]
+pub fn check_metadata_consistency(beatmap_folder: String) -> Result<MetadataReport, String> {
+    let (maps, osb_files) = load_metadata_set(&beatmap_folder)?;
+    Ok(metadata_report(&maps, &osb_files))
+}
+
+#[tauri::command]
+pub fn apply_metadata(
+    app: tauri::AppHandle,
+    beatmap_folder: String,
+    metadata: BeatmapMetadata,
+    rename_files: bool,
+    dry_run: bool,
+) -> Result<PlanResult, String> {
+    let (maps, osb_files) = load_metadata_set(&beatmap_folder)?;
+    let folder = Path::new(&beatmap_folder);
+    let plan = plan_metadata_fix(folder, &maps, &osb_files, &metadata, rename_files)?.with_backup(
+        backup_store(&app)?,
+        folder,
+        "metadata",
+    );
+
+    let mut message = processed_message(&plan, dry_run);
+    let renamed = plan.count(FileAction::Rename);
+    if renamed > 0 {
+        message.push_str(&format!(
+            ", {} {} file{}",
+            if dry_run { "would rename" } else { "renamed" },
+            renamed,
+            if renamed != 1 { "s" } else { "" }
+        ));
+    }
+    plan.finish(dry_run, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn osu(title: &str, version: &str) -> String {
+        format!(
+            "osu file format v14\n\n[Metadata]\nTitle:{}\nArtist:Artist\nCreator:Mapper\n\
+             Version:{}\n",
+            title, version
+        )
+    }
+
+    fn set(files: &[(&str, String)]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, content) in files {
+            fs::write(dir.path().join(name), content).unwrap();
+        }
+        dir
+    }
+
+    fn plan(
+        dir: &tempfile::TempDir,
+        title: &str,
+        rename_files: bool,
+    ) -> Result<ChangePlan, String> {
+        let (maps, osb_files) = load_metadata_set(dir.path().to_str().unwrap())?;
+        let mut metadata = BeatmapMetadata::from(&maps[0].beatmap.metadata);
+        metadata.title = title.to_string();
+        plan_metadata_fix(dir.path(), &maps, &osb_files, &metadata, rename_files)
+    }
+
+    fn file_names(dir: &tempfile::TempDir) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn rewrites_and_renames_every_difficulty() {
+        let dir = set(&[
+            ("a.osu", osu("Old", "Easy")),
+            ("b.osu", osu("Old", "Hard")),
+            ("sb.osb", "[Events]\n".to_string()),
+        ]);
+        plan(&dir, "New", true).unwrap().execute().unwrap();
+
+        assert_eq!(
+            file_names(&dir),
+            [
+                "Artist - New (Mapper) [Easy].osu",
+                "Artist - New (Mapper) [Hard].osu",
+                "Artist - New (Mapper).osb",
+            ]
+        );
+        let content = fs::read_to_string(dir.path().join("Artist - New (Mapper) [Hard].osu"));
+        assert!(content.unwrap().contains("Title:New"));
+    }
+
+    #[test]
+    fn without_renaming_only_contents_change() {
+        let dir = set(&[("a.osu", osu("Old", "Easy"))]);
+        let plan = plan(&dir, "New", false).unwrap();
+        assert_eq!(plan.count(FileAction::Modify), 1);
+        assert_eq!(plan.count(FileAction::Rename), 0);
+    }
+
+    #[test]
+    fn rejects_difficulties_that_would_share_a_name() {
+        let dir = set(&[("a.osu", osu("Old", "Hard")), ("b.osu", osu("Old", "HARD"))]);
+        assert_eq!(
+            plan(&dir, "New", true).err(),
+            Some(
+                "More than one difficulty would be named Artist - New (Mapper) [HARD].osu"
+                    .to_string()
+            )
+        );
+        // Without renaming the names don't matter.
+        assert!(plan(&dir, "New", false).is_ok());
+    }
+
+    #[test]
+    fn rejects_renaming_over_another_file() {
+        let dir = set(&[
+            ("a.osu", osu("Old", "Easy")),
+            ("Artist - New (Mapper) [Easy].osu", osu("Other", "Other")),
+        ]);
+        let (maps, osb_files) = load_metadata_set(dir.path().to_str().unwrap()).unwrap();
+        let mut metadata = BeatmapMetadata::from(&maps[0].beatmap.metadata);
+        metadata.title = "New".to_string();
+        let maps: Vec<MapFile> = maps.into_iter().filter(|m| m.name == "a.osu").collect();
+        assert_eq!(
+            plan_metadata_fix(dir.path(), &maps, &osb_files, &metadata, true).err(),
+            Some("Artist - New (Mapper) [Easy].osu already exists".to_string())
+        );
+    }
+
+    #[test]
+    fn renames_when_only_case_differs() {
+        let dir = set(&[("artist - old (mapper) [easy].osu", osu("Old", "Easy"))]);
+        let plan = plan(&dir, "Old", true).unwrap();
+        assert_eq!(plan.count(FileAction::Rename), 1);
+        plan.execute().unwrap();
+        assert_eq!(file_names(&dir), ["Artist - Old (Mapper) [Easy].osu"]);
+    }
+
+    #[test]
+    fn leaves_several_osb_files_alone() {
+        let dir = set(&[
+            ("a.osu", osu("Old", "Easy")),
+            ("one.osb", "[Events]\n".to_string()),
+            ("two.osb", "[Events]\n".to_string()),
+        ]);
+        plan(&dir, "Old", true).unwrap().execute().unwrap();
+        assert_eq!(
+            file_names(&dir),
+            ["Artist - Old (Mapper) [Easy].osu", "one.osb", "two.osb"]
+        );
+    }
+}
@@ -1,7 +1,33 @@
+pub mod backup;
 pub mod beatmap;
+pub mod checks;
 pub mod clone;
+pub mod collection;
+pub mod customize;
+pub mod difficulty;
 pub mod download;
+pub mod export;
+pub mod import;
+pub mod metadata;
+pub mod replay;
+pub mod scores;
+pub mod set_files;
+pub mod transform;
+pub mod watcher;
 
+pub use backup::*;
 pub use beatmap::*;
+pub use checks::*;
 pub use clone::*;
+pub use collection::*;
+pub use customize::*;
+pub use difficulty::*;
 pub use download::*;
+pub use export::*;
+pub use import::*;
+pub use metadata::*;
+pub use replay::*;
+pub use scores::*;
+pub use set_files::*;
+pub use transform::*;
+pub use watcher::*;
@@ -0,0 +1,44 @@
+use crate::commands::beatmap::with_songs_index;
+use crate::models::beatmap::Beatmap;
+use crate::models::replay::Replay;
+use crate::models::songs_index::SongsIndexState;
+use serde::Serialize;
+use std::path::Path;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ReplayWithBeatmap {
+    pub replay: Replay,
+    pub beatmap_path: Option<String>,
+    pub beatmap: Option<Beatmap>,
+}
+
+#[tauri::command]
+pub fn read_replay(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SongsIndexState>,
+    base_path: String,
+    replay_path: String,
+) -> Result<ReplayWithBeatmap, String> {
+    let replay = Replay::from_file(Path::new(&replay_path))?;
+
+    // The replay only records the beatmap's MD5, so the index is used to find its .osu.
+    let beatmap_path = with_songs_index(&app, &state, &base_path, |index| {
+        index
+            .find_by_md5(&replay.score.beatmap_md5)
+            .map(|(set, difficulty)| {
+                Path::new(&base_path)
+                    .join(&set.folder_name)
+                    .join(&difficulty.file_name)
+            })
+    })?;
+    let beatmap = beatmap_path
+        .as_deref()
+        .map(Beatmap::from_file)
+        .transpose()?;
+
+    Ok(ReplayWithBeatmap {
+        replay,
+        beatmap_path: beatmap_path.map(|path| path.display().to_string()),
+        beatmap,
+    })
+}
@@ -0,0 +1,19 @@
+use crate::models::scores_db::{scores_db_path, Score, ScoresDb};
+use std::collections::HashMap;
+use std::path::Path;
+
+#[tauri::command]
+pub fn get_local_scores(
+    base_path: String,
+    beatmap_md5s: Vec<String>,
+) -> Result<HashMap<String, Vec<Score>>, String> {
+    let db = ScoresDb::from_file(&scores_db_path(Path::new(&base_path))?)?;
+
+    Ok(beatmap_md5s
+        .into_iter()
+        .map(|md5| {
+            let scores = db.scores_for(&md5);
+            (md5, scores)
+        })
+        .collect())
+}
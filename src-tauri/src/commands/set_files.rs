@@ -0,0 +1,193 @@
+use crate::commands::backup::backup_store;
+use crate::commands::clone::is_skin_file;
+use crate::utils::files::list_files;
+use crate::utils::references::{clean_path, FileReference, SetReferences, SAMPLE_EXTENSIONS};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Debug, Clone)]
+pub struct UnusedFile {
+    pub path: String,
+    pub size: u64,
+    pub skin_element: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct CaseMismatch {
+    pub referenced: String,
+    pub actual: String,
+    pub sources: Vec<String>,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct SetFileReport {
+    pub unused: Vec<UnusedFile>,
+    pub missing: Vec<FileReference>,
+    // Indexed hitsounds with no custom sample, which fall back to the skin's.
+    pub skin_fallback: Vec<FileReference>,
+    pub case_mismatches: Vec<CaseMismatch>,
+    pub deleted: Vec<String>,
+    pub backup_id: Option<String>,
+}
+
+fn relative_path(folder: &Path, path: &Path) -> Result<String, String> {
+    Ok(path
+        .strip_prefix(folder)
+        .map_err(|_| format!("Invalid file path: {}", path.display()))?
+        .to_string_lossy()
+        .replace('\\', "/"))
+}
+
+pub fn set_file_report(folder: &Path) -> Result<SetFileReport, String> {
+    let references = SetReferences::scan(folder)?;
+    let mut report = SetFileReport::default();
+
+    // Lowercased relative path -> path as it exists on disk.
+    let mut on_disk: HashMap<String, String> = HashMap::new();
+    for path in list_files(folder)? {
+        let relative = relative_path(folder, &path)?;
+        on_disk.insert(relative.to_lowercase(), relative.clone());
+
+        if references.is_referenced(&relative) {
+            continue;
+        }
+        let file_name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default();
+        report.unused.push(UnusedFile {
+            size: fs::metadata(&path).map(|m| m.len()).unwrap_or(0),
+            skin_element: !relative.contains('/') && is_skin_file(file_name),
+            path: relative,
+        });
+    }
+
+    for reference in references.files() {
+        let written = clean_path(&reference.path);
+        match on_disk.get(&written.to_lowercase()) {
+            None => report.missing.push(reference.clone()),
+            Some(actual) if *actual != written => report.case_mismatches.push(CaseMismatch {
+                referenced: written,
+                actual: actual.clone(),
+                sources: reference.sources.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+
+    for reference in references.hitsounds() {
+        let exists = SAMPLE_EXTENSIONS
+            .iter()
+            .any(|ext| on_disk.contains_key(&format!("{}.{}", reference.path, ext)));
+        if !exists {
+            report.skin_fallback.push(reference.clone());
+        }
+    }
+
+    report.unused.sort_by(|a, b| a.path.cmp(&b.path));
+    report.missing.sort_by(|a, b| a.path.cmp(&b.path));
+    report.skin_fallback.sort_by(|a, b| a.path.cmp(&b.path));
+    report
+        .case_mismatches
+        .sort_by(|a, b| a.referenced.cmp(&b.referenced));
+    Ok(report)
+}
+
+#[tauri::command]
+pub fn analyze_set_files(
+    app: tauri::AppHandle,
+    beatmap_folder: String,
+    delete_unused: bool,
+) -> Result<SetFileReport, String> {
+    let folder = Path::new(&beatmap_folder);
+    if !folder.is_dir() {
+        return Err(format!("Folder not found: {}", beatmap_folder));
+    }
+
+    let mut report = set_file_report(folder)?;
+    if !delete_unused {
+        return Ok(report);
+    }
+
+    // Skin elements are used by the beatmap skin even though no .osu names them.
+    let targets: Vec<PathBuf> = report
+        .unused
+        .iter()
+        .filter(|file| !file.skin_element)
+        .map(|file| folder.join(&file.path))
+        .collect();
+    if targets.is_empty() {
+        return Ok(report);
+    }
+
+    let snapshot = backup_store(&app)?.snapshot(folder, "delete_unused", &targets)?;
+    for path in &targets {
+        fs::remove_file(path).map_err(|e| format!("Failed to delete {}: {}", path.display(), e))?;
+        report.deleted.push(relative_path(folder, path)?);
+    }
+    report
+        .unused
+        .retain(|file| !report.deleted.contains(&file.path));
+    report.backup_id = Some(snapshot.id);
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_map(folder: &Path, hit_objects: &str) {
+        let content = format!(
+            "osu file format v14\n\n[General]\nAudioFilename: audio.mp3\nSampleSet: Soft\n\n\
+             [Difficulty]\nSliderMultiplier:1.4\nSliderTickRate:1\n\n\
+             [TimingPoints]\n0,500,4,2,2,100,1,0\n\n[HitObjects]\n{}\n",
+            hit_objects
+        );
+        fs::write(folder.join("map.osu"), content).unwrap();
+        fs::write(folder.join("audio.mp3"), "").unwrap();
+    }
+
+    fn paths(references: &[FileReference]) -> Vec<&str> {
+        references.iter().map(|r| r.path.as_str()).collect()
+    }
+
+    #[test]
+    fn indexed_samples_fall_back_to_skin() {
+        let dir = tempfile::tempdir().unwrap();
+        write_map(
+            dir.path(),
+            "256,192,1000,1,8,0:0:0:0:\n256,192,1500,1,0,0:0:0:0:custom.wav",
+        );
+        fs::write(dir.path().join("soft-hitclap2.ogg"), "").unwrap();
+
+        let report = set_file_report(dir.path()).unwrap();
+        assert_eq!(paths(&report.missing), vec!["custom.wav"]);
+        assert_eq!(paths(&report.skin_fallback), vec!["soft-hitnormal2"]);
+        assert!(report.unused.is_empty());
+    }
+
+    #[test]
+    fn slider_ticks_only_when_the_slider_has_them() {
+        let dir = tempfile::tempdir().unwrap();
+        // 140px between ticks, so only the longer slider has any.
+        write_map(
+            dir.path(),
+            "100,100,1000,2,0,L|200:100,1,100\n100,100,2000,2,0,L|400:100,1,300",
+        );
+        let report = set_file_report(dir.path()).unwrap();
+        assert_eq!(
+            paths(&report.skin_fallback),
+            vec!["soft-hitnormal2", "soft-sliderslide2", "soft-slidertick2"]
+        );
+
+        write_map(dir.path(), "100,100,1000,2,0,L|200:100,1,100");
+        let report = set_file_report(dir.path()).unwrap();
+        assert_eq!(
+            paths(&report.skin_fallback),
+            vec!["soft-hitnormal2", "soft-sliderslide2"]
+        );
+    }
+}
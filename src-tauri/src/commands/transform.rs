@@ -0,0 +1,83 @@
+use crate::commands::backup::backup_store;
+use crate::commands::list_osu_files;
+use crate::models::beatmap::Beatmap;
+use crate::transforms::pipeline::{available_transforms, Pipeline, TransformInfo};
+use crate::utils::plan::{ChangePlan, FileAction, PlanResult};
+use std::path::Path;
+
+pub fn plan_pipeline_for_files(
+    beatmap_folder: &str,
+    osu_files: Vec<String>,
+    pipeline: &Pipeline,
+    create_backup: bool,
+) -> Result<ChangePlan, String> {
+    pipeline.validate()?;
+
+    let folder = Path::new(beatmap_folder);
+    let osu_files = if osu_files.is_empty() {
+        list_osu_files(beatmap_folder.to_string())?
+    } else {
+        osu_files
+    };
+
+    if osu_files.is_empty() {
+        return Err("No .osu files found in beatmap".to_string());
+    }
+
+    let mut plan = ChangePlan::new();
+    for file_name in &osu_files {
+        let path = folder.join(file_name);
+        let mut beatmap = Beatmap::from_file(&path)?;
+        pipeline
+            .apply(&mut beatmap)
+            .map_err(|e| format!("{}: {}", file_name, e))?;
+
+        if create_backup {
+            plan.copy(&path, &folder.join(format!("{}.backup", file_name)));
+        }
+        plan.write(&path, beatmap.to_osu_string())?;
+    }
+
+    Ok(plan)
+}
+
+pub fn processed_message(plan: &ChangePlan, dry_run: bool) -> String {
+    let count = plan.count(FileAction::Modify);
+    format!(
+        "{} {} file{}",
+        if dry_run {
+            "Would modify"
+        } else {
+            "Successfully processed"
+        },
+        count,
+        if count != 1 { "s" } else { "" }
+    )
+}
+
+#[tauri::command]
+pub fn list_transforms() -> Vec<TransformInfo> {
+    available_transforms()
+}
+
+#[tauri::command]
+pub fn preview_transforms(file_path: String, pipeline: Pipeline) -> Result<String, String> {
+    let mut beatmap = Beatmap::from_file(Path::new(&file_path))?;
+    pipeline.apply(&mut beatmap)?;
+    Ok(beatmap.to_osu_string())
+}
+
+#[tauri::command]
+pub fn apply_transforms(
+    app: tauri::AppHandle,
+    beatmap_folder: String,
+    osu_files: Vec<String>,
+    pipeline: Pipeline,
+    create_backup: bool,
+    dry_run: bool,
+) -> Result<PlanResult, String> {
+    let plan = plan_pipeline_for_files(&beatmap_folder, osu_files, &pipeline, create_backup)?
+        .with_backup(backup_store(&app)?, Path::new(&beatmap_folder), "transform");
+    let message = processed_message(&plan, dry_run);
+    plan.finish(dry_run, message)
+}
@@ -0,0 +1,175 @@
+use crate::models::songs_index::{is_set_folder, IndexChanges, SongsIndexState, SONGS_INDEX_FILE};
+use crate::utils::files::app_data_path;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::{Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{Emitter, Manager};
+
+pub const SONGS_INDEX_EVENT: &str = "songs-index-changed";
+const DEBOUNCE: Duration = Duration::from_millis(500);
+const MAX_BATCH: Duration = Duration::from_secs(5);
+
+#[derive(Default)]
+pub struct SongsWatcherState(Mutex<Option<RecommendedWatcher>>);
+
+impl SongsWatcherState {
+    fn lock(&self) -> Result<MutexGuard<'_, Option<RecommendedWatcher>>, String> {
+        self.0
+            .lock()
+            .map_err(|_| "Watcher is unavailable".to_string())
+    }
+}
+
+fn changed_folder(base: &Path, path: &Path) -> Option<String> {
+    match path.strip_prefix(base).ok()?.components().next()? {
+        Component::Normal(name) => name
+            .to_str()
+            .filter(|name| is_set_folder(name))
+            .map(|s| s.to_string()),
+        _ => None,
+    }
+}
+
+fn collect_folders(base: &Path, event: notify::Result<Event>, folders: &mut HashSet<String>) {
+    let Ok(event) = event else {
+        return;
+    };
+    if matches!(event.kind, EventKind::Access(_)) {
+        return;
+    }
+
+    folders.extend(
+        event
+            .paths
+            .iter()
+            .filter_map(|path| changed_folder(base, path)),
+    );
+}
+
+pub fn update_index(
+    app: &tauri::AppHandle,
+    base: &Path,
+    folders: &HashSet<String>,
+) -> Result<IndexChanges, String> {
+    let state = app.state::<SongsIndexState>();
+    let mut guard = state.lock()?;
+
+    let mut changes = IndexChanges::default();
+    let Some(index) = guard
+        .as_mut()
+        .filter(|index| Path::new(&index.songs_path) == base)
+    else {
+        return Ok(changes);
+    };
+
+    for folder in folders {
+        index.refresh_folder(folder, &mut changes);
+    }
+
+    if !changes.is_empty() {
+        index.save(&app_data_path(app, SONGS_INDEX_FILE)?)?;
+    }
+    Ok(changes)
+}
+
+// Events are batched until the folder has been quiet for a moment, since an
+// osu! import writes every file of a set separately.
+fn spawn_index_updater(
+    app: tauri::AppHandle,
+    base: PathBuf,
+    receiver: Receiver<notify::Result<Event>>,
+) {
+    thread::spawn(move || {
+        while let Ok(event) = receiver.recv() {
+            let started = Instant::now();
+            let mut folders = HashSet::new();
+            collect_folders(&base, event, &mut folders);
+
+            while started.elapsed() < MAX_BATCH {
+                match receiver.recv_timeout(DEBOUNCE) {
+                    Ok(event) => collect_folders(&base, event, &mut folders),
+                    Err(_) => break,
+                }
+            }
+
+            if folders.is_empty() {
+                continue;
+            }
+
+            match update_index(&app, &base, &folders) {
+                Ok(changes) if !changes.is_empty() => {
+                    let _ = app.emit(SONGS_INDEX_EVENT, changes);
+                }
+                Ok(_) => {}
+                Err(e) => eprintln!("[watcher] Failed to update songs index: {}", e),
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub fn watch_songs_folder(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SongsWatcherState>,
+    base_path: String,
+) -> Result<(), String> {
+    let base = PathBuf::from(&base_path);
+    if !base.is_dir() {
+        return Err(format!("Folder not found: {}", base_path));
+    }
+
+    let (sender, receiver) = channel();
+    let mut watcher = notify::recommended_watcher(sender)
+        .map_err(|e| format!("Failed to create watcher: {}", e))?;
+    watcher
+        .watch(&base, RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch {}: {}", base_path, e))?;
+
+    spawn_index_updater(app, base, receiver);
+
+    *state.lock()? = Some(watcher);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn unwatch_songs_folder(state: tauri::State<'_, SongsWatcherState>) -> Result<(), String> {
+    *state.lock()? = None;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{CreateKind, ModifyKind};
+
+    #[test]
+    fn changes_map_to_their_set_folder() {
+        let base = Path::new("/osu/Songs");
+        assert_eq!(
+            changed_folder(base, &base.join("1 Set").join("sb").join("star.png")),
+            Some("1 Set".to_string())
+        );
+        assert_eq!(changed_folder(base, base), None);
+        assert_eq!(changed_folder(base, Path::new("/elsewhere/1 Set")), None);
+    }
+
+    #[test]
+    fn import_temp_folders_are_ignored() {
+        let base = Path::new("/osu/Songs");
+        let temp = base.join(".import-abc123");
+        assert_eq!(changed_folder(base, &temp.join("map.osu")), None);
+
+        let mut folders = HashSet::new();
+        let event = Event::new(EventKind::Create(CreateKind::File))
+            .add_path(temp.join("map.osu"))
+            .add_path(base.join("1 Set").join("map.osu"));
+        collect_folders(base, Ok(event), &mut folders);
+        let event = Event::new(EventKind::Modify(ModifyKind::Any)).add_path(temp);
+        collect_folders(base, Ok(event), &mut folders);
+        assert_eq!(folders, HashSet::from(["1 Set".to_string()]));
+    }
+}
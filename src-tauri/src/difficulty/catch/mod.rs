@@ -0,0 +1,236 @@
+pub mod objects;
+
+use crate::difficulty::strain::{strain_decay, weighted_sum, StrainPeaks};
+use crate::difficulty::{apply_mods, difficulty_range};
+use crate::models::beatmap::Beatmap;
+use crate::models::mods::{self, HARD_ROCK};
+use objects::{catch_objects, catcher_width, CatchObject, CatchObjectKind};
+use serde::Serialize;
+
+const STAR_SCALING_FACTOR: f64 = 0.153;
+
+const SKILL_MULTIPLIER: f64 = 900.0;
+const STRAIN_DECAY_BASE: f64 = 0.2;
+const DECAY_WEIGHT: f64 = 0.94;
+const SECTION_LENGTH: f64 = 750.0;
+const NORMALIZED_HITOBJECT_RADIUS: f64 = 41.0;
+const ABSOLUTE_PLAYER_POSITIONING_ERROR: f64 = 16.0;
+const DIRECTION_CHANGE_BONUS: f64 = 21.0;
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct CatchDifficultyAttributes {
+    pub star_rating: f64,
+    pub approach_rate: f64,
+    pub max_combo: u32,
+    pub fruit_count: u32,
+    pub droplet_count: u32,
+    pub tiny_droplet_count: u32,
+    pub banana_count: u32,
+    pub hyper_dash_count: u32,
+    pub clock_rate: f64,
+    pub converted: bool,
+}
+
+struct Movement {
+    half_catcher_width: f64,
+    clock_rate: f64,
+    last_player_position: Option<f64>,
+    last_distance_moved: f64,
+    last_strain_time: f64,
+}
+
+impl Movement {
+    // How hard it is to move from `last` to `current`, with positions scaled so the
+    // catcher is the same size for every circle size.
+    fn strain_of(&mut self, current: &CatchObject, last: &CatchObject) -> f64 {
+        let scaling_factor = NORMALIZED_HITOBJECT_RADIUS / self.half_catcher_width;
+        let position = current.effective_x() * scaling_factor;
+        let last_position = last.effective_x() * scaling_factor;
+        // Strain is capped at the equivalent of 375 BPM streams.
+        let strain_time = ((current.start_time - last.start_time) / self.clock_rate).max(40.0);
+
+        let previous_player_position = *self.last_player_position.get_or_insert(last_position);
+        let reach = NORMALIZED_HITOBJECT_RADIUS - ABSOLUTE_PLAYER_POSITIONING_ERROR;
+        let mut player_position =
+            previous_player_position.clamp(position - reach, position + reach);
+        let distance_moved = player_position - previous_player_position;
+
+        let weighted_strain_time = strain_time + 13.0 + 3.0 / self.clock_rate;
+        let mut distance_addition = distance_moved.abs().powf(1.3) / 510.0;
+        let sqrt_strain = weighted_strain_time.sqrt();
+
+        if distance_moved.abs() > 0.1 {
+            if self.last_distance_moved.abs() > 0.1
+                && distance_moved.signum() != self.last_distance_moved.signum()
+            {
+                let bonus_factor = distance_moved.abs().min(50.0) / 50.0;
+                let antiflow_factor = (self.last_distance_moved.abs().min(70.0) / 70.0).max(0.38);
+                distance_addition += DIRECTION_CHANGE_BONUS / (self.last_strain_time + 16.0).sqrt()
+                    * bonus_factor
+                    * antiflow_factor
+                    * (1.0 - (weighted_strain_time / 1000.0).powi(3)).max(0.0);
+            }
+            // Some weight for every movement, so streams count too.
+            distance_addition += 12.5 * distance_moved.abs().min(NORMALIZED_HITOBJECT_RADIUS * 2.0)
+                / (NORMALIZED_HITOBJECT_RADIUS * 6.0)
+                / sqrt_strain;
+        }
+
+        // Edge dashes leave almost no room for error.
+        if last.distance_to_hyper_dash <= 20.0 {
+            let edge_dash_bonus = if last.hyper_dash {
+                // A hyperdash always lands in the right place.
+                player_position = position;
+                0.0
+            } else {
+                5.7
+            };
+            distance_addition *= 1.0
+                + edge_dash_bonus
+                    * ((20.0 - last.distance_to_hyper_dash) / 20.0)
+                    * ((strain_time * self.clock_rate).min(265.0) / 265.0).powf(1.5);
+        }
+
+        self.last_player_position = Some(player_position);
+        self.last_distance_moved = distance_moved;
+        self.last_strain_time = strain_time;
+        distance_addition / weighted_strain_time
+    }
+}
+
+pub fn calculate(beatmap: &Beatmap, mods: i32) -> CatchDifficultyAttributes {
+    let clock_rate = mods::clock_rate(mods);
+    let difficulty = apply_mods(&beatmap.difficulty, mods);
+    let objects = catch_objects(beatmap, &difficulty, mods & HARD_ROCK != 0);
+
+    let count = |kind: CatchObjectKind| objects.iter().filter(|o| o.kind == kind).count() as u32;
+    let preempt = difficulty_range(difficulty.approach_rate, 1800.0, 1200.0, 450.0) / clock_rate;
+    let mut attributes = CatchDifficultyAttributes {
+        approach_rate: if preempt > 1200.0 {
+            (1800.0 - preempt) / 120.0
+        } else {
+            (1200.0 - preempt) / 150.0 + 5.0
+        },
+        max_combo: count(CatchObjectKind::Fruit) + count(CatchObjectKind::Droplet),
+        fruit_count: count(CatchObjectKind::Fruit),
+        droplet_count: count(CatchObjectKind::Droplet),
+        tiny_droplet_count: count(CatchObjectKind::TinyDroplet),
+        banana_count: count(CatchObjectKind::Banana),
+        hyper_dash_count: objects.iter().filter(|o| o.hyper_dash).count() as u32,
+        clock_rate,
+        converted: beatmap.general.mode != 2,
+        ..Default::default()
+    };
+
+    // Only objects that add to the combo are rated.
+    let rated: Vec<&CatchObject> = objects
+        .iter()
+        .filter(|o| matches!(o.kind, CatchObjectKind::Fruit | CatchObjectKind::Droplet))
+        .collect();
+
+    // Above CS 5.5 the catcher is treated as smaller still, since nobody catches perfectly.
+    let half_catcher_width =
+        catcher_width(&difficulty) * 0.5 * (1.0 - (difficulty.circle_size - 5.5).max(0.0) * 0.0625);
+    let mut movement = Movement {
+        half_catcher_width,
+        clock_rate,
+        last_player_position: None,
+        last_distance_moved: 0.0,
+        last_strain_time: 0.0,
+    };
+
+    let mut peaks = StrainPeaks::with_section_length(SECTION_LENGTH);
+    let mut strain = 0.0;
+    for pair in rated.windows(2) {
+        let (last, current) = (pair[0], pair[1]);
+        let start_time = current.start_time / clock_rate;
+        let last_time = last.start_time / clock_rate;
+        let previous = strain;
+        peaks.start_object(start_time, |time| {
+            previous * strain_decay(STRAIN_DECAY_BASE, time - last_time)
+        });
+        strain = strain * strain_decay(STRAIN_DECAY_BASE, start_time - last_time)
+            + movement.strain_of(current, last) * SKILL_MULTIPLIER;
+        peaks.record(strain);
+    }
+
+    attributes.star_rating = weighted_sum(peaks.peaks(), DECAY_WEIGHT).sqrt() * STAR_SCALING_FACTOR;
+    attributes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::difficulty::fixtures::{self, assert_close};
+    use crate::models::mods::{DOUBLE_TIME, EASY};
+
+    // Fruits spread across the screen every 3/4 of a beat, with a short slider every tenth.
+    fn native() -> Beatmap {
+        let mut objects = String::new();
+        for i in 0..300 {
+            let x = [64, 200, 448, 300, 96, 256][i % 6];
+            let time = 1000 + i * 250;
+            if i % 10 == 9 {
+                let end = if x > 256 { x - 140 } else { x + 140 };
+                objects += &format!("{},192,{},2,0,L|{}:192,1,140\n", x, time, end);
+            } else {
+                objects += &format!("{},192,{},1,0,0:0:0:0:\n", x, time);
+            }
+        }
+        fixtures::map(2, 4.0, &objects)
+    }
+
+    fn converted() -> Beatmap {
+        let objects = fixtures::jump_objects(400)
+            + "100,100,70000,2,0,L|380:100,2,280\n256,192,72000,12,0,74000,0:0:0:0:\n";
+        fixtures::map(0, 4.0, &objects)
+    }
+
+    // Regression values from this calculator; any change to the skill should be deliberate.
+    fn assert_star_ratings(beatmap: &Beatmap, expected: [f64; 4]) {
+        for (mods, expected) in [0, HARD_ROCK, DOUBLE_TIME, EASY].into_iter().zip(expected) {
+            let label = format!("mods {} stars", mods);
+            assert_close(&label, calculate(beatmap, mods).star_rating, expected);
+        }
+    }
+
+    #[test]
+    fn native_reference_values() {
+        let beatmap = native();
+        let attributes = calculate(&beatmap, 0);
+        assert!(!attributes.converted);
+        assert_eq!(attributes.fruit_count, 330);
+        assert_eq!(attributes.droplet_count, 0);
+        assert_eq!(attributes.tiny_droplet_count, 90);
+        assert_eq!(attributes.hyper_dash_count, 9);
+        assert_eq!(attributes.max_combo, 330);
+        assert_star_ratings(&beatmap, [4.3280, 4.9575, 6.0259, 3.9372]);
+    }
+
+    #[test]
+    fn converted_reference_values() {
+        let beatmap = converted();
+        let attributes = calculate(&beatmap, 0);
+        assert!(attributes.converted);
+        assert_eq!(attributes.fruit_count, 403);
+        assert_eq!(attributes.droplet_count, 2);
+        assert_eq!(attributes.tiny_droplet_count, 12);
+        assert_eq!(attributes.banana_count, 33);
+        assert_eq!(attributes.hyper_dash_count, 399);
+        assert_eq!(attributes.max_combo, 405);
+        assert_star_ratings(&beatmap, [6.4664, 6.9648, 9.5638, 5.8846]);
+    }
+
+    #[test]
+    fn approach_rate_follows_mods() {
+        let beatmap = native();
+        assert_close("AR", calculate(&beatmap, 0).approach_rate, 9.0);
+        assert_close("HR AR", calculate(&beatmap, HARD_ROCK).approach_rate, 10.0);
+        assert_close("EZ AR", calculate(&beatmap, EASY).approach_rate, 4.5);
+        assert_close(
+            "DT AR",
+            calculate(&beatmap, DOUBLE_TIME).approach_rate,
+            31.0 / 3.0,
+        );
+    }
+}
@@ -0,0 +1,465 @@
+use crate::difficulty::slider::{SliderEventKind, SliderTiming};
+use crate::models::beatmap::{Beatmap, Difficulty};
+use crate::models::hitobject::HitObjectKind;
+
+pub const PLAYFIELD_WIDTH: f64 = 512.0;
+const CATCHER_BASE_SIZE: f64 = 106.75;
+pub const ALLOWED_CATCH_RANGE: f64 = 0.8;
+const BASE_DASH_SPEED: f64 = 1.0;
+const RNG_SEED: i32 = 1337;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CatchObjectKind {
+    Fruit,
+    Droplet,
+    TinyDroplet,
+    Banana,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CatchObject {
+    pub kind: CatchObjectKind,
+    pub start_time: f64,
+    pub x: f64,
+    pub x_offset: f64,
+    pub hyper_dash: bool,
+    // How much further the catcher could still have walked without needing a hyperdash.
+    pub distance_to_hyper_dash: f64,
+}
+
+impl CatchObject {
+    fn new(kind: CatchObjectKind, start_time: f64, x: f64) -> Self {
+        CatchObject {
+            kind,
+            start_time,
+            x,
+            x_offset: 0.0,
+            hyper_dash: false,
+            distance_to_hyper_dash: 0.0,
+        }
+    }
+
+    pub fn effective_x(&self) -> f64 {
+        (self.x + self.x_offset).clamp(0.0, PLAYFIELD_WIDTH)
+    }
+}
+
+// Top-level objects as converted; streams and showers keep their nested objects.
+enum CatchGroup {
+    Fruit(CatchObject),
+    JuiceStream {
+        start_time: f64,
+        last_control_x: f64,
+        nested: Vec<CatchObject>,
+    },
+    BananaShower(Vec<CatchObject>),
+}
+
+// The xorshift generator osu!stable seeds per map, needed to reproduce its offsets.
+struct LegacyRandom {
+    x: u32,
+    y: u32,
+    z: u32,
+    w: u32,
+}
+
+impl LegacyRandom {
+    fn new(seed: i32) -> Self {
+        LegacyRandom {
+            x: seed as u32,
+            y: 842_502_087,
+            z: 3_579_807_591,
+            w: 273_326_509,
+        }
+    }
+
+    fn next_u32(&mut self) -> u32 {
+        let t = self.x ^ (self.x << 11);
+        self.x = self.y;
+        self.y = self.z;
+        self.z = self.w;
+        self.w = self.w ^ (self.w >> 19) ^ t ^ (t >> 8);
+        self.w
+    }
+
+    fn next(&mut self) -> i32 {
+        (self.next_u32() & 0x7FFF_FFFF) as i32
+    }
+
+    fn next_double(&mut self) -> f64 {
+        self.next() as f64 / (i32::MAX as f64 + 1.0)
+    }
+
+    fn next_range(&mut self, lower: f64, upper: f64) -> i32 {
+        (lower + self.next_double() * (upper - lower)) as i32
+    }
+
+    fn next_bool(&mut self) -> bool {
+        self.next_u32() & 1 == 1
+    }
+}
+
+pub fn catcher_width(difficulty: &Difficulty) -> f64 {
+    let scale = 1.0 - 0.7 * (difficulty.circle_size - 5.0) / 5.0;
+    CATCHER_BASE_SIZE * scale.abs() * ALLOWED_CATCH_RANGE
+}
+
+fn juice_stream(
+    beatmap: &Beatmap,
+    difficulty: &Difficulty,
+    object: &crate::models::hitobject::HitObject,
+    slider: &crate::models::hitobject::Slider,
+) -> CatchGroup {
+    let timing = SliderTiming::new(beatmap, difficulty, object.time, object.position, slider);
+    let x = object.position.x;
+    let x_at = |progress: f64| x + timing.offset_at(progress).x;
+
+    let mut nested = Vec::new();
+    let events = timing.events(object.time);
+    for (i, event) in events.iter().enumerate() {
+        // Tiny droplets fill the gaps between the larger objects.
+        if let Some(last) = i.checked_sub(1).map(|i| events[i]) {
+            let since_last = (event.time as i64 - last.time as i64) as f64;
+            if since_last > 80.0 {
+                let mut spacing = since_last;
+                while spacing > 100.0 {
+                    spacing /= 2.0;
+                }
+                let mut t = spacing;
+                while t < since_last {
+                    let progress = last.path_progress
+                        + (t / since_last) * (event.path_progress - last.path_progress);
+                    nested.push(CatchObject::new(
+                        CatchObjectKind::TinyDroplet,
+                        t + last.time,
+                        x_at(progress),
+                    ));
+                    t += spacing;
+                }
+            }
+        }
+
+        let kind = match event.kind {
+            SliderEventKind::Tick => CatchObjectKind::Droplet,
+            SliderEventKind::LegacyLastTick => continue,
+            _ => CatchObjectKind::Fruit,
+        };
+        nested.push(CatchObject::new(
+            kind,
+            event.time,
+            x_at(event.path_progress),
+        ));
+    }
+
+    CatchGroup::JuiceStream {
+        start_time: object.time,
+        last_control_x: slider.control_points.last().map(|p| p.x).unwrap_or(x),
+        nested,
+    }
+}
+
+fn banana_shower(start_time: f64, end_time: f64) -> CatchGroup {
+    let duration = end_time - start_time;
+    let mut spacing = duration;
+    while spacing > 100.0 {
+        spacing /= 2.0;
+    }
+    let mut bananas = Vec::new();
+    if spacing > 0.0 {
+        let mut time = start_time;
+        while time <= end_time {
+            bananas.push(CatchObject::new(CatchObjectKind::Banana, time, 0.0));
+            time += spacing;
+        }
+    }
+    CatchGroup::BananaShower(bananas)
+}
+
+fn apply_random_offset(position: &mut f64, max_offset: f64, rng: &mut LegacyRandom) {
+    let right = rng.next_bool();
+    let amount = (rng.next_range(0.0, max_offset.max(0.0)) as f64).min(20.0);
+    if right {
+        if *position + amount <= PLAYFIELD_WIDTH {
+            *position += amount;
+        } else {
+            *position -= amount;
+        }
+    } else if *position - amount >= 0.0 {
+        *position -= amount;
+    } else {
+        *position += amount;
+    }
+}
+
+// Hard Rock nudges fruits further apart, the same way osu!stable did.
+fn apply_hard_rock_offset(
+    fruit: &mut CatchObject,
+    last_position: &mut Option<f64>,
+    last_start_time: &mut f64,
+    rng: &mut LegacyRandom,
+) {
+    let mut position = fruit.x;
+    let start_time = fruit.start_time;
+    let Some(last) = *last_position else {
+        *last_position = Some(position);
+        *last_start_time = start_time;
+        return;
+    };
+
+    let position_diff = position - last;
+    let time_diff = (start_time - *last_start_time) as i32;
+    if time_diff > 1000 {
+        *last_position = Some(position);
+        *last_start_time = start_time;
+        return;
+    }
+    if position_diff == 0.0 {
+        apply_random_offset(&mut position, time_diff as f64 / 4.0, rng);
+        fruit.x_offset = position - fruit.x;
+        return;
+    }
+
+    if position_diff.abs() < (time_diff / 3) as f64 {
+        if position_diff > 0.0 {
+            if position + position_diff < PLAYFIELD_WIDTH {
+                position += position_diff;
+            }
+        } else if position + position_diff > 0.0 {
+            position += position_diff;
+        }
+    }
+    fruit.x_offset = position - fruit.x;
+    *last_position = Some(position);
+    *last_start_time = start_time;
+}
+
+fn apply_position_offsets(groups: &mut [CatchGroup], hard_rock: bool) {
+    let mut rng = LegacyRandom::new(RNG_SEED);
+    let mut last_position = None;
+    let mut last_start_time = 0.0;
+
+    for group in groups {
+        match group {
+            CatchGroup::Fruit(fruit) => {
+                if hard_rock {
+                    apply_hard_rock_offset(
+                        fruit,
+                        &mut last_position,
+                        &mut last_start_time,
+                        &mut rng,
+                    );
+                }
+            }
+            CatchGroup::BananaShower(bananas) => {
+                for banana in bananas {
+                    banana.x_offset = rng.next_range(0.0, PLAYFIELD_WIDTH) as f64 - banana.x;
+                    // osu!stable also rolled each banana's type, rotation and colour.
+                    rng.next();
+                    rng.next();
+                    rng.next();
+                }
+            }
+            CatchGroup::JuiceStream {
+                start_time,
+                last_control_x,
+                nested,
+            } => {
+                last_position = Some(*last_control_x);
+                last_start_time = *start_time;
+                for object in nested {
+                    match object.kind {
+                        CatchObjectKind::TinyDroplet => {
+                            object.x_offset = (rng.next_range(-20.0, 20.0) as f64)
+                                .clamp(-object.x, PLAYFIELD_WIDTH - object.x);
+                        }
+                        // Droplet rotation.
+                        CatchObjectKind::Droplet => {
+                            rng.next();
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn apply_hyper_dashes(objects: &mut [CatchObject], difficulty: &Difficulty) {
+    let half_catcher_width = catcher_width(difficulty) / 2.0 / ALLOWED_CATCH_RANGE;
+    let mut palpable: Vec<&mut CatchObject> = objects
+        .iter_mut()
+        .filter(|o| matches!(o.kind, CatchObjectKind::Fruit | CatchObjectKind::Droplet))
+        .collect();
+
+    let mut last_direction = 0;
+    let mut last_excess = half_catcher_width;
+    for i in 0..palpable.len().saturating_sub(1) {
+        let next_x = palpable[i + 1].effective_x();
+        let next_time = palpable[i + 1].start_time;
+        let current = &mut palpable[i];
+
+        let direction = if next_x > current.effective_x() {
+            1
+        } else {
+            -1
+        };
+        // A quarter of a frame of leniency, as in osu!stable.
+        let time_to_next =
+            (next_time as i32 - current.start_time as i32) as f64 - 1000.0 / 60.0 / 4.0;
+        let distance_to_next = (next_x - current.effective_x()).abs()
+            - if last_direction == direction {
+                last_excess
+            } else {
+                half_catcher_width
+            };
+        let distance_to_hyper = time_to_next * BASE_DASH_SPEED - distance_to_next;
+
+        if distance_to_hyper < 0.0 {
+            current.hyper_dash = true;
+            last_excess = half_catcher_width;
+        } else {
+            current.distance_to_hyper_dash = distance_to_hyper;
+            last_excess = distance_to_hyper.clamp(0.0, half_catcher_width);
+        }
+        last_direction = direction;
+    }
+}
+
+// Every catchable object in time order, with offsets and hyperdashes applied.
+pub fn catch_objects(
+    beatmap: &Beatmap,
+    difficulty: &Difficulty,
+    hard_rock: bool,
+) -> Vec<CatchObject> {
+    let mut groups: Vec<CatchGroup> = beatmap
+        .hit_objects
+        .iter()
+        .map(|object| match &object.kind {
+            HitObjectKind::Slider(slider) => juice_stream(beatmap, difficulty, object, slider),
+            HitObjectKind::Spinner { end_time } | HitObjectKind::Hold { end_time } => {
+                banana_shower(object.time, *end_time)
+            }
+            HitObjectKind::Circle => CatchGroup::Fruit(CatchObject::new(
+                CatchObjectKind::Fruit,
+                object.time,
+                object.position.x,
+            )),
+        })
+        .collect();
+
+    apply_position_offsets(&mut groups, hard_rock);
+
+    let mut objects: Vec<CatchObject> = groups
+        .into_iter()
+        .flat_map(|group| match group {
+            CatchGroup::Fruit(fruit) => vec![fruit],
+            CatchGroup::JuiceStream { nested, .. } => nested,
+            CatchGroup::BananaShower(bananas) => bananas,
+        })
+        .collect();
+    objects.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+
+    apply_hyper_dashes(&mut objects, difficulty);
+    objects
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::difficulty::fixtures;
+
+    fn fruits(positions: &[(f64, f64)]) -> Vec<CatchObject> {
+        positions
+            .iter()
+            .map(|&(time, x)| CatchObject::new(CatchObjectKind::Fruit, time, x))
+            .collect()
+    }
+
+    fn hyper_dashes(objects: &[CatchObject]) -> Vec<bool> {
+        objects.iter().map(|o| o.hyper_dash).collect()
+    }
+
+    #[test]
+    fn catcher_width_follows_circle_size() {
+        let mut difficulty = Difficulty {
+            circle_size: 5.0,
+            ..Default::default()
+        };
+        assert!((catcher_width(&difficulty) - 106.75 * ALLOWED_CATCH_RANGE).abs() < 1e-9);
+        difficulty.circle_size = 4.0;
+        assert!((catcher_width(&difficulty) - 106.75 * 1.14 * ALLOWED_CATCH_RANGE).abs() < 1e-9);
+    }
+
+    #[test]
+    fn hyper_dashes_when_walking_is_too_slow() {
+        // At CS 4 half the catcher is 60.85 wide, and 200 ms leaves 195.83 ms to move.
+        let difficulty = Difficulty {
+            circle_size: 4.0,
+            ..Default::default()
+        };
+        let mut objects = fruits(&[(0.0, 0.0), (200.0, 250.0), (400.0, 250.0)]);
+        apply_hyper_dashes(&mut objects, &difficulty);
+        assert_eq!(hyper_dashes(&objects), vec![false, false, false]);
+        assert!((objects[0].distance_to_hyper_dash - 6.680).abs() < 1e-3);
+
+        let mut objects = fruits(&[(0.0, 0.0), (200.0, 300.0), (400.0, 300.0)]);
+        apply_hyper_dashes(&mut objects, &difficulty);
+        assert_eq!(hyper_dashes(&objects), vec![true, false, false]);
+    }
+
+    #[test]
+    fn hyper_dashes_carry_little_slack_forward() {
+        let difficulty = Difficulty {
+            circle_size: 4.0,
+            ..Default::default()
+        };
+        // The first move only just makes it, so the second can't count on the catcher's width.
+        let mut objects = fruits(&[(0.0, 0.0), (200.0, 250.0), (400.0, 500.0)]);
+        apply_hyper_dashes(&mut objects, &difficulty);
+        assert_eq!(hyper_dashes(&objects), vec![false, true, false]);
+
+        // Turning around resets it.
+        let mut objects = fruits(&[(0.0, 250.0), (200.0, 500.0), (400.0, 250.0)]);
+        apply_hyper_dashes(&mut objects, &difficulty);
+        assert_eq!(hyper_dashes(&objects), vec![false, false, false]);
+    }
+
+    #[test]
+    fn hyper_dashes_skip_tiny_droplets_and_bananas() {
+        let difficulty = Difficulty {
+            circle_size: 4.0,
+            ..Default::default()
+        };
+        let mut objects = fruits(&[(0.0, 0.0), (200.0, 300.0)]);
+        objects.insert(
+            1,
+            CatchObject::new(CatchObjectKind::TinyDroplet, 100.0, 0.0),
+        );
+        objects.insert(2, CatchObject::new(CatchObjectKind::Banana, 150.0, 0.0));
+        apply_hyper_dashes(&mut objects, &difficulty);
+        assert_eq!(hyper_dashes(&objects), vec![true, false, false, false]);
+    }
+
+    #[test]
+    fn converts_standard_objects() {
+        let objects = "256,192,1000,1,0,0:0:0:0:\n\
+            100,192,2000,2,0,L|380:192,2,280\n\
+            256,192,5000,12,0,6000,0:0:0:0:\n";
+        let beatmap = fixtures::map(0, 4.0, objects);
+        let objects = catch_objects(&beatmap, &beatmap.difficulty, false);
+        let count = |kind: CatchObjectKind| objects.iter().filter(|o| o.kind == kind).count();
+
+        // The slider's head, repeat and tail are fruits with a droplet per tick between.
+        assert_eq!(count(CatchObjectKind::Fruit), 4);
+        assert_eq!(count(CatchObjectKind::Droplet), 2);
+        assert!(count(CatchObjectKind::TinyDroplet) > 0);
+        assert!(count(CatchObjectKind::Banana) > 0);
+        assert!(objects
+            .windows(2)
+            .all(|w| w[0].start_time <= w[1].start_time));
+        assert!(objects
+            .iter()
+            .filter(|o| o.kind == CatchObjectKind::Banana)
+            .all(|o| o.start_time >= 5000.0 && o.start_time <= 6000.0));
+    }
+}
@@ -0,0 +1,38 @@
+use crate::models::beatmap::Beatmap;
+
+// A 180 BPM map with OD 8, AR 9 and HP 5, in any mode.
+pub fn map(mode: u8, circle_size: f64, objects: &str) -> Beatmap {
+    let content = format!(
+        "osu file format v14\n\n[General]\nStackLeniency: 0.7\nMode: {}\n\n\
+         [Difficulty]\nHPDrainRate:5\nCircleSize:{}\nOverallDifficulty:8\nApproachRate:9\n\
+         SliderMultiplier:1.4\nSliderTickRate:1\n\n\
+         [TimingPoints]\n0,333.333,4,2,0,100,1,0\n\n[HitObjects]\n{}",
+        mode, circle_size, objects
+    );
+    Beatmap::parse(&content).unwrap()
+}
+
+// Full-screen 1/2 jumps, with a clap on every third to give converts some colour.
+pub fn jump_objects(count: i32) -> String {
+    let mut objects = String::new();
+    for i in 0..count {
+        let (x, y) = if i % 2 == 0 {
+            (60, 60 + (i % 7) * 40)
+        } else {
+            (450, 320 - (i % 5) * 40)
+        };
+        let sound = if i % 3 == 0 { 2 } else { 0 };
+        objects += &format!("{},{},{},1,{},0:0:0:0:\n", x, y, 1000 + i * 167, sound);
+    }
+    objects
+}
+
+pub fn assert_close(name: &str, actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() <= expected.abs() * 1e-3,
+        "{}: expected {}, got {}",
+        name,
+        expected,
+        actual
+    );
+}
@@ -0,0 +1,239 @@
+use crate::difficulty::strain::{weighted_sum, StrainPeaks};
+use crate::models::beatmap::Beatmap;
+use crate::models::hitobject::HitObjectKind;
+use crate::models::mods;
+use serde::Serialize;
+
+const DIFFICULTY_MULTIPLIER: f64 = 0.018;
+const INDIVIDUAL_DECAY_BASE: f64 = 0.125;
+const OVERALL_DECAY_BASE: f64 = 0.3;
+// Releases closer together than this (in ms) are about as easy as a single release.
+const RELEASE_THRESHOLD: f64 = 24.0;
+const DECAY_WEIGHT: f64 = 0.9;
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct ManiaDifficultyAttributes {
+    pub star_rating: f64,
+    pub key_count: u32,
+    pub max_combo: u32,
+    pub note_count: u32,
+    pub hold_note_count: u32,
+    pub clock_rate: f64,
+}
+
+struct ManiaNote {
+    column: usize,
+    start_time: f64,
+    end_time: f64,
+}
+
+fn apply_decay(value: f64, delta_time: f64, decay_base: f64) -> f64 {
+    value * decay_base.powf(delta_time / 1000.0)
+}
+
+// Each column keeps its own strain for jacks and trills, on top of an overall
+// strain for the density of the whole map.
+struct Strain {
+    start_times: Vec<f64>,
+    end_times: Vec<f64>,
+    individual_strains: Vec<f64>,
+    individual_strain: f64,
+    overall_strain: f64,
+}
+
+impl Strain {
+    fn new(key_count: usize) -> Self {
+        Strain {
+            start_times: vec![0.0; key_count],
+            end_times: vec![0.0; key_count],
+            individual_strains: vec![0.0; key_count],
+            individual_strain: 0.0,
+            overall_strain: 1.0,
+        }
+    }
+
+    fn strain_of(&mut self, note: &ManiaNote, delta_time: f64) -> f64 {
+        let (start_time, end_time) = (note.start_time, note.end_time);
+        let mut is_overlapping = false;
+        let mut closest_end_time = (end_time - start_time).abs();
+        // Everything is a little harder while another note is being held.
+        let mut hold_factor = 1.0;
+        for &other_end in &self.end_times {
+            is_overlapping |= other_end - 1.0 > start_time && end_time - 1.0 > other_end;
+            if other_end - 1.0 > end_time {
+                hold_factor = 1.25;
+            }
+            closest_end_time = closest_end_time.min((end_time - other_end).abs());
+        }
+
+        // Releasing a hold awkwardly is harder, unless another note is released with it.
+        let hold_addition = if is_overlapping {
+            1.0 / (1.0 + (0.5 * (RELEASE_THRESHOLD - closest_end_time)).exp())
+        } else {
+            0.0
+        };
+
+        let column = note.column;
+        self.individual_strains[column] = apply_decay(
+            self.individual_strains[column],
+            start_time - self.start_times[column],
+            INDIVIDUAL_DECAY_BASE,
+        ) + 2.0 * hold_factor;
+        // Within a chord, the hardest column counts.
+        self.individual_strain = if delta_time <= 1.0 {
+            self.individual_strain.max(self.individual_strains[column])
+        } else {
+            self.individual_strains[column]
+        };
+
+        self.overall_strain = apply_decay(self.overall_strain, delta_time, OVERALL_DECAY_BASE)
+            + (1.0 + hold_addition) * hold_factor;
+
+        self.start_times[column] = start_time;
+        self.end_times[column] = end_time;
+        self.individual_strain + self.overall_strain
+    }
+
+    fn initial_strain(&self, delta_time: f64) -> f64 {
+        apply_decay(self.individual_strain, delta_time, INDIVIDUAL_DECAY_BASE)
+            + apply_decay(self.overall_strain, delta_time, OVERALL_DECAY_BASE)
+    }
+}
+
+// Only native mania maps can be rated; converting standard maps isn't supported.
+pub fn calculate(beatmap: &Beatmap, mods: i32) -> ManiaDifficultyAttributes {
+    let clock_rate = mods::clock_rate(mods);
+    let key_count = (beatmap.difficulty.circle_size.round() as u32).clamp(1, 18);
+
+    // osu!stable sorted by rounded start time, keeping the file order for ties.
+    let mut objects: Vec<_> = beatmap.hit_objects.iter().collect();
+    objects.sort_by_key(|object| object.time.round() as i64);
+    let notes: Vec<ManiaNote> = objects
+        .into_iter()
+        .map(|object| ManiaNote {
+            column: object.column(key_count) as usize,
+            start_time: object.time / clock_rate,
+            end_time: match object.kind {
+                HitObjectKind::Hold { end_time } => end_time / clock_rate,
+                _ => object.time / clock_rate,
+            },
+        })
+        .collect();
+
+    let hold_note_count = beatmap.hit_objects.iter().filter(|o| o.is_hold()).count() as u32;
+    let max_combo = beatmap
+        .hit_objects
+        .iter()
+        .map(|object| match object.kind {
+            HitObjectKind::Hold { end_time } => 1 + ((end_time - object.time) / 100.0) as u32,
+            _ => 1,
+        })
+        .sum();
+
+    let mut strain = Strain::new(key_count as usize);
+    let mut peaks = StrainPeaks::default();
+    for pair in notes.windows(2) {
+        let (last, note) = (&pair[0], &pair[1]);
+        peaks.start_object(note.start_time, |time| {
+            strain.initial_strain(time - last.start_time)
+        });
+        let value = strain.strain_of(note, note.start_time - last.start_time);
+        peaks.record(value);
+    }
+
+    ManiaDifficultyAttributes {
+        star_rating: weighted_sum(peaks.peaks(), DECAY_WEIGHT) * DIFFICULTY_MULTIPLIER,
+        key_count,
+        max_combo,
+        note_count: beatmap.hit_objects.len() as u32 - hold_note_count,
+        hold_note_count,
+        clock_rate,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::difficulty::fixtures::{self, assert_close};
+    use crate::models::mods::{DOUBLE_TIME, EASY, HARD_ROCK};
+
+    fn note(column: usize, start_time: f64, end_time: f64) -> ManiaNote {
+        ManiaNote {
+            column,
+            start_time,
+            end_time,
+        }
+    }
+
+    // A 4K roll with a 500 ms hold on every tenth note.
+    fn chart(holds: bool) -> Beatmap {
+        let mut objects = String::new();
+        for i in 0..300 {
+            let x = [64, 192, 320, 448][i % 4];
+            let time = 1000 + i * 120;
+            if holds && i % 10 == 0 {
+                objects += &format!("{},192,{},128,0,{}:0:0:0:0:\n", x, time, time + 500);
+            } else {
+                objects += &format!("{},192,{},1,0,0:0:0:0:\n", x, time);
+            }
+        }
+        fixtures::map(3, 4.0, &objects)
+    }
+
+    #[test]
+    fn notes_are_harder_while_holding() {
+        let mut strain = Strain::new(2);
+        assert_close("hold", strain.strain_of(&note(0, 0.0, 1000.0), 0.0), 4.0);
+        // Column 1's strain gets the hold factor, and the overall strain decays over 100 ms.
+        let expected = 2.0 * 1.25 + 2.0 * OVERALL_DECAY_BASE.powf(0.1) + 1.25;
+        assert_close(
+            "tap",
+            strain.strain_of(&note(1, 100.0, 100.0), 100.0),
+            expected,
+        );
+    }
+
+    #[test]
+    fn releasing_apart_is_harder_than_together() {
+        let release = |end_time: f64| {
+            let mut strain = Strain::new(2);
+            strain.strain_of(&note(0, 0.0, 1000.0), 0.0);
+            strain.strain_of(&note(1, 200.0, end_time), 200.0)
+        };
+        let together = release(1000.0);
+        let apart = release(1500.0);
+        assert!(apart > together);
+        // Almost a full extra point of overall strain.
+        assert!((apart - together - 1.0).abs() < 0.01);
+    }
+
+    // Regression values from this calculator; any change to the strain should be deliberate.
+    #[test]
+    fn native_reference_values() {
+        let beatmap = chart(true);
+        let attributes = calculate(&beatmap, 0);
+        assert_eq!(attributes.key_count, 4);
+        assert_eq!(attributes.note_count, 270);
+        assert_eq!(attributes.hold_note_count, 30);
+        assert_eq!(attributes.max_combo, 270 + 30 * 6);
+        assert_close("stars", attributes.star_rating, 2.1912);
+        assert_close(
+            "DT stars",
+            calculate(&beatmap, DOUBLE_TIME).star_rating,
+            3.0497,
+        );
+        for mods in [HARD_ROCK, EASY] {
+            assert_eq!(
+                calculate(&beatmap, mods).star_rating,
+                attributes.star_rating
+            );
+        }
+        assert!(attributes.star_rating > calculate(&chart(false), 0).star_rating);
+    }
+
+    #[test]
+    fn standard_maps_are_not_converted() {
+        let beatmap = fixtures::map(0, 4.0, &fixtures::jump_objects(10));
+        assert!(crate::difficulty::calculate(&beatmap, 3, 0).is_err());
+    }
+}
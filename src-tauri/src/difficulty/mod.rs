@@ -0,0 +1,99 @@
+pub mod catch;
+#[cfg(test)]
+mod fixtures;
+pub mod mania;
+pub mod osu;
+pub mod slider;
+pub mod strain;
+pub mod taiko;
+
+use crate::models::beatmap::{Beatmap, Difficulty};
+use crate::models::mods::{EASY, HARD_ROCK};
+use catch::CatchDifficultyAttributes;
+use mania::ManiaDifficultyAttributes;
+use osu::OsuDifficultyAttributes;
+use serde::Serialize;
+use taiko::TaikoDifficultyAttributes;
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum DifficultyAttributes {
+    Osu(OsuDifficultyAttributes),
+    Taiko(TaikoDifficultyAttributes),
+    Catch(CatchDifficultyAttributes),
+    Mania(ManiaDifficultyAttributes),
+}
+
+impl DifficultyAttributes {
+    pub fn star_rating(&self) -> f64 {
+        match self {
+            DifficultyAttributes::Osu(a) => a.star_rating,
+            DifficultyAttributes::Taiko(a) => a.star_rating,
+            DifficultyAttributes::Catch(a) => a.star_rating,
+            DifficultyAttributes::Mania(a) => a.star_rating,
+        }
+    }
+
+    pub fn max_combo(&self) -> u32 {
+        match self {
+            DifficultyAttributes::Osu(a) => a.max_combo,
+            DifficultyAttributes::Taiko(a) => a.max_combo,
+            DifficultyAttributes::Catch(a) => a.max_combo,
+            DifficultyAttributes::Mania(a) => a.max_combo,
+        }
+    }
+}
+
+// Maps a 0-10 difficulty setting onto a value that is `mid` at 5 and `min`/`max` at the ends.
+pub fn difficulty_range(difficulty: f64, min: f64, mid: f64, max: f64) -> f64 {
+    if difficulty > 5.0 {
+        mid + (max - mid) * (difficulty - 5.0) / 5.0
+    } else if difficulty < 5.0 {
+        mid - (mid - min) * (5.0 - difficulty) / 5.0
+    } else {
+        mid
+    }
+}
+
+// Hard Rock and Easy change the map's settings before anything else is calculated.
+pub fn apply_mods(difficulty: &Difficulty, mods: i32) -> Difficulty {
+    let mut adjusted = difficulty.clone();
+    let scale = |value: f64, ratio: f64| (value * ratio).min(10.0);
+
+    if mods & HARD_ROCK != 0 {
+        adjusted.circle_size = scale(difficulty.circle_size, 1.3);
+        adjusted.approach_rate = scale(difficulty.approach_rate, 1.4);
+        adjusted.overall_difficulty = scale(difficulty.overall_difficulty, 1.4);
+        adjusted.hp_drain_rate = scale(difficulty.hp_drain_rate, 1.4);
+    } else if mods & EASY != 0 {
+        adjusted.circle_size *= 0.5;
+        adjusted.approach_rate *= 0.5;
+        adjusted.overall_difficulty *= 0.5;
+        adjusted.hp_drain_rate *= 0.5;
+    }
+
+    adjusted
+}
+
+// Rates the map in `mode`. Standard maps can be converted to taiko and catch the same
+// way the game does; every other cross-mode combination is rejected.
+pub fn calculate(beatmap: &Beatmap, mode: u8, mods: i32) -> Result<DifficultyAttributes, String> {
+    let native = beatmap.general.mode;
+    if native != mode && !(native == 0 && (mode == 1 || mode == 2)) {
+        return Err(match (native, mode) {
+            (0, 3) => "Converting osu!standard maps to mania isn't supported".to_string(),
+            _ => format!(
+                "Mode {} beatmaps can't be converted to mode {}",
+                native, mode
+            ),
+        });
+    }
+
+    match mode {
+        0 => Ok(DifficultyAttributes::Osu(osu::calculate(beatmap, mods))),
+        1 => Ok(DifficultyAttributes::Taiko(taiko::calculate(beatmap, mods))),
+        2 => Ok(DifficultyAttributes::Catch(catch::calculate(beatmap, mods))),
+        3 => Ok(DifficultyAttributes::Mania(mania::calculate(beatmap, mods))),
+        _ => Err(format!("Unknown game mode: {}", mode)),
+    }
+}
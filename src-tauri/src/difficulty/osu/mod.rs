@@ -0,0 +1,262 @@
+pub mod objects;
+pub mod performance;
+pub mod skills;
+
+use crate::difficulty::{apply_mods, difficulty_range};
+use crate::models::beatmap::Beatmap;
+use crate::models::mods::{self, FLASHLIGHT, HIDDEN, RELAX};
+use objects::{difficulty_objects, max_combo, osu_objects, OsuObjectKind, PlayfieldParams};
+use serde::Serialize;
+
+const DIFFICULTY_MULTIPLIER: f64 = 0.0675;
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct OsuDifficultyAttributes {
+    pub star_rating: f64,
+    pub aim: f64,
+    pub speed: f64,
+    pub flashlight: f64,
+    pub slider_factor: f64,
+    pub speed_note_count: f64,
+    pub approach_rate: f64,
+    pub overall_difficulty: f64,
+    pub hp_drain_rate: f64,
+    pub max_combo: u32,
+    pub circle_count: u32,
+    pub slider_count: u32,
+    pub spinner_count: u32,
+    pub clock_rate: f64,
+}
+
+impl OsuDifficultyAttributes {
+    pub fn object_count(&self) -> u32 {
+        self.circle_count + self.slider_count + self.spinner_count
+    }
+}
+
+// Performance of a skill rating on its own, before length and accuracy scaling.
+pub fn base_performance(rating: f64) -> f64 {
+    (5.0 * (rating / DIFFICULTY_MULTIPLIER).max(1.0) - 4.0).powi(3) / 100_000.0
+}
+
+pub fn calculate(beatmap: &Beatmap, mods: i32) -> OsuDifficultyAttributes {
+    let clock_rate = mods::clock_rate(mods);
+    let difficulty = apply_mods(&beatmap.difficulty, mods);
+    let params = PlayfieldParams::new(&difficulty);
+    let great_window = difficulty_range(difficulty.overall_difficulty, 80.0, 50.0, 20.0);
+
+    let objects = osu_objects(beatmap, &difficulty);
+    let count = |f: fn(&OsuObjectKind) -> bool| objects.iter().filter(|o| f(&o.kind)).count();
+
+    // Timing-based settings are reported as they feel once the clock rate is applied.
+    let preempt = params.preempt / clock_rate;
+    let great_window_rate = great_window / clock_rate;
+    let mut attributes = OsuDifficultyAttributes {
+        approach_rate: if preempt > 1200.0 {
+            (1800.0 - preempt) / 120.0
+        } else {
+            (1200.0 - preempt) / 150.0 + 5.0
+        },
+        overall_difficulty: (80.0 - great_window_rate) / 6.0,
+        hp_drain_rate: difficulty.hp_drain_rate,
+        max_combo: max_combo(&objects),
+        circle_count: count(|k| matches!(k, OsuObjectKind::Circle)) as u32,
+        slider_count: count(|k| matches!(k, OsuObjectKind::Slider(_))) as u32,
+        spinner_count: count(|k| matches!(k, OsuObjectKind::Spinner)) as u32,
+        clock_rate,
+        slider_factor: 1.0,
+        ..Default::default()
+    };
+
+    let difficulty_objects = difficulty_objects(&objects, params.radius, great_window, clock_rate);
+    if difficulty_objects.is_empty() {
+        return attributes;
+    }
+
+    let rating = |value: f64| value.sqrt() * DIFFICULTY_MULTIPLIER;
+    let mut aim = rating(skills::aim(&difficulty_objects, true));
+    let aim_no_sliders = rating(skills::aim(&difficulty_objects, false));
+    let (speed, speed_note_count) = skills::speed(&difficulty_objects);
+    let mut speed = rating(speed);
+    let mut flashlight = if mods & FLASHLIGHT != 0 {
+        rating(skills::flashlight(
+            &difficulty_objects,
+            mods & HIDDEN != 0,
+            &params,
+        ))
+    } else {
+        0.0
+    };
+    attributes.slider_factor = if aim > 0.0 { aim_no_sliders / aim } else { 1.0 };
+
+    if mods & RELAX != 0 {
+        aim *= 0.9;
+        speed = 0.0;
+        flashlight *= 0.7;
+    }
+
+    let flashlight_performance = if mods & FLASHLIGHT != 0 {
+        flashlight.powi(2) * 25.0
+    } else {
+        0.0
+    };
+    let total = (base_performance(aim).powf(1.1)
+        + base_performance(speed).powf(1.1)
+        + flashlight_performance.powf(1.1))
+    .powf(1.0 / 1.1);
+
+    attributes.star_rating = if total > 0.00001 {
+        performance::PERFORMANCE_BASE_MULTIPLIER.cbrt()
+            * 0.027
+            * ((100_000.0 / 2f64.powf(1.0 / 1.1) * total).cbrt() + 4.0)
+    } else {
+        0.0
+    };
+    attributes.aim = aim;
+    attributes.speed = speed;
+    attributes.flashlight = flashlight;
+    attributes.speed_note_count = speed_note_count;
+    attributes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::performance::pp_at_accuracy;
+    use super::*;
+    use crate::difficulty::fixtures::{self, assert_close};
+    use crate::models::mods::{DOUBLE_TIME, EASY, HALF_TIME, HARD_ROCK};
+
+    struct Expected {
+        mods: i32,
+        star_rating: f64,
+        aim: f64,
+        speed: f64,
+        pp_95: f64,
+        pp_100: f64,
+    }
+
+    fn map(objects: &str) -> Beatmap {
+        fixtures::map(0, 4.0, objects)
+    }
+
+    fn jumps() -> Beatmap {
+        map(&fixtures::jump_objects(400))
+    }
+
+    // Sixteen-note 1/4 streams around a circle, each followed by a one-beat slider.
+    fn streams() -> Beatmap {
+        let mut objects = String::new();
+        let mut time = 1000;
+        for block in 0..20 {
+            for i in 0..16 {
+                let angle = (block * 16 + i) as f64 * 0.35;
+                let x = (256.0 + 120.0 * angle.cos()) as i32;
+                let y = (192.0 + 120.0 * angle.sin()) as i32;
+                objects += &format!("{},{},{},1,0,0:0:0:0:\n", x, y, time);
+                time += 83;
+            }
+            objects += &format!("100,300,{},2,0,P|200:250|300:300,1,140\n", time);
+            time += 667;
+        }
+        map(&objects)
+    }
+
+    // Regression values from this calculator; any change to the skills should be deliberate.
+    fn assert_attributes(beatmap: &Beatmap, expected: &[Expected]) {
+        for expected in expected {
+            let attributes = calculate(beatmap, expected.mods);
+            let label = |name: &str| format!("mods {} {}", expected.mods, name);
+            assert_close(
+                &label("stars"),
+                attributes.star_rating,
+                expected.star_rating,
+            );
+            assert_close(&label("aim"), attributes.aim, expected.aim);
+            assert_close(&label("speed"), attributes.speed, expected.speed);
+            assert_close(
+                &label("pp 95%"),
+                pp_at_accuracy(&attributes, expected.mods, 0.95),
+                expected.pp_95,
+            );
+            assert_close(
+                &label("pp 100%"),
+                pp_at_accuracy(&attributes, expected.mods, 1.0),
+                expected.pp_100,
+            );
+        }
+    }
+
+    #[test]
+    fn jump_map_reference_values() {
+        #[rustfmt::skip]
+        assert_attributes(&jumps(), &[
+            Expected { mods: 0, star_rating: 6.6752, aim: 3.7874, speed: 2.0698, pp_95: 277.200, pp_100: 335.483 },
+            Expected { mods: HARD_ROCK, star_rating: 7.1687, aim: 4.1017, speed: 2.0698, pp_95: 369.788, pp_100: 488.966 },
+            Expected { mods: DOUBLE_TIME, star_rating: 9.7247, aim: 5.5104, speed: 3.0545, pp_95: 872.778, pp_100: 1007.958 },
+            Expected { mods: EASY, star_rating: 6.0728, aim: 3.3935, speed: 2.0698, pp_95: 223.109, pp_100: 244.799 },
+        ]);
+    }
+
+    #[test]
+    fn stream_map_reference_values() {
+        #[rustfmt::skip]
+        assert_attributes(&streams(), &[
+            Expected { mods: 0, star_rating: 6.3032, aim: 3.1310, speed: 2.8916, pp_95: 223.052, pp_100: 286.848 },
+            Expected { mods: HARD_ROCK, star_rating: 6.6291, aim: 3.3655, speed: 2.9482, pp_95: 288.913, pp_100: 408.633 },
+            Expected { mods: DOUBLE_TIME, star_rating: 9.1135, aim: 4.2208, speed: 4.4933, pp_95: 702.463, pp_100: 851.092 },
+            Expected { mods: EASY, star_rating: 5.7228, aim: 2.8409, speed: 2.6276, pp_95: 169.258, pp_100: 193.625 },
+        ]);
+    }
+
+    #[test]
+    fn mods_adjust_settings() {
+        let beatmap = jumps();
+        let settings = |mods: i32| {
+            let attributes = calculate(&beatmap, mods);
+            (attributes.approach_rate, attributes.overall_difficulty)
+        };
+        let (ar, od) = settings(0);
+        assert_close("NM AR", ar, 9.0);
+        assert_close("NM OD", od, 8.0);
+        // HR multiplies by 1.4 and caps at 10, EZ halves.
+        let (ar, od) = settings(HARD_ROCK);
+        assert_close("HR AR", ar, 10.0);
+        assert_close("HR OD", od, 10.0);
+        let (ar, od) = settings(EASY);
+        assert_close("EZ AR", ar, 4.5);
+        assert_close("EZ OD", od, 4.0);
+        // A 600 ms preempt and 32 ms great window, sped up by 1.5x or slowed to 0.75x.
+        let (ar, od) = settings(DOUBLE_TIME);
+        assert_close("DT AR", ar, 31.0 / 3.0);
+        assert_close("DT OD", od, (80.0 - 32.0 / 1.5) / 6.0);
+        let (ar, od) = settings(HALF_TIME);
+        assert_close("HT AR", ar, 23.0 / 3.0);
+        assert_close("HT OD", od, (80.0 - 32.0 / 0.75) / 6.0);
+    }
+
+    #[test]
+    fn flashlight_only_rated_with_the_mod() {
+        let beatmap = streams();
+        assert_eq!(calculate(&beatmap, 0).flashlight, 0.0);
+        let flashlight = calculate(&beatmap, FLASHLIGHT);
+        assert!(flashlight.flashlight > 0.0);
+        assert!(flashlight.star_rating > calculate(&beatmap, 0).star_rating);
+    }
+
+    #[test]
+    fn counts_combo_and_objects() {
+        let attributes = calculate(&streams(), 0);
+        assert_eq!(attributes.circle_count, 320);
+        assert_eq!(attributes.slider_count, 20);
+        // The sliders are exactly one tick long, so only their heads and tails count.
+        assert_eq!(attributes.max_combo, 320 + 20 * 2);
+
+        // Two spans of 280px at 140px per beat, with a tick per beat.
+        let repeat = map("100,100,1000,2,0,L|380:100,2,280\n");
+        assert_eq!(calculate(&repeat, 0).max_combo, 5);
+
+        let empty = calculate(&map(""), 0);
+        assert_eq!(empty.star_rating, 0.0);
+        assert_eq!(pp_at_accuracy(&empty, 0, 1.0), 0.0);
+    }
+}
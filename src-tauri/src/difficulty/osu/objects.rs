@@ -0,0 +1,462 @@
+use crate::difficulty::difficulty_range;
+use crate::difficulty::slider::{SliderEventKind, SliderTiming};
+use crate::models::beatmap::{Beatmap, Difficulty};
+use crate::models::hitobject::{HitObjectKind, Slider};
+use crate::utils::curve::Vec2;
+
+pub const OBJECT_RADIUS: f64 = 64.0;
+pub const NORMALISED_RADIUS: f64 = 50.0;
+pub const MIN_DELTA_TIME: f64 = 25.0;
+
+const STACK_DISTANCE: f64 = 3.0;
+// Players may let go of a slider this long before its end without breaking combo.
+const TAIL_LENIENCY: f64 = -36.0;
+const MAXIMUM_SLIDER_RADIUS: f64 = NORMALISED_RADIUS * 2.4;
+const ASSUMED_SLIDER_RADIUS: f64 = NORMALISED_RADIUS * 1.8;
+// osu!stable rounds the playfield scale slightly differently; lazer keeps that for parity.
+const BROKEN_GAMEFIELD_ROUNDING_ALLOWANCE: f64 = 1.00041;
+
+#[derive(Debug, Clone, Copy)]
+pub struct NestedObject {
+    pub kind: SliderEventKind,
+    pub time: f64,
+    pub position: Vec2,
+}
+
+#[derive(Debug, Clone)]
+pub struct SliderData {
+    pub timing: SliderTiming,
+    // Ticks, repeats and the tail, in the order they're hit.
+    pub nested: Vec<NestedObject>,
+    pub lazy_end_position: Vec2,
+    pub lazy_travel_distance: f64,
+    pub lazy_travel_time: f64,
+}
+
+impl SliderData {
+    pub fn repeat_count(&self) -> u32 {
+        self.timing.span_count.saturating_sub(1)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum OsuObjectKind {
+    Circle,
+    Slider(Box<SliderData>),
+    Spinner,
+}
+
+#[derive(Debug, Clone)]
+pub struct OsuObject {
+    pub kind: OsuObjectKind,
+    pub start_time: f64,
+    pub end_time: f64,
+    pub position: Vec2,
+    pub end_position: Vec2,
+    pub stack_height: i32,
+    pub stacked_position: Vec2,
+    pub stacked_end_position: Vec2,
+}
+
+impl OsuObject {
+    pub fn slider(&self) -> Option<&SliderData> {
+        match &self.kind {
+            OsuObjectKind::Slider(slider) => Some(slider),
+            _ => None,
+        }
+    }
+
+    pub fn is_spinner(&self) -> bool {
+        matches!(self.kind, OsuObjectKind::Spinner)
+    }
+
+    // Where the cursor is assumed to rest once the object has been played.
+    pub fn end_cursor_position(&self) -> Vec2 {
+        self.slider()
+            .map(|slider| slider.lazy_end_position)
+            .unwrap_or(self.stacked_position)
+    }
+}
+
+// Map-wide values derived from the (mod-adjusted) difficulty settings.
+#[derive(Debug, Clone, Copy)]
+pub struct PlayfieldParams {
+    pub scale: f64,
+    pub radius: f64,
+    pub preempt: f64,
+    pub fade_in: f64,
+}
+
+impl PlayfieldParams {
+    pub fn new(difficulty: &Difficulty) -> Self {
+        let scale = (1.0 - 0.7 * (difficulty.circle_size - 5.0) / 5.0) / 2.0
+            * BROKEN_GAMEFIELD_ROUNDING_ALLOWANCE;
+        let preempt = difficulty_range(difficulty.approach_rate, 1800.0, 1200.0, 450.0);
+        PlayfieldParams {
+            scale,
+            radius: OBJECT_RADIUS * scale,
+            preempt,
+            fade_in: 400.0 * (preempt / 450.0).min(1.0),
+        }
+    }
+}
+
+fn slider_data(
+    beatmap: &Beatmap,
+    difficulty: &Difficulty,
+    time: f64,
+    position: Vec2,
+    slider: &Slider,
+) -> SliderData {
+    let timing = SliderTiming::new(beatmap, difficulty, time, position, slider);
+    let nested = timing
+        .events(time)
+        .into_iter()
+        .filter(|e| {
+            matches!(
+                e.kind,
+                SliderEventKind::Tick | SliderEventKind::Repeat | SliderEventKind::Tail
+            )
+        })
+        .map(|e| NestedObject {
+            kind: e.kind,
+            time: e.time,
+            position: position + timing.offset_at(e.path_progress),
+        })
+        .collect();
+
+    SliderData {
+        timing,
+        nested,
+        lazy_end_position: position,
+        lazy_travel_distance: 0.0,
+        lazy_travel_time: 0.0,
+    }
+}
+
+pub fn osu_objects(beatmap: &Beatmap, difficulty: &Difficulty) -> Vec<OsuObject> {
+    let mut objects: Vec<OsuObject> = beatmap
+        .hit_objects
+        .iter()
+        .map(|object| {
+            let position = object.position;
+            let (kind, end_time, end_position) = match &object.kind {
+                HitObjectKind::Slider(slider) => {
+                    let data = slider_data(beatmap, difficulty, object.time, position, slider);
+                    let tail = data.nested.last().copied();
+                    (
+                        OsuObjectKind::Slider(Box::new(data)),
+                        tail.map(|t| t.time).unwrap_or(object.time),
+                        tail.map(|t| t.position).unwrap_or(position),
+                    )
+                }
+                HitObjectKind::Spinner { end_time } => {
+                    (OsuObjectKind::Spinner, *end_time, position)
+                }
+                HitObjectKind::Circle | HitObjectKind::Hold { .. } => {
+                    (OsuObjectKind::Circle, object.time, position)
+                }
+            };
+            OsuObject {
+                kind,
+                start_time: object.time,
+                end_time,
+                position,
+                end_position,
+                stack_height: 0,
+                stacked_position: position,
+                stacked_end_position: end_position,
+            }
+        })
+        .collect();
+
+    let params = PlayfieldParams::new(difficulty);
+    let stack_threshold = params.preempt * beatmap.general.stack_leniency;
+    if beatmap.format_version >= 6 {
+        apply_stacking(&mut objects, stack_threshold);
+    } else {
+        apply_stacking_old(&mut objects, stack_threshold);
+    }
+
+    for object in &mut objects {
+        let offset = object.stack_height as f64 * params.scale * -6.4;
+        let offset = Vec2::new(offset, offset);
+        object.stacked_position = object.position + offset;
+        object.stacked_end_position = object.end_position + offset;
+        if let OsuObjectKind::Slider(slider) = &mut object.kind {
+            for nested in &mut slider.nested {
+                nested.position = nested.position + offset;
+            }
+        }
+        compute_slider_cursor(object, params.radius);
+    }
+
+    objects
+}
+
+fn apply_stacking(objects: &mut [OsuObject], stack_threshold: f64) {
+    for i in (1..objects.len()).rev() {
+        if objects[i].stack_height != 0 || objects[i].is_spinner() {
+            continue;
+        }
+
+        let mut current = i;
+        if matches!(objects[i].kind, OsuObjectKind::Circle) {
+            for n in (0..i).rev() {
+                if objects[n].is_spinner() {
+                    continue;
+                }
+                if objects[current].start_time - objects[n].end_time > stack_threshold {
+                    break;
+                }
+
+                // A slider ending on top of the stack pushes everything after it the other way.
+                if objects[n].slider().is_some()
+                    && objects[n].end_position.distance(objects[current].position) < STACK_DISTANCE
+                {
+                    let offset = objects[current].stack_height - objects[n].stack_height + 1;
+                    for j in n + 1..=i {
+                        if objects[n].end_position.distance(objects[j].position) < STACK_DISTANCE {
+                            objects[j].stack_height -= offset;
+                        }
+                    }
+                    break;
+                }
+
+                if objects[n].position.distance(objects[current].position) < STACK_DISTANCE {
+                    objects[n].stack_height = objects[current].stack_height + 1;
+                    current = n;
+                }
+            }
+        } else if objects[i].slider().is_some() {
+            for n in (0..i).rev() {
+                if objects[n].is_spinner() {
+                    continue;
+                }
+                if objects[current].start_time - objects[n].start_time > stack_threshold {
+                    break;
+                }
+                if objects[n].end_position.distance(objects[current].position) < STACK_DISTANCE {
+                    objects[n].stack_height = objects[current].stack_height + 1;
+                    current = n;
+                }
+            }
+        }
+    }
+}
+
+// Stacking as it worked for maps saved before file format v6.
+fn apply_stacking_old(objects: &mut [OsuObject], stack_threshold: f64) {
+    for i in 0..objects.len() {
+        if objects[i].stack_height != 0 && objects[i].slider().is_none() {
+            continue;
+        }
+
+        let mut start_time = objects[i].end_time;
+        let mut slider_stack = 0;
+        for j in i + 1..objects.len() {
+            if objects[j].start_time - stack_threshold > start_time {
+                break;
+            }
+
+            if objects[j].position.distance(objects[i].position) < STACK_DISTANCE {
+                objects[i].stack_height += 1;
+                start_time = objects[j].end_time;
+            } else if objects[j].position.distance(objects[i].end_position) < STACK_DISTANCE {
+                slider_stack += 1;
+                objects[j].stack_height -= slider_stack;
+                start_time = objects[j].end_time;
+            }
+        }
+    }
+}
+
+// Follows the laziest cursor path that still keeps every tick, repeat and the
+// tail inside the follow circle.
+fn compute_slider_cursor(object: &mut OsuObject, radius: f64) {
+    let start_time = object.start_time;
+    let stacked_position = object.stacked_position;
+    let OsuObjectKind::Slider(slider) = &mut object.kind else {
+        return;
+    };
+
+    let duration = slider.timing.duration();
+    let mut nested = slider.nested.clone();
+    let mut tracking_end_time =
+        (start_time + duration + TAIL_LENIENCY).max(start_time + duration / 2.0) - start_time;
+
+    // A tick inside the leniency window still has to be tracked, which moves it to the end.
+    if let Some(index) = nested.iter().rposition(|n| n.kind == SliderEventKind::Tick) {
+        let last_tick = nested[index];
+        if last_tick.time - start_time > tracking_end_time {
+            tracking_end_time = last_tick.time - start_time;
+            nested.remove(index);
+            nested.push(last_tick);
+        }
+    }
+    slider.lazy_travel_time = tracking_end_time;
+
+    let span_duration = slider.timing.span_duration;
+    let mut end_time_min = if span_duration > 0.0 {
+        tracking_end_time / span_duration
+    } else {
+        0.0
+    };
+    end_time_min = if end_time_min % 2.0 >= 1.0 {
+        1.0 - end_time_min % 1.0
+    } else {
+        end_time_min % 1.0
+    };
+    slider.lazy_end_position = stacked_position + slider.timing.offset_at(end_time_min);
+
+    let scaling_factor = NORMALISED_RADIUS / radius;
+    let mut cursor = stacked_position;
+    let last = nested.len().saturating_sub(1);
+    for (i, target) in nested.iter().enumerate() {
+        let mut movement = target.position - cursor;
+        let mut required_movement = ASSUMED_SLIDER_RADIUS;
+
+        if i == last {
+            let lazy_movement = slider.lazy_end_position - cursor;
+            if lazy_movement.length() < movement.length() {
+                movement = lazy_movement;
+            }
+        } else if target.kind == SliderEventKind::Repeat {
+            required_movement = NORMALISED_RADIUS;
+        }
+
+        let movement_length = scaling_factor * movement.length();
+        if movement_length > required_movement {
+            let ratio = (movement_length - required_movement) / movement_length;
+            cursor = cursor + movement * ratio;
+            slider.lazy_travel_distance += movement_length * ratio;
+        }
+
+        if i == last {
+            slider.lazy_end_position = cursor;
+        }
+    }
+}
+
+pub fn max_combo(objects: &[OsuObject]) -> u32 {
+    objects
+        .iter()
+        .map(|object| match &object.kind {
+            OsuObjectKind::Slider(slider) => 1 + slider.nested.len() as u32,
+            _ => 1,
+        })
+        .sum()
+}
+
+// Per-object movement data the difficulty skills are evaluated on. Times are
+// already divided by the clock rate of the selected mods.
+#[derive(Debug, Clone)]
+pub struct DifficultyObject<'a> {
+    pub base: &'a OsuObject,
+    pub start_time: f64,
+    pub delta_time: f64,
+    pub strain_time: f64,
+    pub lazy_jump_distance: f64,
+    pub minimum_jump_distance: f64,
+    pub minimum_jump_time: f64,
+    pub travel_distance: f64,
+    pub travel_time: f64,
+    pub angle: Option<f64>,
+    // Twice the 300 window, so the full span in which a 300 can be hit.
+    pub hit_window_great: f64,
+}
+
+impl<'a> DifficultyObject<'a> {
+    fn new(
+        base: &'a OsuObject,
+        last: &'a OsuObject,
+        last_last: Option<&'a OsuObject>,
+        radius: f64,
+        great_window: f64,
+        clock_rate: f64,
+    ) -> Self {
+        let delta_time = (base.start_time - last.start_time) / clock_rate;
+        let strain_time = delta_time.max(MIN_DELTA_TIME);
+        let mut object = DifficultyObject {
+            base,
+            start_time: base.start_time / clock_rate,
+            delta_time,
+            strain_time,
+            lazy_jump_distance: 0.0,
+            minimum_jump_distance: 0.0,
+            minimum_jump_time: strain_time,
+            travel_distance: 0.0,
+            travel_time: 0.0,
+            angle: None,
+            hit_window_great: if base.is_spinner() {
+                0.0
+            } else {
+                2.0 * great_window / clock_rate
+            },
+        };
+
+        if let Some(slider) = base.slider() {
+            object.travel_distance = slider.lazy_travel_distance
+                * (1.0 + slider.repeat_count() as f64 / 2.5).powf(1.0 / 2.5);
+            object.travel_time = (slider.lazy_travel_time / clock_rate).max(MIN_DELTA_TIME);
+        }
+
+        if base.is_spinner() || last.is_spinner() {
+            return object;
+        }
+
+        // Small circles are harder to aim than their distances alone suggest.
+        let mut scaling_factor = NORMALISED_RADIUS / radius;
+        if radius < 30.0 {
+            scaling_factor *= 1.0 + (30.0 - radius).min(5.0) / 50.0;
+        }
+
+        let last_cursor = last.end_cursor_position();
+        object.lazy_jump_distance =
+            (base.stacked_position * scaling_factor - last_cursor * scaling_factor).length();
+        object.minimum_jump_distance = object.lazy_jump_distance;
+
+        if let Some(last_slider) = last.slider() {
+            let last_travel_time = (last_slider.lazy_travel_time / clock_rate).max(MIN_DELTA_TIME);
+            object.minimum_jump_time = (strain_time - last_travel_time).max(MIN_DELTA_TIME);
+
+            let tail_jump_distance =
+                (last.stacked_end_position - base.stacked_position).length() * scaling_factor;
+            object.minimum_jump_distance = (object.lazy_jump_distance
+                - (MAXIMUM_SLIDER_RADIUS - ASSUMED_SLIDER_RADIUS))
+                .min(tail_jump_distance - MAXIMUM_SLIDER_RADIUS)
+                .max(0.0);
+        }
+
+        if let Some(last_last) = last_last.filter(|o| !o.is_spinner()) {
+            let v1 = last_last.end_cursor_position() - last.stacked_position;
+            let v2 = base.stacked_position - last_cursor;
+            let dot = v1.dot(v2);
+            let det = v1.x * v2.y - v1.y * v2.x;
+            object.angle = Some(det.atan2(dot).abs());
+        }
+
+        object
+    }
+}
+
+pub fn difficulty_objects(
+    objects: &[OsuObject],
+    radius: f64,
+    great_window: f64,
+    clock_rate: f64,
+) -> Vec<DifficultyObject<'_>> {
+    (1..objects.len())
+        .map(|i| {
+            let last_last = if i > 1 { Some(&objects[i - 2]) } else { None };
+            DifficultyObject::new(
+                &objects[i],
+                &objects[i - 1],
+                last_last,
+                radius,
+                great_window,
+                clock_rate,
+            )
+        })
+        .collect()
+}
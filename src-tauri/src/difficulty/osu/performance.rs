@@ -0,0 +1,317 @@
+use super::{base_performance, OsuDifficultyAttributes};
+use crate::models::mods::{FLASHLIGHT, HIDDEN, NO_FAIL, RELAX, SCORE_V2, SPUN_OUT};
+use serde::Serialize;
+
+pub const PERFORMANCE_BASE_MULTIPLIER: f64 = 1.14;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScoreState {
+    pub max_combo: u32,
+    pub count_300: u32,
+    pub count_100: u32,
+    pub count_50: u32,
+    pub count_miss: u32,
+}
+
+impl ScoreState {
+    // A full combo with the 100s (and 50s, below 1/3 accuracy) needed to reach `accuracy`.
+    pub fn from_accuracy(attributes: &OsuDifficultyAttributes, accuracy: f64) -> Self {
+        let total = attributes.object_count();
+        let accuracy = accuracy.clamp(0.0, 1.0);
+        let count_100 = ((1.5 * total as f64 * (1.0 - accuracy)).round() as u32).min(total);
+        let (count_100, count_50) = if accuracy < 1.0 / 3.0 {
+            // 100s alone can't go lower than 1/3; make up the rest with 50s.
+            let count_50 = ((total as f64 * (2.0 - 6.0 * accuracy)).round() as u32).min(total);
+            (total - count_50, count_50)
+        } else {
+            (count_100, 0)
+        };
+        ScoreState {
+            max_combo: attributes.max_combo,
+            count_300: total - count_100 - count_50,
+            count_100,
+            count_50,
+            count_miss: 0,
+        }
+    }
+
+    fn total_hits(&self) -> u32 {
+        self.count_300 + self.count_100 + self.count_50 + self.count_miss
+    }
+
+    fn accuracy(&self) -> f64 {
+        let total = self.total_hits();
+        if total == 0 {
+            return 0.0;
+        }
+        (self.count_300 * 6 + self.count_100 * 2 + self.count_50) as f64 / (total * 6) as f64
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct OsuPerformance {
+    pub pp: f64,
+    pub aim: f64,
+    pub speed: f64,
+    pub accuracy: f64,
+    pub flashlight: f64,
+}
+
+struct Context<'a> {
+    attributes: &'a OsuDifficultyAttributes,
+    score: ScoreState,
+    mods: i32,
+    accuracy: f64,
+    total_hits: f64,
+    effective_miss_count: f64,
+}
+
+impl Context<'_> {
+    fn has(&self, flag: i32) -> bool {
+        self.mods & flag != 0
+    }
+
+    fn length_bonus(&self) -> f64 {
+        let total = self.total_hits;
+        0.95 + 0.4 * (total / 2000.0).min(1.0)
+            + if total > 2000.0 {
+                (total / 2000.0).log10() * 0.5
+            } else {
+                0.0
+            }
+    }
+
+    fn miss_penalty(&self, exponent: f64) -> f64 {
+        if self.effective_miss_count > 0.0 {
+            0.97 * (1.0 - (self.effective_miss_count / self.total_hits).powf(0.775)).powf(exponent)
+        } else {
+            1.0
+        }
+    }
+
+    fn combo_scaling(&self) -> f64 {
+        let max_combo = self.attributes.max_combo;
+        if max_combo == 0 {
+            return 1.0;
+        }
+        (self.score.max_combo as f64 / max_combo as f64)
+            .powf(0.8)
+            .min(1.0)
+    }
+
+    fn aim(&self) -> f64 {
+        let attributes = self.attributes;
+        let approach_rate = attributes.approach_rate;
+        let length_bonus = self.length_bonus();
+        let mut value = base_performance(attributes.aim) * length_bonus;
+        value *= self.miss_penalty(self.effective_miss_count);
+        value *= self.combo_scaling();
+
+        let mut approach_rate_factor = 0.0;
+        if approach_rate > 10.33 {
+            approach_rate_factor = 0.3 * (approach_rate - 10.33);
+        } else if approach_rate < 8.0 {
+            approach_rate_factor = 0.05 * (8.0 - approach_rate);
+        }
+        if self.has(RELAX) {
+            approach_rate_factor = 0.0;
+        }
+        value *= 1.0 + approach_rate_factor * length_bonus;
+        if self.has(HIDDEN) {
+            value *= 1.0 + 0.04 * (12.0 - approach_rate);
+        }
+
+        // Dropped slider ends suggest the harder sliders weren't actually followed.
+        if attributes.slider_count > 0 {
+            let difficult_sliders = attributes.slider_count as f64 * 0.15;
+            let score = &self.score;
+            let dropped = ((score.count_100 + score.count_50 + score.count_miss) as f64)
+                .min(attributes.max_combo as f64 - score.max_combo as f64)
+                .clamp(0.0, difficult_sliders);
+            value *= (1.0 - attributes.slider_factor) * (1.0 - dropped / difficult_sliders).powi(3)
+                + attributes.slider_factor;
+        }
+
+        value * self.accuracy * (0.98 + attributes.overall_difficulty.powi(2) / 2500.0)
+    }
+
+    fn speed(&self) -> f64 {
+        if self.has(RELAX) {
+            return 0.0;
+        }
+        let attributes = self.attributes;
+        let length_bonus = self.length_bonus();
+        let mut value = base_performance(attributes.speed) * length_bonus;
+        value *= self.miss_penalty(self.effective_miss_count.powf(0.875));
+        value *= self.combo_scaling();
+
+        if attributes.approach_rate > 10.33 {
+            value *= 1.0 + 0.3 * (attributes.approach_rate - 10.33) * length_bonus;
+        }
+        if self.has(HIDDEN) {
+            value *= 1.0 + 0.04 * (12.0 - attributes.approach_rate);
+        }
+
+        // Only accuracy on the notes that make up the speed rating counts here.
+        let score = &self.score;
+        let relevant_total_diff = self.total_hits - attributes.speed_note_count;
+        let great = score.count_300 as f64;
+        let ok = score.count_100 as f64;
+        let meh = score.count_50 as f64;
+        let relevant_great = (great - relevant_total_diff).max(0.0);
+        let relevant_ok = (ok - (relevant_total_diff - great).max(0.0)).max(0.0);
+        let relevant_meh = (meh - (relevant_total_diff - great - ok).max(0.0)).max(0.0);
+        let relevant_accuracy = if attributes.speed_note_count == 0.0 {
+            0.0
+        } else {
+            (relevant_great * 6.0 + relevant_ok * 2.0 + relevant_meh)
+                / (attributes.speed_note_count * 6.0)
+        };
+
+        let overall_difficulty = attributes.overall_difficulty;
+        value *= (0.95 + overall_difficulty.powi(2) / 750.0)
+            * ((self.accuracy + relevant_accuracy) / 2.0)
+                .powf((14.5 - overall_difficulty.max(8.0)) / 2.0);
+
+        let meh_allowance = self.total_hits / 500.0;
+        if meh >= meh_allowance {
+            value *= 0.99f64.powf(meh - meh_allowance);
+        }
+        value
+    }
+
+    fn accuracy(&self) -> f64 {
+        if self.has(RELAX) {
+            return 0.0;
+        }
+        let attributes = self.attributes;
+        let score = &self.score;
+        let mut objects_with_accuracy = attributes.circle_count;
+        if self.has(SCORE_V2) {
+            objects_with_accuracy += attributes.slider_count;
+        }
+        let better_accuracy = if objects_with_accuracy > 0 {
+            let great = score.count_300 as f64 - (self.total_hits - objects_with_accuracy as f64);
+            ((great * 6.0 + score.count_100 as f64 * 2.0 + score.count_50 as f64)
+                / (objects_with_accuracy as f64 * 6.0))
+                .max(0.0)
+        } else {
+            0.0
+        };
+
+        let mut value =
+            1.52163f64.powf(attributes.overall_difficulty) * better_accuracy.powi(24) * 2.83;
+        value *= (objects_with_accuracy as f64 / 1000.0).powf(0.3).min(1.15);
+        if self.has(HIDDEN) {
+            value *= 1.08;
+        }
+        if self.has(FLASHLIGHT) {
+            value *= 1.02;
+        }
+        value
+    }
+
+    fn flashlight(&self) -> f64 {
+        if !self.has(FLASHLIGHT) {
+            return 0.0;
+        }
+        let total = self.total_hits;
+        let mut value = self.attributes.flashlight.powi(2) * 25.0;
+        value *= self.miss_penalty(self.effective_miss_count.powf(0.875));
+        value *= self.combo_scaling();
+        value *= 0.7
+            + 0.1 * (total / 200.0).min(1.0)
+            + if total > 200.0 {
+                0.2 * ((total - 200.0) / 200.0).min(1.0)
+            } else {
+                0.0
+            };
+        value *= 0.5 + self.accuracy / 2.0;
+        value * (0.98 + self.attributes.overall_difficulty.powi(2) / 2500.0)
+    }
+}
+
+// Misses are estimated from a dropped combo too, since slider breaks don't count as misses.
+fn effective_miss_count(attributes: &OsuDifficultyAttributes, score: &ScoreState) -> f64 {
+    let mut combo_based = 0.0;
+    if attributes.slider_count > 0 {
+        let full_combo_threshold =
+            attributes.max_combo as f64 - 0.1 * attributes.slider_count as f64;
+        if (score.max_combo as f64) < full_combo_threshold {
+            combo_based = full_combo_threshold / (score.max_combo as f64).max(1.0);
+        }
+    }
+    combo_based = combo_based.min((score.count_100 + score.count_50 + score.count_miss) as f64);
+    combo_based.max(score.count_miss as f64)
+}
+
+pub fn performance(
+    attributes: &OsuDifficultyAttributes,
+    mods: i32,
+    score: ScoreState,
+) -> OsuPerformance {
+    let total_hits = score.total_hits() as f64;
+    if total_hits == 0.0 {
+        return OsuPerformance::default();
+    }
+
+    let mut context = Context {
+        attributes,
+        score,
+        mods,
+        accuracy: score.accuracy(),
+        total_hits,
+        effective_miss_count: effective_miss_count(attributes, &score),
+    };
+
+    let mut multiplier = PERFORMANCE_BASE_MULTIPLIER;
+    if context.has(NO_FAIL) {
+        multiplier *= (1.0 - 0.02 * context.effective_miss_count).max(0.9);
+    }
+    if context.has(SPUN_OUT) {
+        multiplier *= 1.0 - (attributes.spinner_count as f64 / total_hits).powf(0.85);
+    }
+    if context.has(RELAX) {
+        // Relax players can't be penalised for misses directly, so 100s and 50s count instead.
+        let overall_difficulty = attributes.overall_difficulty;
+        let ok_multiplier = if overall_difficulty > 0.0 {
+            (1.0 - (overall_difficulty / 13.33).powf(1.8)).max(0.0)
+        } else {
+            1.0
+        };
+        let meh_multiplier = if overall_difficulty > 0.0 {
+            (1.0 - (overall_difficulty / 13.33).powi(5)).max(0.0)
+        } else {
+            1.0
+        };
+        context.effective_miss_count = (context.effective_miss_count
+            + score.count_100 as f64 * ok_multiplier
+            + score.count_50 as f64 * meh_multiplier)
+            .min(total_hits);
+    }
+
+    let aim = context.aim();
+    let speed = context.speed();
+    let accuracy = context.accuracy();
+    let flashlight = context.flashlight();
+    let pp = (aim.powf(1.1) + speed.powf(1.1) + accuracy.powf(1.1) + flashlight.powf(1.1))
+        .powf(1.0 / 1.1)
+        * multiplier;
+
+    OsuPerformance {
+        pp,
+        aim,
+        speed,
+        accuracy,
+        flashlight,
+    }
+}
+
+pub fn pp_at_accuracy(attributes: &OsuDifficultyAttributes, mods: i32, accuracy: f64) -> f64 {
+    performance(
+        attributes,
+        mods,
+        ScoreState::from_accuracy(attributes, accuracy),
+    )
+    .pp
+}
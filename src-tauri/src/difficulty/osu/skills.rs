@@ -0,0 +1,446 @@
+use super::objects::{DifficultyObject, OsuObjectKind, PlayfieldParams};
+use crate::difficulty::strain::{strain_decay, weighted_sum, StrainPeaks};
+use std::f64::consts::PI;
+
+const AIM_MULTIPLIER: f64 = 23.55;
+const AIM_DECAY_BASE: f64 = 0.15;
+const WIDE_ANGLE_MULTIPLIER: f64 = 1.5;
+const ACUTE_ANGLE_MULTIPLIER: f64 = 1.95;
+const SLIDER_MULTIPLIER: f64 = 1.35;
+const VELOCITY_CHANGE_MULTIPLIER: f64 = 0.75;
+
+const SPEED_MULTIPLIER: f64 = 1375.0;
+const SPEED_DECAY_BASE: f64 = 0.3;
+const SINGLE_SPACING_THRESHOLD: f64 = 125.0;
+const MIN_SPEED_BONUS: f64 = 75.0;
+const SPEED_BALANCING_FACTOR: f64 = 40.0;
+const RHYTHM_HISTORY_TIME: f64 = 5000.0;
+const RHYTHM_HISTORY_NOTES: usize = 32;
+const RHYTHM_MULTIPLIER: f64 = 0.75;
+
+const FLASHLIGHT_MULTIPLIER: f64 = 0.052;
+const FLASHLIGHT_DECAY_BASE: f64 = 0.15;
+const FLASHLIGHT_HISTORY_NOTES: usize = 10;
+const MAX_OPACITY_BONUS: f64 = 0.4;
+const HIDDEN_BONUS: f64 = 0.2;
+const MIN_SLIDER_VELOCITY: f64 = 0.5;
+const FLASHLIGHT_SLIDER_MULTIPLIER: f64 = 1.3;
+const MIN_ANGLE_MULTIPLIER: f64 = 0.2;
+// Hidden fades objects out over this fraction of their approach time.
+const HIDDEN_FADE_OUT_MULTIPLIER: f64 = 0.3;
+
+pub const DEFAULT_DIFFICULTY_MULTIPLIER: f64 = 1.06;
+const DECAY_WEIGHT: f64 = 0.9;
+const REDUCED_STRAIN_BASELINE: f64 = 0.75;
+
+fn is_slider(object: &DifficultyObject) -> bool {
+    matches!(object.base.kind, OsuObjectKind::Slider(_))
+}
+
+fn wide_angle_bonus(angle: f64) -> f64 {
+    (0.75 * (angle.clamp(PI / 6.0, 5.0 / 6.0 * PI) - PI / 6.0))
+        .sin()
+        .powi(2)
+}
+
+fn acute_angle_bonus(angle: f64) -> f64 {
+    1.0 - wide_angle_bonus(angle)
+}
+
+fn evaluate_aim(objects: &[DifficultyObject], index: usize, with_sliders: bool) -> f64 {
+    let current = &objects[index];
+    if current.base.is_spinner() || index <= 1 || objects[index - 1].base.is_spinner() {
+        return 0.0;
+    }
+    let last = &objects[index - 1];
+    let last_last = &objects[index - 2];
+
+    let slider_velocity = |slider: &DifficultyObject, next: &DifficultyObject| {
+        slider.travel_distance / slider.travel_time
+            + next.minimum_jump_distance / next.minimum_jump_time
+    };
+
+    let mut current_velocity = current.lazy_jump_distance / current.strain_time;
+    if with_sliders && is_slider(last) {
+        current_velocity = current_velocity.max(slider_velocity(last, current));
+    }
+    let mut previous_velocity = last.lazy_jump_distance / last.strain_time;
+    if with_sliders && is_slider(last_last) {
+        previous_velocity = previous_velocity.max(slider_velocity(last_last, last));
+    }
+
+    let mut wide_bonus = 0.0;
+    let mut acute_bonus = 0.0;
+    let mut velocity_change_bonus = 0.0;
+    let mut strain = current_velocity;
+
+    let (shorter, longer) = (
+        current.strain_time.min(last.strain_time),
+        current.strain_time.max(last.strain_time),
+    );
+    // Angle bonuses only apply while the rhythm stays roughly the same.
+    if longer < 1.25 * shorter {
+        if let (Some(angle), Some(last_angle), Some(last_last_angle)) =
+            (current.angle, last.angle, last_last.angle)
+        {
+            let angle_bonus = current_velocity.min(previous_velocity);
+            wide_bonus = wide_angle_bonus(angle);
+            acute_bonus = acute_angle_bonus(angle);
+
+            if current.strain_time > 100.0 {
+                acute_bonus = 0.0;
+            } else {
+                acute_bonus *= acute_angle_bonus(last_angle)
+                    * angle_bonus.min(125.0 / current.strain_time)
+                    * (PI / 2.0 * ((100.0 - current.strain_time) / 25.0).min(1.0))
+                        .sin()
+                        .powi(2)
+                    * (PI / 2.0 * (current.lazy_jump_distance.clamp(50.0, 100.0) - 50.0) / 50.0)
+                        .sin()
+                        .powi(2);
+            }
+
+            // Repeating the same angle is easier than the bonus alone suggests.
+            wide_bonus *=
+                angle_bonus * (1.0 - wide_bonus.min(wide_angle_bonus(last_angle).powi(3)));
+            acute_bonus *=
+                0.5 + 0.5 * (1.0 - acute_bonus.min(acute_angle_bonus(last_last_angle).powi(3)));
+        }
+    }
+
+    if previous_velocity.max(current_velocity) != 0.0 {
+        let previous = (last.lazy_jump_distance + last_last.travel_distance) / last.strain_time;
+        let current_v = (current.lazy_jump_distance + last.travel_distance) / current.strain_time;
+        let distance_ratio = (PI / 2.0 * (previous - current_v).abs() / previous.max(current_v))
+            .sin()
+            .powi(2);
+        let overlap_buff = (125.0 / shorter).min((previous - current_v).abs());
+        velocity_change_bonus = overlap_buff * distance_ratio * (shorter / longer).powi(2);
+    }
+
+    strain += (acute_bonus * ACUTE_ANGLE_MULTIPLIER).max(
+        wide_bonus * WIDE_ANGLE_MULTIPLIER + velocity_change_bonus * VELOCITY_CHANGE_MULTIPLIER,
+    );
+    if with_sliders && is_slider(last) {
+        strain += last.travel_distance / last.travel_time * SLIDER_MULTIPLIER;
+    }
+    strain
+}
+
+fn evaluate_speed(objects: &[DifficultyObject], index: usize) -> f64 {
+    let current = &objects[index];
+    if current.base.is_spinner() {
+        return 0.0;
+    }
+
+    // Notes much closer to the next one than to the previous one can be doubletapped.
+    let mut doubletapness = 1.0;
+    if let Some(next) = objects.get(index + 1) {
+        let current_delta = current.delta_time.max(1.0);
+        let next_delta = next.delta_time.max(1.0);
+        let delta_difference = (next_delta - current_delta).abs();
+        let speed_ratio = current_delta / current_delta.max(delta_difference);
+        let window_ratio = (current_delta / current.hit_window_great).min(1.0).powi(2);
+        doubletapness = speed_ratio.powf(1.0 - window_ratio);
+    }
+
+    let strain_time = current.strain_time
+        / (current.strain_time / current.hit_window_great / 0.93).clamp(0.92, 1.0);
+    let mut speed_bonus = 1.0;
+    if strain_time < MIN_SPEED_BONUS {
+        speed_bonus += 0.75 * ((MIN_SPEED_BONUS - strain_time) / SPEED_BALANCING_FACTOR).powi(2);
+    }
+
+    let travel_distance = if index > 0 {
+        objects[index - 1].travel_distance
+    } else {
+        0.0
+    };
+    let distance = SINGLE_SPACING_THRESHOLD.min(travel_distance + current.minimum_jump_distance);
+    (speed_bonus + speed_bonus * (distance / SINGLE_SPACING_THRESHOLD).powf(3.5)) * doubletapness
+        / strain_time
+}
+
+// Rewards changes in rhythm, weighted by how recently they happened.
+fn evaluate_rhythm(objects: &[DifficultyObject], index: usize) -> f64 {
+    let current = &objects[index];
+    if current.base.is_spinner() {
+        return 0.0;
+    }
+    let previous = |n: usize| &objects[index - 1 - n];
+
+    let mut previous_island_size = 0;
+    let mut complexity_sum = 0.0;
+    let mut island_size = 1;
+    let mut start_ratio = 0.0;
+    let mut first_delta_switch = false;
+
+    let history = index.min(RHYTHM_HISTORY_NOTES);
+    let mut rhythm_start = 0;
+    while rhythm_start + 2 < history
+        && current.start_time - previous(rhythm_start).start_time < RHYTHM_HISTORY_TIME
+    {
+        rhythm_start += 1;
+    }
+
+    for i in (1..=rhythm_start).rev() {
+        let object = previous(i - 1);
+        let prev = previous(i);
+        let last = previous(i + 1);
+
+        let decay = ((RHYTHM_HISTORY_TIME - (current.start_time - object.start_time))
+            / RHYTHM_HISTORY_TIME)
+            .min((history - i) as f64 / history as f64);
+
+        let current_delta = object.strain_time;
+        let prev_delta = prev.strain_time;
+        let last_delta = last.strain_time;
+        let ratio = 1.0
+            + 6.0
+                * 0.5f64.min(
+                    (PI / (prev_delta.min(current_delta) / prev_delta.max(current_delta)))
+                        .sin()
+                        .powi(2),
+                );
+        let window_penalty = (((prev_delta - current_delta).abs() - object.hit_window_great * 0.3)
+            .max(0.0)
+            / (object.hit_window_great * 0.3))
+            .min(1.0);
+        let mut effective_ratio = window_penalty * ratio;
+
+        if first_delta_switch {
+            if !(prev_delta > 1.25 * current_delta || prev_delta * 1.25 < current_delta) {
+                if island_size < 7 {
+                    island_size += 1;
+                }
+            } else {
+                if is_slider(object) {
+                    effective_ratio *= 0.125;
+                }
+                if is_slider(prev) {
+                    effective_ratio *= 0.25;
+                }
+                if previous_island_size == island_size {
+                    effective_ratio *= 0.25;
+                }
+                if previous_island_size % 2 == island_size % 2 {
+                    effective_ratio *= 0.5;
+                }
+                if last_delta > prev_delta + 10.0 && prev_delta > current_delta + 10.0 {
+                    effective_ratio *= 0.125;
+                }
+
+                complexity_sum += (effective_ratio * start_ratio).sqrt()
+                    * decay
+                    * (4.0 + island_size as f64).sqrt()
+                    / 2.0
+                    * (4.0 + previous_island_size as f64).sqrt()
+                    / 2.0;
+                start_ratio = effective_ratio;
+                previous_island_size = island_size;
+                if prev_delta * 1.25 < current_delta {
+                    first_delta_switch = false;
+                }
+                island_size = 1;
+            }
+        } else if prev_delta > 1.25 * current_delta {
+            first_delta_switch = true;
+            start_ratio = effective_ratio;
+            island_size = 1;
+        }
+    }
+
+    (4.0 + complexity_sum * RHYTHM_MULTIPLIER).sqrt() / 2.0
+}
+
+fn opacity_at(object: &DifficultyObject, time: f64, hidden: bool, params: &PlayfieldParams) -> f64 {
+    let start_time = object.base.start_time;
+    if time > start_time {
+        return 0.0;
+    }
+    let fade_in_start = start_time - params.preempt;
+    let fade_in = ((time - fade_in_start) / params.fade_in).clamp(0.0, 1.0);
+    if !hidden {
+        return fade_in;
+    }
+    let fade_out_start = fade_in_start + params.fade_in;
+    let fade_out_duration = params.preempt * HIDDEN_FADE_OUT_MULTIPLIER;
+    fade_in.min(1.0 - ((time - fade_out_start) / fade_out_duration).clamp(0.0, 1.0))
+}
+
+fn evaluate_flashlight(
+    objects: &[DifficultyObject],
+    index: usize,
+    hidden: bool,
+    params: &PlayfieldParams,
+) -> f64 {
+    let current = &objects[index];
+    if current.base.is_spinner() {
+        return 0.0;
+    }
+
+    let scaling_factor = 52.0 / params.radius;
+    let mut small_distance_nerf = 1.0;
+    let mut cumulative_strain_time = 0.0;
+    let mut result = 0.0;
+    let mut angle_repeat_count = 0.0;
+    let mut last = current;
+
+    for i in 0..index.min(FLASHLIGHT_HISTORY_NOTES) {
+        let object = &objects[index - 1 - i];
+        if !object.base.is_spinner() {
+            let jump_distance = current
+                .base
+                .stacked_position
+                .distance(object.base.stacked_end_position);
+            cumulative_strain_time += last.strain_time;
+            if i == 0 {
+                small_distance_nerf = (jump_distance / 75.0).min(1.0);
+            }
+
+            let stack_nerf = (object.lazy_jump_distance / scaling_factor / 25.0).min(1.0);
+            let opacity_bonus = 1.0
+                + MAX_OPACITY_BONUS
+                    * (1.0 - opacity_at(current, object.base.start_time, hidden, params));
+            result += stack_nerf * opacity_bonus * scaling_factor * jump_distance
+                / cumulative_strain_time;
+
+            if let (Some(angle), Some(current_angle)) = (object.angle, current.angle) {
+                if (angle - current_angle).abs() < 0.02 {
+                    angle_repeat_count += (1.0 - 0.1 * i as f64).max(0.0);
+                }
+            }
+        }
+        last = object;
+    }
+
+    result = (small_distance_nerf * result).powi(2);
+    if hidden {
+        result *= 1.0 + HIDDEN_BONUS;
+    }
+    result *= MIN_ANGLE_MULTIPLIER + (1.0 - MIN_ANGLE_MULTIPLIER) / (angle_repeat_count + 1.0);
+
+    if let Some(slider) = current.base.slider() {
+        let pixel_travel_distance = slider.lazy_travel_distance / scaling_factor;
+        let mut slider_bonus = (pixel_travel_distance / current.travel_time - MIN_SLIDER_VELOCITY)
+            .max(0.0)
+            .sqrt()
+            * pixel_travel_distance;
+        if slider.repeat_count() > 0 {
+            slider_bonus /= (slider.repeat_count() + 1) as f64;
+        }
+        result += slider_bonus * FLASHLIGHT_SLIDER_MULTIPLIER;
+    }
+    result
+}
+
+// Runs a decaying strain over every object and collects the section peaks.
+// `strain_at` receives the previous strain and returns the new one for the object.
+fn strain_peaks(
+    objects: &[DifficultyObject],
+    decay_base: f64,
+    mut strain_at: impl FnMut(usize, f64) -> f64,
+) -> StrainPeaks {
+    let mut peaks = StrainPeaks::default();
+    let mut strain = 0.0;
+    for index in 0..objects.len() {
+        let previous_time = if index > 0 {
+            objects[index - 1].start_time
+        } else {
+            objects[index].start_time
+        };
+        let current_strain = strain;
+        peaks.start_object(objects[index].start_time, |time| {
+            current_strain * strain_decay(decay_base, time - previous_time)
+        });
+        strain = strain_at(index, strain);
+        peaks.record(strain);
+    }
+    peaks
+}
+
+// The hardest sections are scaled down a little so a single spike can't carry the rating.
+fn reduced_difficulty(peaks: Vec<f64>, reduced_sections: usize, multiplier: f64) -> f64 {
+    let mut strains: Vec<f64> = peaks.into_iter().filter(|p| *p > 0.0).collect();
+    strains.sort_by(|a, b| b.total_cmp(a));
+    for (i, strain) in strains.iter_mut().take(reduced_sections).enumerate() {
+        let progress = (i as f64 / reduced_sections as f64).clamp(0.0, 1.0);
+        let scale = (1.0 + 9.0 * progress).log10();
+        *strain *= REDUCED_STRAIN_BASELINE + (1.0 - REDUCED_STRAIN_BASELINE) * scale;
+    }
+    weighted_sum(strains, DECAY_WEIGHT) * multiplier
+}
+
+pub fn aim(objects: &[DifficultyObject], with_sliders: bool) -> f64 {
+    let peaks = strain_peaks(objects, AIM_DECAY_BASE, |index, strain| {
+        strain * strain_decay(AIM_DECAY_BASE, objects[index].delta_time)
+            + evaluate_aim(objects, index, with_sliders) * AIM_MULTIPLIER
+    });
+    reduced_difficulty(peaks.peaks(), 10, DEFAULT_DIFFICULTY_MULTIPLIER)
+}
+
+// Returns the speed difficulty and the number of notes that meaningfully contribute to it.
+pub fn speed(objects: &[DifficultyObject]) -> (f64, f64) {
+    let mut peaks = StrainPeaks::default();
+    let mut object_strains = Vec::with_capacity(objects.len());
+    let mut strain = 0.0;
+    let mut rhythm = 0.0;
+
+    for index in 0..objects.len() {
+        let current = &objects[index];
+        let previous_time = if index > 0 {
+            objects[index - 1].start_time
+        } else {
+            current.start_time
+        };
+        let total = strain * rhythm;
+        peaks.start_object(current.start_time, |time| {
+            total * strain_decay(SPEED_DECAY_BASE, time - previous_time)
+        });
+
+        strain = strain * strain_decay(SPEED_DECAY_BASE, current.strain_time)
+            + evaluate_speed(objects, index) * SPEED_MULTIPLIER;
+        rhythm = evaluate_rhythm(objects, index);
+        object_strains.push(strain * rhythm);
+        peaks.record(strain * rhythm);
+    }
+
+    let max_strain = object_strains.iter().copied().fold(0.0, f64::max);
+    let note_count = if max_strain > 0.0 {
+        object_strains
+            .iter()
+            .map(|s| 1.0 / (1.0 + (-(s / max_strain * 12.0 - 6.0)).exp()))
+            .sum()
+    } else {
+        0.0
+    };
+
+    (reduced_difficulty(peaks.peaks(), 5, 1.04), note_count)
+}
+
+pub fn flashlight(objects: &[DifficultyObject], hidden: bool, params: &PlayfieldParams) -> f64 {
+    let peaks = strain_peaks(objects, FLASHLIGHT_DECAY_BASE, |index, strain| {
+        strain * strain_decay(FLASHLIGHT_DECAY_BASE, objects[index].delta_time)
+            + evaluate_flashlight(objects, index, hidden, params) * FLASHLIGHT_MULTIPLIER
+    });
+    peaks.peaks().iter().sum::<f64>() * DEFAULT_DIFFICULTY_MULTIPLIER
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reduces_only_the_hardest_sections() {
+        let single = reduced_difficulty(vec![100.0], 10, 1.0);
+        assert!((single - 100.0 * REDUCED_STRAIN_BASELINE).abs() < 1e-9);
+
+        // The eleventh section isn't reduced, so once re-sorted it's weighted first.
+        let ten = reduced_difficulty(vec![100.0; 10], 10, 1.0);
+        let eleven = reduced_difficulty(vec![100.0; 11], 10, 1.0);
+        assert!((eleven - (100.0 + DECAY_WEIGHT * ten)).abs() < 1e-9);
+
+        // Empty sections are ignored.
+        assert_eq!(reduced_difficulty(vec![0.0, 100.0], 10, 1.0), single);
+    }
+}
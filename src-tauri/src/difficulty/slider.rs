@@ -0,0 +1,158 @@
+use crate::models::beatmap::{Beatmap, Difficulty};
+use crate::models::hitobject::Slider;
+use crate::utils::curve::{self, Vec2};
+
+const BASE_SCORING_DISTANCE: f64 = 100.0;
+const MAX_SLIDER_LENGTH: f64 = 100_000.0;
+// osu!stable judged the end of a slider this long before it actually finishes.
+const LEGACY_LAST_TICK_OFFSET: f64 = -36.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SliderEventKind {
+    Head,
+    Tick,
+    Repeat,
+    LegacyLastTick,
+    Tail,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SliderEvent {
+    pub kind: SliderEventKind,
+    pub time: f64,
+    // 0 is the slider's head and 1 the far end of its path.
+    pub path_progress: f64,
+}
+
+// Timing of a slider as the game plays it, shared by every ruleset that converts sliders.
+#[derive(Debug, Clone)]
+pub struct SliderTiming {
+    // One span of the path, relative to the slider's head.
+    pub path: Vec<Vec2>,
+    pub length: f64,
+    pub velocity: f64,
+    pub tick_distance: f64,
+    pub span_count: u32,
+    pub span_duration: f64,
+}
+
+impl SliderTiming {
+    pub fn new(
+        beatmap: &Beatmap,
+        difficulty: &Difficulty,
+        time: f64,
+        position: Vec2,
+        slider: &Slider,
+    ) -> Self {
+        let timing = &beatmap.timing_points;
+        let slider_velocity = timing.slider_velocity_at(time);
+        let scoring_distance =
+            BASE_SCORING_DISTANCE * difficulty.slider_multiplier * slider_velocity;
+        let velocity = scoring_distance / timing.beat_length_at(time);
+        // Before v8 ticks ignored the slider velocity multiplier.
+        let tick_distance_multiplier = if beatmap.format_version < 8 {
+            1.0 / slider_velocity
+        } else {
+            1.0
+        };
+
+        let path: Vec<Vec2> = slider
+            .path(position)
+            .into_iter()
+            .map(|point| point - position)
+            .collect();
+        let length = curve::path_length(&path).min(MAX_SLIDER_LENGTH);
+        let tick_distance = if difficulty.slider_tick_rate > 0.0 {
+            (scoring_distance / difficulty.slider_tick_rate * tick_distance_multiplier)
+                .clamp(0.0, length)
+        } else {
+            0.0
+        };
+
+        SliderTiming {
+            path,
+            length,
+            velocity,
+            tick_distance,
+            span_count: slider.slides.max(1),
+            span_duration: if velocity > 0.0 {
+                length / velocity
+            } else {
+                0.0
+            },
+        }
+    }
+
+    pub fn duration(&self) -> f64 {
+        self.span_duration * self.span_count as f64
+    }
+
+    pub fn offset_at(&self, progress: f64) -> Vec2 {
+        curve::point_at_distance(&self.path, self.length * progress.clamp(0.0, 1.0))
+    }
+
+    pub fn events(&self, start_time: f64) -> Vec<SliderEvent> {
+        let mut events = vec![SliderEvent {
+            kind: SliderEventKind::Head,
+            time: start_time,
+            path_progress: 0.0,
+        }];
+        let min_distance_from_end = self.velocity * 10.0;
+
+        for span in 0..self.span_count {
+            let span_start = start_time + span as f64 * self.span_duration;
+            let reversed = span % 2 == 1;
+
+            let mut ticks = Vec::new();
+            if self.tick_distance > 0.0 && self.length > 0.0 {
+                let mut distance = self.tick_distance;
+                while distance <= self.length && distance < self.length - min_distance_from_end {
+                    let progress = distance / self.length;
+                    let time_progress = if reversed { 1.0 - progress } else { progress };
+                    ticks.push(SliderEvent {
+                        kind: SliderEventKind::Tick,
+                        time: span_start + time_progress * self.span_duration,
+                        path_progress: progress,
+                    });
+                    distance += self.tick_distance;
+                }
+            }
+            if reversed {
+                ticks.reverse();
+            }
+            events.extend(ticks);
+
+            if span < self.span_count - 1 {
+                events.push(SliderEvent {
+                    kind: SliderEventKind::Repeat,
+                    time: span_start + self.span_duration,
+                    path_progress: ((span + 1) % 2) as f64,
+                });
+            }
+        }
+
+        let total_duration = self.duration();
+        let final_span_start = start_time + (self.span_count - 1) as f64 * self.span_duration;
+        let final_span_end = (start_time + total_duration / 2.0)
+            .max(final_span_start + self.span_duration + LEGACY_LAST_TICK_OFFSET);
+        let mut final_progress = if self.span_duration > 0.0 {
+            (final_span_end - final_span_start) / self.span_duration
+        } else {
+            0.0
+        };
+        if self.span_count.is_multiple_of(2) {
+            final_progress = 1.0 - final_progress;
+        }
+        events.push(SliderEvent {
+            kind: SliderEventKind::LegacyLastTick,
+            time: final_span_end,
+            path_progress: final_progress,
+        });
+        events.push(SliderEvent {
+            kind: SliderEventKind::Tail,
+            time: start_time + total_duration,
+            path_progress: (self.span_count % 2) as f64,
+        });
+        events
+    }
+}
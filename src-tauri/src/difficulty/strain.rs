@@ -0,0 +1,99 @@
+// Strain skills keep a decaying value per object and rate a map by the highest
+// value reached in each section of this length.
+pub const SECTION_LENGTH: f64 = 400.0;
+
+pub fn strain_decay(base: f64, ms: f64) -> f64 {
+    base.powf(ms / 1000.0)
+}
+
+#[derive(Debug, Clone)]
+pub struct StrainPeaks {
+    section_length: f64,
+    peaks: Vec<f64>,
+    current_peak: f64,
+    section_end: Option<f64>,
+}
+
+impl Default for StrainPeaks {
+    fn default() -> Self {
+        StrainPeaks::with_section_length(SECTION_LENGTH)
+    }
+}
+
+impl StrainPeaks {
+    pub fn with_section_length(section_length: f64) -> Self {
+        StrainPeaks {
+            section_length,
+            peaks: Vec::new(),
+            current_peak: 0.0,
+            section_end: None,
+        }
+    }
+
+    // Must be called before each object's strain is recorded. `initial_strain`
+    // returns the previous object's strain decayed to the given time.
+    pub fn start_object(&mut self, time: f64, initial_strain: impl Fn(f64) -> f64) {
+        let mut section_end = self
+            .section_end
+            .unwrap_or_else(|| (time / self.section_length).ceil() * self.section_length);
+
+        while time > section_end {
+            self.peaks.push(self.current_peak);
+            self.current_peak = initial_strain(section_end);
+            section_end += self.section_length;
+        }
+        self.section_end = Some(section_end);
+    }
+
+    pub fn record(&mut self, strain: f64) {
+        self.current_peak = self.current_peak.max(strain);
+    }
+
+    pub fn peaks(&self) -> Vec<f64> {
+        let mut peaks = self.peaks.clone();
+        if self.section_end.is_some() {
+            peaks.push(self.current_peak);
+        }
+        peaks
+    }
+}
+
+// Sum of strains from highest to lowest, each weighted by `decay_weight` more than the last.
+pub fn weighted_sum(mut strains: Vec<f64>, decay_weight: f64) -> f64 {
+    strains.sort_by(|a, b| b.total_cmp(a));
+    let mut weight = 1.0;
+    let mut total = 0.0;
+    for strain in strains {
+        total += strain * weight;
+        weight *= decay_weight;
+    }
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decays_per_second() {
+        assert_eq!(strain_decay(0.15, 1000.0), 0.15);
+        assert!((strain_decay(0.3, 500.0) - 0.3f64.sqrt()).abs() < 1e-12);
+        assert_eq!(strain_decay(0.3, 0.0), 1.0);
+    }
+
+    #[test]
+    fn peaks_per_section() {
+        let mut peaks = StrainPeaks::default();
+        for (time, strain) in [(100.0, 5.0), (300.0, 3.0), (500.0, 2.0), (1300.0, 1.0)] {
+            peaks.start_object(time, |_| 0.5);
+            peaks.record(strain);
+        }
+        // Sections end at 400, 800, 1200 and 1600; the empty one keeps the decayed strain.
+        assert_eq!(peaks.peaks(), vec![5.0, 2.0, 0.5, 1.0]);
+    }
+
+    #[test]
+    fn weights_hardest_first() {
+        assert_eq!(weighted_sum(vec![1.0, 4.0, 2.0], 0.5), 4.0 + 1.0 + 0.25);
+    }
+}
@@ -0,0 +1,156 @@
+use super::objects::{HitType, TaikoDifficultyObject};
+
+const MAX_REPETITION_INTERVAL: usize = 16;
+
+// Consecutive objects of the same colour.
+struct MonoStreak {
+    objects: Vec<usize>,
+    hit_type: Option<HitType>,
+}
+
+// Consecutive mono streaks of the same length, e.g. kkd kkd or k d k d.
+struct AlternatingPattern {
+    streaks: Vec<usize>,
+}
+
+// Alternating patterns that keep repeating with the same structure.
+struct RepeatingPattern {
+    patterns: Vec<usize>,
+    repetition_interval: usize,
+}
+
+struct Encoding {
+    streaks: Vec<MonoStreak>,
+    patterns: Vec<AlternatingPattern>,
+    repeating: Vec<RepeatingPattern>,
+}
+
+impl Encoding {
+    fn run_length(&self, streak: usize) -> usize {
+        self.streaks[streak].objects.len()
+    }
+
+    fn identical_mono_length(&self, a: usize, b: usize) -> bool {
+        self.run_length(self.patterns[a].streaks[0]) == self.run_length(self.patterns[b].streaks[0])
+    }
+
+    fn pattern_repeats(&self, a: usize, b: usize) -> bool {
+        let (first, second) = (&self.patterns[a], &self.patterns[b]);
+        self.identical_mono_length(a, b)
+            && first.streaks.len() == second.streaks.len()
+            && self.streaks[first.streaks[0]].hit_type == self.streaks[second.streaks[0]].hit_type
+    }
+
+    fn repeating_matches(&self, a: usize, b: usize) -> bool {
+        let (first, second) = (&self.repeating[a], &self.repeating[b]);
+        first.patterns.len() == second.patterns.len()
+            && first
+                .patterns
+                .iter()
+                .zip(&second.patterns)
+                .take(2)
+                .all(|(x, y)| self.identical_mono_length(*x, *y))
+    }
+}
+
+fn encode(objects: &[TaikoDifficultyObject]) -> Encoding {
+    let mut streaks: Vec<MonoStreak> = Vec::new();
+    let mut last_note_type = None;
+    for object in objects {
+        let hit_type = object.object.hit_type();
+        // Drum rolls and swells never continue a streak, but a hit may continue theirs.
+        let continues =
+            hit_type.is_some() && last_note_type.is_some() && hit_type == last_note_type;
+        match streaks.last_mut() {
+            Some(streak) if continues => streak.objects.push(object.index),
+            _ => streaks.push(MonoStreak {
+                objects: vec![object.index],
+                hit_type,
+            }),
+        }
+        if hit_type.is_some() {
+            last_note_type = hit_type;
+        }
+    }
+
+    let mut patterns = Vec::new();
+    let mut current = Vec::new();
+    for i in 0..streaks.len() {
+        current.push(i);
+        if i == streaks.len() - 1 || streaks[i].objects.len() != streaks[i + 1].objects.len() {
+            patterns.push(AlternatingPattern {
+                streaks: std::mem::take(&mut current),
+            });
+        }
+    }
+
+    let mut encoding = Encoding {
+        streaks,
+        patterns,
+        repeating: Vec::new(),
+    };
+
+    let count = encoding.patterns.len();
+    let coupled =
+        |encoding: &Encoding, i: usize| i + 2 < count && encoding.pattern_repeats(i, i + 2);
+    let mut i = 0;
+    while i < count {
+        let mut group = Vec::new();
+        if coupled(&encoding, i) {
+            while coupled(&encoding, i) {
+                group.push(i);
+                i += 1;
+            }
+            group.push(i);
+            group.push(i + 1);
+            i += 1;
+        } else {
+            group.push(i);
+        }
+        encoding.repeating.push(RepeatingPattern {
+            patterns: group,
+            repetition_interval: MAX_REPETITION_INTERVAL + 1,
+        });
+        i += 1;
+    }
+
+    for i in 0..encoding.repeating.len() {
+        let interval = (1..MAX_REPETITION_INTERVAL)
+            .take_while(|interval| *interval <= i)
+            .find(|interval| encoding.repeating_matches(i, i - interval));
+        encoding.repeating[i].repetition_interval = interval.unwrap_or(MAX_REPETITION_INTERVAL + 1);
+    }
+    encoding
+}
+
+fn sigmoid(value: f64, center: f64, width: f64, middle: f64, height: f64) -> f64 {
+    (std::f64::consts::E * -(value - center) / width).tanh() * (height / 2.0) + middle
+}
+
+// Colour difficulty each object adds, concentrated on the first object of each
+// streak and pattern it starts.
+pub fn colour_difficulties(objects: &[TaikoDifficultyObject]) -> Vec<f64> {
+    let encoding = encode(objects);
+    let mut difficulties = vec![0.0; objects.len()];
+
+    for repeating in &encoding.repeating {
+        let repeating_difficulty =
+            2.0 * (1.0 - sigmoid(repeating.repetition_interval as f64, 2.0, 2.0, 0.5, 1.0));
+        for (pattern_index, &pattern) in repeating.patterns.iter().enumerate() {
+            let pattern_difficulty =
+                sigmoid(pattern_index as f64, 2.0, 2.0, 0.5, 1.0) * repeating_difficulty;
+            for (streak_index, &streak) in encoding.patterns[pattern].streaks.iter().enumerate() {
+                let first = encoding.streaks[streak].objects[0];
+                difficulties[first] +=
+                    sigmoid(streak_index as f64, 2.0, 2.0, 0.5, 1.0) * pattern_difficulty * 0.5;
+                if streak_index == 0 {
+                    difficulties[first] += pattern_difficulty;
+                    if pattern_index == 0 {
+                        difficulties[first] += repeating_difficulty;
+                    }
+                }
+            }
+        }
+    }
+    difficulties
+}
@@ -0,0 +1,191 @@
+pub mod colour;
+pub mod objects;
+pub mod skills;
+
+use crate::difficulty::{apply_mods, difficulty_range};
+use crate::models::beatmap::Beatmap;
+use crate::models::mods;
+use objects::{difficulty_objects, taiko_objects, TaikoObjectKind};
+use serde::Serialize;
+use skills::TaikoSkills;
+
+const DIFFICULTY_MULTIPLIER: f64 = 1.35;
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct TaikoDifficultyAttributes {
+    pub star_rating: f64,
+    pub rhythm: f64,
+    pub colour: f64,
+    pub stamina: f64,
+    // Milliseconds either side of a hit that still count as a GREAT.
+    pub great_hit_window: f64,
+    pub max_combo: u32,
+    pub hit_count: u32,
+    pub drum_roll_count: u32,
+    pub swell_count: u32,
+    pub clock_rate: f64,
+    pub converted: bool,
+}
+
+fn rescale(star_rating: f64) -> f64 {
+    if star_rating < 0.0 {
+        star_rating
+    } else {
+        10.43 * (star_rating / 8.0 + 1.0).ln()
+    }
+}
+
+pub fn calculate(beatmap: &Beatmap, mods: i32) -> TaikoDifficultyAttributes {
+    let clock_rate = mods::clock_rate(mods);
+    let difficulty = apply_mods(&beatmap.difficulty, mods);
+    let converted = beatmap.general.mode != 1;
+
+    let objects = taiko_objects(beatmap);
+    let count = |f: fn(&TaikoObjectKind) -> bool| objects.iter().filter(|o| f(&o.kind)).count();
+    let hit_count = count(|k| matches!(k, TaikoObjectKind::Hit(_))) as u32;
+    let mut attributes = TaikoDifficultyAttributes {
+        great_hit_window: difficulty_range(difficulty.overall_difficulty, 50.0, 35.0, 20.0)
+            / clock_rate,
+        max_combo: hit_count,
+        hit_count,
+        drum_roll_count: count(|k| matches!(k, TaikoObjectKind::DrumRoll)) as u32,
+        swell_count: count(|k| matches!(k, TaikoObjectKind::Swell)) as u32,
+        clock_rate,
+        converted,
+        ..Default::default()
+    };
+
+    let difficulty_objects = difficulty_objects(&objects, clock_rate);
+    if difficulty_objects.is_empty() {
+        return attributes;
+    }
+
+    let skills = TaikoSkills::new(&difficulty_objects);
+    attributes.rhythm = skills.rhythm() * DIFFICULTY_MULTIPLIER;
+    attributes.colour = skills.colour() * DIFFICULTY_MULTIPLIER;
+    attributes.stamina = skills.stamina() * DIFFICULTY_MULTIPLIER;
+
+    let mut star_rating = rescale(skills.combined() * DIFFICULTY_MULTIPLIER * 1.4);
+    // Converts can often be played with more than two fingers per colour, which the
+    // stamina skill doesn't account for.
+    if converted {
+        star_rating *= 0.925;
+        if attributes.colour < 2.0 && attributes.stamina > 8.0 {
+            star_rating *= 0.8;
+        }
+    }
+    attributes.star_rating = star_rating;
+    attributes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::difficulty::fixtures::{self, assert_close};
+    use crate::models::mods::{DOUBLE_TIME, EASY, HARD_ROCK};
+
+    // Twelve-note colour patterns with finishers, switching between 1/4 and 1/2 rhythm,
+    // then a drum roll and a swell.
+    fn native() -> Beatmap {
+        let sounds = [0, 0, 8, 0, 8, 8, 0, 2, 0, 0, 8, 4];
+        let mut objects = String::new();
+        let mut time = 1000.0;
+        for i in 0..480 {
+            objects += &format!("256,192,{},1,{},0:0:0:0:\n", time as i32, sounds[i % 12]);
+            time += if (i / 16) % 3 == 2 { 166.667 } else { 83.333 };
+        }
+        let end = time as i32;
+        objects += &format!("256,192,{},2,0,L|456:192,1,280\n", end + 200);
+        objects += &format!("256,192,{},12,0,{},0:0:0:0:\n", end + 1000, end + 2500);
+        fixtures::map(1, 5.0, &objects)
+    }
+
+    fn converted() -> Beatmap {
+        let objects = fixtures::jump_objects(400)
+            + "100,100,70000,2,0,L|380:100,2,280\n256,192,72000,12,0,74000,0:0:0:0:\n";
+        fixtures::map(0, 4.0, &objects)
+    }
+
+    fn assert_skills(attributes: &TaikoDifficultyAttributes, expected: [f64; 4]) {
+        assert_close("stars", attributes.star_rating, expected[0]);
+        assert_close("rhythm", attributes.rhythm, expected[1]);
+        assert_close("colour", attributes.colour, expected[2]);
+        assert_close("stamina", attributes.stamina, expected[3]);
+    }
+
+    // Regression values from this calculator; any change to the skills should be deliberate.
+    #[test]
+    fn native_reference_values() {
+        let beatmap = native();
+        let attributes = calculate(&beatmap, 0);
+        assert!(!attributes.converted);
+        assert_eq!(attributes.hit_count, 480);
+        assert_eq!(attributes.max_combo, 480);
+        assert_eq!(attributes.drum_roll_count, 1);
+        assert_eq!(attributes.swell_count, 1);
+        assert_skills(&attributes, [6.0236, 0.0646, 2.9454, 2.6947]);
+        assert_skills(
+            &calculate(&beatmap, DOUBLE_TIME),
+            [7.9724, 0.0657, 4.2390, 4.0757],
+        );
+    }
+
+    #[test]
+    fn converted_reference_values() {
+        let beatmap = converted();
+        let attributes = calculate(&beatmap, 0);
+        assert!(attributes.converted);
+        assert_eq!(attributes.hit_count, 400);
+        assert_eq!(attributes.drum_roll_count, 1);
+        assert_eq!(attributes.swell_count, 1);
+        assert_skills(&attributes, [2.0820, 0.0, 0.3213, 1.3525]);
+        assert_skills(
+            &calculate(&beatmap, DOUBLE_TIME),
+            [2.9983, 0.0, 0.3338, 2.0683],
+        );
+    }
+
+    #[test]
+    fn only_rate_changes_affect_stars() {
+        let beatmap = native();
+        let star_rating = calculate(&beatmap, 0).star_rating;
+        for mods in [HARD_ROCK, EASY] {
+            assert_eq!(calculate(&beatmap, mods).star_rating, star_rating);
+        }
+        // OD 8 scaled by HR and EZ, then by the clock rate.
+        assert_close("window", calculate(&beatmap, 0).great_hit_window, 26.0);
+        assert_close(
+            "HR window",
+            calculate(&beatmap, HARD_ROCK).great_hit_window,
+            20.0,
+        );
+        assert_close(
+            "EZ window",
+            calculate(&beatmap, EASY).great_hit_window,
+            38.0,
+        );
+        assert_close(
+            "DT window",
+            calculate(&beatmap, DOUBLE_TIME).great_hit_window,
+            26.0 / 1.5,
+        );
+    }
+
+    #[test]
+    fn colour_and_stamina_follow_the_pattern() {
+        let stream = |sounds: [u8; 2]| {
+            let mut objects = String::new();
+            for i in 0..200 {
+                objects += &format!("256,192,{},1,{},0:0:0:0:\n", 1000 + i * 125, sounds[i % 2]);
+            }
+            calculate(&fixtures::map(1, 5.0, &objects), 0)
+        };
+        let mono = stream([0, 0]);
+        let alternating = stream([0, 8]);
+        assert!(alternating.colour > mono.colour);
+        // Hitting one colour leaves fewer fingers to share the load.
+        assert!(mono.stamina > alternating.stamina);
+        // An unchanging rhythm has nothing for the rhythm skill to rate.
+        assert_eq!(mono.rhythm, 0.0);
+    }
+}
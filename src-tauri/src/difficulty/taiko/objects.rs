@@ -0,0 +1,203 @@
+use crate::models::beatmap::Beatmap;
+use crate::models::hitobject::{HitObjectKind, HITSOUND_CLAP, HITSOUND_WHISTLE};
+use std::collections::HashSet;
+
+const BASE_SCORING_DISTANCE: f64 = 100.0;
+// osu!stable made taiko sliders 1.4x faster than the standard map they came from.
+const LEGACY_VELOCITY_MULTIPLIER: f64 = 1.4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HitType {
+    Centre,
+    Rim,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TaikoObjectKind {
+    Hit(HitType),
+    DrumRoll,
+    Swell,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TaikoObject {
+    pub kind: TaikoObjectKind,
+    pub start_time: f64,
+}
+
+impl TaikoObject {
+    pub fn hit_type(&self) -> Option<HitType> {
+        match self.kind {
+            TaikoObjectKind::Hit(hit_type) => Some(hit_type),
+            _ => None,
+        }
+    }
+}
+
+fn hit_type(sound: u8) -> HitType {
+    if sound & (HITSOUND_CLAP | HITSOUND_WHISTLE) != 0 {
+        HitType::Rim
+    } else {
+        HitType::Centre
+    }
+}
+
+// Short standard sliders become a run of hits instead of a drum roll. Returns the
+// spacing between those hits when that's the case.
+fn slider_hit_spacing(
+    beatmap: &Beatmap,
+    time: f64,
+    pixel_length: f64,
+    spans: u32,
+    converted: bool,
+) -> Option<(f64, f64)> {
+    let timing = &beatmap.timing_points;
+    let difficulty = &beatmap.difficulty;
+    let beat_length = timing.beat_length_at(time);
+    let mut adjusted_beat_length = beat_length / timing.slider_velocity_at(time);
+
+    let distance = pixel_length * spans as f64 * LEGACY_VELOCITY_MULTIPLIER;
+    let taiko_velocity =
+        BASE_SCORING_DISTANCE * difficulty.slider_multiplier * LEGACY_VELOCITY_MULTIPLIER;
+    let duration = (distance / taiko_velocity * adjusted_beat_length).trunc();
+    if !converted || difficulty.slider_tick_rate <= 0.0 {
+        return None;
+    }
+
+    let osu_velocity = taiko_velocity * (1000.0 / adjusted_beat_length);
+    // osu!stable only used the slider velocity to decide, not to space the hits, from v8 on.
+    if beatmap.format_version >= 8 {
+        adjusted_beat_length = beat_length;
+    }
+    let spacing = (adjusted_beat_length / difficulty.slider_tick_rate).min(duration / spans as f64);
+    (spacing > 0.0 && distance / osu_velocity * 1000.0 < 2.0 * adjusted_beat_length)
+        .then_some((spacing, duration))
+}
+
+// Applies osu!stable's conversion rules when `beatmap` isn't a taiko map.
+pub fn taiko_objects(beatmap: &Beatmap) -> Vec<TaikoObject> {
+    let converted = beatmap.general.mode != 1;
+    let mut objects = Vec::new();
+
+    for object in &beatmap.hit_objects {
+        match &object.kind {
+            HitObjectKind::Circle => objects.push(TaikoObject {
+                kind: TaikoObjectKind::Hit(hit_type(object.hit_sound)),
+                start_time: object.time,
+            }),
+            HitObjectKind::Slider(slider) => {
+                let spans = slider.slides.max(1);
+                let spacing = slider_hit_spacing(
+                    beatmap,
+                    object.time,
+                    slider.pixel_length(object.position),
+                    spans,
+                    converted,
+                );
+                let Some((spacing, duration)) = spacing else {
+                    objects.push(TaikoObject {
+                        kind: TaikoObjectKind::DrumRoll,
+                        start_time: object.time,
+                    });
+                    continue;
+                };
+
+                // Each hit takes the next edge's sounds, wrapping around.
+                let edge_count = spans as usize + 1;
+                let mut edge = 0;
+                let mut time = object.time;
+                while time <= object.time + duration + spacing / 8.0 {
+                    let sound = slider
+                        .edge_sounds
+                        .get(edge)
+                        .copied()
+                        .unwrap_or(object.hit_sound);
+                    objects.push(TaikoObject {
+                        kind: TaikoObjectKind::Hit(hit_type(sound)),
+                        start_time: time,
+                    });
+                    edge = (edge + 1) % edge_count;
+                    time += spacing;
+                }
+            }
+            HitObjectKind::Spinner { .. } | HitObjectKind::Hold { .. } => {
+                objects.push(TaikoObject {
+                    kind: TaikoObjectKind::Swell,
+                    start_time: object.time,
+                })
+            }
+        }
+    }
+
+    // Objects at the same time merge into one (strong) hit.
+    let mut seen = HashSet::new();
+    objects.retain(|o| seen.insert(o.start_time.to_bits()));
+    objects
+}
+
+struct Rhythm {
+    ratio: f64,
+    difficulty: f64,
+}
+
+const fn rhythm(numerator: u32, denominator: u32, difficulty: f64) -> Rhythm {
+    Rhythm {
+        ratio: numerator as f64 / denominator as f64,
+        difficulty,
+    }
+}
+
+// Interval ratios to the previous note, and how hard each change is to read.
+const COMMON_RHYTHMS: [Rhythm; 9] = [
+    rhythm(1, 1, 0.0),
+    rhythm(2, 1, 0.3),
+    rhythm(1, 2, 0.5),
+    rhythm(3, 1, 0.3),
+    rhythm(1, 3, 0.35),
+    rhythm(3, 2, 0.6),
+    rhythm(2, 3, 0.4),
+    rhythm(5, 4, 0.5),
+    rhythm(4, 5, 0.7),
+];
+
+#[derive(Debug, Clone)]
+pub struct TaikoDifficultyObject {
+    pub object: TaikoObject,
+    pub index: usize,
+    pub start_time: f64,
+    pub delta_time: f64,
+    pub rhythm: usize,
+}
+
+impl TaikoDifficultyObject {
+    pub fn rhythm_difficulty(&self) -> f64 {
+        COMMON_RHYTHMS[self.rhythm].difficulty
+    }
+}
+
+pub fn difficulty_objects(objects: &[TaikoObject], clock_rate: f64) -> Vec<TaikoDifficultyObject> {
+    let mut difficulty_objects = Vec::new();
+
+    for i in 2.min(objects.len())..objects.len() {
+        let object = objects[i];
+        let delta_time = (object.start_time - objects[i - 1].start_time) / clock_rate;
+        let previous_length = (objects[i - 1].start_time - objects[i - 2].start_time) / clock_rate;
+        let ratio = delta_time / previous_length;
+        let rhythm = (0..COMMON_RHYTHMS.len())
+            .min_by(|a, b| {
+                (COMMON_RHYTHMS[*a].ratio - ratio)
+                    .abs()
+                    .total_cmp(&(COMMON_RHYTHMS[*b].ratio - ratio).abs())
+            })
+            .unwrap_or(0);
+
+        difficulty_objects.push(TaikoDifficultyObject {
+            object,
+            index: difficulty_objects.len(),
+            start_time: object.start_time / clock_rate,
+            delta_time,
+            rhythm,
+        });
+    }
+    difficulty_objects
+}
@@ -0,0 +1,198 @@
+use super::colour::colour_difficulties;
+use super::objects::{HitType, TaikoDifficultyObject};
+use crate::difficulty::strain::{strain_decay, weighted_sum, StrainPeaks};
+use std::collections::VecDeque;
+
+const RHYTHM_MULTIPLIER: f64 = 10.0;
+const RHYTHM_DECAY_BASE: f64 = 0.0;
+const RHYTHM_STRAIN_DECAY: f64 = 0.96;
+const RHYTHM_HISTORY_LENGTH: usize = 5;
+
+const COLOUR_MULTIPLIER: f64 = 0.12;
+const COLOUR_DECAY_BASE: f64 = 0.8;
+
+const STAMINA_MULTIPLIER: f64 = 1.1;
+const STAMINA_DECAY_BASE: f64 = 0.4;
+
+const FINAL_MULTIPLIER: f64 = 0.0625;
+const RHYTHM_SKILL_MULTIPLIER: f64 = 0.2 * FINAL_MULTIPLIER;
+const COLOUR_SKILL_MULTIPLIER: f64 = 0.375 * FINAL_MULTIPLIER;
+const STAMINA_SKILL_MULTIPLIER: f64 = 0.375 * FINAL_MULTIPLIER;
+const DECAY_WEIGHT: f64 = 0.9;
+
+// Section peaks of a strain that decays by `decay_base` per second and grows by
+// `multiplier` times each object's value.
+fn decaying_peaks(
+    objects: &[TaikoDifficultyObject],
+    decay_base: f64,
+    multiplier: f64,
+    mut value_of: impl FnMut(&TaikoDifficultyObject) -> f64,
+) -> Vec<f64> {
+    let mut peaks = StrainPeaks::default();
+    let mut strain = 0.0;
+    for (i, object) in objects.iter().enumerate() {
+        let previous_time = if i > 0 {
+            objects[i - 1].start_time
+        } else {
+            object.start_time
+        };
+        let current = strain;
+        peaks.start_object(object.start_time, |time| {
+            current * strain_decay(decay_base, time - previous_time)
+        });
+        strain =
+            strain * strain_decay(decay_base, object.delta_time) + value_of(object) * multiplier;
+        peaks.record(strain);
+    }
+    peaks.peaks()
+}
+
+#[derive(Default)]
+struct RhythmState {
+    strain: f64,
+    notes_since_change: usize,
+    history: VecDeque<(usize, usize)>,
+}
+
+impl RhythmState {
+    fn reset(&mut self) {
+        self.strain = 0.0;
+        self.notes_since_change = 0;
+    }
+
+    // Penalises a rhythm change that repeats one made shortly before.
+    fn repetition_penalty(&mut self, object: &TaikoDifficultyObject) -> f64 {
+        if self.history.len() == RHYTHM_HISTORY_LENGTH {
+            self.history.pop_front();
+        }
+        self.history.push_back((object.index, object.rhythm));
+
+        let mut penalty = 1.0;
+        let count = self.history.len();
+        for compared in 2..=RHYTHM_HISTORY_LENGTH / 2 {
+            if count < compared + 1 {
+                continue;
+            }
+            for start in (0..count - compared).rev() {
+                let same = (0..compared)
+                    .all(|i| self.history[start + i].1 == self.history[count - compared + i].1);
+                if same {
+                    let notes_since = object.index - self.history[start].0;
+                    penalty *= (0.032 * notes_since as f64).min(1.0);
+                    break;
+                }
+            }
+        }
+        penalty
+    }
+
+    fn strain_of(&mut self, object: &TaikoDifficultyObject) -> f64 {
+        if object.object.hit_type().is_none() {
+            self.reset();
+            return 0.0;
+        }
+        self.strain *= RHYTHM_STRAIN_DECAY;
+        self.notes_since_change += 1;
+
+        let difficulty = object.rhythm_difficulty();
+        if difficulty == 0.0 {
+            return 0.0;
+        }
+
+        let pattern_length = self.notes_since_change as f64;
+        let length_penalty = (0.15 * pattern_length)
+            .min(1.0)
+            .min((2.5 - 0.15 * pattern_length).clamp(0.0, 1.0));
+        // Slow rhythm changes are easy to read and stop counting entirely past 210ms.
+        let speed_penalty = if object.delta_time < 80.0 {
+            1.0
+        } else if object.delta_time < 210.0 {
+            (1.4 - 0.005 * object.delta_time).max(0.0)
+        } else {
+            self.reset();
+            0.0
+        };
+
+        let strain = difficulty * self.repetition_penalty(object) * length_penalty * speed_penalty;
+        self.notes_since_change = 0;
+        self.strain += strain;
+        self.strain
+    }
+}
+
+pub struct TaikoSkills {
+    rhythm: Vec<f64>,
+    colour: Vec<f64>,
+    stamina: Vec<f64>,
+}
+
+fn norm(p: f64, values: &[f64]) -> f64 {
+    values.iter().map(|v| v.powf(p)).sum::<f64>().powf(1.0 / p)
+}
+
+impl TaikoSkills {
+    pub fn new(objects: &[TaikoDifficultyObject]) -> Self {
+        let mut rhythm = RhythmState::default();
+        let rhythm = decaying_peaks(objects, RHYTHM_DECAY_BASE, RHYTHM_MULTIPLIER, |o| {
+            rhythm.strain_of(o)
+        });
+
+        let colours = colour_difficulties(objects);
+        let colour = decaying_peaks(objects, COLOUR_DECAY_BASE, COLOUR_MULTIPLIER, |o| {
+            colours[o.index]
+        });
+
+        // Each colour is assumed to be played with alternating hands, so a note is
+        // strained by the one two notes back of the same colour.
+        let mut centre_times = Vec::new();
+        let mut rim_times = Vec::new();
+        let stamina = decaying_peaks(objects, STAMINA_DECAY_BASE, STAMINA_MULTIPLIER, |o| {
+            let times = match o.object.hit_type() {
+                Some(HitType::Centre) => &mut centre_times,
+                Some(HitType::Rim) => &mut rim_times,
+                None => return 0.0,
+            };
+            times.push(o.start_time);
+            match times.len().checked_sub(3).map(|i| times[i]) {
+                Some(previous) => 0.5 + 30.0 / (o.start_time - previous).max(50.0),
+                None => 0.0,
+            }
+        });
+
+        TaikoSkills {
+            rhythm,
+            colour,
+            stamina,
+        }
+    }
+
+    pub fn rhythm(&self) -> f64 {
+        weighted_sum(self.rhythm.clone(), DECAY_WEIGHT) * RHYTHM_SKILL_MULTIPLIER
+    }
+
+    pub fn colour(&self) -> f64 {
+        weighted_sum(self.colour.clone(), DECAY_WEIGHT) * COLOUR_SKILL_MULTIPLIER
+    }
+
+    pub fn stamina(&self) -> f64 {
+        weighted_sum(self.stamina.clone(), DECAY_WEIGHT) * STAMINA_SKILL_MULTIPLIER
+    }
+
+    // Colour and stamina are combined first since they're both about which keys are hit.
+    pub fn combined(&self) -> f64 {
+        let peaks = (0..self.colour.len())
+            .map(|i| {
+                let peak = norm(
+                    1.5,
+                    &[
+                        self.colour[i] * COLOUR_SKILL_MULTIPLIER,
+                        self.stamina[i] * STAMINA_SKILL_MULTIPLIER,
+                    ],
+                );
+                norm(2.0, &[peak, self.rhythm[i] * RHYTHM_SKILL_MULTIPLIER])
+            })
+            .filter(|peak| *peak > 0.0)
+            .collect();
+        weighted_sum(peaks, DECAY_WEIGHT)
+    }
+}
@@ -1,6 +1,9 @@
+pub mod checks;
 mod commands;
-mod models;
-mod utils;
+pub mod difficulty;
+pub mod models;
+pub mod transforms;
+pub mod utils;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -10,6 +13,8 @@ pub fn run() {
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_dialog::init())
+        .manage(models::songs_index::SongsIndexState::default())
+        .manage(commands::SongsWatcherState::default())
         .register_uri_scheme_protocol("asset", move |_app, request| {
             use percent_encoding::percent_decode_str;
             use std::{fs, path::PathBuf};
@@ -57,9 +62,34 @@ pub fn run() {
             commands::run_download,
             commands::detect_osu_path,
             commands::scan_songs_step,
+            commands::refresh_songs_index,
+            commands::get_beatmapset_details,
+            commands::get_local_scores,
+            commands::watch_songs_folder,
+            commands::unwatch_songs_folder,
             commands::list_osu_files,
             commands::read_osu_file,
+            commands::read_replay,
             commands::clone_beatmap,
+            commands::customize_beatmap,
+            commands::export_osz,
+            commands::import_osz,
+            commands::analyze_set_files,
+            commands::check_storyboard,
+            commands::analyze_difficulty_spread,
+            commands::list_map_checks,
+            commands::run_map_checks,
+            commands::check_metadata_consistency,
+            commands::apply_metadata,
+            commands::calculate_difficulty,
+            commands::list_collections,
+            commands::add_to_collection,
+            commands::list_transforms,
+            commands::preview_transforms,
+            commands::apply_transforms,
+            commands::list_backups,
+            commands::restore_backup,
+            commands::delete_backup,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,971 @@
+use crate::models::hitobject::{HitObject, HitObjectKind};
+use crate::models::storyboard::{is_storyboard_line, parse_variables, Storyboard};
+use crate::models::timing::{TimingPoint, TimingPoints};
+use crate::utils::parser::{
+    detect_eol, is_skippable, join_lines, merge_items, merge_pairs, parse_pairs, section_name,
+    split_lines, RawLine,
+};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+const SECTION_ORDER: [&str; 8] = [
+    "General",
+    "Editor",
+    "Metadata",
+    "Difficulty",
+    "Events",
+    "TimingPoints",
+    "Colours",
+    "HitObjects",
+];
+
+pub(crate) fn format_bool(value: bool) -> String {
+    if value { "1" } else { "0" }.to_string()
+}
+
+fn parse_bool(value: &str) -> bool {
+    value == "1" || value.eq_ignore_ascii_case("true")
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct General {
+    pub audio_filename: String,
+    pub audio_lead_in: i32,
+    pub preview_time: i32,
+    pub countdown: u8,
+    pub sample_set: String,
+    pub stack_leniency: f64,
+    pub mode: u8,
+    pub letterbox_in_breaks: bool,
+    pub use_skin_sprites: bool,
+    pub overlay_position: String,
+    pub skin_preference: String,
+    pub epilepsy_warning: bool,
+    pub countdown_offset: i32,
+    pub special_style: bool,
+    pub widescreen_storyboard: bool,
+    pub samples_match_playback_rate: bool,
+}
+
+impl Default for General {
+    fn default() -> Self {
+        General {
+            audio_filename: String::new(),
+            audio_lead_in: 0,
+            preview_time: -1,
+            countdown: 1,
+            sample_set: "Normal".to_string(),
+            stack_leniency: 0.7,
+            mode: 0,
+            letterbox_in_breaks: false,
+            use_skin_sprites: false,
+            overlay_position: "NoChange".to_string(),
+            skin_preference: String::new(),
+            epilepsy_warning: false,
+            countdown_offset: 0,
+            special_style: false,
+            widescreen_storyboard: false,
+            samples_match_playback_rate: false,
+        }
+    }
+}
+
+impl General {
+    fn from_pairs(pairs: &[(String, String)]) -> Self {
+        let mut general = General::default();
+        for (key, value) in pairs {
+            match key.as_str() {
+                "AudioFilename" => general.audio_filename = value.clone(),
+                "AudioLeadIn" => general.audio_lead_in = value.parse().unwrap_or(0),
+                "PreviewTime" => general.preview_time = value.parse().unwrap_or(-1),
+                "Countdown" => general.countdown = value.parse().unwrap_or(1),
+                "SampleSet" => general.sample_set = value.clone(),
+                "StackLeniency" => general.stack_leniency = value.parse().unwrap_or(0.7),
+                "Mode" => general.mode = value.parse().unwrap_or(0),
+                "LetterboxInBreaks" => general.letterbox_in_breaks = parse_bool(value),
+                "UseSkinSprites" => general.use_skin_sprites = parse_bool(value),
+                "OverlayPosition" => general.overlay_position = value.clone(),
+                "SkinPreference" => general.skin_preference = value.clone(),
+                "EpilepsyWarning" => general.epilepsy_warning = parse_bool(value),
+                "CountdownOffset" => general.countdown_offset = value.parse().unwrap_or(0),
+                "SpecialStyle" => general.special_style = parse_bool(value),
+                "WidescreenStoryboard" => general.widescreen_storyboard = parse_bool(value),
+                "SamplesMatchPlaybackRate" => {
+                    general.samples_match_playback_rate = parse_bool(value)
+                }
+                _ => {}
+            }
+        }
+        general
+    }
+
+    fn to_pairs(&self) -> Vec<(String, String)> {
+        vec![
+            ("AudioFilename".into(), self.audio_filename.clone()),
+            ("AudioLeadIn".into(), self.audio_lead_in.to_string()),
+            ("PreviewTime".into(), self.preview_time.to_string()),
+            ("Countdown".into(), self.countdown.to_string()),
+            ("SampleSet".into(), self.sample_set.clone()),
+            ("StackLeniency".into(), self.stack_leniency.to_string()),
+            ("Mode".into(), self.mode.to_string()),
+            (
+                "LetterboxInBreaks".into(),
+                format_bool(self.letterbox_in_breaks),
+            ),
+            ("UseSkinSprites".into(), format_bool(self.use_skin_sprites)),
+            ("OverlayPosition".into(), self.overlay_position.clone()),
+            ("SkinPreference".into(), self.skin_preference.clone()),
+            ("EpilepsyWarning".into(), format_bool(self.epilepsy_warning)),
+            ("CountdownOffset".into(), self.countdown_offset.to_string()),
+            ("SpecialStyle".into(), format_bool(self.special_style)),
+            (
+                "WidescreenStoryboard".into(),
+                format_bool(self.widescreen_storyboard),
+            ),
+            (
+                "SamplesMatchPlaybackRate".into(),
+                format_bool(self.samples_match_playback_rate),
+            ),
+        ]
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Editor {
+    pub bookmarks: Vec<i32>,
+    pub distance_spacing: f64,
+    pub beat_divisor: i32,
+    pub grid_size: i32,
+    pub timeline_zoom: f64,
+}
+
+impl Default for Editor {
+    fn default() -> Self {
+        Editor {
+            bookmarks: Vec::new(),
+            distance_spacing: 1.0,
+            beat_divisor: 4,
+            grid_size: 4,
+            timeline_zoom: 1.0,
+        }
+    }
+}
+
+impl Editor {
+    fn from_pairs(pairs: &[(String, String)]) -> Self {
+        let mut editor = Editor::default();
+        for (key, value) in pairs {
+            match key.as_str() {
+                "Bookmarks" => {
+                    editor.bookmarks = value
+                        .split(',')
+                        .filter_map(|b| b.trim().parse().ok())
+                        .collect()
+                }
+                "DistanceSpacing" => editor.distance_spacing = value.parse().unwrap_or(1.0),
+                "BeatDivisor" => editor.beat_divisor = value.parse().unwrap_or(4),
+                "GridSize" => editor.grid_size = value.parse().unwrap_or(4),
+                "TimelineZoom" => editor.timeline_zoom = value.parse().unwrap_or(1.0),
+                _ => {}
+            }
+        }
+        editor
+    }
+
+    fn to_pairs(&self) -> Vec<(String, String)> {
+        let mut pairs = Vec::new();
+        if !self.bookmarks.is_empty() {
+            let bookmarks: Vec<String> = self.bookmarks.iter().map(|b| b.to_string()).collect();
+            pairs.push(("Bookmarks".into(), bookmarks.join(",")));
+        }
+        pairs.push(("DistanceSpacing".into(), self.distance_spacing.to_string()));
+        pairs.push(("BeatDivisor".into(), self.beat_divisor.to_string()));
+        pairs.push(("GridSize".into(), self.grid_size.to_string()));
+        pairs.push(("TimelineZoom".into(), self.timeline_zoom.to_string()));
+        pairs
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Metadata {
+    pub title: String,
+    pub title_unicode: String,
+    pub artist: String,
+    pub artist_unicode: String,
+    pub creator: String,
+    pub version: String,
+    pub source: String,
+    pub tags: String,
+    pub beatmap_id: i32,
+    pub beatmap_set_id: i32,
+}
+
+impl Metadata {
+    fn from_pairs(pairs: &[(String, String)]) -> Self {
+        let mut metadata = Metadata {
+            beatmap_set_id: -1,
+            ..Default::default()
+        };
+        for (key, value) in pairs {
+            match key.as_str() {
+                "Title" => metadata.title = value.clone(),
+                "TitleUnicode" => metadata.title_unicode = value.clone(),
+                "Artist" => metadata.artist = value.clone(),
+                "ArtistUnicode" => metadata.artist_unicode = value.clone(),
+                "Creator" => metadata.creator = value.clone(),
+                "Version" => metadata.version = value.clone(),
+                "Source" => metadata.source = value.clone(),
+                "Tags" => metadata.tags = value.clone(),
+                "BeatmapID" => metadata.beatmap_id = value.parse().unwrap_or(0),
+                "BeatmapSetID" => metadata.beatmap_set_id = value.parse().unwrap_or(-1),
+                _ => {}
+            }
+        }
+        metadata
+    }
+
+    fn to_pairs(&self) -> Vec<(String, String)> {
+        vec![
+            ("Title".into(), self.title.clone()),
+            ("TitleUnicode".into(), self.title_unicode.clone()),
+            ("Artist".into(), self.artist.clone()),
+            ("ArtistUnicode".into(), self.artist_unicode.clone()),
+            ("Creator".into(), self.creator.clone()),
+            ("Version".into(), self.version.clone()),
+            ("Source".into(), self.source.clone()),
+            ("Tags".into(), self.tags.clone()),
+            ("BeatmapID".into(), self.beatmap_id.to_string()),
+            ("BeatmapSetID".into(), self.beatmap_set_id.to_string()),
+        ]
+    }
+
+    // Fields every difficulty in a set should agree on; Version and BeatmapID are
+    // expected to differ.
+    pub fn shared_fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("Title", self.title.clone()),
+            ("TitleUnicode", self.title_unicode.clone()),
+            ("Artist", self.artist.clone()),
+            ("ArtistUnicode", self.artist_unicode.clone()),
+            ("Creator", self.creator.clone()),
+            ("Source", self.source.clone()),
+            ("Tags", self.tags.clone()),
+            ("BeatmapSetID", self.beatmap_set_id.to_string()),
+        ]
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Difficulty {
+    pub hp_drain_rate: f64,
+    pub circle_size: f64,
+    pub overall_difficulty: f64,
+    pub approach_rate: f64,
+    pub slider_multiplier: f64,
+    pub slider_tick_rate: f64,
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Difficulty {
+            hp_drain_rate: 5.0,
+            circle_size: 5.0,
+            overall_difficulty: 5.0,
+            approach_rate: 5.0,
+            slider_multiplier: 1.4,
+            slider_tick_rate: 1.0,
+        }
+    }
+}
+
+impl Difficulty {
+    fn from_pairs(pairs: &[(String, String)]) -> Self {
+        let mut difficulty = Difficulty::default();
+        let mut approach_rate = None;
+        for (key, value) in pairs {
+            match key.as_str() {
+                "HPDrainRate" => difficulty.hp_drain_rate = value.parse().unwrap_or(5.0),
+                "CircleSize" => difficulty.circle_size = value.parse().unwrap_or(5.0),
+                "OverallDifficulty" => difficulty.overall_difficulty = value.parse().unwrap_or(5.0),
+                "ApproachRate" => approach_rate = value.parse().ok(),
+                "SliderMultiplier" => difficulty.slider_multiplier = value.parse().unwrap_or(1.4),
+                "SliderTickRate" => difficulty.slider_tick_rate = value.parse().unwrap_or(1.0),
+                _ => {}
+            }
+        }
+        difficulty.approach_rate = approach_rate.unwrap_or(difficulty.overall_difficulty);
+        difficulty
+    }
+
+    fn to_pairs(&self) -> Vec<(String, String)> {
+        vec![
+            ("HPDrainRate".into(), self.hp_drain_rate.to_string()),
+            ("CircleSize".into(), self.circle_size.to_string()),
+            (
+                "OverallDifficulty".into(),
+                self.overall_difficulty.to_string(),
+            ),
+            ("ApproachRate".into(), self.approach_rate.to_string()),
+            (
+                "SliderMultiplier".into(),
+                self.slider_multiplier.to_string(),
+            ),
+            ("SliderTickRate".into(), self.slider_tick_rate.to_string()),
+        ]
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Event {
+    Background {
+        filename: String,
+        x_offset: i32,
+        y_offset: i32,
+    },
+    Video {
+        start_time: i32,
+        filename: String,
+        x_offset: i32,
+        y_offset: i32,
+    },
+    Break {
+        start_time: i32,
+        end_time: i32,
+    },
+    Other(String),
+}
+
+impl Event {
+    pub fn parse(line: &str) -> Option<Self> {
+        if is_skippable(line) {
+            return None;
+        }
+
+        if line.starts_with(' ') || line.starts_with('_') {
+            return Some(Event::Other(line.to_string()));
+        }
+
+        let parts: Vec<&str> = line.split(',').map(|p| p.trim()).collect();
+        let int = |i: usize| {
+            parts
+                .get(i)
+                .and_then(|p| p.parse::<f64>().ok())
+                .map(|v| v as i32)
+        };
+        let filename = |i: usize| parts.get(i).map(|p| p.trim_matches('"').to_string());
+
+        let event = match parts[0] {
+            "0" | "Background" => Event::Background {
+                filename: filename(2)?,
+                x_offset: int(3).unwrap_or(0),
+                y_offset: int(4).unwrap_or(0),
+            },
+            "1" | "Video" => Event::Video {
+                start_time: int(1)?,
+                filename: filename(2)?,
+                x_offset: int(3).unwrap_or(0),
+                y_offset: int(4).unwrap_or(0),
+            },
+            "2" | "Break" => Event::Break {
+                start_time: int(1)?,
+                end_time: int(2)?,
+            },
+            _ => Event::Other(line.to_string()),
+        };
+
+        Some(event)
+    }
+
+    pub fn to_osu_string(&self) -> String {
+        match self {
+            Event::Background {
+                filename,
+                x_offset,
+                y_offset,
+            } => format!("0,0,\"{}\",{},{}", filename, x_offset, y_offset),
+            Event::Video {
+                start_time,
+                filename,
+                x_offset,
+                y_offset,
+            } => format!(
+                "Video,{},\"{}\",{},{}",
+                start_time, filename, x_offset, y_offset
+            ),
+            Event::Break {
+                start_time,
+                end_time,
+            } => format!("2,{},{}", start_time, end_time),
+            Event::Other(line) => line.clone(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Colour {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Colour {
+    pub fn parse(value: &str) -> Option<Self> {
+        let parts: Vec<u8> = value
+            .split(',')
+            .map(|p| p.trim().parse::<u8>())
+            .collect::<Result<_, _>>()
+            .ok()?;
+        if parts.len() < 3 {
+            return None;
+        }
+        Some(Colour {
+            r: parts[0],
+            g: parts[1],
+            b: parts[2],
+        })
+    }
+
+    pub fn to_osu_string(self) -> String {
+        format!("{},{},{}", self.r, self.g, self.b)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Colours {
+    pub combo_colours: Vec<Colour>,
+    pub slider_track_override: Option<Colour>,
+    pub slider_border: Option<Colour>,
+}
+
+impl Colours {
+    fn from_pairs(pairs: &[(String, String)]) -> Self {
+        let mut colours = Colours::default();
+        let mut combos: Vec<(u32, Colour)> = Vec::new();
+        for (key, value) in pairs {
+            let Some(colour) = Colour::parse(value) else {
+                continue;
+            };
+            match key.as_str() {
+                "SliderTrackOverride" => colours.slider_track_override = Some(colour),
+                "SliderBorder" => colours.slider_border = Some(colour),
+                _ => {
+                    if let Some(index) = key.strip_prefix("Combo").and_then(|n| n.parse().ok()) {
+                        combos.push((index, colour));
+                    }
+                }
+            }
+        }
+        combos.sort_by_key(|(index, _)| *index);
+        colours.combo_colours = combos.into_iter().map(|(_, colour)| colour).collect();
+        colours
+    }
+
+    fn to_pairs(&self) -> Vec<(String, String)> {
+        let mut pairs: Vec<(String, String)> = self
+            .combo_colours
+            .iter()
+            .enumerate()
+            .map(|(i, colour)| (format!("Combo{}", i + 1), colour.to_osu_string()))
+            .collect();
+        if let Some(colour) = &self.slider_track_override {
+            pairs.push(("SliderTrackOverride".into(), colour.to_osu_string()));
+        }
+        if let Some(colour) = &self.slider_border {
+            pairs.push(("SliderBorder".into(), colour.to_osu_string()));
+        }
+        pairs
+    }
+}
+
+#[derive(Debug, Clone)]
+struct RawSection {
+    name: String,
+    header: RawLine,
+    lines: Vec<RawLine>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Layout {
+    bom: bool,
+    eol: String,
+    preamble: Vec<RawLine>,
+    sections: Vec<RawSection>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Beatmap {
+    pub format_version: u32,
+    pub general: General,
+    pub editor: Editor,
+    pub metadata: Metadata,
+    pub difficulty: Difficulty,
+    pub events: Vec<Event>,
+    pub timing_points: TimingPoints,
+    pub colours: Colours,
+    pub hit_objects: Vec<HitObject>,
+    #[serde(skip)]
+    layout: Layout,
+}
+
+impl Default for Beatmap {
+    fn default() -> Self {
+        Beatmap {
+            format_version: 14,
+            general: General::default(),
+            editor: Editor::default(),
+            metadata: Metadata {
+                beatmap_set_id: -1,
+                ..Default::default()
+            },
+            difficulty: Difficulty::default(),
+            events: Vec::new(),
+            timing_points: TimingPoints::default(),
+            colours: Colours::default(),
+            hit_objects: Vec::new(),
+            layout: Layout::default(),
+        }
+    }
+}
+
+fn parse_format_version(line: &str) -> Option<u32> {
+    line.trim()
+        .strip_prefix("osu file format v")
+        .and_then(|v| v.trim().parse().ok())
+}
+
+fn parse_items<T>(lines: &[RawLine], parse: impl Fn(&str) -> Option<T>) -> Vec<T> {
+    lines.iter().filter_map(|line| parse(&line.text)).collect()
+}
+
+impl Beatmap {
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let (bom, content) = match content.strip_prefix('\u{feff}') {
+            Some(rest) => (true, rest),
+            None => (false, content),
+        };
+
+        let mut layout = Layout {
+            bom,
+            eol: detect_eol(content).to_string(),
+            ..Default::default()
+        };
+
+        for line in split_lines(content) {
+            if let Some(name) = section_name(&line.text) {
+                layout.sections.push(RawSection {
+                    name: name.to_string(),
+                    header: line,
+                    lines: Vec::new(),
+                });
+            } else if let Some(section) = layout.sections.last_mut() {
+                section.lines.push(line);
+            } else {
+                layout.preamble.push(line);
+            }
+        }
+
+        let format_version = layout
+            .preamble
+            .iter()
+            .find(|line| !line.text.trim().is_empty())
+            .and_then(|line| parse_format_version(&line.text))
+            .ok_or_else(|| "Missing osu file format header".to_string())?;
+
+        let mut beatmap = Beatmap {
+            format_version,
+            ..Default::default()
+        };
+
+        beatmap.layout = layout;
+
+        beatmap.general = General::from_pairs(&parse_pairs(&beatmap.lines_of("General")));
+        beatmap.editor = Editor::from_pairs(&parse_pairs(&beatmap.lines_of("Editor")));
+        beatmap.metadata = Metadata::from_pairs(&parse_pairs(&beatmap.lines_of("Metadata")));
+        beatmap.difficulty = Difficulty::from_pairs(&parse_pairs(&beatmap.lines_of("Difficulty")));
+        beatmap.events = parse_items(&beatmap.lines_of("Events"), Event::parse);
+        beatmap.timing_points = TimingPoints::from(parse_items(
+            &beatmap.lines_of("TimingPoints"),
+            TimingPoint::parse,
+        ));
+        beatmap.colours = Colours::from_pairs(&parse_pairs(&beatmap.lines_of("Colours")));
+        beatmap.hit_objects = parse_items(&beatmap.lines_of("HitObjects"), HitObject::parse);
+
+        Ok(beatmap)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Beatmap::parse(&content)
+    }
+
+    pub fn eol(&self) -> &str {
+        if self.layout.eol.is_empty() {
+            "\r\n"
+        } else {
+            &self.layout.eol
+        }
+    }
+
+    pub fn slider_duration(&self, object: &HitObject) -> f64 {
+        match &object.kind {
+            HitObjectKind::Slider(slider) => self.timing_points.slider_duration(
+                object.time,
+                slider.pixel_length(object.position),
+                slider.slides,
+                self.difficulty.slider_multiplier,
+            ),
+            _ => 0.0,
+        }
+    }
+
+    pub fn end_time(&self, object: &HitObject) -> f64 {
+        match &object.kind {
+            HitObjectKind::Circle => object.time,
+            HitObjectKind::Slider(_) => object.time + self.slider_duration(object),
+            HitObjectKind::Spinner { end_time } | HitObjectKind::Hold { end_time } => *end_time,
+        }
+    }
+
+    pub fn last_object_end(&self) -> f64 {
+        let start = self.hit_objects.first().map(|o| o.time).unwrap_or(0.0);
+        self.hit_objects
+            .iter()
+            .map(|o| self.end_time(o))
+            .fold(start, f64::max)
+    }
+
+    // Seconds from the first object to the end of the last, not counting breaks.
+    pub fn drain_time(&self) -> f64 {
+        let start = self.hit_objects.first().map(|o| o.time).unwrap_or(0.0);
+        let break_time: f64 = self
+            .events
+            .iter()
+            .filter_map(|event| match event {
+                Event::Break {
+                    start_time,
+                    end_time,
+                } => Some((end_time - start_time).max(0) as f64),
+                _ => None,
+            })
+            .sum();
+        ((self.last_object_end() - start - break_time) / 1000.0).max(0.0)
+    }
+
+    pub fn background(&self) -> Option<&str> {
+        self.events.iter().find_map(|event| match event {
+            Event::Background { filename, .. } => Some(filename.as_str()),
+            _ => None,
+        })
+    }
+
+    // The storyboard embedded in [Events], with [Variables] expanded.
+    pub fn storyboard(&self) -> Storyboard {
+        let variables = self.lines_of("Variables");
+        let variables = parse_variables(variables.iter().map(|line| line.text.as_str()));
+        Storyboard::from_event_lines(
+            self.events.iter().filter_map(|event| match event {
+                Event::Other(line) => Some(line.as_str()),
+                _ => None,
+            }),
+            &variables,
+        )
+    }
+
+    // Replaces the embedded storyboard, keeping background, video, break and
+    // unrecognised events where they are.
+    pub fn set_storyboard(&mut self, storyboard: &Storyboard) {
+        self.events.retain(|event| match event {
+            Event::Other(line) => !is_storyboard_line(line),
+            _ => true,
+        });
+        self.events
+            .extend(storyboard.to_event_lines().into_iter().map(Event::Other));
+    }
+
+    fn section_lines(&self, name: &str, lines: &[RawLine]) -> Vec<RawLine> {
+        let eol = self.eol();
+        let fresh = self.layout.sections.is_empty();
+        let pairs = |current: Vec<(String, String)>, snapshot: Vec<(String, String)>| {
+            merge_pairs(lines, &current, if fresh { &[] } else { &snapshot }, eol)
+        };
+        let items = |current: Vec<String>, normalize: &dyn Fn(&str) -> Option<String>| {
+            merge_items(lines, &current, normalize, eol)
+        };
+
+        match name {
+            "General" => pairs(
+                self.general.to_pairs(),
+                General::from_pairs(&parse_pairs(lines)).to_pairs(),
+            ),
+            "Editor" => pairs(
+                self.editor.to_pairs(),
+                Editor::from_pairs(&parse_pairs(lines)).to_pairs(),
+            ),
+            "Metadata" => pairs(
+                self.metadata.to_pairs(),
+                Metadata::from_pairs(&parse_pairs(lines)).to_pairs(),
+            ),
+            "Difficulty" => pairs(
+                self.difficulty.to_pairs(),
+                Difficulty::from_pairs(&parse_pairs(lines)).to_pairs(),
+            ),
+            "Colours" => pairs(
+                self.colours.to_pairs(),
+                Colours::from_pairs(&parse_pairs(lines)).to_pairs(),
+            ),
+            "Events" => items(
+                self.events.iter().map(|e| e.to_osu_string()).collect(),
+                &|line| Event::parse(line).map(|e| e.to_osu_string()),
+            ),
+            "TimingPoints" => items(
+                self.timing_points
+                    .iter()
+                    .map(|t| t.to_osu_string())
+                    .collect(),
+                &|line| TimingPoint::parse(line).map(|t| t.to_osu_string()),
+            ),
+            "HitObjects" => items(
+                self.hit_objects.iter().map(|h| h.to_osu_string()).collect(),
+                &|line| HitObject::parse(line).map(|h| h.to_osu_string()),
+            ),
+            _ => lines.to_vec(),
+        }
+    }
+
+    fn lines_of(&self, name: &str) -> Vec<RawLine> {
+        self.layout
+            .sections
+            .iter()
+            .filter(|s| s.name == name)
+            .flat_map(|s| s.lines.iter().cloned())
+            .collect()
+    }
+
+    pub fn to_osu_string(&self) -> String {
+        let eol = self.eol().to_string();
+        let mut output: Vec<RawLine> = Vec::new();
+
+        let mut preamble = self.layout.preamble.clone();
+        match preamble
+            .iter_mut()
+            .find(|line| !line.text.trim().is_empty())
+        {
+            Some(line) => {
+                if parse_format_version(&line.text) != Some(self.format_version) {
+                    line.text = format!("osu file format v{}", self.format_version);
+                }
+            }
+            None => {
+                preamble.insert(
+                    0,
+                    RawLine::new(format!("osu file format v{}", self.format_version), &eol),
+                );
+                preamble.push(RawLine::new("", &eol));
+            }
+        }
+        output.extend(preamble);
+
+        let original: Vec<&str> = self
+            .layout
+            .sections
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect();
+
+        let mut sections = self.layout.sections.clone();
+        for (order, name) in SECTION_ORDER.iter().enumerate() {
+            if original.contains(name) {
+                continue;
+            }
+
+            let mut lines = self.section_lines(name, &[]);
+            if lines.is_empty() {
+                continue;
+            }
+
+            let position = sections
+                .iter()
+                .rposition(|s| {
+                    SECTION_ORDER
+                        .iter()
+                        .position(|n| *n == s.name)
+                        .is_some_and(|p| p < order)
+                })
+                .map(|p| p + 1)
+                .unwrap_or(0);
+
+            if position < sections.len() {
+                lines.push(RawLine::new("", &eol));
+            }
+            sections.insert(
+                position,
+                RawSection {
+                    name: name.to_string(),
+                    header: RawLine::new(format!("[{}]", name), &eol),
+                    lines,
+                },
+            );
+        }
+
+        let mut emitted: Vec<&str> = Vec::new();
+        for section in &sections {
+            let name = section.name.as_str();
+            let known = SECTION_ORDER.contains(&name);
+            let inserted = !original.contains(&name);
+
+            if known && !inserted {
+                if emitted.contains(&name) {
+                    continue;
+                }
+                emitted.push(name);
+            }
+
+            if inserted
+                && output
+                    .last()
+                    .is_some_and(|line| !line.text.trim().is_empty())
+            {
+                output.push(RawLine::new("", &eol));
+            }
+            output.push(section.header.clone());
+
+            if known && !inserted {
+                output.extend(self.section_lines(name, &self.lines_of(name)));
+            } else {
+                output.extend(section.lines.iter().cloned());
+            }
+        }
+
+        let mut text = join_lines(&output, &eol);
+        if self.layout.bom {
+            text.insert(0, '\u{feff}');
+        }
+        text
+    }
+
+    pub fn write_to(&self, path: &Path) -> Result<(), String> {
+        fs::write(path, self.to_osu_string())
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAP: &str = "osu file format v14
+
+[General]
+AudioFilename: audio.mp3
+AudioLeadIn: 0
+PreviewTime: 45000
+Mode: 0
+SampleSet: Soft
+SampleVolume: 70
+UnknownGeneralKey: 1
+
+[Editor]
+// kept where it is
+DistanceSpacing: 1.2
+BeatDivisor: 4
+
+[Metadata]
+Title:Song
+TitleUnicode:Song (Unicode)
+Artist:Someone
+ArtistUnicode:Someone (Unicode)
+Creator:Mapper
+Version:Hard
+Source:
+Tags:one two
+BeatmapID:1
+BeatmapSetID:2
+
+[Difficulty]
+HPDrainRate:5
+CircleSize:4
+OverallDifficulty:8
+ApproachRate:9
+SliderMultiplier:1.4
+SliderTickRate:1
+
+[Events]
+//Background and Video events
+0,0,\"bg.jpg\",0,0
+//Break Periods
+2,5000,8000
+
+[TimingPoints]
+0,333.333333333333,4,2,0,100,1,0
+1000,-50,4,2,0,100,0,1
+
+[Mystery]
+anything: goes here
+// and here
+
+[HitObjects]
+256,192,0,5,0,0:0:0:0:
+100,100,500,2,0,B|200:100|200:100|300:200,1,250.5
+256,192,1500,12,0,3000,0:0:0:0:
+";
+
+    fn assert_round_trip(content: &str) {
+        let beatmap = Beatmap::parse(content).unwrap();
+        assert_eq!(beatmap.to_osu_string(), content);
+    }
+
+    #[test]
+    fn round_trips_lf_file() {
+        assert_round_trip(MAP);
+    }
+
+    #[test]
+    fn round_trips_crlf_file() {
+        assert_round_trip(&MAP.replace('\n', "\r\n"));
+    }
+
+    #[test]
+    fn round_trips_bom_and_missing_final_newline() {
+        assert_round_trip(&format!("\u{feff}{}", MAP.trim_end()));
+    }
+
+    #[test]
+    fn round_trips_comments_and_unknown_sections() {
+        let content = "osu file format v9\n\
+            // a comment before anything\n\
+            \n\
+            [General]\n\
+            // comment in General\n\
+            AudioFilename: a.mp3\n\
+            SomethingNew: yes\n\
+            \n\
+            [Fonts]\n\
+            Name: Arial\n\
+            \n\
+            [HitObjects]\n\
+            // comment in HitObjects\n\
+            64,64,100,1,0,0:0:0:0:\n";
+        assert_round_trip(content);
+    }
+
+    #[test]
+    fn prefix_keys_parse_separately() {
+        let beatmap = Beatmap::parse(MAP).unwrap();
+        assert_eq!(beatmap.metadata.title, "Song");
+        assert_eq!(beatmap.metadata.title_unicode, "Song (Unicode)");
+        assert_eq!(beatmap.metadata.artist, "Someone");
+        assert_eq!(beatmap.metadata.artist_unicode, "Someone (Unicode)");
+        assert_eq!(beatmap.metadata.beatmap_id, 1);
+        assert_eq!(beatmap.metadata.beatmap_set_id, 2);
+    }
+
+    #[test]
+    fn edits_only_touch_changed_lines() {
+        let mut beatmap = Beatmap::parse(MAP).unwrap();
+        beatmap.metadata.title = "Renamed".to_string();
+        let expected = MAP.replace("Title:Song\n", "Title:Renamed\n");
+        assert_eq!(beatmap.to_osu_string(), expected);
+    }
+}
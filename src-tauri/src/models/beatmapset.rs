@@ -1,3 +1,5 @@
+use crate::models::beatmap::Metadata;
+use crate::utils::files::sanitize_file_name;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Debug, Clone)]
@@ -11,7 +13,7 @@ pub struct Beatmapset {
     pub beatmap_set_id: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct BeatmapMetadata {
     pub title: String,
     pub title_unicode: String,
@@ -21,3 +23,34 @@ pub struct BeatmapMetadata {
     pub source: String,
     pub tags: String,
 }
+
+impl BeatmapMetadata {
+    // The name osu! gives a difficulty's file, e.g. `Artist - Title (Creator) [Hard].osu`.
+    pub fn osu_file_name(&self, version: &str) -> String {
+        sanitize_file_name(&format!(
+            "{} - {} ({}) [{}].osu",
+            self.artist, self.title, self.creator, version
+        ))
+    }
+
+    pub fn osb_file_name(&self) -> String {
+        sanitize_file_name(&format!(
+            "{} - {} ({}).osb",
+            self.artist, self.title, self.creator
+        ))
+    }
+}
+
+impl From<&Metadata> for BeatmapMetadata {
+    fn from(metadata: &Metadata) -> Self {
+        BeatmapMetadata {
+            title: metadata.title.clone(),
+            title_unicode: metadata.title_unicode.clone(),
+            artist: metadata.artist.clone(),
+            artist_unicode: metadata.artist_unicode.clone(),
+            creator: metadata.creator.clone(),
+            source: metadata.source.clone(),
+            tags: metadata.tags.clone(),
+        }
+    }
+}
@@ -0,0 +1,254 @@
+use crate::utils::binary::{BinaryReader, BinaryWriter};
+use crate::utils::files::osu_data_path;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const COLLECTION_DB_FILE: &str = "collection.db";
+const DEFAULT_VERSION: i32 = 20150203;
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Collection {
+    pub name: String,
+    pub beatmap_md5s: Vec<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct CollectionSummary {
+    pub name: String,
+    pub beatmap_count: usize,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct CollectionDb {
+    pub version: i32,
+    pub collections: Vec<Collection>,
+}
+
+impl Default for CollectionDb {
+    fn default() -> Self {
+        CollectionDb {
+            version: DEFAULT_VERSION,
+            collections: Vec::new(),
+        }
+    }
+}
+
+pub fn collection_db_path(songs_folder: &Path) -> Result<PathBuf, String> {
+    osu_data_path(songs_folder, COLLECTION_DB_FILE)
+}
+
+impl CollectionDb {
+    pub fn parse(data: &[u8]) -> Result<Self, String> {
+        let mut reader = BinaryReader::new(data);
+        let version = reader.read_i32()?;
+        let count = reader.read_i32()?.max(0) as usize;
+
+        let mut collections = Vec::with_capacity(count.min(4096));
+        for _ in 0..count {
+            let name = reader.read_string()?;
+            let size = reader.read_i32()?.max(0) as usize;
+            let mut beatmap_md5s = Vec::with_capacity(size.min(65_536));
+            for _ in 0..size {
+                beatmap_md5s.push(reader.read_string()?);
+            }
+            collections.push(Collection { name, beatmap_md5s });
+        }
+
+        Ok(CollectionDb {
+            version,
+            collections,
+        })
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(CollectionDb::default());
+        }
+        let data =
+            fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        CollectionDb::parse(&data)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = BinaryWriter::new();
+        writer.write_i32(self.version);
+        writer.write_i32(self.collections.len() as i32);
+        for collection in &self.collections {
+            writer.write_string(&collection.name);
+            writer.write_i32(collection.beatmap_md5s.len() as i32);
+            for md5 in &collection.beatmap_md5s {
+                writer.write_string(md5);
+            }
+        }
+        writer.into_bytes()
+    }
+
+    pub fn summaries(&self) -> Vec<CollectionSummary> {
+        self.collections
+            .iter()
+            .map(|c| CollectionSummary {
+                name: c.name.clone(),
+                beatmap_count: c.beatmap_md5s.len(),
+            })
+            .collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Collection> {
+        self.collections.iter().find(|c| c.name == name)
+    }
+
+    pub fn hashes(&self, name: &str) -> HashSet<&str> {
+        self.get(name)
+            .map(|c| c.beatmap_md5s.iter().map(|h| h.as_str()).collect())
+            .unwrap_or_default()
+    }
+
+    // Returns how many hashes were new to the collection, creating it if needed.
+    pub fn add(&mut self, name: &str, md5s: &[String]) -> usize {
+        let index = match self.collections.iter().position(|c| c.name == name) {
+            Some(index) => index,
+            None => {
+                self.collections.push(Collection {
+                    name: name.to_string(),
+                    beatmap_md5s: Vec::new(),
+                });
+                self.collections.len() - 1
+            }
+        };
+
+        let collection = &mut self.collections[index];
+        let mut added = 0;
+        for md5 in md5s {
+            if !collection.beatmap_md5s.contains(md5) {
+                collection.beatmap_md5s.push(md5.clone());
+                added += 1;
+            }
+        }
+        added
+    }
+
+    pub fn remove(&mut self, name: &str, md5s: &[String]) -> usize {
+        let Some(collection) = self.collections.iter_mut().find(|c| c.name == name) else {
+            return 0;
+        };
+        let before = collection.beatmap_md5s.len();
+        collection.beatmap_md5s.retain(|h| !md5s.contains(h));
+        before - collection.beatmap_md5s.len()
+    }
+
+    // Keeps collections pointing at a difficulty after its contents (and so its hash) change.
+    pub fn replace_hash(&mut self, old: &str, new: &str) -> usize {
+        let mut replaced = 0;
+        for collection in self.collections.iter_mut() {
+            let Some(position) = collection.beatmap_md5s.iter().position(|h| h == old) else {
+                continue;
+            };
+            collection.beatmap_md5s.retain(|h| h != old);
+            if !collection.beatmap_md5s.iter().any(|h| h == new) {
+                collection.beatmap_md5s.insert(position, new.to_string());
+            }
+            replaced += 1;
+        }
+        replaced
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn md5(n: u8) -> String {
+        format!("{:032x}", n)
+    }
+
+    fn sample() -> CollectionDb {
+        CollectionDb {
+            version: DEFAULT_VERSION,
+            collections: vec![
+                Collection {
+                    name: "Favourites ★".to_string(),
+                    beatmap_md5s: vec![md5(1), md5(2)],
+                },
+                Collection {
+                    name: String::new(),
+                    beatmap_md5s: Vec::new(),
+                },
+                Collection {
+                    name: "x".repeat(200),
+                    beatmap_md5s: vec![md5(3)],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        let db = sample();
+        assert_eq!(CollectionDb::parse(&db.to_bytes()).unwrap(), db);
+        let empty = CollectionDb::default();
+        assert_eq!(CollectionDb::parse(&empty.to_bytes()).unwrap(), empty);
+    }
+
+    #[test]
+    fn writes_osu_byte_layout() {
+        let name = "x".repeat(200);
+        let expected = [
+            &20150203i32.to_le_bytes()[..],
+            &3i32.to_le_bytes(),
+            // "Favourites ★" is 14 bytes of UTF-8.
+            &[0x0b, 14],
+            "Favourites ★".as_bytes(),
+            &2i32.to_le_bytes(),
+            &[0x0b, 32],
+            md5(1).as_bytes(),
+            &[0x0b, 32],
+            md5(2).as_bytes(),
+            // An empty name is written as the "absent" marker.
+            &[0x00],
+            &0i32.to_le_bytes(),
+            // 200 takes two ULEB128 bytes.
+            &[0x0b, 0xc8, 0x01],
+            name.as_bytes(),
+            &1i32.to_le_bytes(),
+            &[0x0b, 32],
+            md5(3).as_bytes(),
+        ]
+        .concat();
+        assert_eq!(sample().to_bytes(), expected);
+        assert_eq!(CollectionDb::parse(&expected).unwrap(), sample());
+    }
+
+    #[test]
+    fn truncated_file_is_an_error() {
+        let data = sample().to_bytes();
+        for len in 0..data.len() {
+            assert!(CollectionDb::parse(&data[..len]).is_err(), "cut at {}", len);
+        }
+    }
+
+    #[test]
+    fn missing_file_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = CollectionDb::from_file(&dir.path().join(COLLECTION_DB_FILE)).unwrap();
+        assert_eq!(db, CollectionDb::default());
+    }
+
+    #[test]
+    fn add_remove_and_replace() {
+        let mut db = CollectionDb::default();
+        assert_eq!(db.add("New", &[md5(1), md5(2), md5(1)]), 2);
+        assert_eq!(db.add("New", &[md5(2), md5(3)]), 1);
+        assert_eq!(db.summaries()[0].beatmap_count, 3);
+        assert_eq!(db.remove("New", &[md5(2), md5(9)]), 1);
+        assert_eq!(db.remove("Missing", &[md5(1)]), 0);
+
+        db.add("Other", &[md5(3), md5(4)]);
+        // md5(3) keeps its place; "Other" already had md5(4), so it isn't added twice.
+        assert_eq!(db.replace_hash(&md5(3), &md5(4)), 2);
+        assert_eq!(db.get("New").unwrap().beatmap_md5s, [md5(1), md5(4)]);
+        assert_eq!(db.get("Other").unwrap().beatmap_md5s, [md5(4)]);
+        assert!(db.hashes("Other").contains(md5(4).as_str()));
+    }
+}
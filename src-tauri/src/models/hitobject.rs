@@ -0,0 +1,403 @@
+use crate::utils::curve::{self, CurveType, Vec2};
+use crate::utils::parser::is_skippable;
+use serde::{Deserialize, Serialize};
+
+pub const TYPE_CIRCLE: u8 = 1;
+pub const TYPE_SLIDER: u8 = 2;
+pub const TYPE_NEW_COMBO: u8 = 4;
+pub const TYPE_SPINNER: u8 = 8;
+pub const TYPE_HOLD: u8 = 128;
+
+pub const HITSOUND_NORMAL: u8 = 1;
+pub const HITSOUND_WHISTLE: u8 = 2;
+pub const HITSOUND_FINISH: u8 = 4;
+pub const HITSOUND_CLAP: u8 = 8;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct HitSample {
+    pub normal_set: u8,
+    pub addition_set: u8,
+    pub index: i32,
+    pub volume: i32,
+    pub filename: String,
+}
+
+impl HitSample {
+    pub fn parse(value: &str) -> Option<Self> {
+        let parts: Vec<&str> = value.split(':').collect();
+        if parts.len() < 3 {
+            return None;
+        }
+
+        Some(HitSample {
+            normal_set: parts[0].parse().ok()?,
+            addition_set: parts[1].parse().ok()?,
+            index: parts[2].parse().unwrap_or(0),
+            volume: parts.get(3).and_then(|v| v.parse().ok()).unwrap_or(0),
+            filename: parts.get(4).map(|f| f.to_string()).unwrap_or_default(),
+        })
+    }
+
+    pub fn to_osu_string(&self) -> String {
+        format!(
+            "{}:{}:{}:{}:{}",
+            self.normal_set, self.addition_set, self.index, self.volume, self.filename
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct EdgeSet {
+    pub normal_set: u8,
+    pub addition_set: u8,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Slider {
+    pub curve_type: CurveType,
+    pub control_points: Vec<Vec2>,
+    pub slides: u32,
+    pub length: f64,
+    pub edge_sounds: Vec<u8>,
+    pub edge_sets: Vec<EdgeSet>,
+}
+
+impl Slider {
+    fn parse(fields: &[&str]) -> Option<Self> {
+        let mut curve_parts = fields.first()?.split('|');
+        let curve_type = curve_parts
+            .next()?
+            .chars()
+            .next()
+            .and_then(CurveType::from_char)
+            .unwrap_or(CurveType::Bezier);
+
+        let control_points = curve_parts
+            .filter_map(|p| {
+                let (x, y) = p.split_once(':')?;
+                Some(Vec2::new(x.parse().ok()?, y.parse().ok()?))
+            })
+            .collect();
+
+        let edge_sounds = fields
+            .get(3)
+            .map(|s| s.split('|').filter_map(|v| v.parse().ok()).collect())
+            .unwrap_or_default();
+
+        let edge_sets = fields
+            .get(4)
+            .map(|s| {
+                s.split('|')
+                    .filter_map(|set| {
+                        let (normal, addition) = set.split_once(':')?;
+                        Some(EdgeSet {
+                            normal_set: normal.parse().ok()?,
+                            addition_set: addition.parse().ok()?,
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Some(Slider {
+            curve_type,
+            control_points,
+            slides: fields.get(1)?.parse::<u32>().ok()?.max(1),
+            length: fields.get(2).and_then(|l| l.parse().ok()).unwrap_or(0.0),
+            edge_sounds,
+            edge_sets,
+        })
+    }
+
+    fn fields(&self, with_edges: bool) -> Vec<String> {
+        let mut curve = vec![self.curve_type.to_char().to_string()];
+        curve.extend(
+            self.control_points
+                .iter()
+                .map(|p| format!("{}:{}", p.x, p.y)),
+        );
+
+        let mut fields = vec![
+            curve.join("|"),
+            self.slides.to_string(),
+            self.length.to_string(),
+        ];
+
+        if with_edges || !self.edge_sounds.is_empty() || !self.edge_sets.is_empty() {
+            let edges = self.slides as usize + 1;
+            let sounds: Vec<String> = (0..edges)
+                .map(|i| self.edge_sounds.get(i).copied().unwrap_or(0).to_string())
+                .collect();
+            let sets: Vec<String> = (0..edges)
+                .map(|i| {
+                    let set = self.edge_sets.get(i).copied().unwrap_or_default();
+                    format!("{}:{}", set.normal_set, set.addition_set)
+                })
+                .collect();
+            fields.push(sounds.join("|"));
+            fields.push(sets.join("|"));
+        }
+
+        fields
+    }
+
+    pub fn path_points(&self, start: Vec2) -> Vec<Vec2> {
+        let mut points = Vec::with_capacity(self.control_points.len() + 1);
+        points.push(start);
+        points.extend(self.control_points.iter().copied());
+        points
+    }
+
+    pub fn calculated_length(&self, start: Vec2) -> f64 {
+        curve::path_length(&curve::approximate(
+            self.curve_type,
+            &self.path_points(start),
+        ))
+    }
+
+    pub fn path(&self, start: Vec2) -> Vec<Vec2> {
+        let approximated = curve::approximate(self.curve_type, &self.path_points(start));
+        curve::fit_to_length(&approximated, self.length)
+    }
+
+    pub fn pixel_length(&self, start: Vec2) -> f64 {
+        if self.length > 0.0 {
+            self.length
+        } else {
+            self.calculated_length(start)
+        }
+    }
+
+    pub fn position_at(&self, start: Vec2, progress: f64) -> Vec2 {
+        let path = self.path(start);
+        let length = curve::path_length(&path);
+        curve::point_at_distance(&path, length * progress.clamp(0.0, 1.0))
+    }
+
+    pub fn end_position(&self, start: Vec2) -> Vec2 {
+        if self.slides.is_multiple_of(2) {
+            start
+        } else {
+            self.position_at(start, 1.0)
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum HitObjectKind {
+    Circle,
+    Slider(Slider),
+    Spinner { end_time: f64 },
+    Hold { end_time: f64 },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HitObject {
+    pub position: Vec2,
+    pub time: f64,
+    pub new_combo: bool,
+    pub combo_skip: u8,
+    pub hit_sound: u8,
+    pub kind: HitObjectKind,
+    pub hit_sample: Option<HitSample>,
+}
+
+impl HitObject {
+    pub fn parse(line: &str) -> Option<Self> {
+        if is_skippable(line) {
+            return None;
+        }
+
+        let parts: Vec<&str> = line.trim().split(',').collect();
+        if parts.len() < 5 {
+            return None;
+        }
+
+        let object_type: u8 = parts[3].parse().ok()?;
+        let extras = &parts[5..];
+        let sample = |i: usize| extras.get(i).and_then(|s| HitSample::parse(s));
+
+        let (kind, hit_sample) = if object_type & TYPE_SLIDER != 0 {
+            (HitObjectKind::Slider(Slider::parse(extras)?), sample(5))
+        } else if object_type & TYPE_SPINNER != 0 {
+            let end_time = extras.first()?.parse().ok()?;
+            (HitObjectKind::Spinner { end_time }, sample(1))
+        } else if object_type & TYPE_HOLD != 0 {
+            let field = extras.first()?;
+            let (end_time, rest) = field.split_once(':').unwrap_or((field, ""));
+            (
+                HitObjectKind::Hold {
+                    end_time: end_time.parse().ok()?,
+                },
+                HitSample::parse(rest),
+            )
+        } else {
+            (HitObjectKind::Circle, sample(0))
+        };
+
+        Some(HitObject {
+            position: Vec2::new(parts[0].parse().ok()?, parts[1].parse().ok()?),
+            time: parts[2].parse().ok()?,
+            new_combo: object_type & TYPE_NEW_COMBO != 0,
+            combo_skip: (object_type >> 4) & 0b111,
+            hit_sound: parts[4].parse().ok()?,
+            kind,
+            hit_sample,
+        })
+    }
+
+    pub fn object_type(&self) -> u8 {
+        let kind = match self.kind {
+            HitObjectKind::Circle => TYPE_CIRCLE,
+            HitObjectKind::Slider(_) => TYPE_SLIDER,
+            HitObjectKind::Spinner { .. } => TYPE_SPINNER,
+            HitObjectKind::Hold { .. } => TYPE_HOLD,
+        };
+        let new_combo = if self.new_combo { TYPE_NEW_COMBO } else { 0 };
+        kind | new_combo | ((self.combo_skip & 0b111) << 4)
+    }
+
+    pub fn to_osu_string(&self) -> String {
+        let mut fields = vec![
+            self.position.x.to_string(),
+            self.position.y.to_string(),
+            self.time.to_string(),
+            self.object_type().to_string(),
+            self.hit_sound.to_string(),
+        ];
+        let sample = self.hit_sample.as_ref().map(|s| s.to_osu_string());
+
+        match &self.kind {
+            HitObjectKind::Circle => fields.extend(sample),
+            HitObjectKind::Slider(slider) => {
+                fields.extend(slider.fields(sample.is_some()));
+                fields.extend(sample);
+            }
+            HitObjectKind::Spinner { end_time } => {
+                fields.push(end_time.to_string());
+                fields.extend(sample);
+            }
+            HitObjectKind::Hold { end_time } => match sample {
+                Some(sample) => fields.push(format!("{}:{}", end_time, sample)),
+                None => fields.push(end_time.to_string()),
+            },
+        }
+
+        fields.join(",")
+    }
+
+    pub fn is_circle(&self) -> bool {
+        matches!(self.kind, HitObjectKind::Circle)
+    }
+
+    pub fn is_slider(&self) -> bool {
+        matches!(self.kind, HitObjectKind::Slider(_))
+    }
+
+    pub fn is_spinner(&self) -> bool {
+        matches!(self.kind, HitObjectKind::Spinner { .. })
+    }
+
+    pub fn is_hold(&self) -> bool {
+        matches!(self.kind, HitObjectKind::Hold { .. })
+    }
+
+    pub fn slider(&self) -> Option<&Slider> {
+        match &self.kind {
+            HitObjectKind::Slider(slider) => Some(slider),
+            _ => None,
+        }
+    }
+
+    pub fn slider_mut(&mut self) -> Option<&mut Slider> {
+        match &mut self.kind {
+            HitObjectKind::Slider(slider) => Some(slider),
+            _ => None,
+        }
+    }
+
+    pub fn end_position(&self) -> Vec2 {
+        match &self.kind {
+            HitObjectKind::Slider(slider) => slider.end_position(self.position),
+            _ => self.position,
+        }
+    }
+
+    // Mania columns are derived from x; `key_count` comes from CircleSize.
+    pub fn column(&self, key_count: u32) -> u32 {
+        let key_count = key_count.max(1);
+        ((self.position.x * key_count as f64 / 512.0).floor() as u32).min(key_count - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slider(line: &str) -> (HitObject, Slider) {
+        let object = HitObject::parse(line).unwrap();
+        let slider = object.slider().unwrap().clone();
+        (object, slider)
+    }
+
+    fn assert_near(actual: Vec2, expected: Vec2) {
+        assert!(
+            actual.distance(expected) < 0.01,
+            "expected {:?}, got {:?}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn linear_slider_uses_declared_length() {
+        let (object, slider) = slider("100,100,1000,2,0,L|400:100,1,150");
+        assert_eq!(slider.control_points, vec![Vec2::new(400.0, 100.0)]);
+        assert_eq!(slider.calculated_length(object.position), 300.0);
+        assert_eq!(slider.pixel_length(object.position), 150.0);
+        assert_near(object.end_position(), Vec2::new(250.0, 100.0));
+    }
+
+    #[test]
+    fn missing_length_falls_back_to_calculated() {
+        let (object, slider) = slider("0,0,1000,2,0,L|0:120,1,0");
+        assert_eq!(slider.pixel_length(object.position), 120.0);
+    }
+
+    #[test]
+    fn perfect_circle_slider_end_position() {
+        let (object, slider) = slider("0,192,1000,2,0,P|50:142|100:192,1,78.5398");
+        // A quarter of the way around a circle of radius 50 centred on (50, 192).
+        let end = object.end_position();
+        // The approximated arc is a little shorter than the real one, so allow some slack.
+        assert!((end.distance(Vec2::new(50.0, 192.0)) - 50.0).abs() < 0.1);
+        assert!(end.distance(Vec2::new(50.0, 142.0)) < 0.1);
+        assert!((slider.calculated_length(object.position) - 157.08).abs() < 0.5);
+    }
+
+    #[test]
+    fn red_anchor_slider_end_position() {
+        let (object, _) = slider("0,0,1000,2,0,B|100:0|100:0|100:100,1,150");
+        assert_near(object.end_position(), Vec2::new(100.0, 50.0));
+    }
+
+    #[test]
+    fn end_position_depends_on_slide_parity() {
+        let (odd, _) = slider("100,100,1000,2,0,L|300:100,3,200");
+        assert_near(odd.end_position(), Vec2::new(300.0, 100.0));
+
+        let (even, _) = slider("100,100,1000,2,0,L|300:100,2,200");
+        assert_eq!(even.end_position(), Vec2::new(100.0, 100.0));
+    }
+
+    #[test]
+    fn parses_other_kinds() {
+        let spinner = HitObject::parse("256,192,1000,12,0,3000,0:0:0:0:").unwrap();
+        assert_eq!(spinner.kind, HitObjectKind::Spinner { end_time: 3000.0 });
+        assert!(spinner.new_combo);
+
+        let hold = HitObject::parse("64,192,1000,128,0,1500:0:0:0:0:").unwrap();
+        assert_eq!(hold.kind, HitObjectKind::Hold { end_time: 1500.0 });
+        assert_eq!(hold.column(4), 0);
+    }
+}
@@ -1 +1,12 @@
+pub mod beatmap;
 pub mod beatmapset;
+pub mod collection_db;
+pub mod hitobject;
+pub mod mods;
+pub mod osu_db;
+pub mod query;
+pub mod replay;
+pub mod scores_db;
+pub mod songs_index;
+pub mod storyboard;
+pub mod timing;
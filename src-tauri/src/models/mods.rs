@@ -0,0 +1,74 @@
+pub const NO_FAIL: i32 = 1;
+pub const EASY: i32 = 1 << 1;
+pub const TOUCH_DEVICE: i32 = 1 << 2;
+pub const HIDDEN: i32 = 1 << 3;
+pub const HARD_ROCK: i32 = 1 << 4;
+pub const SUDDEN_DEATH: i32 = 1 << 5;
+pub const DOUBLE_TIME: i32 = 1 << 6;
+pub const RELAX: i32 = 1 << 7;
+pub const HALF_TIME: i32 = 1 << 8;
+pub const NIGHTCORE: i32 = 1 << 9;
+pub const FLASHLIGHT: i32 = 1 << 10;
+pub const AUTOPLAY: i32 = 1 << 11;
+pub const SPUN_OUT: i32 = 1 << 12;
+pub const AUTOPILOT: i32 = 1 << 13;
+pub const PERFECT: i32 = 1 << 14;
+pub const TARGET_PRACTICE: i32 = 1 << 23;
+pub const SCORE_V2: i32 = 1 << 29;
+
+const ACRONYMS: [(i32, &str); 31] = [
+    (NO_FAIL, "NF"),
+    (EASY, "EZ"),
+    (TOUCH_DEVICE, "TD"),
+    (HIDDEN, "HD"),
+    (HARD_ROCK, "HR"),
+    (SUDDEN_DEATH, "SD"),
+    (DOUBLE_TIME, "DT"),
+    (RELAX, "RX"),
+    (HALF_TIME, "HT"),
+    (NIGHTCORE, "NC"),
+    (FLASHLIGHT, "FL"),
+    (AUTOPLAY, "AT"),
+    (SPUN_OUT, "SO"),
+    (AUTOPILOT, "AP"),
+    (PERFECT, "PF"),
+    (1 << 15, "4K"),
+    (1 << 16, "5K"),
+    (1 << 17, "6K"),
+    (1 << 18, "7K"),
+    (1 << 19, "8K"),
+    (1 << 20, "FI"),
+    (1 << 21, "RD"),
+    (1 << 22, "CN"),
+    (TARGET_PRACTICE, "TP"),
+    (1 << 24, "9K"),
+    (1 << 25, "CO"),
+    (1 << 26, "1K"),
+    (1 << 27, "3K"),
+    (1 << 28, "2K"),
+    (SCORE_V2, "V2"),
+    (1 << 30, "MR"),
+];
+
+// NC and PF are always stored together with DT and SD, so only the stronger one is listed.
+pub fn mod_acronyms(mods: i32) -> Vec<&'static str> {
+    ACRONYMS
+        .iter()
+        .filter(|(bit, _)| mods & bit != 0)
+        .filter(|(bit, _)| {
+            !(*bit == DOUBLE_TIME && mods & NIGHTCORE != 0
+                || *bit == SUDDEN_DEATH && mods & PERFECT != 0)
+        })
+        .map(|(_, acronym)| *acronym)
+        .collect()
+}
+
+pub fn clock_rate(mods: i32) -> f64 {
+    if mods & (DOUBLE_TIME | NIGHTCORE) != 0 {
+        1.5
+    } else if mods & HALF_TIME != 0 {
+        0.75
+    } else {
+        1.0
+    }
+}
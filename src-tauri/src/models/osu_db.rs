@@ -0,0 +1,550 @@
+use crate::utils::binary::BinaryReader;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+// Layout changes in osu!.db, keyed by the client version that introduced them.
+const VERSION_FLOAT_DIFFICULTY: i32 = 20140609;
+const VERSION_NO_ENTRY_SIZE: i32 = 20191106;
+const VERSION_FLOAT_STAR_RATING: i32 = 20250107;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RankedStatus {
+    Unknown,
+    Unsubmitted,
+    Pending,
+    Ranked,
+    Approved,
+    Qualified,
+    Loved,
+}
+
+impl RankedStatus {
+    pub fn from_byte(value: u8) -> Self {
+        match value {
+            1 => RankedStatus::Unsubmitted,
+            2 | 3 => RankedStatus::Pending,
+            4 => RankedStatus::Ranked,
+            5 => RankedStatus::Approved,
+            6 => RankedStatus::Qualified,
+            7 => RankedStatus::Loved,
+            _ => RankedStatus::Unknown,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RankedStatus::Unknown => "unknown",
+            RankedStatus::Unsubmitted => "unsubmitted",
+            RankedStatus::Pending => "pending",
+            RankedStatus::Ranked => "ranked",
+            RankedStatus::Approved => "approved",
+            RankedStatus::Qualified => "qualified",
+            RankedStatus::Loved => "loved",
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct DbTimingPoint {
+    pub beat_length: f64,
+    pub offset: f64,
+    pub uninherited: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DbBeatmap {
+    pub artist: String,
+    pub artist_unicode: String,
+    pub title: String,
+    pub title_unicode: String,
+    pub creator: String,
+    pub version: String,
+    pub audio_filename: String,
+    pub md5: String,
+    pub file_name: String,
+    pub status: RankedStatus,
+    pub circles: u16,
+    pub sliders: u16,
+    pub spinners: u16,
+    pub last_modified: i64,
+    pub approach_rate: f32,
+    pub circle_size: f32,
+    pub hp_drain_rate: f32,
+    pub overall_difficulty: f32,
+    pub slider_velocity: f64,
+    // Star ratings per mode (osu!, taiko, catch, mania), keyed by mod combination.
+    pub star_ratings: [Vec<(i32, f64)>; 4],
+    pub drain_time: i32,
+    pub total_time: i32,
+    pub preview_time: i32,
+    pub timing_points: Vec<DbTimingPoint>,
+    pub beatmap_id: i32,
+    pub beatmap_set_id: i32,
+    pub thread_id: i32,
+    pub grades: [u8; 4],
+    pub local_offset: i16,
+    pub stack_leniency: f32,
+    pub mode: u8,
+    pub source: String,
+    pub tags: String,
+    pub online_offset: i16,
+    pub title_font: String,
+    pub unplayed: bool,
+    pub last_played: i64,
+    pub is_osz2: bool,
+    pub folder_name: String,
+    pub last_checked: i64,
+    pub ignore_hitsounds: bool,
+    pub ignore_skin: bool,
+    pub disable_storyboard: bool,
+    pub disable_video: bool,
+    pub visual_override: bool,
+    pub mania_scroll_speed: u8,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct OsuDb {
+    pub version: i32,
+    pub folder_count: i32,
+    pub account_unlocked: bool,
+    pub unlock_date: i64,
+    pub player_name: String,
+    pub beatmaps: Vec<DbBeatmap>,
+    pub permissions: i32,
+}
+
+fn read_star_ratings(reader: &mut BinaryReader, version: i32) -> Result<Vec<(i32, f64)>, String> {
+    let count = reader.read_i32()?.max(0) as usize;
+    let mut ratings = Vec::with_capacity(count.min(64));
+
+    for _ in 0..count {
+        reader.read_u8()?;
+        let mods = reader.read_i32()?;
+        reader.read_u8()?;
+        let stars = if version >= VERSION_FLOAT_STAR_RATING {
+            reader.read_f32()? as f64
+        } else {
+            reader.read_f64()?
+        };
+        ratings.push((mods, stars));
+    }
+
+    Ok(ratings)
+}
+
+impl DbBeatmap {
+    fn read(reader: &mut BinaryReader, version: i32) -> Result<Self, String> {
+        if version < VERSION_NO_ENTRY_SIZE {
+            reader.read_i32()?;
+        }
+
+        let artist = reader.read_string()?;
+        let artist_unicode = reader.read_string()?;
+        let title = reader.read_string()?;
+        let title_unicode = reader.read_string()?;
+        let creator = reader.read_string()?;
+        let difficulty_name = reader.read_string()?;
+        let audio_filename = reader.read_string()?;
+        let md5 = reader.read_string()?;
+        let file_name = reader.read_string()?;
+        let status = RankedStatus::from_byte(reader.read_u8()?);
+        let circles = reader.read_u16()?;
+        let sliders = reader.read_u16()?;
+        let spinners = reader.read_u16()?;
+        let last_modified = reader.read_datetime()?;
+
+        let mut difficulty = [0.0f32; 4];
+        for value in difficulty.iter_mut() {
+            *value = if version < VERSION_FLOAT_DIFFICULTY {
+                reader.read_u8()? as f32
+            } else {
+                reader.read_f32()?
+            };
+        }
+        let slider_velocity = reader.read_f64()?;
+
+        let star_ratings = if version >= VERSION_FLOAT_DIFFICULTY {
+            [
+                read_star_ratings(reader, version)?,
+                read_star_ratings(reader, version)?,
+                read_star_ratings(reader, version)?,
+                read_star_ratings(reader, version)?,
+            ]
+        } else {
+            Default::default()
+        };
+
+        let drain_time = reader.read_i32()?;
+        let total_time = reader.read_i32()?;
+        let preview_time = reader.read_i32()?;
+
+        let timing_count = reader.read_i32()?.max(0) as usize;
+        let mut timing_points = Vec::with_capacity(timing_count.min(4096));
+        for _ in 0..timing_count {
+            timing_points.push(DbTimingPoint {
+                beat_length: reader.read_f64()?,
+                offset: reader.read_f64()?,
+                uninherited: reader.read_bool()?,
+            });
+        }
+
+        let beatmap_id = reader.read_i32()?;
+        let beatmap_set_id = reader.read_i32()?;
+        let thread_id = reader.read_i32()?;
+        let grades = [
+            reader.read_u8()?,
+            reader.read_u8()?,
+            reader.read_u8()?,
+            reader.read_u8()?,
+        ];
+        let local_offset = reader.read_i16()?;
+        let stack_leniency = reader.read_f32()?;
+        let mode = reader.read_u8()?;
+        let source = reader.read_string()?;
+        let tags = reader.read_string()?;
+        let online_offset = reader.read_i16()?;
+        let title_font = reader.read_string()?;
+        let unplayed = reader.read_bool()?;
+        let last_played = reader.read_datetime()?;
+        let is_osz2 = reader.read_bool()?;
+        let folder_name = reader.read_string()?;
+        let last_checked = reader.read_datetime()?;
+        let ignore_hitsounds = reader.read_bool()?;
+        let ignore_skin = reader.read_bool()?;
+        let disable_storyboard = reader.read_bool()?;
+        let disable_video = reader.read_bool()?;
+        let visual_override = reader.read_bool()?;
+        if version < VERSION_FLOAT_DIFFICULTY {
+            reader.read_i16()?;
+        }
+        reader.read_i32()?;
+        let mania_scroll_speed = reader.read_u8()?;
+
+        Ok(DbBeatmap {
+            artist,
+            artist_unicode,
+            title,
+            title_unicode,
+            creator,
+            version: difficulty_name,
+            audio_filename,
+            md5,
+            file_name,
+            status,
+            circles,
+            sliders,
+            spinners,
+            last_modified,
+            approach_rate: difficulty[0],
+            circle_size: difficulty[1],
+            hp_drain_rate: difficulty[2],
+            overall_difficulty: difficulty[3],
+            slider_velocity,
+            star_ratings,
+            drain_time,
+            total_time,
+            preview_time,
+            timing_points,
+            beatmap_id,
+            beatmap_set_id,
+            thread_id,
+            grades,
+            local_offset,
+            stack_leniency,
+            mode,
+            source,
+            tags,
+            online_offset,
+            title_font,
+            unplayed,
+            last_played,
+            is_osz2,
+            folder_name,
+            last_checked,
+            ignore_hitsounds,
+            ignore_skin,
+            disable_storyboard,
+            disable_video,
+            visual_override,
+            mania_scroll_speed,
+        })
+    }
+
+    pub fn star_rating(&self, mods: i32) -> Option<f64> {
+        self.star_ratings
+            .get(self.mode as usize)?
+            .iter()
+            .find(|(m, _)| *m == mods)
+            .map(|(_, stars)| *stars)
+    }
+
+    pub fn object_count(&self) -> usize {
+        self.circles as usize + self.sliders as usize + self.spinners as usize
+    }
+
+    pub fn bpm_range(&self) -> (f64, f64) {
+        let bpms: Vec<f64> = self
+            .timing_points
+            .iter()
+            .filter(|p| p.uninherited && p.beat_length > 0.0)
+            .map(|p| 60000.0 / p.beat_length)
+            .collect();
+
+        (
+            bpms.iter().copied().reduce(f64::min).unwrap_or(0.0),
+            bpms.iter().copied().reduce(f64::max).unwrap_or(0.0),
+        )
+    }
+}
+
+impl OsuDb {
+    pub fn parse(data: &[u8]) -> Result<Self, String> {
+        let mut reader = BinaryReader::new(data);
+
+        let version = reader.read_i32()?;
+        let folder_count = reader.read_i32()?;
+        let account_unlocked = reader.read_bool()?;
+        let unlock_date = reader.read_datetime()?;
+        let player_name = reader.read_string()?;
+
+        let count = reader.read_i32()?.max(0) as usize;
+        let mut beatmaps = Vec::with_capacity(count.min(100_000));
+        for i in 0..count {
+            beatmaps.push(
+                DbBeatmap::read(&mut reader, version)
+                    .map_err(|e| format!("Failed to read beatmap {}: {}", i, e))?,
+            );
+        }
+
+        let permissions = if reader.remaining() >= 4 {
+            reader.read_i32()?
+        } else {
+            0
+        };
+
+        Ok(OsuDb {
+            version,
+            folder_count,
+            account_unlocked,
+            unlock_date,
+            player_name,
+            beatmaps,
+            permissions,
+        })
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let data =
+            fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        OsuDb::parse(&data)
+    }
+
+    pub fn by_folder(&self) -> HashMap<&str, Vec<&DbBeatmap>> {
+        let mut folders: HashMap<&str, Vec<&DbBeatmap>> = HashMap::new();
+        for beatmap in &self.beatmaps {
+            folders
+                .entry(beatmap.folder_name.as_str())
+                .or_default()
+                .push(beatmap);
+        }
+        folders
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::utils::binary::BinaryWriter;
+
+    const LAST_MODIFIED: i64 = 1_600_000_000_000;
+
+    fn write_entry(writer: &mut BinaryWriter, version: i32, index: u8) {
+        let mut entry = BinaryWriter::new();
+        for value in ["Artist", "", "Title", "", "Mapper"] {
+            entry.write_string(value);
+        }
+        entry.write_string(&format!("Diff {}", index));
+        entry.write_string("audio.mp3");
+        entry.write_string(&format!("{:032x}", index));
+        entry.write_string(&format!("Artist - Title (Mapper) [Diff {}].osu", index));
+        entry.write_u8(4);
+        entry.write_u16(100 + index as u16);
+        entry.write_u16(50);
+        entry.write_u16(1);
+        entry.write_datetime(LAST_MODIFIED);
+
+        for value in [9.0, 4.0, 5.0, 8.0] {
+            if version < VERSION_FLOAT_DIFFICULTY {
+                entry.write_u8(value as u8);
+            } else {
+                entry.write_f32(value as f32 + 0.5);
+            }
+        }
+        entry.write_f64(1.4);
+
+        if version >= VERSION_FLOAT_DIFFICULTY {
+            for mode in 0..4 {
+                let ratings: &[(i32, f64)] = if mode == 0 {
+                    &[(0, 5.25), (64, 7.5)]
+                } else {
+                    &[]
+                };
+                entry.write_i32(ratings.len() as i32);
+                for &(mods, stars) in ratings {
+                    entry.write_u8(0x08);
+                    entry.write_i32(mods);
+                    entry.write_u8(0x0d);
+                    if version >= VERSION_FLOAT_STAR_RATING {
+                        entry.write_f32(stars as f32);
+                    } else {
+                        entry.write_f64(stars);
+                    }
+                }
+            }
+        }
+
+        entry.write_i32(90);
+        entry.write_i32(95_000);
+        entry.write_i32(40_000);
+        entry.write_i32(2);
+        for (beat_length, offset, uninherited) in [(400.0, 1000.0, true), (-50.0, 2000.0, false)] {
+            entry.write_f64(beat_length);
+            entry.write_f64(offset);
+            entry.write_bool(uninherited);
+        }
+
+        entry.write_i32(1000 + index as i32);
+        entry.write_i32(77);
+        entry.write_i32(0);
+        entry.write_bytes(&[9, 9, 9, 9]);
+        entry.write_i16(0);
+        entry.write_f32(0.7);
+        entry.write_u8(0);
+        entry.write_string("");
+        entry.write_string("tag1 tag2");
+        entry.write_i16(0);
+        entry.write_string("");
+        entry.write_bool(true);
+        entry.write_datetime(0);
+        entry.write_bool(false);
+        entry.write_string("77 Artist - Title");
+        entry.write_datetime(LAST_MODIFIED);
+        entry.write_bytes(&[0, 0, 0, 0, 0]);
+        if version < VERSION_FLOAT_DIFFICULTY {
+            entry.write_i16(0);
+        }
+        entry.write_i32(0);
+        entry.write_u8(0);
+
+        let entry = entry.into_bytes();
+        if version < VERSION_NO_ENTRY_SIZE {
+            writer.write_i32(entry.len() as i32);
+        }
+        writer.write_bytes(&entry);
+    }
+
+    // Two difficulties of "77 Artist - Title", also used by the songs index tests.
+    pub(crate) fn database(version: i32) -> Vec<u8> {
+        let mut writer = BinaryWriter::new();
+        writer.write_i32(version);
+        writer.write_i32(1);
+        writer.write_bool(true);
+        writer.write_datetime(0);
+        writer.write_string("player");
+        writer.write_i32(2);
+        write_entry(&mut writer, version, 1);
+        write_entry(&mut writer, version, 2);
+        writer.write_i32(4);
+        writer.into_bytes()
+    }
+
+    fn assert_common(db: &OsuDb, version: i32) {
+        assert_eq!(db.version, version);
+        assert_eq!(db.player_name, "player");
+        assert_eq!(db.permissions, 4);
+        assert_eq!(db.beatmaps.len(), 2);
+
+        let beatmap = &db.beatmaps[1];
+        assert_eq!(beatmap.artist, "Artist");
+        assert_eq!(beatmap.version, "Diff 2");
+        assert_eq!(beatmap.md5, format!("{:032x}", 2));
+        assert_eq!(beatmap.status, RankedStatus::Ranked);
+        assert_eq!(beatmap.object_count(), 153);
+        assert_eq!(beatmap.last_modified, LAST_MODIFIED);
+        assert_eq!(beatmap.slider_velocity, 1.4);
+        assert_eq!(beatmap.drain_time, 90);
+        assert_eq!(beatmap.preview_time, 40_000);
+        assert_eq!(beatmap.bpm_range(), (150.0, 150.0));
+        assert_eq!(beatmap.beatmap_id, 1002);
+        assert_eq!(beatmap.beatmap_set_id, 77);
+        assert_eq!(beatmap.tags, "tag1 tag2");
+        assert_eq!(beatmap.folder_name, "77 Artist - Title");
+        assert_eq!(beatmap.last_checked, LAST_MODIFIED);
+        assert_eq!(db.by_folder()["77 Artist - Title"].len(), 2);
+    }
+
+    #[test]
+    fn reads_byte_difficulty_without_star_ratings() {
+        let db = OsuDb::parse(&database(20140101)).unwrap();
+        assert_common(&db, 20140101);
+        let beatmap = &db.beatmaps[0];
+        assert_eq!(beatmap.approach_rate, 9.0);
+        assert_eq!(beatmap.overall_difficulty, 8.0);
+        assert!(beatmap.star_ratings.iter().all(|r| r.is_empty()));
+        assert_eq!(beatmap.star_rating(0), None);
+    }
+
+    #[test]
+    fn reads_float_difficulty_and_double_star_ratings() {
+        let db = OsuDb::parse(&database(20160408)).unwrap();
+        assert_common(&db, 20160408);
+        let beatmap = &db.beatmaps[0];
+        assert_eq!(beatmap.approach_rate, 9.5);
+        assert_eq!(beatmap.circle_size, 4.5);
+        assert_eq!(beatmap.star_rating(0), Some(5.25));
+        assert_eq!(beatmap.star_rating(64), Some(7.5));
+        assert_eq!(beatmap.star_rating(16), None);
+    }
+
+    #[test]
+    fn reads_entries_without_size_field() {
+        let db = OsuDb::parse(&database(20191106)).unwrap();
+        assert_common(&db, 20191106);
+        assert_eq!(db.beatmaps[0].star_rating(64), Some(7.5));
+    }
+
+    #[test]
+    fn reads_float_star_ratings() {
+        let db = OsuDb::parse(&database(20250107)).unwrap();
+        assert_common(&db, 20250107);
+        assert_eq!(db.beatmaps[0].star_rating(0), Some(5.25));
+        assert_eq!(db.beatmaps[0].hp_drain_rate, 5.5);
+    }
+
+    #[test]
+    fn truncated_database_is_an_error() {
+        for version in [20140101, 20160408, 20191106, 20250107] {
+            let data = database(version);
+            // Everything but the trailing permissions field is required.
+            for len in 0..data.len() - 4 {
+                assert!(
+                    OsuDb::parse(&data[..len]).is_err(),
+                    "v{} cut at {} bytes parsed",
+                    version,
+                    len
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_string_marker_is_an_error() {
+        let mut data = database(20250107);
+        // The player name's string marker.
+        data[17] = 0x05;
+        assert!(OsuDb::parse(&data).is_err());
+    }
+}
@@ -0,0 +1,497 @@
+use crate::models::songs_index::{DifficultyEntry, SetEntry};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operator {
+    Contains,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+}
+
+impl Operator {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "=" | ":" => Some(Operator::Contains),
+            "==" => Some(Operator::Equal),
+            "!=" => Some(Operator::NotEqual),
+            "<" => Some(Operator::Less),
+            "<=" => Some(Operator::LessEqual),
+            ">" => Some(Operator::Greater),
+            ">=" => Some(Operator::GreaterEqual),
+            _ => None,
+        }
+    }
+
+    fn compare(self, actual: f64, expected: f64, tolerance: f64) -> bool {
+        match self {
+            Operator::Contains | Operator::Equal => (actual - expected).abs() < tolerance,
+            Operator::NotEqual => (actual - expected).abs() >= tolerance,
+            Operator::Less => actual < expected,
+            Operator::LessEqual => actual <= expected,
+            Operator::Greater => actual > expected,
+            Operator::GreaterEqual => actual >= expected,
+        }
+    }
+
+    // Ranges (like a map's BPM span) match when any value inside them would.
+    fn compare_range(self, min: f64, max: f64, expected: f64, tolerance: f64) -> bool {
+        match self {
+            Operator::Contains | Operator::Equal => {
+                expected > min - tolerance && expected < max + tolerance
+            }
+            Operator::NotEqual => !Operator::Equal.compare_range(min, max, expected, tolerance),
+            Operator::Less | Operator::LessEqual => self.compare(min, expected, tolerance),
+            Operator::Greater | Operator::GreaterEqual => self.compare(max, expected, tolerance),
+        }
+    }
+
+    fn compare_text(self, actual: &str, expected: &str) -> bool {
+        let actual = actual.to_lowercase();
+        match self {
+            Operator::Contains => actual.contains(expected),
+            Operator::Equal => actual == expected,
+            Operator::NotEqual => actual != expected,
+            Operator::Less => actual.as_str() < expected,
+            Operator::LessEqual => actual.as_str() <= expected,
+            Operator::Greater => actual.as_str() > expected,
+            Operator::GreaterEqual => actual.as_str() >= expected,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SetField {
+    Title,
+    Artist,
+    Creator,
+    Source,
+    Tags,
+    Status,
+    SetId,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DifficultyField {
+    Version,
+    Mode,
+    Keys,
+    Bpm,
+    Length,
+    ApproachRate,
+    CircleSize,
+    OverallDifficulty,
+    HpDrainRate,
+    Stars,
+    Objects,
+    BeatmapId,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Field {
+    Set(SetField),
+    Difficulty(DifficultyField),
+}
+
+impl Field {
+    fn parse(key: &str) -> Option<Self> {
+        let field = match key {
+            "title" => Field::Set(SetField::Title),
+            "artist" => Field::Set(SetField::Artist),
+            "creator" | "mapper" => Field::Set(SetField::Creator),
+            "source" => Field::Set(SetField::Source),
+            "tag" | "tags" => Field::Set(SetField::Tags),
+            "status" => Field::Set(SetField::Status),
+            "setid" | "beatmapsetid" => Field::Set(SetField::SetId),
+            "diff" | "version" => Field::Difficulty(DifficultyField::Version),
+            "mode" => Field::Difficulty(DifficultyField::Mode),
+            "keys" | "key" => Field::Difficulty(DifficultyField::Keys),
+            "bpm" => Field::Difficulty(DifficultyField::Bpm),
+            "length" => Field::Difficulty(DifficultyField::Length),
+            "ar" => Field::Difficulty(DifficultyField::ApproachRate),
+            "cs" => Field::Difficulty(DifficultyField::CircleSize),
+            "od" => Field::Difficulty(DifficultyField::OverallDifficulty),
+            "hp" | "dr" => Field::Difficulty(DifficultyField::HpDrainRate),
+            "stars" | "star" | "sr" => Field::Difficulty(DifficultyField::Stars),
+            "objects" => Field::Difficulty(DifficultyField::Objects),
+            "id" | "beatmapid" => Field::Difficulty(DifficultyField::BeatmapId),
+            _ => return None,
+        };
+        Some(field)
+    }
+}
+
+pub fn parse_mode(value: &str) -> Option<u8> {
+    match value {
+        "0" | "osu" | "std" | "standard" => Some(0),
+        "1" | "taiko" => Some(1),
+        "2" | "catch" | "ctb" | "fruits" => Some(2),
+        "3" | "mania" => Some(3),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Condition {
+    Text(String),
+    Filter {
+        field: Field,
+        operator: Operator,
+        value: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Term {
+    condition: Condition,
+    negated: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Query {
+    terms: Vec<Term>,
+}
+
+fn tokenize(input: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+
+    for c in input.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                current.push(c);
+            }
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+
+    tokens
+}
+
+fn unquote(s: &str) -> String {
+    s.trim_matches('"').to_lowercase()
+}
+
+fn parse_filter(token: &str) -> Option<Condition> {
+    let key_end = token.find(|c: char| !c.is_ascii_alphabetic())?;
+    let (key, rest) = token.split_at(key_end);
+    let field = Field::parse(&key.to_lowercase())?;
+
+    let op_end = rest
+        .find(|c: char| !matches!(c, '=' | '<' | '>' | '!' | ':'))
+        .unwrap_or(rest.len());
+    let operator = Operator::parse(&rest[..op_end])?;
+    let value = unquote(&rest[op_end..]);
+    if value.is_empty() {
+        return None;
+    }
+
+    Some(Condition::Filter {
+        field,
+        operator,
+        value,
+    })
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Self {
+        let terms = tokenize(input)
+            .into_iter()
+            .filter_map(|token| {
+                let (negated, body) = match token.strip_prefix(['-', '!']) {
+                    Some(rest) if !rest.is_empty() => (true, rest),
+                    _ => (false, token.as_str()),
+                };
+
+                let condition =
+                    parse_filter(body).unwrap_or_else(|| Condition::Text(unquote(body)));
+                match &condition {
+                    Condition::Text(text) if text.is_empty() => None,
+                    _ => Some(Term { condition, negated }),
+                }
+            })
+            .collect();
+
+        Query { terms }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    // Set-level terms must hold for the set and difficulty-level terms must all be
+    // satisfied by the same difficulty. A negated difficulty term excludes the set
+    // if any of its difficulties matches it.
+    pub fn matches(&self, set: &SetEntry) -> bool {
+        if self.terms.is_empty() {
+            return true;
+        }
+
+        let searchable = set.searchable();
+        let mut difficulty_terms = Vec::new();
+        for term in &self.terms {
+            let holds = match &term.condition {
+                Condition::Text(text) => searchable.contains(text.as_str()) != term.negated,
+                Condition::Filter {
+                    field: Field::Set(field),
+                    operator,
+                    value,
+                } => set_matches(set, *field, *operator, value) != term.negated,
+                Condition::Filter {
+                    field: Field::Difficulty(field),
+                    operator,
+                    value,
+                } => {
+                    let matches = |difficulty: &DifficultyEntry| {
+                        difficulty_matches(difficulty, *field, *operator, value)
+                    };
+                    if term.negated {
+                        !set.difficulties.iter().any(matches)
+                    } else {
+                        difficulty_terms.push(matches);
+                        true
+                    }
+                }
+            };
+            if !holds {
+                return false;
+            }
+        }
+
+        difficulty_terms.is_empty()
+            || set
+                .difficulties
+                .iter()
+                .any(|difficulty| difficulty_terms.iter().all(|matches| matches(difficulty)))
+    }
+}
+
+fn set_matches(set: &SetEntry, field: SetField, operator: Operator, value: &str) -> bool {
+    let text =
+        |a: &str, b: &str| operator.compare_text(a, value) || operator.compare_text(b, value);
+    match field {
+        SetField::Title => text(&set.title, &set.title_unicode),
+        SetField::Artist => text(&set.artist, &set.artist_unicode),
+        SetField::Creator => operator.compare_text(&set.creator, value),
+        SetField::Source => operator.compare_text(&set.source, value),
+        SetField::Tags => operator.compare_text(&set.tags, value),
+        SetField::Status => set
+            .status
+            .as_deref()
+            .is_some_and(|status| operator.compare_text(status, value)),
+        SetField::SetId => value
+            .parse::<f64>()
+            .is_ok_and(|id| operator.compare(set.beatmap_set_id as f64, id, 0.5)),
+    }
+}
+
+fn difficulty_matches(
+    difficulty: &DifficultyEntry,
+    field: DifficultyField,
+    operator: Operator,
+    value: &str,
+) -> bool {
+    if field == DifficultyField::Version {
+        return operator.compare_text(&difficulty.version, value);
+    }
+
+    let expected = if field == DifficultyField::Mode {
+        parse_mode(value).map(|mode| mode as f64)
+    } else {
+        value.parse::<f64>().ok()
+    };
+    let Some(expected) = expected else {
+        return false;
+    };
+
+    let number = |actual: f64, tolerance: f64| operator.compare(actual, expected, tolerance);
+    match field {
+        DifficultyField::Version => false,
+        DifficultyField::Mode => number(difficulty.mode as f64, 0.5),
+        DifficultyField::Keys => difficulty.mode == 3 && number(difficulty.circle_size, 0.5),
+        DifficultyField::Bpm => {
+            operator.compare_range(difficulty.bpm_min, difficulty.bpm_max, expected, 0.5)
+        }
+        DifficultyField::Length => number(difficulty.drain_time, 1.0),
+        DifficultyField::ApproachRate => number(difficulty.approach_rate, 0.05),
+        DifficultyField::CircleSize => number(difficulty.circle_size, 0.05),
+        DifficultyField::OverallDifficulty => number(difficulty.overall_difficulty, 0.05),
+        DifficultyField::HpDrainRate => number(difficulty.hp_drain_rate, 0.05),
+        DifficultyField::Stars => difficulty
+            .star_rating
+            .is_some_and(|stars| number(stars, 0.05)),
+        DifficultyField::Objects => number(difficulty.object_count as f64, 0.5),
+        DifficultyField::BeatmapId => number(difficulty.beatmap_id as f64, 0.5),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn difficulty(version: &str, mode: u8, circle_size: f64, stars: f64) -> DifficultyEntry {
+        DifficultyEntry {
+            file_name: format!("{}.osu", version),
+            file_mtime: 0,
+            md5: String::new(),
+            version: version.to_string(),
+            mode,
+            beatmap_id: 10,
+            hp_drain_rate: 5.0,
+            circle_size,
+            overall_difficulty: 8.0,
+            approach_rate: 9.3,
+            bpm_min: 100.0,
+            bpm_max: 200.0,
+            drain_time: 90.0,
+            object_count: 100,
+            star_rating: Some(stars),
+            audio_filename: "audio.mp3".to_string(),
+            background: None,
+        }
+    }
+
+    fn set() -> SetEntry {
+        SetEntry {
+            folder_name: "292301 xi - Blue Zenith".to_string(),
+            folder_mtime: 0,
+            files_mtime: 0,
+            title: "Blue Zenith".to_string(),
+            title_unicode: String::new(),
+            artist: "xi".to_string(),
+            artist_unicode: String::new(),
+            creator: "Asphyxia".to_string(),
+            source: String::new(),
+            tags: "touhou rock".to_string(),
+            beatmap_set_id: 292301,
+            status: Some("ranked".to_string()),
+            audio_path: None,
+            background_path: None,
+            difficulties: vec![
+                difficulty("FOUR DIMENSIONS", 0, 4.0, 7.2),
+                difficulty("7K Hard", 3, 7.0, 3.1),
+            ],
+        }
+    }
+
+    fn matches(query: &str) -> bool {
+        Query::parse(query).matches(&set())
+    }
+
+    fn filter(field: Field, operator: Operator, value: &str, negated: bool) -> Term {
+        Term {
+            condition: Condition::Filter {
+                field,
+                operator,
+                value: value.to_string(),
+            },
+            negated,
+        }
+    }
+
+    #[test]
+    fn parses_filters_text_and_negation() {
+        let query = Query::parse("Blue -mode=mania ar>=9.5 !\"zenith rock\" diff:\"four dim\"");
+        assert_eq!(
+            query.terms,
+            [
+                Term {
+                    condition: Condition::Text("blue".to_string()),
+                    negated: false,
+                },
+                filter(
+                    Field::Difficulty(DifficultyField::Mode),
+                    Operator::Contains,
+                    "mania",
+                    true
+                ),
+                filter(
+                    Field::Difficulty(DifficultyField::ApproachRate),
+                    Operator::GreaterEqual,
+                    "9.5",
+                    false
+                ),
+                Term {
+                    condition: Condition::Text("zenith rock".to_string()),
+                    negated: true,
+                },
+                filter(
+                    Field::Difficulty(DifficultyField::Version),
+                    Operator::Contains,
+                    "four dim",
+                    false
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_keys_and_operators_are_text() {
+        let terms = Query::parse("foo=bar ar=> title= -").terms;
+        let texts: Vec<Condition> = terms.into_iter().map(|t| t.condition).collect();
+        assert_eq!(
+            texts,
+            [
+                Condition::Text("foo=bar".to_string()),
+                Condition::Text("ar=>".to_string()),
+                Condition::Text("title=".to_string()),
+                Condition::Text("-".to_string()),
+            ]
+        );
+        assert!(Query::parse("  ").is_empty());
+        assert!(Query::parse("\"\"").is_empty());
+    }
+
+    #[test]
+    fn text_and_set_filters() {
+        assert!(matches(""));
+        assert!(matches("blue"));
+        assert!(matches("\"blue zenith\""));
+        assert!(!matches("\"zenith blue\""));
+        assert!(matches("asphyxia 292301"));
+        assert!(matches("artist=xi creator=asph"));
+        assert!(matches("artist==xi"));
+        assert!(!matches("artist==x"));
+        assert!(matches("tags=rock status=ranked setid=292301"));
+        assert!(!matches("-blue"));
+        assert!(!matches("-artist=xi"));
+        assert!(!matches("foo=bar"));
+    }
+
+    #[test]
+    fn difficulty_filters_must_hold_for_one_difficulty() {
+        assert!(matches("mode=mania keys=7"));
+        assert!(matches("mode=osu stars>7"));
+        assert!(!matches("mode=mania stars>7"));
+        assert!(!matches("keys=4"));
+        assert!(matches("diff=\"four dim\""));
+        assert!(matches("bpm=150 bpm>180 bpm<120"));
+        assert!(matches("length=90"));
+        assert!(!matches("length<60"));
+    }
+
+    #[test]
+    fn negated_difficulty_filters_exclude_the_whole_set() {
+        assert!(matches("-mode=taiko"));
+        assert!(!matches("-mode=mania"));
+        assert!(!matches("-stars>6"));
+        assert!(!matches("!diff=hard"));
+        assert!(matches("-stars>8"));
+        assert!(matches("-keys=4"));
+        // Positive terms still pick the difficulty on their own.
+        assert!(matches("mode=mania -stars>8"));
+        assert!(!matches("mode=osu -mode=mania"));
+    }
+}
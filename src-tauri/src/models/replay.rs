@@ -0,0 +1,239 @@
+use crate::models::scores_db::Score;
+use crate::utils::binary::BinaryReader;
+use serde::Serialize;
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+
+pub const KEY_M1: u32 = 1;
+pub const KEY_M2: u32 = 1 << 1;
+pub const KEY_K1: u32 = 1 << 2;
+pub const KEY_K2: u32 = 1 << 3;
+pub const KEY_SMOKE: u32 = 1 << 4;
+
+// A frame with this time delta carries the RNG seed instead of input.
+const SEED_FRAME_DELTA: i64 = -12345;
+
+// In mania replays `x` holds the pressed columns as a bitmask and `keys` is unused.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ReplayFrame {
+    pub time: i64,
+    pub x: f32,
+    pub y: f32,
+    pub keys: u32,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct LifeBarPoint {
+    pub time: i64,
+    pub life: f64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Replay {
+    pub score: Score,
+    pub life_bar: Vec<LifeBarPoint>,
+    pub frames: Vec<ReplayFrame>,
+    pub seed: Option<i32>,
+}
+
+fn parse_life_bar(data: &str) -> Vec<LifeBarPoint> {
+    data.split(',')
+        .filter_map(|point| {
+            let (time, life) = point.split_once('|')?;
+            Some(LifeBarPoint {
+                time: time.trim().parse().ok()?,
+                life: life.trim().parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+// Frames are "delta|x|y|keys" separated by commas, with times relative to the previous frame.
+fn parse_frames(data: &str) -> (Vec<ReplayFrame>, Option<i32>) {
+    let mut frames = Vec::new();
+    let mut seed = None;
+    let mut time = 0i64;
+
+    for frame in data.split(',') {
+        let parts: Vec<&str> = frame.split('|').collect();
+        if parts.len() < 4 {
+            continue;
+        }
+        let Ok(delta) = parts[0].trim().parse::<i64>() else {
+            continue;
+        };
+
+        if delta == SEED_FRAME_DELTA {
+            seed = parts[3].trim().parse().ok();
+            continue;
+        }
+
+        time += delta;
+        frames.push(ReplayFrame {
+            time,
+            x: parts[1].trim().parse().unwrap_or(0.0),
+            y: parts[2].trim().parse().unwrap_or(0.0),
+            keys: parts[3]
+                .trim()
+                .parse::<f64>()
+                .map(|k| k as u32)
+                .unwrap_or(0),
+        });
+    }
+
+    // The sync frames at the start of newer replays can carry negative deltas.
+    frames.sort_by_key(|frame| frame.time);
+    (frames, seed)
+}
+
+pub fn decompress_frames(data: &[u8]) -> Result<String, String> {
+    if data.is_empty() {
+        return Ok(String::new());
+    }
+    let mut output = Vec::new();
+    lzma_rs::lzma_decompress(&mut Cursor::new(data), &mut output)
+        .map_err(|e| format!("Failed to decompress replay data: {}", e))?;
+    Ok(String::from_utf8_lossy(&output).into_owned())
+}
+
+impl Replay {
+    pub fn parse(data: &[u8]) -> Result<Self, String> {
+        let mut reader = BinaryReader::new(data);
+        let mut score = Score::read_header(&mut reader)?;
+        let life_bar = parse_life_bar(&reader.read_string()?);
+        score.timestamp = reader.read_datetime()?;
+
+        let length = reader.read_i32()?.max(0) as usize;
+        let compressed = reader.read_bytes(length)?;
+        score.read_online_score_id(&mut reader)?;
+        score.read_additional_mod_info(&mut reader)?;
+
+        let (frames, seed) = parse_frames(&decompress_frames(&compressed)?);
+
+        Ok(Replay {
+            score,
+            life_bar,
+            frames,
+            seed,
+        })
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let data =
+            fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Replay::parse(&data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::mods::{HARD_ROCK, TARGET_PRACTICE};
+    use crate::models::scores_db::tests::write_header;
+    use crate::utils::binary::BinaryWriter;
+
+    const FRAMES: &str = "0|256|-500|0,-1|256|-500|0,\
+                          16|100.5|200.25|5,17|101|201|15,\
+                          -12345|0|0|123456,";
+
+    fn compress(text: &str) -> Vec<u8> {
+        let mut output = Vec::new();
+        lzma_rs::lzma_compress(&mut Cursor::new(text.as_bytes()), &mut output).unwrap();
+        output
+    }
+
+    fn osr(version: i32, mods: i32, frames: &str) -> Vec<u8> {
+        let mut writer = BinaryWriter::new();
+        write_header(&mut writer, 0, version, mods);
+        writer.write_string("0|1,1000|0.5,2000|0.75");
+        writer.write_datetime(1_600_000_000_000);
+        let compressed = compress(frames);
+        writer.write_i32(compressed.len() as i32);
+        writer.write_bytes(&compressed);
+        if version >= 20140721 {
+            writer.write_i64(4_000_000_000);
+        } else if version >= 20121008 {
+            writer.write_i32(123);
+        }
+        if mods & TARGET_PRACTICE != 0 {
+            writer.write_f64(0.5);
+        }
+        writer.into_bytes()
+    }
+
+    #[test]
+    fn decodes_frames_and_skips_seed_frame() {
+        let replay = Replay::parse(&osr(20150101, HARD_ROCK, FRAMES)).unwrap();
+        assert_eq!(replay.score.mod_names, ["HR"]);
+        assert_eq!(replay.score.online_score_id, 4_000_000_000);
+        assert_eq!(replay.score.timestamp, 1_600_000_000_000);
+        assert_eq!(replay.seed, Some(123456));
+        assert_eq!(
+            replay.life_bar,
+            [
+                LifeBarPoint { time: 0, life: 1.0 },
+                LifeBarPoint {
+                    time: 1000,
+                    life: 0.5
+                },
+                LifeBarPoint {
+                    time: 2000,
+                    life: 0.75
+                },
+            ]
+        );
+
+        // Times accumulate from the deltas, and the sync frame's -1 sorts first.
+        let frames: Vec<(i64, u32)> = replay.frames.iter().map(|f| (f.time, f.keys)).collect();
+        assert_eq!(frames, [(-1, 0), (0, 0), (15, 5), (32, 15)]);
+        assert_eq!(replay.frames[2].x, 100.5);
+        assert_eq!(replay.frames[2].y, 200.25);
+        assert_eq!(replay.frames[3].keys, KEY_M1 | KEY_K1 | KEY_M2 | KEY_K2);
+    }
+
+    #[test]
+    fn online_score_id_depends_on_version() {
+        let replay = Replay::parse(&osr(20121007, 0, FRAMES)).unwrap();
+        assert_eq!(replay.score.online_score_id, 0);
+        let replay = Replay::parse(&osr(20130101, 0, FRAMES)).unwrap();
+        assert_eq!(replay.score.online_score_id, 123);
+    }
+
+    #[test]
+    fn target_practice_accuracy_follows_the_score_id() {
+        let replay = Replay::parse(&osr(20150101, TARGET_PRACTICE, FRAMES)).unwrap();
+        assert_eq!(replay.score.accuracy, 0.5);
+        assert_eq!(replay.score.online_score_id, 4_000_000_000);
+    }
+
+    #[test]
+    fn empty_frame_data() {
+        let replay = Replay::parse(&osr(20150101, 0, "")).unwrap();
+        assert!(replay.frames.is_empty());
+
+        // Some replays store no compressed data at all.
+        let mut writer = BinaryWriter::new();
+        write_header(&mut writer, 0, 20150101, 0);
+        writer.write_string("");
+        writer.write_datetime(0);
+        writer.write_i32(0);
+        writer.write_i64(0);
+        let replay = Replay::parse(&writer.into_bytes()).unwrap();
+        assert!(replay.frames.is_empty());
+        assert_eq!(replay.seed, None);
+    }
+
+    #[test]
+    fn corrupt_data_is_an_error() {
+        let data = osr(20150101, 0, FRAMES);
+        for len in 0..data.len() - 8 {
+            assert!(Replay::parse(&data[..len]).is_err(), "cut at {}", len);
+        }
+
+        let mut garbled = data.clone();
+        let frames_start = data.len() - 8 - compress(FRAMES).len();
+        garbled[frames_start] = 0xff;
+        assert!(Replay::parse(&garbled).is_err());
+    }
+}
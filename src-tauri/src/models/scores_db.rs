@@ -0,0 +1,325 @@
+use crate::models::mods::{mod_acronyms, TARGET_PRACTICE};
+use crate::utils::binary::BinaryReader;
+use crate::utils::files::osu_data_path;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const SCORES_DB_FILE: &str = "scores.db";
+
+// Online score ids were added as an Int and later widened to a Long.
+const VERSION_SCORE_ID: i32 = 20121008;
+const VERSION_LONG_SCORE_ID: i32 = 20140721;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Score {
+    pub mode: u8,
+    pub version: i32,
+    pub beatmap_md5: String,
+    pub player_name: String,
+    pub replay_md5: String,
+    pub count_300: u16,
+    pub count_100: u16,
+    pub count_50: u16,
+    pub count_geki: u16,
+    pub count_katu: u16,
+    pub count_miss: u16,
+    pub score: i32,
+    pub max_combo: u16,
+    pub perfect: bool,
+    pub mods: i32,
+    pub mod_names: Vec<&'static str>,
+    pub accuracy: f64,
+    pub timestamp: i64,
+    pub online_score_id: i64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ScoresDb {
+    pub version: i32,
+    pub scores: HashMap<String, Vec<Score>>,
+}
+
+pub fn scores_db_path(songs_folder: &Path) -> Result<PathBuf, String> {
+    osu_data_path(songs_folder, SCORES_DB_FILE)
+}
+
+impl Score {
+    // Reads the fields shared by scores.db entries and .osr replays, up to and
+    // including the mods. What follows differs between the two formats.
+    pub fn read_header(reader: &mut BinaryReader) -> Result<Self, String> {
+        let mode = reader.read_u8()?;
+        let version = reader.read_i32()?;
+        let beatmap_md5 = reader.read_string()?;
+        let player_name = reader.read_string()?;
+        let replay_md5 = reader.read_string()?;
+        let count_300 = reader.read_u16()?;
+        let count_100 = reader.read_u16()?;
+        let count_50 = reader.read_u16()?;
+        let count_geki = reader.read_u16()?;
+        let count_katu = reader.read_u16()?;
+        let count_miss = reader.read_u16()?;
+        let score = reader.read_i32()?;
+        let max_combo = reader.read_u16()?;
+        let perfect = reader.read_bool()?;
+        let mods = reader.read_i32()?;
+
+        let mut score = Score {
+            mode,
+            version,
+            beatmap_md5,
+            player_name,
+            replay_md5,
+            count_300,
+            count_100,
+            count_50,
+            count_geki,
+            count_katu,
+            count_miss,
+            score,
+            max_combo,
+            perfect,
+            mods,
+            mod_names: mod_acronyms(mods),
+            accuracy: 0.0,
+            timestamp: 0,
+            online_score_id: 0,
+        };
+        score.accuracy = score.calculate_accuracy();
+        Ok(score)
+    }
+
+    pub fn read_online_score_id(&mut self, reader: &mut BinaryReader) -> Result<(), String> {
+        self.online_score_id = if self.version >= VERSION_LONG_SCORE_ID {
+            reader.read_i64()?
+        } else if self.version >= VERSION_SCORE_ID {
+            reader.read_i32()? as i64
+        } else {
+            0
+        };
+        Ok(())
+    }
+
+    // Target Practice stores its accuracy separately since it isn't derived from judgements.
+    pub fn read_additional_mod_info(&mut self, reader: &mut BinaryReader) -> Result<(), String> {
+        if self.mods & TARGET_PRACTICE != 0 {
+            self.accuracy = reader.read_f64()?;
+        }
+        Ok(())
+    }
+
+    fn read(reader: &mut BinaryReader) -> Result<Self, String> {
+        let mut score = Score::read_header(reader)?;
+        reader.read_string()?;
+        score.timestamp = reader.read_datetime()?;
+        reader.read_i32()?;
+        score.read_online_score_id(reader)?;
+        score.read_additional_mod_info(reader)?;
+        Ok(score)
+    }
+
+    fn calculate_accuracy(&self) -> f64 {
+        let [n300, n100, n50, geki, katu, miss] = [
+            self.count_300,
+            self.count_100,
+            self.count_50,
+            self.count_geki,
+            self.count_katu,
+            self.count_miss,
+        ]
+        .map(|n| n as f64);
+
+        let (hit, total) = match self.mode {
+            1 => (n300 + n100 * 0.5, n300 + n100 + miss),
+            2 => (n300 + n100 + n50, n300 + n100 + n50 + katu + miss),
+            3 => (
+                (n300 + geki) * 300.0 + katu * 200.0 + n100 * 100.0 + n50 * 50.0,
+                (n300 + geki + katu + n100 + n50 + miss) * 300.0,
+            ),
+            _ => (
+                n300 * 300.0 + n100 * 100.0 + n50 * 50.0,
+                (n300 + n100 + n50 + miss) * 300.0,
+            ),
+        };
+
+        if total > 0.0 {
+            hit / total
+        } else {
+            0.0
+        }
+    }
+}
+
+impl ScoresDb {
+    pub fn parse(data: &[u8]) -> Result<Self, String> {
+        let mut reader = BinaryReader::new(data);
+        let version = reader.read_i32()?;
+        let count = reader.read_i32()?.max(0) as usize;
+
+        let mut scores = HashMap::with_capacity(count.min(100_000));
+        for i in 0..count {
+            let md5 = reader.read_string()?;
+            let score_count = reader.read_i32()?.max(0) as usize;
+            let mut beatmap_scores = Vec::with_capacity(score_count.min(1024));
+            for _ in 0..score_count {
+                beatmap_scores.push(
+                    Score::read(&mut reader)
+                        .map_err(|e| format!("Failed to read scores for beatmap {}: {}", i, e))?,
+                );
+            }
+            scores.insert(md5, beatmap_scores);
+        }
+
+        Ok(ScoresDb { version, scores })
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(ScoresDb::default());
+        }
+        let data =
+            fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        ScoresDb::parse(&data)
+    }
+
+    pub fn scores_for(&self, md5: &str) -> Vec<Score> {
+        let mut scores = self.scores.get(md5).cloned().unwrap_or_default();
+        scores.sort_by_key(|s| std::cmp::Reverse(s.score));
+        scores
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::models::mods::{DOUBLE_TIME, HIDDEN};
+    use crate::utils::binary::BinaryWriter;
+
+    const TIMESTAMP: i64 = 1_600_000_000_000;
+
+    // The fields `Score::read_header` reads, also used by the replay tests.
+    pub(crate) fn write_header(writer: &mut BinaryWriter, mode: u8, version: i32, mods: i32) {
+        writer.write_u8(mode);
+        writer.write_i32(version);
+        writer.write_string(&format!("{:032x}", 1));
+        writer.write_string("player");
+        writer.write_string(&format!("{:032x}", 2));
+        for count in [300, 20, 5, 60, 10, 2] {
+            writer.write_u16(count);
+        }
+        writer.write_i32(1_234_567);
+        writer.write_u16(450);
+        writer.write_bool(false);
+        writer.write_i32(mods);
+    }
+
+    fn write_score(writer: &mut BinaryWriter, version: i32, mods: i32, score_id: i64) {
+        write_header(writer, 0, version, mods);
+        writer.write_string("");
+        writer.write_datetime(TIMESTAMP);
+        writer.write_i32(-1);
+        if version >= VERSION_LONG_SCORE_ID {
+            writer.write_i64(score_id);
+        } else if version >= VERSION_SCORE_ID {
+            writer.write_i32(score_id as i32);
+        }
+        if mods & TARGET_PRACTICE != 0 {
+            writer.write_f64(0.875);
+        }
+    }
+
+    fn database(version: i32, mods: &[i32]) -> Vec<u8> {
+        let mut writer = BinaryWriter::new();
+        writer.write_i32(version);
+        writer.write_i32(1);
+        writer.write_string(&format!("{:032x}", 1));
+        writer.write_i32(mods.len() as i32);
+        for (i, &mods) in mods.iter().enumerate() {
+            write_score(&mut writer, version, mods, 1000 + i as i64);
+        }
+        writer.into_bytes()
+    }
+
+    fn scores(version: i32, mods: &[i32]) -> Vec<Score> {
+        let db = ScoresDb::parse(&database(version, mods)).unwrap();
+        assert_eq!(db.version, version);
+        db.scores[&format!("{:032x}", 1)].clone()
+    }
+
+    #[test]
+    fn reads_header_fields() {
+        let score = &scores(20150101, &[HIDDEN | DOUBLE_TIME])[0];
+        assert_eq!(score.mode, 0);
+        assert_eq!(score.player_name, "player");
+        assert_eq!(score.replay_md5, format!("{:032x}", 2));
+        assert_eq!(
+            (score.count_300, score.count_100, score.count_50),
+            (300, 20, 5)
+        );
+        assert_eq!(score.count_miss, 2);
+        assert_eq!(score.score, 1_234_567);
+        assert_eq!(score.max_combo, 450);
+        assert_eq!(score.mod_names, ["HD", "DT"]);
+        assert_eq!(score.timestamp, TIMESTAMP);
+        let accuracy = (300.0 * 300.0 + 20.0 * 100.0 + 5.0 * 50.0) / (327.0 * 300.0);
+        assert!((score.accuracy - accuracy).abs() < 1e-12);
+    }
+
+    #[test]
+    fn online_score_id_depends_on_version() {
+        assert_eq!(scores(20121007, &[0])[0].online_score_id, 0);
+        assert_eq!(scores(20121008, &[0])[0].online_score_id, 1000);
+        assert_eq!(scores(20140721, &[0])[0].online_score_id, 1000);
+
+        // Every score has to be read with the right width for the next to line up.
+        let ids: Vec<i64> = [20130101, 20150101]
+            .iter()
+            .flat_map(|&version| scores(version, &[0, 0, 0]))
+            .map(|s| s.online_score_id)
+            .collect();
+        assert_eq!(ids, [1000, 1001, 1002, 1000, 1001, 1002]);
+    }
+
+    #[test]
+    fn target_practice_stores_accuracy() {
+        let scores = scores(20150101, &[TARGET_PRACTICE, 0]);
+        assert_eq!(scores[0].accuracy, 0.875);
+        assert_eq!(scores[1].online_score_id, 1001);
+    }
+
+    #[test]
+    fn accuracy_per_mode() {
+        let accuracy = |mode: u8| {
+            let mut writer = BinaryWriter::new();
+            write_header(&mut writer, mode, 20150101, 0);
+            Score::read_header(&mut BinaryReader::new(&writer.into_bytes()))
+                .unwrap()
+                .accuracy
+        };
+        assert_eq!(accuracy(1), (300.0 + 10.0) / 322.0);
+        assert_eq!(accuracy(2), 325.0 / 337.0);
+        assert_eq!(
+            accuracy(3),
+            (360.0 * 300.0 + 10.0 * 200.0 + 2000.0 + 250.0) / (397.0 * 300.0)
+        );
+    }
+
+    #[test]
+    fn truncated_database_is_an_error() {
+        let data = database(20150101, &[TARGET_PRACTICE, 0]);
+        for len in 8..data.len() {
+            assert!(ScoresDb::parse(&data[..len]).is_err(), "cut at {}", len);
+        }
+    }
+
+    #[test]
+    fn scores_are_sorted_best_first() {
+        let mut db = ScoresDb::parse(&database(20150101, &[0, 0])).unwrap();
+        let md5 = format!("{:032x}", 1);
+        db.scores.get_mut(&md5).unwrap()[1].score = 2_000_000;
+        let scores: Vec<i32> = db.scores_for(&md5).iter().map(|s| s.score).collect();
+        assert_eq!(scores, [2_000_000, 1_234_567]);
+        assert!(db.scores_for("unknown").is_empty());
+    }
+}
@@ -0,0 +1,718 @@
+use crate::models::beatmap::Beatmap;
+use crate::models::beatmapset::Beatmapset;
+use crate::models::osu_db::{DbBeatmap, OsuDb};
+use crate::models::query::Query;
+use crate::utils::files::{content_md5, write_atomic};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+use std::time::UNIX_EPOCH;
+
+pub const SONGS_INDEX_FILE: &str = "songs_index.json";
+const OSU_DB_FILE: &str = "osu!.db";
+const INDEX_VERSION: u32 = 3;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DifficultyEntry {
+    pub file_name: String,
+    pub file_mtime: u64,
+    pub md5: String,
+    pub version: String,
+    pub mode: u8,
+    pub beatmap_id: i32,
+    pub hp_drain_rate: f64,
+    pub circle_size: f64,
+    pub overall_difficulty: f64,
+    pub approach_rate: f64,
+    pub bpm_min: f64,
+    pub bpm_max: f64,
+    // Whole seconds, as osu!.db records it, whichever source the entry came from.
+    pub drain_time: f64,
+    pub object_count: usize,
+    pub star_rating: Option<f64>,
+    pub audio_filename: String,
+    pub background: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SetEntry {
+    pub folder_name: String,
+    pub folder_mtime: u64,
+    pub files_mtime: u64,
+    pub title: String,
+    pub title_unicode: String,
+    pub artist: String,
+    pub artist_unicode: String,
+    pub creator: String,
+    pub source: String,
+    pub tags: String,
+    pub beatmap_set_id: i32,
+    #[serde(default)]
+    pub status: Option<String>,
+    pub audio_path: Option<String>,
+    pub background_path: Option<String>,
+    pub difficulties: Vec<DifficultyEntry>,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct IndexStats {
+    pub total: usize,
+    pub added: usize,
+    pub updated: usize,
+    pub removed: usize,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct IndexChanges {
+    pub added: Vec<String>,
+    pub updated: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl IndexChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.updated.is_empty() && self.removed.is_empty()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SongsIndex {
+    pub version: u32,
+    pub songs_path: String,
+    pub sets: Vec<SetEntry>,
+}
+
+#[derive(Default)]
+pub struct SongsIndexState(Mutex<Option<SongsIndex>>);
+
+impl SongsIndexState {
+    pub fn lock(&self) -> Result<MutexGuard<'_, Option<SongsIndex>>, String> {
+        self.0
+            .lock()
+            .map_err(|_| "Songs index is unavailable".to_string())
+    }
+}
+
+// Imports extract into dot-prefixed temporary folders inside Songs, which aren't sets.
+pub fn is_set_folder(name: &str) -> bool {
+    !name.starts_with('.')
+}
+
+fn mtime_millis(path: &Path) -> u64 {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn osu_files_with_mtime(folder: &Path) -> Vec<(String, u64)> {
+    let Ok(entries) = fs::read_dir(folder) else {
+        return Vec::new();
+    };
+
+    let mut files: Vec<(String, u64)> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            e.path()
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .map(|ext| ext.eq_ignore_ascii_case("osu"))
+                .unwrap_or(false)
+        })
+        .filter_map(|e| {
+            let name = e.file_name().to_str()?.to_string();
+            Some((name, mtime_millis(&e.path())))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+impl DifficultyEntry {
+    fn from_beatmap(file_name: String, file_mtime: u64, md5: String, beatmap: &Beatmap) -> Self {
+        let bpms: Vec<f64> = beatmap
+            .timing_points
+            .red_lines()
+            .filter_map(|p| p.bpm())
+            .filter(|bpm| bpm.is_finite())
+            .collect();
+
+        DifficultyEntry {
+            file_name,
+            file_mtime,
+            md5,
+            version: beatmap.metadata.version.clone(),
+            mode: beatmap.general.mode,
+            beatmap_id: beatmap.metadata.beatmap_id,
+            hp_drain_rate: beatmap.difficulty.hp_drain_rate,
+            circle_size: beatmap.difficulty.circle_size,
+            overall_difficulty: beatmap.difficulty.overall_difficulty,
+            approach_rate: beatmap.difficulty.approach_rate,
+            bpm_min: bpms.iter().copied().reduce(f64::min).unwrap_or(0.0),
+            bpm_max: bpms.iter().copied().reduce(f64::max).unwrap_or(0.0),
+            drain_time: beatmap.drain_time().floor(),
+            object_count: beatmap.hit_objects.len(),
+            star_rating: None,
+            audio_filename: beatmap.general.audio_filename.clone(),
+            background: beatmap.background().map(|s| s.to_string()),
+        }
+    }
+}
+
+impl DifficultyEntry {
+    fn from_db(file_name: String, file_mtime: u64, entry: &DbBeatmap) -> Self {
+        let (bpm_min, bpm_max) = entry.bpm_range();
+
+        DifficultyEntry {
+            file_name,
+            file_mtime,
+            md5: entry.md5.clone(),
+            version: entry.version.clone(),
+            mode: entry.mode,
+            beatmap_id: entry.beatmap_id,
+            hp_drain_rate: entry.hp_drain_rate as f64,
+            circle_size: entry.circle_size as f64,
+            overall_difficulty: entry.overall_difficulty as f64,
+            approach_rate: entry.approach_rate as f64,
+            bpm_min,
+            bpm_max,
+            drain_time: entry.drain_time as f64,
+            object_count: entry.object_count(),
+            star_rating: entry.star_rating(0),
+            audio_filename: entry.audio_filename.clone(),
+            background: None,
+        }
+    }
+}
+
+// osu!.db entries for one set folder, only trusted for files older than the db itself.
+#[derive(Clone, Copy)]
+struct DbFolder<'a> {
+    beatmaps: &'a [&'a DbBeatmap],
+    modified: u64,
+}
+
+impl<'a> DbFolder<'a> {
+    fn entry(&self, file_name: &str, file_mtime: u64) -> Option<&'a DbBeatmap> {
+        if file_mtime > self.modified {
+            return None;
+        }
+        self.beatmaps
+            .iter()
+            .find(|b| b.file_name == file_name)
+            .copied()
+    }
+}
+
+impl SetEntry {
+    // Reuses difficulties whose file mtime hasn't changed since the last scan, and
+    // takes the rest from osu!.db when possible. The first file is always parsed
+    // since the db doesn't record backgrounds.
+    fn scan(
+        folder: &Path,
+        folder_mtime: u64,
+        previous: Option<&SetEntry>,
+        db: Option<DbFolder>,
+    ) -> Option<Self> {
+        let folder_name = folder.file_name()?.to_str()?.to_string();
+        let mut first: Option<Beatmap> = None;
+        let mut difficulties = Vec::new();
+        let files = osu_files_with_mtime(folder);
+        let files_mtime = files.iter().map(|(_, m)| *m).max().unwrap_or(0);
+
+        for (file_name, file_mtime) in files {
+            let cached = previous.and_then(|set| {
+                set.difficulties
+                    .iter()
+                    .find(|d| d.file_name == file_name && d.file_mtime == file_mtime)
+            });
+
+            let db_entry = db.and_then(|db| db.entry(&file_name, file_mtime));
+
+            if first.is_some() {
+                if let Some(cached) = cached {
+                    difficulties.push(cached.clone());
+                    continue;
+                }
+                if let Some(entry) = db_entry {
+                    difficulties.push(DifficultyEntry::from_db(file_name, file_mtime, entry));
+                    continue;
+                }
+            }
+
+            let Ok(content) = fs::read_to_string(folder.join(&file_name)) else {
+                continue;
+            };
+            let Ok(beatmap) = Beatmap::parse(&content) else {
+                continue;
+            };
+            difficulties.push(DifficultyEntry::from_beatmap(
+                file_name,
+                file_mtime,
+                content_md5(&content),
+                &beatmap,
+            ));
+            if first.is_none() {
+                first = Some(beatmap);
+            }
+        }
+
+        let beatmap = first?;
+        let metadata = &beatmap.metadata;
+        let folder_file = |name: &str| {
+            if name.is_empty() {
+                None
+            } else {
+                Some(format!("{}/{}", folder.display(), name))
+            }
+        };
+
+        let mut set = SetEntry {
+            folder_name,
+            folder_mtime,
+            files_mtime,
+            title: metadata.title.clone(),
+            title_unicode: metadata.title_unicode.clone(),
+            artist: metadata.artist.clone(),
+            artist_unicode: metadata.artist_unicode.clone(),
+            creator: metadata.creator.clone(),
+            source: metadata.source.clone(),
+            tags: metadata.tags.clone(),
+            beatmap_set_id: metadata.beatmap_set_id,
+            status: previous.and_then(|set| set.status.clone()),
+            audio_path: folder_file(&beatmap.general.audio_filename),
+            background_path: beatmap.background().and_then(folder_file),
+            difficulties,
+        };
+        if let Some(db) = db {
+            set.enrich(db);
+        }
+        Some(set)
+    }
+
+    fn enrich(&mut self, db: DbFolder) {
+        for difficulty in self.difficulties.iter_mut() {
+            let Some(entry) = db.entry(&difficulty.file_name, difficulty.file_mtime) else {
+                continue;
+            };
+            if difficulty.star_rating.is_none() {
+                difficulty.star_rating = entry.star_rating(0);
+            }
+            self.status = Some(entry.status.as_str().to_string());
+        }
+    }
+
+    // Editing a file in place doesn't touch the folder mtime on every platform,
+    // so the .osu mtimes are compared as well.
+    fn is_current(&self, folder: &Path, folder_mtime: u64) -> bool {
+        self.folder_mtime == folder_mtime
+            && osu_files_with_mtime(folder)
+                .iter()
+                .map(|(_, m)| *m)
+                .max()
+                .unwrap_or(0)
+                == self.files_mtime
+    }
+
+    pub fn in_collection(&self, hashes: &HashSet<&str>) -> bool {
+        self.difficulties
+            .iter()
+            .any(|d| hashes.contains(d.md5.as_str()))
+    }
+
+    pub fn searchable(&self) -> String {
+        let mut text = format!(
+            "{} {} {} {} {} {} {} {}",
+            self.title,
+            self.title_unicode,
+            self.artist,
+            self.artist_unicode,
+            self.creator,
+            self.source,
+            self.tags,
+            self.beatmap_set_id
+        );
+        for difficulty in &self.difficulties {
+            text.push_str(&format!(
+                " {} {}",
+                difficulty.version, difficulty.beatmap_id
+            ));
+        }
+        text.to_lowercase()
+    }
+
+    pub fn to_beatmapset(&self) -> Beatmapset {
+        let display_title = if !self.title.is_empty() && !self.artist.is_empty() {
+            format!("{} - {}", self.artist, self.title)
+        } else {
+            self.folder_name
+                .split_once(' ')
+                .map(|(_, rest)| rest.to_string())
+                .unwrap_or(self.folder_name.clone())
+        };
+        let or_unknown = |s: &str| {
+            if s.is_empty() {
+                "Unknown".to_string()
+            } else {
+                s.to_string()
+            }
+        };
+
+        Beatmapset {
+            folder_name: self.folder_name.clone(),
+            title: display_title,
+            artist: or_unknown(&self.artist),
+            creator: or_unknown(&self.creator),
+            background_path: self.background_path.clone(),
+            beatmap_id: self
+                .difficulties
+                .first()
+                .map(|d| d.beatmap_id.to_string())
+                .unwrap_or_default(),
+            beatmap_set_id: self.beatmap_set_id.to_string(),
+        }
+    }
+}
+
+impl SongsIndex {
+    pub fn new(songs_path: &str) -> Self {
+        SongsIndex {
+            version: INDEX_VERSION,
+            songs_path: songs_path.to_string(),
+            sets: Vec::new(),
+        }
+    }
+
+    pub fn load(index_path: &Path, songs_path: &str) -> Self {
+        fs::read_to_string(index_path)
+            .ok()
+            .and_then(|data| serde_json::from_str::<SongsIndex>(&data).ok())
+            .filter(|index| index.version == INDEX_VERSION && index.songs_path == songs_path)
+            .unwrap_or_else(|| SongsIndex::new(songs_path))
+    }
+
+    pub fn save(&self, index_path: &Path) -> Result<(), String> {
+        if let Some(parent) = index_path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create index folder: {}", e))?;
+        }
+        let data = serde_json::to_string(self)
+            .map_err(|e| format!("Failed to serialize songs index: {}", e))?;
+        write_atomic(index_path, &data)
+    }
+
+    pub fn refresh(&mut self, full: bool) -> Result<IndexStats, String> {
+        let base = Path::new(&self.songs_path);
+        if !base.exists() {
+            return Err(format!("Folder not found: {}", self.songs_path));
+        }
+
+        let folders: Vec<(String, u64)> = fs::read_dir(base)
+            .map_err(|e| format!("Failed to read directory: {}", e))?
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().map(|ft| ft.is_dir()).unwrap_or(false))
+            .filter_map(|e| {
+                let name = e.file_name().to_str()?.to_string();
+                Some((name, mtime_millis(&e.path())))
+            })
+            .filter(|(name, _)| is_set_folder(name))
+            .collect();
+
+        let previous: HashMap<String, SetEntry> = self
+            .sets
+            .drain(..)
+            .map(|set| (set.folder_name.clone(), set))
+            .collect();
+
+        let osu_db = base
+            .parent()
+            .map(|osu_dir| osu_dir.join(OSU_DB_FILE))
+            .filter(|path| path.is_file())
+            .and_then(|path| Some((OsuDb::from_file(&path).ok()?, mtime_millis(&path))));
+        let db_folders: HashMap<&str, Vec<&DbBeatmap>> = osu_db
+            .as_ref()
+            .map(|(db, _)| db.by_folder())
+            .unwrap_or_default();
+        let db_modified = osu_db.as_ref().map(|(_, m)| *m).unwrap_or(0);
+
+        let scanned: Vec<(Option<SetEntry>, bool, bool)> = folders
+            .par_iter()
+            .map(|(name, mtime)| {
+                let folder = base.join(name);
+                let old = previous.get(name);
+                let db = db_folders.get(name.as_str()).map(|beatmaps| DbFolder {
+                    beatmaps,
+                    modified: db_modified,
+                });
+
+                let unchanged = !full && old.is_some_and(|set| set.is_current(&folder, *mtime));
+
+                if unchanged {
+                    let mut set = old.cloned();
+                    if let (Some(set), Some(db)) = (set.as_mut(), db) {
+                        set.enrich(db);
+                    }
+                    return (set, false, false);
+                }
+
+                let cached = if full { None } else { old };
+                (
+                    SetEntry::scan(&folder, *mtime, cached, db),
+                    old.is_none(),
+                    old.is_some(),
+                )
+            })
+            .collect();
+
+        let mut stats = IndexStats::default();
+        for (set, added, updated) in scanned {
+            let Some(set) = set else {
+                continue;
+            };
+            if added {
+                stats.added += 1;
+            }
+            if updated {
+                stats.updated += 1;
+            }
+            self.sets.push(set);
+        }
+
+        let kept: HashSet<&str> = self.sets.iter().map(|s| s.folder_name.as_str()).collect();
+        stats.removed = previous
+            .keys()
+            .filter(|name| !kept.contains(name.as_str()))
+            .count();
+
+        self.sort();
+        stats.total = self.sets.len();
+
+        Ok(stats)
+    }
+
+    fn sort(&mut self) {
+        self.sets.par_sort_by(|a, b| {
+            b.folder_mtime
+                .cmp(&a.folder_mtime)
+                .then_with(|| a.folder_name.cmp(&b.folder_name))
+        });
+    }
+
+    pub fn refresh_folder(&mut self, folder_name: &str, changes: &mut IndexChanges) {
+        let folder = Path::new(&self.songs_path).join(folder_name);
+        let position = self.sets.iter().position(|s| s.folder_name == folder_name);
+
+        if !folder.is_dir() || !is_set_folder(folder_name) {
+            if let Some(position) = position {
+                self.sets.remove(position);
+                changes.removed.push(folder_name.to_string());
+            }
+            return;
+        }
+
+        let mtime = mtime_millis(&folder);
+        let previous = position.map(|i| &self.sets[i]);
+        if previous.is_some_and(|set| set.is_current(&folder, mtime)) {
+            return;
+        }
+
+        match (position, SetEntry::scan(&folder, mtime, previous, None)) {
+            (Some(position), Some(set)) => {
+                self.sets[position] = set;
+                changes.updated.push(folder_name.to_string());
+            }
+            (Some(position), None) => {
+                self.sets.remove(position);
+                changes.removed.push(folder_name.to_string());
+            }
+            (None, Some(set)) => {
+                self.sets.push(set);
+                changes.added.push(folder_name.to_string());
+            }
+            (None, None) => return,
+        }
+        self.sort();
+    }
+
+    pub fn find(&self, folder_name: &str) -> Option<&SetEntry> {
+        self.sets.iter().find(|s| s.folder_name == folder_name)
+    }
+
+    pub fn find_by_md5(&self, md5: &str) -> Option<(&SetEntry, &DifficultyEntry)> {
+        self.sets.iter().find_map(|set| {
+            set.difficulties
+                .iter()
+                .find(|d| d.md5 == md5)
+                .map(|difficulty| (set, difficulty))
+        })
+    }
+
+    pub fn search(&self, query: &str) -> Vec<&SetEntry> {
+        let query = Query::parse(query);
+        if query.is_empty() {
+            return self.sets.iter().collect();
+        }
+
+        self.sets
+            .par_iter()
+            .filter(|set| query.matches(set))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::osu_db::tests::database;
+
+    const FOLDER: &str = "77 Artist - Title";
+
+    // 100.5 seconds from the first object to the spinner's end, 10 of them in a break.
+    fn map(version: &str) -> String {
+        format!(
+            "osu file format v14\n\n[General]\nAudioFilename: audio.mp3\nMode: 0\n\n\
+             [Metadata]\nTitle:Title\nArtist:Artist\nCreator:Mapper\nVersion:{}\n\
+             BeatmapSetID:77\n\n[Events]\n0,0,\"bg.jpg\",0,0\n2,20000,30000\n\n\
+             [TimingPoints]\n1000,400,4,2,0,100,1,0\n\n[HitObjects]\n\
+             256,192,1000,5,0,0:0:0:0:\n256,192,90000,12,0,101500,0:0:0:0:\n",
+            version
+        )
+    }
+
+    fn write_set(songs: &Path, folder: &str, versions: &[&str]) {
+        let folder = songs.join(folder);
+        fs::create_dir_all(&folder).unwrap();
+        for version in versions {
+            let name = format!("Artist - Title (Mapper) [{}].osu", version);
+            fs::write(folder.join(name), map(version)).unwrap();
+        }
+    }
+
+    fn songs_folder() -> (tempfile::TempDir, std::path::PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let songs = dir.path().join("Songs");
+        fs::create_dir_all(&songs).unwrap();
+        (dir, songs)
+    }
+
+    #[test]
+    fn drain_time_matches_between_files_and_osu_db() {
+        let (dir, songs) = songs_folder();
+        write_set(&songs, FOLDER, &["Diff 1", "Diff 2"]);
+        // Written last, so the db is trusted for both files.
+        fs::write(dir.path().join(OSU_DB_FILE), database(20250107)).unwrap();
+
+        let mut index = SongsIndex::new(songs.to_str().unwrap());
+        index.refresh(true).unwrap();
+        let set = index.find(FOLDER).unwrap();
+        assert_eq!(
+            set.background_path,
+            Some(format!("{}/bg.jpg", songs.join(FOLDER).display()))
+        );
+
+        // The first file is always parsed; the second comes from osu!.db.
+        let [parsed, from_db] = &set.difficulties[..] else {
+            panic!("expected two difficulties");
+        };
+        assert_eq!(parsed.md5, content_md5(map("Diff 1")));
+        assert_eq!(from_db.md5, format!("{:032x}", 2));
+        assert_eq!(parsed.drain_time, 90.0);
+        assert_eq!(from_db.drain_time, 90.0);
+        assert_eq!(from_db.star_rating, Some(5.25));
+
+        assert_eq!(index.search("length=90").len(), 1);
+        assert!(index.search("length>95").is_empty());
+    }
+
+    #[test]
+    fn drain_time_without_osu_db() {
+        let (_dir, songs) = songs_folder();
+        write_set(&songs, FOLDER, &["Diff 1", "Diff 2"]);
+
+        let mut index = SongsIndex::new(songs.to_str().unwrap());
+        index.refresh(true).unwrap();
+        let set = index.find(FOLDER).unwrap();
+        assert!(set.difficulties.iter().all(|d| d.drain_time == 90.0));
+        assert!(set.difficulties.iter().all(|d| d.star_rating.is_none()));
+        assert_eq!(index.search("length<91").len(), 1);
+    }
+
+    #[test]
+    fn refresh_counts_added_and_removed_sets() {
+        let (_dir, songs) = songs_folder();
+        write_set(&songs, "1 First", &["Easy"]);
+        write_set(&songs, "2 Second", &["Easy", "Hard"]);
+        fs::create_dir_all(songs.join("3 No maps")).unwrap();
+
+        let mut index = SongsIndex::new(songs.to_str().unwrap());
+        let stats = index.refresh(false).unwrap();
+        assert_eq!((stats.total, stats.added, stats.updated), (2, 2, 0));
+        assert_eq!(index.find("2 Second").unwrap().difficulties.len(), 2);
+
+        let stats = index.refresh(false).unwrap();
+        assert_eq!((stats.added, stats.updated, stats.removed), (0, 0, 0));
+
+        fs::remove_dir_all(songs.join("1 First")).unwrap();
+        let stats = index.refresh(false).unwrap();
+        assert_eq!((stats.total, stats.removed), (1, 1));
+        assert!(index.find("1 First").is_none());
+    }
+
+    #[test]
+    fn refresh_folder_reports_changes() {
+        let (_dir, songs) = songs_folder();
+        let mut index = SongsIndex::new(songs.to_str().unwrap());
+
+        let mut changes = IndexChanges::default();
+        write_set(&songs, FOLDER, &["Diff 1"]);
+        index.refresh_folder(FOLDER, &mut changes);
+        assert_eq!(changes.added, [FOLDER]);
+        assert_eq!(index.find(FOLDER).unwrap().difficulties[0].drain_time, 90.0);
+
+        let mut changes = IndexChanges::default();
+        index.refresh_folder(FOLDER, &mut changes);
+        assert!(changes.is_empty());
+
+        let mut changes = IndexChanges::default();
+        fs::remove_dir_all(songs.join(FOLDER)).unwrap();
+        index.refresh_folder(FOLDER, &mut changes);
+        assert_eq!(changes.removed, [FOLDER]);
+        assert!(index.sets.is_empty());
+    }
+
+    #[test]
+    fn saved_index_is_reloaded_for_the_same_folder_only() {
+        let (dir, songs) = songs_folder();
+        write_set(&songs, FOLDER, &["Diff 1"]);
+        let songs_path = songs.to_str().unwrap();
+        let mut index = SongsIndex::new(songs_path);
+        index.refresh(true).unwrap();
+
+        let path = dir.path().join("cache").join(SONGS_INDEX_FILE);
+        index.save(&path).unwrap();
+        assert_eq!(SongsIndex::load(&path, songs_path).sets.len(), 1);
+        assert!(SongsIndex::load(&path, "elsewhere").sets.is_empty());
+    }
+
+    #[test]
+    fn import_temp_folders_are_not_indexed() {
+        let (_dir, songs) = songs_folder();
+        write_set(&songs, FOLDER, &["Diff 1"]);
+        write_set(&songs, ".import-abc123", &["Diff 1"]);
+
+        let mut index = SongsIndex::new(songs.to_str().unwrap());
+        let stats = index.refresh(false).unwrap();
+        assert_eq!((stats.total, stats.added), (1, 1));
+        assert!(index.find(".import-abc123").is_none());
+
+        let mut changes = IndexChanges::default();
+        index.refresh_folder(".import-abc123", &mut changes);
+        assert!(changes.is_empty());
+        assert!(index.find(".import-abc123").is_none());
+    }
+}
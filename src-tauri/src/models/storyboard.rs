@@ -0,0 +1,1001 @@
+use crate::models::beatmap::Colour;
+use crate::utils::curve::Vec2;
+use crate::utils::parser::{is_skippable, section_name, split_lines};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layer {
+    Background,
+    Fail,
+    Pass,
+    Foreground,
+    Overlay,
+}
+
+impl Layer {
+    pub const ALL: [Layer; 5] = [
+        Layer::Background,
+        Layer::Fail,
+        Layer::Pass,
+        Layer::Foreground,
+        Layer::Overlay,
+    ];
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "Background" | "0" => Some(Layer::Background),
+            "Fail" | "1" => Some(Layer::Fail),
+            "Pass" | "2" => Some(Layer::Pass),
+            "Foreground" | "3" => Some(Layer::Foreground),
+            "Overlay" | "4" => Some(Layer::Overlay),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Layer::Background => "Background",
+            Layer::Fail => "Fail",
+            Layer::Pass => "Pass",
+            Layer::Foreground => "Foreground",
+            Layer::Overlay => "Overlay",
+        }
+    }
+
+    pub fn index(self) -> u8 {
+        self as u8
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Origin {
+    TopLeft,
+    Centre,
+    CentreLeft,
+    TopRight,
+    BottomCentre,
+    TopCentre,
+    Custom,
+    CentreRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Origin {
+    const NAMES: [(Origin, &'static str); 10] = [
+        (Origin::TopLeft, "TopLeft"),
+        (Origin::Centre, "Centre"),
+        (Origin::CentreLeft, "CentreLeft"),
+        (Origin::TopRight, "TopRight"),
+        (Origin::BottomCentre, "BottomCentre"),
+        (Origin::TopCentre, "TopCentre"),
+        (Origin::Custom, "Custom"),
+        (Origin::CentreRight, "CentreRight"),
+        (Origin::BottomLeft, "BottomLeft"),
+        (Origin::BottomRight, "BottomRight"),
+    ];
+
+    pub fn parse(value: &str) -> Option<Self> {
+        if let Ok(index) = value.parse::<usize>() {
+            return Self::NAMES.get(index).map(|(origin, _)| *origin);
+        }
+        Self::NAMES
+            .iter()
+            .find(|(_, name)| *name == value)
+            .map(|(origin, _)| *origin)
+    }
+
+    pub fn name(self) -> &'static str {
+        Self::NAMES[self as usize].1
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoopType {
+    LoopForever,
+    LoopOnce,
+}
+
+impl LoopType {
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "LoopOnce" | "1" => LoopType::LoopOnce,
+            _ => LoopType::LoopForever,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LoopType::LoopForever => "LoopForever",
+            LoopType::LoopOnce => "LoopOnce",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Parameter {
+    FlipHorizontal,
+    FlipVertical,
+    AdditiveBlend,
+}
+
+impl Parameter {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "H" => Some(Parameter::FlipHorizontal),
+            "V" => Some(Parameter::FlipVertical),
+            "A" => Some(Parameter::AdditiveBlend),
+            _ => None,
+        }
+    }
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Parameter::FlipHorizontal => "H",
+            Parameter::FlipVertical => "V",
+            Parameter::AdditiveBlend => "A",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Tween<T> {
+    pub easing: u8,
+    pub start_time: i32,
+    pub end_time: i32,
+    pub from: T,
+    pub to: T,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Command {
+    Fade(Tween<f64>),
+    Move(Tween<Vec2>),
+    MoveX(Tween<f64>),
+    MoveY(Tween<f64>),
+    Scale(Tween<f64>),
+    VectorScale(Tween<Vec2>),
+    Rotate(Tween<f64>),
+    Colour(Tween<Colour>),
+    Parameter {
+        easing: u8,
+        start_time: i32,
+        end_time: i32,
+        parameter: Parameter,
+    },
+    Loop {
+        start_time: i32,
+        loop_count: u32,
+        commands: Vec<Command>,
+    },
+    Trigger {
+        trigger: String,
+        start_time: i32,
+        end_time: i32,
+        group_number: Option<i32>,
+        commands: Vec<Command>,
+    },
+}
+
+fn int(value: &str) -> Option<i32> {
+    value.trim().parse::<f64>().ok().map(|v| v as i32)
+}
+
+fn float(value: &str) -> Option<f64> {
+    value.trim().parse().ok()
+}
+
+fn vec2(values: &[&str]) -> Option<Vec2> {
+    Some(Vec2::new(float(values[0])?, float(values[1])?))
+}
+
+fn colour(values: &[&str]) -> Option<Colour> {
+    let channel = |i: usize| float(values[i]).map(|v| v.clamp(0.0, 255.0) as u8);
+    Some(Colour {
+        r: channel(0)?,
+        g: channel(1)?,
+        b: channel(2)?,
+    })
+}
+
+// "F,0,1000,2000,0,1,0.5" is shorthand for consecutive tweens of the same
+// length, each starting where the previous one ended.
+fn tweens<T: Copy>(
+    easing: u8,
+    start_time: i32,
+    end_time: i32,
+    values: &[&str],
+    arity: usize,
+    read: impl Fn(&[&str]) -> Option<T>,
+) -> Option<Vec<Tween<T>>> {
+    let points: Vec<T> = values
+        .chunks_exact(arity)
+        .map(&read)
+        .collect::<Option<_>>()?;
+    let first = *points.first()?;
+    if points.len() == 1 {
+        return Some(vec![Tween {
+            easing,
+            start_time,
+            end_time,
+            from: first,
+            to: first,
+        }]);
+    }
+
+    let duration = end_time - start_time;
+    Some(
+        points
+            .windows(2)
+            .enumerate()
+            .map(|(i, pair)| Tween {
+                easing,
+                start_time: start_time + duration * i as i32,
+                end_time: end_time + duration * i as i32,
+                from: pair[0],
+                to: pair[1],
+            })
+            .collect(),
+    )
+}
+
+fn format_number(value: f64) -> String {
+    value.to_string()
+}
+
+fn format_vec2(value: Vec2) -> String {
+    format!("{},{}", value.x, value.y)
+}
+
+fn format_tween<T: PartialEq + Copy>(
+    code: &str,
+    tween: &Tween<T>,
+    format: impl Fn(T) -> String,
+) -> String {
+    let end_time = if tween.end_time == tween.start_time {
+        String::new()
+    } else {
+        tween.end_time.to_string()
+    };
+    let mut line = format!(
+        "{},{},{},{},{}",
+        code,
+        tween.easing,
+        tween.start_time,
+        end_time,
+        format(tween.from)
+    );
+    if tween.to != tween.from {
+        line.push(',');
+        line.push_str(&format(tween.to));
+    }
+    line
+}
+
+impl Command {
+    pub fn parse(line: &str) -> Option<Vec<Self>> {
+        let parts: Vec<&str> = line
+            .trim_start_matches([' ', '_'])
+            .trim()
+            .split(',')
+            .collect();
+        let code = *parts.first()?;
+
+        match code {
+            "L" => {
+                return Some(vec![Command::Loop {
+                    start_time: int(parts.get(1)?)?,
+                    loop_count: int(parts.get(2)?)?.max(0) as u32,
+                    commands: Vec::new(),
+                }])
+            }
+            "T" => {
+                return Some(vec![Command::Trigger {
+                    trigger: parts.get(1)?.to_string(),
+                    start_time: int(parts.get(2)?)?,
+                    end_time: int(parts.get(3)?)?,
+                    group_number: parts.get(4).and_then(|g| int(g)),
+                    commands: Vec::new(),
+                }])
+            }
+            _ => {}
+        }
+
+        let easing = int(parts.get(1)?)?.clamp(0, u8::MAX as i32) as u8;
+        let start_time = int(parts.get(2)?)?;
+        let end_time = match parts.get(3)?.trim() {
+            "" => start_time,
+            value => int(value)?,
+        };
+        let values = parts.get(4..).unwrap_or_default();
+
+        let commands = match code {
+            "F" => tweens(easing, start_time, end_time, values, 1, |v| float(v[0]))?
+                .into_iter()
+                .map(Command::Fade)
+                .collect(),
+            "M" => tweens(easing, start_time, end_time, values, 2, vec2)?
+                .into_iter()
+                .map(Command::Move)
+                .collect(),
+            "MX" => tweens(easing, start_time, end_time, values, 1, |v| float(v[0]))?
+                .into_iter()
+                .map(Command::MoveX)
+                .collect(),
+            "MY" => tweens(easing, start_time, end_time, values, 1, |v| float(v[0]))?
+                .into_iter()
+                .map(Command::MoveY)
+                .collect(),
+            "S" => tweens(easing, start_time, end_time, values, 1, |v| float(v[0]))?
+                .into_iter()
+                .map(Command::Scale)
+                .collect(),
+            "V" => tweens(easing, start_time, end_time, values, 2, vec2)?
+                .into_iter()
+                .map(Command::VectorScale)
+                .collect(),
+            "R" => tweens(easing, start_time, end_time, values, 1, |v| float(v[0]))?
+                .into_iter()
+                .map(Command::Rotate)
+                .collect(),
+            "C" => tweens(easing, start_time, end_time, values, 3, colour)?
+                .into_iter()
+                .map(Command::Colour)
+                .collect(),
+            "P" => vec![Command::Parameter {
+                easing,
+                start_time,
+                end_time,
+                parameter: Parameter::parse(values.first()?.trim())?,
+            }],
+            _ => return None,
+        };
+        Some(commands)
+    }
+
+    pub fn start_time(&self) -> i32 {
+        match self {
+            Command::Fade(t)
+            | Command::MoveX(t)
+            | Command::MoveY(t)
+            | Command::Scale(t)
+            | Command::Rotate(t) => t.start_time,
+            Command::Move(t) | Command::VectorScale(t) => t.start_time,
+            Command::Colour(t) => t.start_time,
+            Command::Parameter { start_time, .. }
+            | Command::Loop { start_time, .. }
+            | Command::Trigger { start_time, .. } => *start_time,
+        }
+    }
+
+    // For loops this covers every iteration; trigger bodies are relative to when they fire.
+    pub fn end_time(&self) -> i32 {
+        match self {
+            Command::Fade(t)
+            | Command::MoveX(t)
+            | Command::MoveY(t)
+            | Command::Scale(t)
+            | Command::Rotate(t) => t.end_time,
+            Command::Move(t) | Command::VectorScale(t) => t.end_time,
+            Command::Colour(t) => t.end_time,
+            Command::Parameter { end_time, .. } | Command::Trigger { end_time, .. } => *end_time,
+            Command::Loop {
+                start_time,
+                loop_count,
+                commands,
+            } => {
+                let length = commands.iter().map(|c| c.end_time()).max().unwrap_or(0);
+                start_time + length * (*loop_count).max(1) as i32
+            }
+        }
+    }
+
+    pub fn children(&self) -> &[Command] {
+        match self {
+            Command::Loop { commands, .. } | Command::Trigger { commands, .. } => commands,
+            _ => &[],
+        }
+    }
+
+    fn children_mut(&mut self) -> Option<&mut Vec<Command>> {
+        match self {
+            Command::Loop { commands, .. } | Command::Trigger { commands, .. } => Some(commands),
+            _ => None,
+        }
+    }
+
+    pub fn to_osu_string(&self) -> String {
+        match self {
+            Command::Fade(t) => format_tween("F", t, format_number),
+            Command::Move(t) => format_tween("M", t, format_vec2),
+            Command::MoveX(t) => format_tween("MX", t, format_number),
+            Command::MoveY(t) => format_tween("MY", t, format_number),
+            Command::Scale(t) => format_tween("S", t, format_number),
+            Command::VectorScale(t) => format_tween("V", t, format_vec2),
+            Command::Rotate(t) => format_tween("R", t, format_number),
+            Command::Colour(t) => format_tween("C", t, Colour::to_osu_string),
+            Command::Parameter {
+                easing,
+                start_time,
+                end_time,
+                parameter,
+            } => format!(
+                "P,{},{},{},{}",
+                easing,
+                start_time,
+                if end_time == start_time {
+                    String::new()
+                } else {
+                    end_time.to_string()
+                },
+                parameter.code()
+            ),
+            Command::Loop {
+                start_time,
+                loop_count,
+                ..
+            } => format!("L,{},{}", start_time, loop_count),
+            Command::Trigger {
+                trigger,
+                start_time,
+                end_time,
+                group_number,
+                ..
+            } => match group_number {
+                Some(group) => format!("T,{},{},{},{}", trigger, start_time, end_time, group),
+                None => format!("T,{},{},{}", trigger, start_time, end_time),
+            },
+        }
+    }
+
+    fn write_lines(&self, depth: usize, lines: &mut Vec<String>) {
+        lines.push(format!("{}{}", " ".repeat(depth), self.to_osu_string()));
+        for child in self.children() {
+            child.write_lines(depth + 1, lines);
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Element {
+    Sprite {
+        layer: Layer,
+        origin: Origin,
+        path: String,
+        position: Vec2,
+        commands: Vec<Command>,
+    },
+    Animation {
+        layer: Layer,
+        origin: Origin,
+        path: String,
+        position: Vec2,
+        frame_count: u32,
+        frame_delay: f64,
+        loop_type: LoopType,
+        commands: Vec<Command>,
+    },
+    Sample {
+        time: i32,
+        layer: Layer,
+        path: String,
+        volume: i32,
+    },
+    Video {
+        start_time: i32,
+        path: String,
+        offset: Vec2,
+    },
+}
+
+fn quoted(value: &str) -> String {
+    value.trim().trim_matches('"').to_string()
+}
+
+impl Element {
+    pub fn parse(line: &str) -> Option<Self> {
+        let parts: Vec<&str> = line.trim().split(',').collect();
+        let position = |i: usize| -> Option<Vec2> {
+            Some(Vec2::new(float(parts.get(i)?)?, float(parts.get(i + 1)?)?))
+        };
+
+        let element = match *parts.first()? {
+            "Sprite" | "4" => Element::Sprite {
+                layer: Layer::parse(parts.get(1)?.trim())?,
+                origin: Origin::parse(parts.get(2)?.trim())?,
+                path: quoted(parts.get(3)?),
+                position: position(4)?,
+                commands: Vec::new(),
+            },
+            "Animation" | "6" => Element::Animation {
+                layer: Layer::parse(parts.get(1)?.trim())?,
+                origin: Origin::parse(parts.get(2)?.trim())?,
+                path: quoted(parts.get(3)?),
+                position: position(4)?,
+                frame_count: int(parts.get(6)?)?.max(0) as u32,
+                frame_delay: float(parts.get(7)?)?,
+                loop_type: parts
+                    .get(8)
+                    .map(|l| LoopType::parse(l.trim()))
+                    .unwrap_or(LoopType::LoopForever),
+                commands: Vec::new(),
+            },
+            "Sample" | "5" => Element::Sample {
+                time: int(parts.get(1)?)?,
+                layer: Layer::parse(parts.get(2)?.trim())?,
+                path: quoted(parts.get(3)?),
+                volume: parts.get(4).and_then(|v| int(v)).unwrap_or(100),
+            },
+            "Video" | "1" => Element::Video {
+                start_time: int(parts.get(1)?)?,
+                path: quoted(parts.get(2)?),
+                offset: position(3).unwrap_or_default(),
+            },
+            _ => return None,
+        };
+        Some(element)
+    }
+
+    pub fn layer(&self) -> Option<Layer> {
+        match self {
+            Element::Sprite { layer, .. }
+            | Element::Animation { layer, .. }
+            | Element::Sample { layer, .. } => Some(*layer),
+            Element::Video { .. } => None,
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        match self {
+            Element::Sprite { path, .. }
+            | Element::Animation { path, .. }
+            | Element::Sample { path, .. }
+            | Element::Video { path, .. } => path,
+        }
+    }
+
+    pub fn commands(&self) -> &[Command] {
+        match self {
+            Element::Sprite { commands, .. } | Element::Animation { commands, .. } => commands,
+            _ => &[],
+        }
+    }
+
+    fn commands_mut(&mut self) -> Option<&mut Vec<Command>> {
+        match self {
+            Element::Sprite { commands, .. } | Element::Animation { commands, .. } => {
+                Some(commands)
+            }
+            _ => None,
+        }
+    }
+
+    // Animations load one image per frame, numbered before the extension.
+    pub fn files(&self) -> Vec<String> {
+        match self {
+            Element::Animation {
+                path, frame_count, ..
+            } => {
+                // Only a dot in the file name itself starts the extension.
+                let (stem, extension) = match path.rfind('.') {
+                    Some(i) if !path[i..].contains(['/', '\\']) => path.split_at(i),
+                    _ => (path.as_str(), ""),
+                };
+                (0..*frame_count)
+                    .map(|frame| format!("{}{}{}", stem, frame, extension))
+                    .collect()
+            }
+            _ => vec![self.path().to_string()],
+        }
+    }
+
+    pub fn to_osu_string(&self) -> String {
+        match self {
+            Element::Sprite {
+                layer,
+                origin,
+                path,
+                position,
+                ..
+            } => format!(
+                "Sprite,{},{},\"{}\",{}",
+                layer.name(),
+                origin.name(),
+                path,
+                format_vec2(*position)
+            ),
+            Element::Animation {
+                layer,
+                origin,
+                path,
+                position,
+                frame_count,
+                frame_delay,
+                loop_type,
+                ..
+            } => format!(
+                "Animation,{},{},\"{}\",{},{},{},{}",
+                layer.name(),
+                origin.name(),
+                path,
+                format_vec2(*position),
+                frame_count,
+                frame_delay,
+                loop_type.name()
+            ),
+            Element::Sample {
+                time,
+                layer,
+                path,
+                volume,
+            } => format!("Sample,{},{},\"{}\",{}", time, layer.index(), path, volume),
+            Element::Video {
+                start_time,
+                path,
+                offset,
+            } => format!("Video,{},\"{}\",{}", start_time, path, format_vec2(*offset)),
+        }
+    }
+
+    fn write_lines(&self, lines: &mut Vec<String>) {
+        lines.push(self.to_osu_string());
+        for command in self.commands() {
+            command.write_lines(1, lines);
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Storyboard {
+    pub elements: Vec<Element>,
+}
+
+// Replaces $name tokens, longest names first so "$a" doesn't clobber "$ab".
+pub fn expand_variables(line: &str, variables: &[(String, String)]) -> String {
+    let mut sorted: Vec<&(String, String)> = variables.iter().collect();
+    sorted.sort_by_key(|(name, _)| std::cmp::Reverse(name.len()));
+
+    let mut expanded = line.to_string();
+    for (name, value) in sorted {
+        expanded = expanded.replace(name.as_str(), value);
+    }
+    expanded
+}
+
+pub fn parse_variables<'a>(lines: impl IntoIterator<Item = &'a str>) -> Vec<(String, String)> {
+    lines
+        .into_iter()
+        .filter_map(|line| {
+            let (name, value) = line.trim().split_once('=')?;
+            name.starts_with('$')
+                .then(|| (name.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+// Element headers and their indented commands, even when variables make them unparsable.
+pub fn is_storyboard_line(line: &str) -> bool {
+    line.starts_with([' ', '_'])
+        || matches!(
+            line.split(',').next().map(str::trim),
+            Some("Sprite" | "Animation" | "Sample" | "4" | "5" | "6")
+        )
+}
+
+fn command_depth(line: &str) -> usize {
+    line.chars().take_while(|c| *c == ' ' || *c == '_').count()
+}
+
+impl Storyboard {
+    // Builds a storyboard from raw [Events] lines. Background, video and break
+    // events of a difficulty are left to the beatmap model.
+    pub fn from_event_lines<'a>(
+        lines: impl IntoIterator<Item = &'a str>,
+        variables: &[(String, String)],
+    ) -> Self {
+        let mut storyboard = Storyboard::default();
+        let mut accepts_commands = false;
+
+        for line in lines {
+            if is_skippable(line) {
+                continue;
+            }
+            let line = expand_variables(line, variables);
+            let depth = command_depth(&line);
+
+            if depth == 0 {
+                let element = Element::parse(&line);
+                accepts_commands = matches!(
+                    element,
+                    Some(Element::Sprite { .. } | Element::Animation { .. })
+                );
+                storyboard.elements.extend(element);
+                continue;
+            }
+            if !accepts_commands {
+                continue;
+            }
+
+            let Some(commands) = Command::parse(&line) else {
+                continue;
+            };
+            let Some(target) = storyboard
+                .elements
+                .last_mut()
+                .and_then(|e| e.commands_mut())
+            else {
+                continue;
+            };
+
+            // Commands at depth 2 belong to the most recent loop or trigger.
+            let target = match (depth, target.last_mut().and_then(|c| c.children_mut())) {
+                (2.., Some(children)) => children,
+                _ => target,
+            };
+            target.extend(commands);
+        }
+
+        storyboard
+    }
+
+    pub fn parse(content: &str) -> Self {
+        let mut section = String::new();
+        let mut variables = Vec::new();
+        let mut events = Vec::new();
+
+        for line in split_lines(content.trim_start_matches('\u{feff}')) {
+            if let Some(name) = section_name(&line.text) {
+                section = name.to_string();
+                continue;
+            }
+            match section.as_str() {
+                "Variables" => variables.push(line.text),
+                "Events" => events.push(line.text),
+                _ => {}
+            }
+        }
+
+        let variables = parse_variables(variables.iter().map(|v| v.as_str()));
+        Storyboard::from_event_lines(events.iter().map(|e| e.as_str()), &variables)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Ok(Storyboard::parse(&content))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    // Element and command lines in file order, for embedding in a difficulty's [Events].
+    pub fn to_event_lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for element in &self.elements {
+            element.write_lines(&mut lines);
+        }
+        lines
+    }
+
+    // Full .osb contents, grouped under the comment headers the editor writes.
+    pub fn to_osb_string(&self) -> String {
+        let mut lines = vec![
+            "[Events]".to_string(),
+            "//Background and Video events".to_string(),
+        ];
+        for element in self.elements.iter().filter(|e| e.layer().is_none()) {
+            element.write_lines(&mut lines);
+        }
+
+        for layer in Layer::ALL {
+            lines.push(format!(
+                "//Storyboard Layer {} ({})",
+                layer.index(),
+                layer.name()
+            ));
+            for element in &self.elements {
+                if element.layer() == Some(layer) && !matches!(element, Element::Sample { .. }) {
+                    element.write_lines(&mut lines);
+                }
+            }
+        }
+
+        lines.push("//Storyboard Sound Samples".to_string());
+        for element in &self.elements {
+            if matches!(element, Element::Sample { .. }) {
+                element.write_lines(&mut lines);
+            }
+        }
+
+        let mut output = lines.join("\r\n");
+        output.push_str("\r\n");
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OSB: &str = "[Events]
+//Background and Video events
+Video,-200,\"intro.mp4\",0,0
+//Storyboard Layer 0 (Background)
+Sprite,Background,Centre,\"bg.png\",320,240
+ F,0,0,1000,0,1
+ M,1,0,,320,240
+ L,1000,3
+  R,0,0,500,0,1.5
+  S,0,500,1000,1,0.5
+ T,HitSoundClap,2000,5000,1
+  C,0,0,100,255,255,255,255,0,0
+  P,0,0,,A
+//Storyboard Layer 1 (Fail)
+//Storyboard Layer 2 (Pass)
+//Storyboard Layer 3 (Foreground)
+Animation,Foreground,TopLeft,\"sb/star.png\",0,0,4,50,LoopOnce
+ V,0,0,1000,1,1,2,0.5
+ MX,0,0,1000,0,100
+ MY,2,0,1000,0,100
+//Storyboard Layer 4 (Overlay)
+//Storyboard Sound Samples
+Sample,1000,0,\"hit.wav\",80
+";
+
+    fn crlf(text: &str) -> String {
+        text.replace('\n', "\r\n")
+    }
+
+    fn tween<T>(start_time: i32, end_time: i32, from: T, to: T) -> Tween<T> {
+        Tween {
+            easing: 0,
+            start_time,
+            end_time,
+            from,
+            to,
+        }
+    }
+
+    #[test]
+    fn round_trips_osb() {
+        let storyboard = Storyboard::parse(OSB);
+        let written = storyboard.to_osb_string();
+        assert_eq!(written, crlf(OSB));
+        assert_eq!(Storyboard::parse(&written), storyboard);
+    }
+
+    #[test]
+    fn nests_loop_and_trigger_bodies() {
+        let storyboard = Storyboard::parse(OSB);
+        let commands = storyboard.elements[1].commands();
+        assert_eq!(commands.len(), 4);
+        assert_eq!(
+            commands[2],
+            Command::Loop {
+                start_time: 1000,
+                loop_count: 3,
+                commands: vec![
+                    Command::Rotate(tween(0, 500, 0.0, 1.5)),
+                    Command::Scale(tween(500, 1000, 1.0, 0.5)),
+                ],
+            }
+        );
+        // Each of the three iterations lasts until the loop's last command ends.
+        assert_eq!(commands[2].end_time(), 4000);
+
+        let Command::Trigger {
+            trigger,
+            group_number,
+            commands: body,
+            ..
+        } = &commands[3]
+        else {
+            panic!("expected a trigger");
+        };
+        assert_eq!(trigger, "HitSoundClap");
+        assert_eq!(*group_number, Some(1));
+        assert_eq!(body.len(), 2);
+        assert_eq!(commands[3].end_time(), 5000);
+    }
+
+    #[test]
+    fn elements_round_trip_through_difficulty_events() {
+        let storyboard = Storyboard::parse(OSB);
+        let lines = storyboard.to_event_lines();
+        let reparsed = Storyboard::from_event_lines(lines.iter().map(|l| l.as_str()), &[]);
+        assert_eq!(reparsed, storyboard);
+    }
+
+    #[test]
+    fn underscores_indent_like_spaces() {
+        let lines = [
+            "Sprite,Pass,Centre,\"a.png\",0,0",
+            "_L,0,2",
+            "__F,0,0,100,1",
+        ];
+        let storyboard = Storyboard::from_event_lines(lines, &[]);
+        let children = storyboard.elements[0].commands()[0].children();
+        assert_eq!(children, [Command::Fade(tween(0, 100, 1.0, 1.0))]);
+    }
+
+    #[test]
+    fn shorthand_expands_to_back_to_back_tweens() {
+        assert_eq!(
+            Command::parse("F,0,1000,2000,0,1,0.5").unwrap(),
+            [
+                Command::Fade(tween(1000, 2000, 0.0, 1.0)),
+                Command::Fade(tween(2000, 3000, 1.0, 0.5)),
+            ]
+        );
+        assert_eq!(
+            Command::parse("M,0,0,100,0,0,10,10,20,20").unwrap(),
+            [
+                Command::Move(tween(0, 100, Vec2::new(0.0, 0.0), Vec2::new(10.0, 10.0))),
+                Command::Move(tween(
+                    100,
+                    200,
+                    Vec2::new(10.0, 10.0),
+                    Vec2::new(20.0, 20.0)
+                )),
+            ]
+        );
+        // A single value holds still for the whole command.
+        assert_eq!(
+            Command::parse("S,0,0,,2").unwrap(),
+            [Command::Scale(tween(0, 0, 2.0, 2.0))]
+        );
+        assert_eq!(Command::parse("F,0,0,100"), None);
+        assert_eq!(Command::parse("Z,0,0,100,1"), None);
+    }
+
+    #[test]
+    fn expands_longer_variable_names_first() {
+        let variables = vec![
+            ("$a".to_string(), "Foreground".to_string()),
+            ("$ab".to_string(), "Centre".to_string()),
+        ];
+        assert_eq!(
+            expand_variables("Sprite,$a,$ab,\"x.png\",0,0", &variables),
+            "Sprite,Foreground,Centre,\"x.png\",0,0"
+        );
+
+        let osb =
+            "[Variables]\n$a=Foreground\n$ab=Centre\n\n[Events]\nSprite,$a,$ab,\"x.png\",0,0\n";
+        let storyboard = Storyboard::parse(osb);
+        assert_eq!(storyboard.elements[0].layer(), Some(Layer::Foreground));
+        assert!(matches!(
+            storyboard.elements[0],
+            Element::Sprite {
+                origin: Origin::Centre,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn animation_frames_are_numbered_before_the_extension() {
+        let animation = |path: &str| Element::Animation {
+            layer: Layer::Foreground,
+            origin: Origin::Centre,
+            path: path.to_string(),
+            position: Vec2::new(0.0, 0.0),
+            frame_count: 3,
+            frame_delay: 50.0,
+            loop_type: LoopType::LoopForever,
+            commands: Vec::new(),
+        };
+        assert_eq!(
+            animation("sb/star.png").files(),
+            ["sb/star0.png", "sb/star1.png", "sb/star2.png"]
+        );
+        assert_eq!(
+            animation("sb.v2/frame").files(),
+            ["sb.v2/frame0", "sb.v2/frame1", "sb.v2/frame2"]
+        );
+        let sprite = Element::parse("Sprite,Foreground,Centre,\"sb/a.b.png\",0,0").unwrap();
+        assert_eq!(sprite.files(), ["sb/a.b.png"]);
+    }
+}
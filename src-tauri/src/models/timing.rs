@@ -0,0 +1,331 @@
+use crate::models::beatmap::format_bool;
+use crate::utils::parser::is_skippable;
+use serde::{Deserialize, Serialize};
+use std::ops::{Deref, DerefMut};
+
+pub const EFFECT_KIAI: u8 = 1;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TimingPoint {
+    pub time: f64,
+    pub beat_length: f64,
+    pub meter: i32,
+    pub sample_set: u8,
+    pub sample_index: i32,
+    pub volume: i32,
+    pub uninherited: bool,
+    pub effects: u8,
+}
+
+impl TimingPoint {
+    pub fn parse(line: &str) -> Option<Self> {
+        if is_skippable(line) {
+            return None;
+        }
+
+        let parts: Vec<&str> = line.split(',').map(|p| p.trim()).collect();
+        if parts.len() < 2 {
+            return None;
+        }
+
+        let time = parts[0].parse().ok()?;
+        let beat_length: f64 = parts[1].parse().ok()?;
+        let field = |i: usize| parts.get(i).and_then(|p| p.parse::<i32>().ok());
+
+        Some(TimingPoint {
+            time,
+            beat_length,
+            meter: field(2).filter(|m| *m > 0).unwrap_or(4),
+            sample_set: field(3).unwrap_or(0) as u8,
+            sample_index: field(4).unwrap_or(0),
+            volume: field(5).unwrap_or(100),
+            uninherited: field(6).map(|v| v == 1).unwrap_or(beat_length > 0.0),
+            effects: field(7).unwrap_or(0) as u8,
+        })
+    }
+
+    pub fn to_osu_string(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{}",
+            self.time,
+            self.beat_length,
+            self.meter,
+            self.sample_set,
+            self.sample_index,
+            self.volume,
+            format_bool(self.uninherited),
+            self.effects
+        )
+    }
+}
+
+impl TimingPoint {
+    pub fn bpm(&self) -> Option<f64> {
+        if self.uninherited && self.beat_length > 0.0 {
+            Some(60000.0 / self.beat_length)
+        } else {
+            None
+        }
+    }
+
+    pub fn slider_velocity(&self) -> f64 {
+        if self.uninherited || self.beat_length >= 0.0 {
+            1.0
+        } else {
+            (-100.0 / self.beat_length).clamp(0.1, 10.0)
+        }
+    }
+
+    pub fn kiai(&self) -> bool {
+        self.effects & EFFECT_KIAI != 0
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct TimingState {
+    pub red_line_time: f64,
+    pub beat_length: f64,
+    pub bpm: f64,
+    pub meter: i32,
+    pub slider_velocity: f64,
+    pub sample_set: u8,
+    pub sample_index: i32,
+    pub volume: i32,
+    pub kiai: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(transparent)]
+pub struct TimingPoints(Vec<TimingPoint>);
+
+impl From<Vec<TimingPoint>> for TimingPoints {
+    fn from(points: Vec<TimingPoint>) -> Self {
+        TimingPoints(points)
+    }
+}
+
+impl Deref for TimingPoints {
+    type Target = Vec<TimingPoint>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for TimingPoints {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl TimingPoints {
+    pub fn red_lines(&self) -> impl Iterator<Item = &TimingPoint> {
+        self.0.iter().filter(|p| p.uninherited)
+    }
+
+    // Objects before the first red line are timed by it, as osu! does.
+    pub fn red_line_at(&self, time: f64) -> Option<&TimingPoint> {
+        self.red_lines()
+            .take_while(|p| p.time <= time)
+            .last()
+            .or_else(|| self.red_lines().next())
+    }
+
+    pub fn point_at(&self, time: f64) -> Option<&TimingPoint> {
+        let index = self.0.partition_point(|p| p.time <= time);
+        if index == 0 {
+            self.0.first()
+        } else {
+            self.0.get(index - 1)
+        }
+    }
+
+    pub fn beat_length_at(&self, time: f64) -> f64 {
+        self.red_line_at(time)
+            .map(|p| p.beat_length)
+            .filter(|bl| *bl > 0.0)
+            .unwrap_or(500.0)
+    }
+
+    pub fn bpm_at(&self, time: f64) -> f64 {
+        60000.0 / self.beat_length_at(time)
+    }
+
+    pub fn meter_at(&self, time: f64) -> i32 {
+        self.red_line_at(time).map(|p| p.meter).unwrap_or(4)
+    }
+
+    pub fn slider_velocity_at(&self, time: f64) -> f64 {
+        self.point_at(time)
+            .filter(|p| p.time <= time)
+            .map(|p| p.slider_velocity())
+            .unwrap_or(1.0)
+    }
+
+    pub fn sample_set_at(&self, time: f64) -> u8 {
+        self.point_at(time).map(|p| p.sample_set).unwrap_or(0)
+    }
+
+    pub fn volume_at(&self, time: f64) -> i32 {
+        self.point_at(time).map(|p| p.volume).unwrap_or(100)
+    }
+
+    pub fn kiai_at(&self, time: f64) -> bool {
+        self.point_at(time)
+            .filter(|p| p.time <= time)
+            .map(|p| p.kiai())
+            .unwrap_or(false)
+    }
+
+    pub fn state_at(&self, time: f64) -> TimingState {
+        let red_line = self.red_line_at(time);
+        let point = self.point_at(time);
+        let beat_length = self.beat_length_at(time);
+
+        TimingState {
+            red_line_time: red_line.map(|p| p.time).unwrap_or(0.0),
+            beat_length,
+            bpm: 60000.0 / beat_length,
+            meter: self.meter_at(time),
+            slider_velocity: self.slider_velocity_at(time),
+            sample_set: point.map(|p| p.sample_set).unwrap_or(0),
+            sample_index: point.map(|p| p.sample_index).unwrap_or(0),
+            volume: point.map(|p| p.volume).unwrap_or(100),
+            kiai: self.kiai_at(time),
+        }
+    }
+
+    pub fn slider_duration(
+        &self,
+        time: f64,
+        pixel_length: f64,
+        slides: u32,
+        slider_multiplier: f64,
+    ) -> f64 {
+        let velocity = slider_multiplier * 100.0 * self.slider_velocity_at(time);
+        if velocity <= 0.0 {
+            return 0.0;
+        }
+        pixel_length / velocity * self.beat_length_at(time) * slides as f64
+    }
+
+    pub fn nearest_snap(&self, time: f64, divisor: u32) -> f64 {
+        let Some(red_line) = self.red_line_at(time) else {
+            return time;
+        };
+        let step = self.beat_length_at(time) / divisor.max(1) as f64;
+        red_line.time + ((time - red_line.time) / step).round() * step
+    }
+
+    fn ticks(&self, start: f64, end: f64, step_of: impl Fn(&TimingPoint) -> f64) -> Vec<f64> {
+        let red_lines: Vec<&TimingPoint> = self.red_lines().collect();
+        let mut ticks = Vec::new();
+
+        for (i, red_line) in red_lines.iter().enumerate() {
+            let section_end = red_lines.get(i + 1).map(|p| p.time).unwrap_or(f64::MAX);
+            let step = step_of(red_line);
+            if step <= 0.0 || section_end <= start || red_line.time > end {
+                continue;
+            }
+
+            // Like red_line_at, the first red line also times everything before it.
+            let mut index = ((start - red_line.time) / step).ceil();
+            if i > 0 {
+                index = index.max(0.0);
+            }
+            loop {
+                let tick = red_line.time + index * step;
+                if tick >= section_end || tick > end {
+                    break;
+                }
+                ticks.push(tick);
+                index += 1.0;
+            }
+        }
+
+        ticks
+    }
+
+    pub fn snap_times(&self, start: f64, end: f64, divisor: u32) -> Vec<f64> {
+        self.ticks(start, end, |p| p.beat_length / divisor.max(1) as f64)
+    }
+
+    pub fn measure_times(&self, start: f64, end: f64) -> Vec<f64> {
+        self.ticks(start, end, |p| p.beat_length * p.meter.max(1) as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(lines: &[&str]) -> TimingPoints {
+        TimingPoints::from(
+            lines
+                .iter()
+                .filter_map(|line| TimingPoint::parse(line))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn inherited_points_scale_slider_velocity() {
+        let timing = points(&["0,500,4,2,0,100,1,0", "1000,-50,4,2,0,100,0,0"]);
+        assert_eq!(timing.slider_velocity_at(500.0), 1.0);
+        assert_eq!(timing.slider_velocity_at(1000.0), 2.0);
+        // 280px at 1.4 * 100 * 2 px per beat is one beat, twice.
+        assert_eq!(timing.slider_duration(1000.0, 280.0, 2, 1.4), 1000.0);
+        assert_eq!(timing.slider_duration(500.0, 280.0, 1, 1.4), 1000.0);
+    }
+
+    #[test]
+    fn slider_velocity_is_clamped() {
+        let timing = points(&["0,500,4,2,0,100,1,0", "100,-5,4,2,0,100,0,0"]);
+        assert_eq!(timing.slider_velocity_at(100.0), 10.0);
+    }
+
+    #[test]
+    fn later_red_lines_take_over() {
+        let timing = points(&[
+            "0,500,4,2,0,100,1,0",
+            "1000,400,3,2,0,100,1,0",
+            "1500,-200,4,2,0,100,0,1",
+        ]);
+        assert_eq!(timing.beat_length_at(999.0), 500.0);
+        assert_eq!(timing.beat_length_at(1600.0), 400.0);
+        assert_eq!(timing.meter_at(1600.0), 3);
+        assert!(timing.kiai_at(1600.0));
+        assert_eq!(timing.slider_duration(1600.0, 100.0, 1, 1.0), 800.0);
+
+        assert_eq!(timing.nearest_snap(1190.0, 4), 1200.0);
+        assert_eq!(timing.nearest_snap(880.0, 2), 1000.0);
+        assert_eq!(
+            timing.snap_times(750.0, 1400.0, 2),
+            vec![750.0, 1000.0, 1200.0, 1400.0]
+        );
+        assert_eq!(
+            timing.measure_times(0.0, 3500.0),
+            vec![0.0, 1000.0, 2200.0, 3400.0]
+        );
+    }
+
+    #[test]
+    fn first_red_line_extends_backwards() {
+        let timing = points(&["1000,300,4,2,0,100,1,0"]);
+        assert_eq!(timing.beat_length_at(0.0), 300.0);
+        assert_eq!(timing.nearest_snap(380.0, 1), 400.0);
+        assert_eq!(timing.nearest_snap(930.0, 4), 925.0);
+        assert_eq!(
+            timing.snap_times(0.0, 1000.0, 1),
+            vec![100.0, 400.0, 700.0, 1000.0]
+        );
+        assert_eq!(timing.measure_times(-500.0, 1500.0), vec![-200.0, 1000.0]);
+    }
+
+    #[test]
+    fn snaps_to_thirds() {
+        let timing = points(&["100,600,4,2,0,100,1,0"]);
+        assert_eq!(timing.nearest_snap(301.0, 3), 300.0);
+        assert_eq!(timing.nearest_snap(499.0, 3), 500.0);
+    }
+}
@@ -0,0 +1,631 @@
+use crate::models::beatmap::{Beatmap, Colours, Difficulty, Event};
+use crate::models::hitobject::{HITSOUND_CLAP, HITSOUND_FINISH, HITSOUND_WHISTLE};
+use crate::models::timing::{TimingPoint, TimingPoints, EFFECT_KIAI};
+use crate::transforms::pipeline::{parse_params, Transform};
+use crate::utils::curve::Vec2;
+use serde::Deserialize;
+use serde_json::Value;
+
+pub fn all() -> Vec<Box<dyn Transform>> {
+    vec![
+        Box::new(SetMode),
+        Box::new(SetMetadata),
+        Box::new(ResetDifficulty),
+        Box::new(ResetSampleSet),
+        Box::new(StripColours),
+        Box::new(StripBookmarks),
+        Box::new(StripEvents),
+        Box::new(SimplifyTimingPoints),
+        Box::new(StripTimingPoints),
+        Box::new(StripHitObjects),
+        Box::new(CenterObjects),
+        Box::new(StripNewCombos),
+        Box::new(WhistleToClap),
+    ]
+}
+
+#[derive(Deserialize, Default)]
+struct ModeParams {
+    mode: u8,
+}
+
+pub struct SetMode;
+
+impl Transform for SetMode {
+    fn name(&self) -> &'static str {
+        "set_mode"
+    }
+
+    fn description(&self) -> &'static str {
+        "Change the game mode (0: osu!, 1: taiko, 2: catch, 3: mania)"
+    }
+
+    fn apply(&self, beatmap: &mut Beatmap, params: &Value) -> Result<(), String> {
+        let params: ModeParams = parse_params(params)?;
+        if params.mode > 3 {
+            return Err(format!("Invalid game mode: {}", params.mode));
+        }
+        beatmap.general.mode = params.mode;
+        Ok(())
+    }
+}
+
+#[derive(Deserialize, Default)]
+struct MetadataParams {
+    title: Option<String>,
+    title_unicode: Option<String>,
+    artist: Option<String>,
+    artist_unicode: Option<String>,
+    creator: Option<String>,
+    version: Option<String>,
+    source: Option<String>,
+    tags: Option<String>,
+    beatmap_id: Option<i32>,
+    beatmap_set_id: Option<i32>,
+}
+
+pub struct SetMetadata;
+
+impl Transform for SetMetadata {
+    fn name(&self) -> &'static str {
+        "set_metadata"
+    }
+
+    fn description(&self) -> &'static str {
+        "Overwrite the given [Metadata] fields"
+    }
+
+    fn apply(&self, beatmap: &mut Beatmap, params: &Value) -> Result<(), String> {
+        let params: MetadataParams = parse_params(params)?;
+        let metadata = &mut beatmap.metadata;
+
+        let fields = [
+            (params.title, &mut metadata.title),
+            (params.title_unicode, &mut metadata.title_unicode),
+            (params.artist, &mut metadata.artist),
+            (params.artist_unicode, &mut metadata.artist_unicode),
+            (params.creator, &mut metadata.creator),
+            (params.version, &mut metadata.version),
+            (params.source, &mut metadata.source),
+            (params.tags, &mut metadata.tags),
+        ];
+        for (value, field) in fields {
+            if let Some(value) = value {
+                *field = value;
+            }
+        }
+
+        if let Some(id) = params.beatmap_id {
+            metadata.beatmap_id = id;
+        }
+        if let Some(id) = params.beatmap_set_id {
+            metadata.beatmap_set_id = id;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+struct DifficultyParams {
+    hp_drain_rate: f64,
+    circle_size: f64,
+    overall_difficulty: f64,
+    approach_rate: f64,
+    slider_multiplier: f64,
+    slider_tick_rate: f64,
+}
+
+impl Default for DifficultyParams {
+    fn default() -> Self {
+        DifficultyParams {
+            hp_drain_rate: 5.0,
+            circle_size: 2.0,
+            overall_difficulty: 5.0,
+            approach_rate: 5.0,
+            slider_multiplier: 1.4,
+            slider_tick_rate: 1.0,
+        }
+    }
+}
+
+pub struct ResetDifficulty;
+
+impl Transform for ResetDifficulty {
+    fn name(&self) -> &'static str {
+        "reset_difficulty"
+    }
+
+    fn description(&self) -> &'static str {
+        "Reset HP/CS/OD/AR, slider multiplier and tick rate"
+    }
+
+    fn apply(&self, beatmap: &mut Beatmap, params: &Value) -> Result<(), String> {
+        let params: DifficultyParams = parse_params(params)?;
+        beatmap.difficulty = Difficulty {
+            hp_drain_rate: params.hp_drain_rate,
+            circle_size: params.circle_size,
+            overall_difficulty: params.overall_difficulty,
+            approach_rate: params.approach_rate,
+            slider_multiplier: params.slider_multiplier,
+            slider_tick_rate: params.slider_tick_rate,
+        };
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+struct SampleSetParams {
+    sample_set: String,
+}
+
+impl Default for SampleSetParams {
+    fn default() -> Self {
+        SampleSetParams {
+            sample_set: "Normal".to_string(),
+        }
+    }
+}
+
+pub struct ResetSampleSet;
+
+impl Transform for ResetSampleSet {
+    fn name(&self) -> &'static str {
+        "reset_sample_set"
+    }
+
+    fn description(&self) -> &'static str {
+        "Set the default sample set in [General]"
+    }
+
+    fn apply(&self, beatmap: &mut Beatmap, params: &Value) -> Result<(), String> {
+        let params: SampleSetParams = parse_params(params)?;
+        beatmap.general.sample_set = params.sample_set;
+        Ok(())
+    }
+}
+
+pub struct StripColours;
+
+impl Transform for StripColours {
+    fn name(&self) -> &'static str {
+        "strip_colours"
+    }
+
+    fn description(&self) -> &'static str {
+        "Remove combo and slider colours"
+    }
+
+    fn apply(&self, beatmap: &mut Beatmap, _params: &Value) -> Result<(), String> {
+        beatmap.colours = Colours::default();
+        Ok(())
+    }
+}
+
+pub struct StripBookmarks;
+
+impl Transform for StripBookmarks {
+    fn name(&self) -> &'static str {
+        "strip_bookmarks"
+    }
+
+    fn description(&self) -> &'static str {
+        "Delete all editor bookmarks"
+    }
+
+    fn apply(&self, beatmap: &mut Beatmap, _params: &Value) -> Result<(), String> {
+        beatmap.editor.bookmarks.clear();
+        Ok(())
+    }
+}
+
+#[derive(Deserialize, Default)]
+struct EventsParams {
+    background: Option<String>,
+}
+
+pub struct StripEvents;
+
+impl Transform for StripEvents {
+    fn name(&self) -> &'static str {
+        "strip_events"
+    }
+
+    fn description(&self) -> &'static str {
+        "Remove every event except the background, optionally replacing it"
+    }
+
+    fn apply(&self, beatmap: &mut Beatmap, params: &Value) -> Result<(), String> {
+        let params: EventsParams = parse_params(params)?;
+        beatmap
+            .events
+            .retain(|event| matches!(event, Event::Background { .. }));
+
+        if let Some(background) = params.background {
+            for event in beatmap.events.iter_mut() {
+                *event = Event::Background {
+                    filename: background.clone(),
+                    x_offset: 0,
+                    y_offset: 0,
+                };
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct SimplifyTimingPoints;
+
+impl Transform for SimplifyTimingPoints {
+    fn name(&self) -> &'static str {
+        "simplify_timing_points"
+    }
+
+    fn description(&self) -> &'static str {
+        "Keep red lines and kiai toggles only, with default samples"
+    }
+
+    fn apply(&self, beatmap: &mut Beatmap, _params: &Value) -> Result<(), String> {
+        let points = &beatmap.timing_points;
+        let mut result = Vec::new();
+        let mut in_kiai = false;
+
+        for point in points.iter() {
+            let has_kiai = point.kiai();
+            let effects = if has_kiai { EFFECT_KIAI } else { 0 };
+
+            if point.uninherited {
+                result.push(TimingPoint {
+                    sample_set: 1,
+                    sample_index: 0,
+                    volume: 100,
+                    effects,
+                    ..point.clone()
+                });
+                in_kiai = has_kiai;
+                continue;
+            }
+
+            if has_kiai != in_kiai {
+                if let Some(red_line) = points.red_line_at(point.time) {
+                    result.push(TimingPoint {
+                        time: point.time,
+                        beat_length: -100.0,
+                        meter: red_line.meter,
+                        sample_set: 1,
+                        sample_index: 0,
+                        volume: 100,
+                        uninherited: false,
+                        effects,
+                    });
+                }
+
+                in_kiai = has_kiai;
+            }
+        }
+
+        beatmap.timing_points = TimingPoints::from(result);
+        Ok(())
+    }
+}
+
+pub struct StripTimingPoints;
+
+impl Transform for StripTimingPoints {
+    fn name(&self) -> &'static str {
+        "strip_timing_points"
+    }
+
+    fn description(&self) -> &'static str {
+        "Remove all timing points"
+    }
+
+    fn apply(&self, beatmap: &mut Beatmap, _params: &Value) -> Result<(), String> {
+        beatmap.timing_points.clear();
+        Ok(())
+    }
+}
+
+pub struct StripHitObjects;
+
+impl Transform for StripHitObjects {
+    fn name(&self) -> &'static str {
+        "strip_hit_objects"
+    }
+
+    fn description(&self) -> &'static str {
+        "Remove all hit objects"
+    }
+
+    fn apply(&self, beatmap: &mut Beatmap, _params: &Value) -> Result<(), String> {
+        beatmap.hit_objects.clear();
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+struct CenterParams {
+    x: f64,
+    y: f64,
+}
+
+impl Default for CenterParams {
+    fn default() -> Self {
+        CenterParams { x: 256.0, y: 192.0 }
+    }
+}
+
+pub struct CenterObjects;
+
+impl Transform for CenterObjects {
+    fn name(&self) -> &'static str {
+        "center_objects"
+    }
+
+    fn description(&self) -> &'static str {
+        "Move all hit objects to the center of the playfield"
+    }
+
+    fn apply(&self, beatmap: &mut Beatmap, params: &Value) -> Result<(), String> {
+        let params: CenterParams = parse_params(params)?;
+        for object in beatmap.hit_objects.iter_mut() {
+            object.position = Vec2::new(params.x, params.y);
+        }
+        Ok(())
+    }
+}
+
+pub struct StripNewCombos;
+
+impl Transform for StripNewCombos {
+    fn name(&self) -> &'static str {
+        "strip_new_combos"
+    }
+
+    fn description(&self) -> &'static str {
+        "Remove new combo flags from circles, except the first object"
+    }
+
+    fn apply(&self, beatmap: &mut Beatmap, _params: &Value) -> Result<(), String> {
+        for object in beatmap.hit_objects.iter_mut().skip(1) {
+            if object.is_circle() && object.new_combo && object.combo_skip == 0 {
+                object.new_combo = false;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct WhistleToClap;
+
+impl Transform for WhistleToClap {
+    fn name(&self) -> &'static str {
+        "whistle_to_clap"
+    }
+
+    fn description(&self) -> &'static str {
+        "Replace whistle hitsounds with clap"
+    }
+
+    fn apply(&self, beatmap: &mut Beatmap, _params: &Value) -> Result<(), String> {
+        for object in beatmap.hit_objects.iter_mut() {
+            if object.hit_sound == HITSOUND_WHISTLE {
+                object.hit_sound = HITSOUND_CLAP;
+            } else if object.hit_sound == HITSOUND_WHISTLE | HITSOUND_FINISH {
+                object.hit_sound = HITSOUND_CLAP | HITSOUND_FINISH;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const MAP: &str = "osu file format v14
+
+[General]
+AudioFilename: audio.mp3
+SampleSet: Soft
+Mode: 0
+
+[Editor]
+Bookmarks: 1000,2000
+
+[Metadata]
+Title:Song
+Artist:Someone
+Creator:Mapper
+Version:Hard
+BeatmapID:10
+BeatmapSetID:20
+
+[Difficulty]
+HPDrainRate:6
+CircleSize:4
+OverallDifficulty:8
+ApproachRate:9
+SliderMultiplier:1.8
+SliderTickRate:2
+
+[Events]
+0,0,\"bg.jpg\",0,0
+Video,0,\"video.mp4\",0,0
+2,5000,8000
+Sprite,Foreground,Centre,\"star.png\",320,240
+
+[TimingPoints]
+0,500,4,2,1,60,1,0
+500,-50,4,2,1,60,0,0
+1000,-100,4,2,1,60,0,1
+1500,-50,4,2,1,60,0,1
+2000,-100,4,2,1,60,0,0
+3000,400,3,2,1,60,1,1
+
+[Colours]
+Combo1 : 255,0,0
+SliderBorder : 0,0,0
+
+[HitObjects]
+100,100,0,5,2,0:0:0:0:
+200,100,500,5,6,0:0:0:0:
+300,100,1000,6,0,L|400:100,1,100
+400,100,1500,5,8,0:0:0:0:
+400,100,2000,1,0,0:0:0:0:
+";
+
+    fn beatmap() -> Beatmap {
+        Beatmap::parse(MAP).unwrap()
+    }
+
+    fn applied(transform: &dyn Transform, params: Value) -> Beatmap {
+        let mut beatmap = beatmap();
+        transform.apply(&mut beatmap, &params).unwrap();
+        beatmap
+    }
+
+    #[test]
+    fn names_are_unique() {
+        let mut names: Vec<&str> = all().iter().map(|t| t.name()).collect();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), all().len());
+    }
+
+    #[test]
+    fn set_mode() {
+        assert_eq!(applied(&SetMode, json!({ "mode": 3 })).general.mode, 3);
+        let err = SetMode.apply(&mut beatmap(), &json!({ "mode": 4 }));
+        assert_eq!(err, Err("Invalid game mode: 4".to_string()));
+        assert!(SetMode
+            .apply(&mut beatmap(), &json!({ "mode": "x" }))
+            .is_err());
+    }
+
+    #[test]
+    fn set_metadata_only_touches_given_fields() {
+        let beatmap = applied(
+            &SetMetadata,
+            json!({ "title": "Other", "tags": "a b", "beatmap_set_id": -1 }),
+        );
+        let metadata = &beatmap.metadata;
+        assert_eq!(metadata.title, "Other");
+        assert_eq!(metadata.tags, "a b");
+        assert_eq!(metadata.beatmap_set_id, -1);
+        assert_eq!(metadata.artist, "Someone");
+        assert_eq!(metadata.version, "Hard");
+        assert_eq!(metadata.beatmap_id, 10);
+    }
+
+    #[test]
+    fn reset_difficulty() {
+        let difficulty = applied(&ResetDifficulty, Value::Null).difficulty;
+        assert_eq!(difficulty.hp_drain_rate, 5.0);
+        assert_eq!(difficulty.circle_size, 2.0);
+        assert_eq!(difficulty.slider_multiplier, 1.4);
+        assert_eq!(difficulty.slider_tick_rate, 1.0);
+
+        let difficulty = applied(&ResetDifficulty, json!({ "circle_size": 4.5 })).difficulty;
+        assert_eq!(difficulty.circle_size, 4.5);
+        assert_eq!(difficulty.overall_difficulty, 5.0);
+    }
+
+    #[test]
+    fn reset_sample_set() {
+        assert_eq!(
+            applied(&ResetSampleSet, Value::Null).general.sample_set,
+            "Normal"
+        );
+        let beatmap = applied(&ResetSampleSet, json!({ "sample_set": "Drum" }));
+        assert_eq!(beatmap.general.sample_set, "Drum");
+    }
+
+    #[test]
+    fn strip_colours_and_bookmarks() {
+        assert_eq!(
+            applied(&StripColours, Value::Null).colours,
+            Colours::default()
+        );
+        assert!(applied(&StripBookmarks, Value::Null)
+            .editor
+            .bookmarks
+            .is_empty());
+    }
+
+    #[test]
+    fn strip_events_keeps_the_background() {
+        let beatmap = applied(&StripEvents, Value::Null);
+        assert_eq!(beatmap.background(), Some("bg.jpg"));
+        assert_eq!(beatmap.events.len(), 1);
+
+        let beatmap = applied(&StripEvents, json!({ "background": "other.png" }));
+        assert_eq!(beatmap.background(), Some("other.png"));
+        assert_eq!(beatmap.events.len(), 1);
+    }
+
+    #[test]
+    fn simplify_timing_points_keeps_red_lines_and_kiai_toggles() {
+        let points = applied(&SimplifyTimingPoints, Value::Null).timing_points;
+        let lines: Vec<String> = points.iter().map(|p| p.to_osu_string()).collect();
+        // The SV changes go, and the second kiai green line is redundant.
+        assert_eq!(
+            lines,
+            [
+                "0,500,4,1,0,100,1,0",
+                "1000,-100,4,1,0,100,0,1",
+                "2000,-100,4,1,0,100,0,0",
+                "3000,400,3,1,0,100,1,1",
+            ]
+        );
+    }
+
+    #[test]
+    fn strip_timing_points_and_objects() {
+        assert!(applied(&StripTimingPoints, Value::Null)
+            .timing_points
+            .is_empty());
+        assert!(applied(&StripHitObjects, Value::Null)
+            .hit_objects
+            .is_empty());
+    }
+
+    #[test]
+    fn center_objects() {
+        let beatmap = applied(&CenterObjects, Value::Null);
+        assert!(beatmap
+            .hit_objects
+            .iter()
+            .all(|o| o.position == Vec2::new(256.0, 192.0)));
+
+        let beatmap = applied(&CenterObjects, json!({ "x": 0.0 }));
+        assert_eq!(beatmap.hit_objects[0].position, Vec2::new(0.0, 192.0));
+    }
+
+    #[test]
+    fn strip_new_combos_from_later_circles() {
+        let beatmap = applied(&StripNewCombos, Value::Null);
+        let new_combos: Vec<bool> = beatmap.hit_objects.iter().map(|o| o.new_combo).collect();
+        // The first object and sliders keep theirs.
+        assert_eq!(new_combos, [true, false, true, false, false]);
+    }
+
+    #[test]
+    fn whistle_to_clap() {
+        let beatmap = applied(&WhistleToClap, Value::Null);
+        let hit_sounds: Vec<u8> = beatmap.hit_objects.iter().map(|o| o.hit_sound).collect();
+        assert_eq!(
+            hit_sounds,
+            [
+                HITSOUND_CLAP,
+                HITSOUND_CLAP | HITSOUND_FINISH,
+                0,
+                HITSOUND_CLAP,
+                0
+            ]
+        );
+    }
+}
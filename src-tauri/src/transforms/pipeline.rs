@@ -0,0 +1,191 @@
+use crate::models::beatmap::Beatmap;
+use crate::transforms::builtin;
+use once_cell::sync::Lazy;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+pub trait Transform: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn description(&self) -> &'static str;
+    fn apply(&self, beatmap: &mut Beatmap, params: &Value) -> Result<(), String>;
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct TransformInfo {
+    pub name: String,
+    pub description: String,
+}
+
+static REGISTRY: Lazy<Vec<Box<dyn Transform>>> = Lazy::new(builtin::all);
+
+pub fn find_transform(name: &str) -> Option<&'static dyn Transform> {
+    REGISTRY
+        .iter()
+        .find(|t| t.name() == name)
+        .map(|t| t.as_ref())
+}
+
+pub fn available_transforms() -> Vec<TransformInfo> {
+    REGISTRY
+        .iter()
+        .map(|t| TransformInfo {
+            name: t.name().to_string(),
+            description: t.description().to_string(),
+        })
+        .collect()
+}
+
+pub fn parse_params<T: DeserializeOwned + Default>(params: &Value) -> Result<T, String> {
+    if params.is_null() {
+        return Ok(T::default());
+    }
+    serde_json::from_value(params.clone()).map_err(|e| format!("Invalid parameters: {}", e))
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TransformStep {
+    pub name: String,
+    #[serde(default)]
+    pub params: Value,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(transparent)]
+pub struct Pipeline {
+    pub steps: Vec<TransformStep>,
+}
+
+impl From<Vec<TransformStep>> for Pipeline {
+    fn from(steps: Vec<TransformStep>) -> Self {
+        Pipeline { steps }
+    }
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Pipeline::default()
+    }
+
+    pub fn with(mut self, name: &str, params: Value) -> Self {
+        self.steps.push(TransformStep {
+            name: name.to_string(),
+            params,
+        });
+        self
+    }
+
+    pub fn then(mut self, other: &Pipeline) -> Self {
+        self.steps.extend(other.steps.iter().cloned());
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        for step in &self.steps {
+            if find_transform(&step.name).is_none() {
+                return Err(format!("Unknown transform: {}", step.name));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn apply(&self, beatmap: &mut Beatmap) -> Result<(), String> {
+        for step in &self.steps {
+            let transform = find_transform(&step.name)
+                .ok_or_else(|| format!("Unknown transform: {}", step.name))?;
+            transform
+                .apply(beatmap, &step.params)
+                .map_err(|e| format!("{}: {}", step.name, e))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const MAP: &str = "osu file format v14
+
+[General]
+Mode: 0
+
+[Metadata]
+Title:Song
+Version:Hard
+
+[HitObjects]
+100,100,0,5,2,0:0:0:0:
+";
+
+    #[test]
+    fn serializes_as_a_list_of_steps() {
+        let pipeline = Pipeline::new()
+            .with("set_mode", json!({ "mode": 1 }))
+            .with("strip_bookmarks", Value::Null);
+        let json = serde_json::to_value(&pipeline).unwrap();
+        assert_eq!(
+            json,
+            json!([
+                { "name": "set_mode", "params": { "mode": 1 } },
+                { "name": "strip_bookmarks", "params": null }
+            ])
+        );
+
+        let parsed: Pipeline = serde_json::from_value(json).unwrap();
+        let names: Vec<&str> = parsed.steps.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["set_mode", "strip_bookmarks"]);
+        assert_eq!(parsed.steps[0].params, json!({ "mode": 1 }));
+    }
+
+    #[test]
+    fn params_default_to_null() {
+        let parsed: Pipeline = serde_json::from_str(r#"[{ "name": "strip_colours" }]"#).unwrap();
+        assert!(parsed.steps[0].params.is_null());
+    }
+
+    #[test]
+    fn steps_apply_in_order() {
+        let first = Pipeline::new().with("set_metadata", json!({ "title": "First" }));
+        let second = Pipeline::new().with("set_metadata", json!({ "title": "Second" }));
+
+        let mut beatmap = Beatmap::parse(MAP).unwrap();
+        first.clone().then(&second).apply(&mut beatmap).unwrap();
+        assert_eq!(beatmap.metadata.title, "Second");
+
+        let mut beatmap = Beatmap::parse(MAP).unwrap();
+        second.then(&first).apply(&mut beatmap).unwrap();
+        assert_eq!(beatmap.metadata.title, "First");
+    }
+
+    #[test]
+    fn unknown_transforms_are_rejected() {
+        let pipeline = Pipeline::new()
+            .with("set_mode", json!({ "mode": 1 }))
+            .with("no_such_transform", Value::Null);
+        assert_eq!(
+            pipeline.validate(),
+            Err("Unknown transform: no_such_transform".to_string())
+        );
+
+        // Steps before the unknown one still ran on the beatmap passed in.
+        let mut beatmap = Beatmap::parse(MAP).unwrap();
+        assert!(pipeline.apply(&mut beatmap).is_err());
+        assert_eq!(beatmap.general.mode, 1);
+    }
+
+    #[test]
+    fn errors_name_the_step() {
+        let pipeline = Pipeline::new().with("set_mode", json!({ "mode": 9 }));
+        let mut beatmap = Beatmap::parse(MAP).unwrap();
+        assert_eq!(
+            pipeline.apply(&mut beatmap),
+            Err("set_mode: Invalid game mode: 9".to_string())
+        );
+    }
+}
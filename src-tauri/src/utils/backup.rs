@@ -0,0 +1,322 @@
+use crate::utils::files::content_md5;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const MANIFEST_FILE: &str = "manifest.json";
+const FILES_DIR: &str = "files";
+const MAX_SNAPSHOTS: usize = 20;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SnapshotFile {
+    pub path: String,
+    pub existed: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Snapshot {
+    pub id: String,
+    pub operation: String,
+    pub created_at: u64,
+    pub beatmap_folder: String,
+    pub folder_existed: bool,
+    pub files: Vec<SnapshotFile>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BackupStore {
+    root: PathBuf,
+}
+
+// Two sets can share a folder name (in different Songs folders, or after a rename), so
+// backups are keyed by the full path. The parent is canonicalized rather than the folder
+// itself so the key stays the same before a folder is created and after it is removed.
+fn folder_key(set_folder: &Path) -> Result<String, String> {
+    let name = set_folder
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| format!("Invalid beatmap folder: {}", set_folder.display()))?;
+    let parent = match set_folder.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let parent = fs::canonicalize(&parent).unwrap_or(parent);
+    Ok(content_md5(parent.join(name).to_string_lossy().as_bytes()))
+}
+
+fn relative_path(set_folder: &Path, path: &Path) -> Result<String, String> {
+    let relative = path
+        .strip_prefix(set_folder)
+        .map_err(|_| format!("{} is outside of {}", path.display(), set_folder.display()))?;
+    Ok(relative.to_string_lossy().replace('\\', "/"))
+}
+
+fn is_empty_dir(path: &Path) -> bool {
+    fs::read_dir(path)
+        .map(|mut entries| entries.next().is_none())
+        .unwrap_or(false)
+}
+
+// Removes folders left empty by deleting `path`, stopping at the set folder.
+fn remove_empty_parents(set_folder: &Path, path: &Path) {
+    let mut current = path.parent();
+    while let Some(dir) = current {
+        if dir == set_folder || !dir.starts_with(set_folder) || !is_empty_dir(dir) {
+            break;
+        }
+        if fs::remove_dir(dir).is_err() {
+            break;
+        }
+        current = dir.parent();
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+impl BackupStore {
+    pub fn new(root: PathBuf) -> Self {
+        BackupStore { root }
+    }
+
+    fn set_dir(&self, set_folder: &Path) -> Result<PathBuf, String> {
+        Ok(self.root.join(folder_key(set_folder)?))
+    }
+
+    pub fn snapshot(
+        &self,
+        set_folder: &Path,
+        operation: &str,
+        files: &[PathBuf],
+    ) -> Result<Snapshot, String> {
+        let snapshot = self.create_snapshot(set_folder, operation, files)?;
+        self.prune(set_folder)?;
+        Ok(snapshot)
+    }
+
+    fn create_snapshot(
+        &self,
+        set_folder: &Path,
+        operation: &str,
+        files: &[PathBuf],
+    ) -> Result<Snapshot, String> {
+        let created_at = now_millis();
+        let safe_operation: String = operation
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '_' })
+            .collect();
+
+        let set_dir = self.set_dir(set_folder)?;
+        let mut id = format!("{}-{}", created_at, safe_operation);
+        let mut suffix = 1;
+        while set_dir.join(&id).exists() {
+            id = format!("{}-{}-{}", created_at, safe_operation, suffix);
+            suffix += 1;
+        }
+
+        let snapshot_dir = set_dir.join(&id);
+        let files_dir = snapshot_dir.join(FILES_DIR);
+        fs::create_dir_all(&files_dir)
+            .map_err(|e| format!("Failed to create backup folder: {}", e))?;
+
+        let mut entries: Vec<SnapshotFile> = Vec::new();
+        for path in files {
+            let relative = relative_path(set_folder, path)?;
+            if entries.iter().any(|f| f.path == relative) {
+                continue;
+            }
+
+            let existed = path.is_file();
+            if existed {
+                let dest = files_dir.join(&relative);
+                if let Some(parent) = dest.parent() {
+                    fs::create_dir_all(parent)
+                        .map_err(|e| format!("Failed to create backup folder: {}", e))?;
+                }
+                fs::copy(path, &dest).map_err(|e| format!("Failed to back up file: {}", e))?;
+            }
+
+            entries.push(SnapshotFile {
+                path: relative,
+                existed,
+            });
+        }
+
+        let snapshot = Snapshot {
+            id,
+            operation: operation.to_string(),
+            created_at,
+            beatmap_folder: set_folder.display().to_string(),
+            folder_existed: set_folder.is_dir(),
+            files: entries,
+        };
+
+        let manifest = serde_json::to_string_pretty(&snapshot)
+            .map_err(|e| format!("Failed to serialize backup manifest: {}", e))?;
+        fs::write(snapshot_dir.join(MANIFEST_FILE), manifest)
+            .map_err(|e| format!("Failed to write backup manifest: {}", e))?;
+
+        Ok(snapshot)
+    }
+
+    pub fn list(&self, set_folder: &Path) -> Result<Vec<Snapshot>, String> {
+        let set_dir = self.set_dir(set_folder)?;
+        if !set_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut snapshots: Vec<Snapshot> = fs::read_dir(&set_dir)
+            .map_err(|e| format!("Failed to read backups: {}", e))?
+            .filter_map(|e| e.ok())
+            .filter_map(|entry| {
+                let manifest = fs::read_to_string(entry.path().join(MANIFEST_FILE)).ok()?;
+                serde_json::from_str(&manifest).ok()
+            })
+            .collect();
+
+        snapshots.sort_by(|a, b| b.created_at.cmp(&a.created_at).then(b.id.cmp(&a.id)));
+        Ok(snapshots)
+    }
+
+    pub fn get(&self, set_folder: &Path, id: &str) -> Result<Snapshot, String> {
+        self.list(set_folder)?
+            .into_iter()
+            .find(|s| s.id == id)
+            .ok_or_else(|| format!("Backup not found: {}", id))
+    }
+
+    // The current state is snapshotted first so a restore can itself be undone.
+    pub fn restore(&self, set_folder: &Path, id: &str) -> Result<Snapshot, String> {
+        let snapshot = self.get(set_folder, id)?;
+        let files_dir = self.set_dir(set_folder)?.join(&snapshot.id).join(FILES_DIR);
+
+        let current: Vec<PathBuf> = snapshot
+            .files
+            .iter()
+            .map(|f| set_folder.join(&f.path))
+            .collect();
+        self.create_snapshot(set_folder, "restore", &current)?;
+
+        for file in &snapshot.files {
+            let target = set_folder.join(&file.path);
+            if file.existed {
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)
+                        .map_err(|e| format!("Failed to create folder: {}", e))?;
+                }
+                fs::copy(files_dir.join(&file.path), &target)
+                    .map_err(|e| format!("Failed to restore {}: {}", file.path, e))?;
+            } else if target.exists() {
+                fs::remove_file(&target)
+                    .map_err(|e| format!("Failed to remove {}: {}", file.path, e))?;
+                remove_empty_parents(set_folder, &target);
+            }
+        }
+
+        if !snapshot.folder_existed && is_empty_dir(set_folder) {
+            let _ = fs::remove_dir(set_folder);
+        }
+
+        self.prune(set_folder)?;
+        Ok(snapshot)
+    }
+
+    pub fn delete(&self, set_folder: &Path, id: &str) -> Result<(), String> {
+        let snapshot = self.get(set_folder, id)?;
+        fs::remove_dir_all(self.set_dir(set_folder)?.join(&snapshot.id))
+            .map_err(|e| format!("Failed to delete backup: {}", e))
+    }
+
+    fn prune(&self, set_folder: &Path) -> Result<(), String> {
+        let set_dir = self.set_dir(set_folder)?;
+        for snapshot in self.list(set_folder)?.iter().skip(MAX_SNAPSHOTS) {
+            fs::remove_dir_all(set_dir.join(&snapshot.id))
+                .map_err(|e| format!("Failed to delete backup: {}", e))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_folder_names_keep_separate_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = BackupStore::new(dir.path().join("backups"));
+        let first = dir.path().join("songs1").join("123 Artist - Title");
+        let second = dir.path().join("songs2").join("123 Artist - Title");
+        for folder in [&first, &second] {
+            fs::create_dir_all(folder).unwrap();
+            fs::write(folder.join("map.osu"), "v1").unwrap();
+        }
+
+        store
+            .snapshot(&first, "edit", &[first.join("map.osu")])
+            .unwrap();
+        assert_eq!(store.list(&first).unwrap().len(), 1);
+        assert!(store.list(&second).unwrap().is_empty());
+
+        // A relative path to the same folder shares its history.
+        let relative = first.join("..").join("123 Artist - Title");
+        assert_eq!(store.list(&relative).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn restore_removes_created_files_and_folders() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = BackupStore::new(dir.path().join("backups"));
+        let folder = dir.path().join("set");
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join("map.osu"), "before").unwrap();
+        fs::create_dir_all(folder.join("sb")).unwrap();
+        fs::write(folder.join("sb").join("keep.png"), "").unwrap();
+
+        let added = [
+            folder.join("sb").join("new").join("star.png"),
+            folder.join("hitsounds").join("soft-hitclap.wav"),
+        ];
+        let mut files = vec![folder.join("map.osu")];
+        files.extend(added.iter().cloned());
+        let snapshot = store.snapshot(&folder, "edit", &files).unwrap();
+
+        fs::write(folder.join("map.osu"), "after").unwrap();
+        for path in &added {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+
+        store.restore(&folder, &snapshot.id).unwrap();
+        assert_eq!(
+            fs::read_to_string(folder.join("map.osu")).unwrap(),
+            "before"
+        );
+        assert!(!folder.join("hitsounds").exists());
+        assert!(!folder.join("sb").join("new").exists());
+        assert!(folder.join("sb").join("keep.png").exists());
+    }
+
+    #[test]
+    fn restore_removes_created_set_folder() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = BackupStore::new(dir.path().join("backups"));
+        let folder = dir.path().join("new set");
+        let map = folder.join("sb").join("map.osu");
+        let snapshot = store
+            .snapshot(&folder, "clone", std::slice::from_ref(&map))
+            .unwrap();
+
+        fs::create_dir_all(map.parent().unwrap()).unwrap();
+        fs::write(&map, "").unwrap();
+
+        store.restore(&folder, &snapshot.id).unwrap();
+        assert!(!folder.exists());
+        assert_eq!(store.list(&folder).unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,219 @@
+use std::io::{Cursor, Read};
+
+// .NET DateTime ticks at the Unix epoch.
+const UNIX_EPOCH_TICKS: i64 = 621_355_968_000_000_000;
+
+pub fn ticks_to_unix_millis(ticks: i64) -> i64 {
+    if ticks <= 0 {
+        return 0;
+    }
+    (ticks - UNIX_EPOCH_TICKS) / 10_000
+}
+
+pub fn unix_millis_to_ticks(millis: i64) -> i64 {
+    millis * 10_000 + UNIX_EPOCH_TICKS
+}
+
+// Little-endian reader for the .NET BinaryWriter layout used by osu!stable's databases.
+pub struct BinaryReader<'a> {
+    cursor: Cursor<&'a [u8]>,
+}
+
+impl<'a> BinaryReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        BinaryReader {
+            cursor: Cursor::new(data),
+        }
+    }
+
+    pub fn position(&self) -> u64 {
+        self.cursor.position()
+    }
+
+    pub fn remaining(&self) -> usize {
+        let len = self.cursor.get_ref().len() as u64;
+        len.saturating_sub(self.cursor.position()) as usize
+    }
+
+    pub fn read_bytes(&mut self, len: usize) -> Result<Vec<u8>, String> {
+        if len > self.remaining() {
+            return Err(format!(
+                "Unexpected end of data at offset {}",
+                self.position()
+            ));
+        }
+        let mut buffer = vec![0; len];
+        self.cursor
+            .read_exact(&mut buffer)
+            .map_err(|e| format!("Failed to read data: {}", e))?;
+        Ok(buffer)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let mut buffer = [0; N];
+        self.cursor
+            .read_exact(&mut buffer)
+            .map_err(|_| format!("Unexpected end of data at offset {}", self.position()))?;
+        Ok(buffer)
+    }
+
+    pub fn skip(&mut self, len: usize) -> Result<(), String> {
+        self.read_bytes(len).map(|_| ())
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, String> {
+        Ok(self.read_array::<1>()?[0])
+    }
+
+    pub fn read_bool(&mut self) -> Result<bool, String> {
+        Ok(self.read_u8()? != 0)
+    }
+
+    pub fn read_i16(&mut self) -> Result<i16, String> {
+        Ok(i16::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_u16(&mut self) -> Result<u16, String> {
+        Ok(u16::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_i32(&mut self) -> Result<i32, String> {
+        Ok(i32::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_i64(&mut self) -> Result<i64, String> {
+        Ok(i64::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_f32(&mut self) -> Result<f32, String> {
+        Ok(f32::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_f64(&mut self) -> Result<f64, String> {
+        Ok(f64::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_uleb128(&mut self) -> Result<u64, String> {
+        let mut result = 0u64;
+        let mut shift = 0;
+        loop {
+            let byte = self.read_u8()?;
+            if shift >= 64 {
+                return Err("Invalid ULEB128 value".to_string());
+            }
+            result |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(result);
+            }
+            shift += 7;
+        }
+    }
+
+    // Strings are 0x00 for "absent" or 0x0b followed by a ULEB128 length and UTF-8 bytes.
+    pub fn read_string(&mut self) -> Result<String, String> {
+        match self.read_u8()? {
+            0x00 => Ok(String::new()),
+            0x0b => {
+                let len = self.read_uleb128()? as usize;
+                let bytes = self.read_bytes(len)?;
+                Ok(String::from_utf8_lossy(&bytes).into_owned())
+            }
+            other => Err(format!(
+                "Invalid string marker 0x{:02x} at offset {}",
+                other,
+                self.position() - 1
+            )),
+        }
+    }
+
+    pub fn read_datetime(&mut self) -> Result<i64, String> {
+        Ok(ticks_to_unix_millis(self.read_i64()?))
+    }
+}
+
+#[derive(Default)]
+pub struct BinaryWriter {
+    buffer: Vec<u8>,
+}
+
+impl BinaryWriter {
+    pub fn new() -> Self {
+        BinaryWriter::default()
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buffer
+    }
+
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    pub fn write_u8(&mut self, value: u8) {
+        self.buffer.push(value);
+    }
+
+    pub fn write_bool(&mut self, value: bool) {
+        self.write_u8(value as u8);
+    }
+
+    pub fn write_i16(&mut self, value: i16) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn write_u16(&mut self, value: u16) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn write_i32(&mut self, value: i32) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn write_i64(&mut self, value: i64) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn write_f32(&mut self, value: f32) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn write_f64(&mut self, value: f64) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn write_uleb128(&mut self, mut value: u64) {
+        loop {
+            let mut byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value != 0 {
+                byte |= 0x80;
+            }
+            self.write_u8(byte);
+            if value == 0 {
+                break;
+            }
+        }
+    }
+
+    pub fn write_string(&mut self, value: &str) {
+        if value.is_empty() {
+            self.write_u8(0x00);
+            return;
+        }
+        self.write_u8(0x0b);
+        self.write_uleb128(value.len() as u64);
+        self.write_bytes(value.as_bytes());
+    }
+
+    pub fn write_datetime(&mut self, unix_millis: i64) {
+        self.write_i64(if unix_millis <= 0 {
+            0
+        } else {
+            unix_millis_to_ticks(unix_millis)
+        });
+    }
+}
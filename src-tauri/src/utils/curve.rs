@@ -0,0 +1,496 @@
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+use std::ops::{Add, Mul, Sub};
+
+const BEZIER_TOLERANCE: f64 = 0.25;
+const CIRCULAR_ARC_TOLERANCE: f64 = 0.1;
+const CATMULL_DETAIL: usize = 50;
+const MAX_BEZIER_SUBDIVISIONS: usize = 10_000;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct Vec2 {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Vec2 {
+    pub fn new(x: f64, y: f64) -> Self {
+        Vec2 { x, y }
+    }
+
+    pub fn length(self) -> f64 {
+        self.length_squared().sqrt()
+    }
+
+    pub fn length_squared(self) -> f64 {
+        self.x * self.x + self.y * self.y
+    }
+
+    pub fn distance(self, other: Vec2) -> f64 {
+        (other - self).length()
+    }
+
+    pub fn dot(self, other: Vec2) -> f64 {
+        self.x * other.x + self.y * other.y
+    }
+
+    pub fn lerp(self, other: Vec2, t: f64) -> Vec2 {
+        self + (other - self) * t
+    }
+
+    pub fn normalized(self) -> Vec2 {
+        let length = self.length();
+        if length == 0.0 {
+            self
+        } else {
+            self * (1.0 / length)
+        }
+    }
+}
+
+impl Add for Vec2 {
+    type Output = Vec2;
+
+    fn add(self, other: Vec2) -> Vec2 {
+        Vec2::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Vec2;
+
+    fn sub(self, other: Vec2) -> Vec2 {
+        Vec2::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Mul<f64> for Vec2 {
+    type Output = Vec2;
+
+    fn mul(self, scalar: f64) -> Vec2 {
+        Vec2::new(self.x * scalar, self.y * scalar)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum CurveType {
+    Bezier,
+    Linear,
+    PerfectCircle,
+    Catmull,
+}
+
+impl CurveType {
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            'B' => Some(CurveType::Bezier),
+            'L' => Some(CurveType::Linear),
+            'P' => Some(CurveType::PerfectCircle),
+            'C' => Some(CurveType::Catmull),
+            _ => None,
+        }
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            CurveType::Bezier => 'B',
+            CurveType::Linear => 'L',
+            CurveType::PerfectCircle => 'P',
+            CurveType::Catmull => 'C',
+        }
+    }
+}
+
+fn bezier_is_flat(points: &[Vec2]) -> bool {
+    points.windows(3).all(|w| {
+        (w[0] - w[1] * 2.0 + w[2]).length_squared() <= BEZIER_TOLERANCE * BEZIER_TOLERANCE * 4.0
+    })
+}
+
+fn bezier_subdivide(points: &[Vec2]) -> (Vec<Vec2>, Vec<Vec2>) {
+    let count = points.len();
+    let mut midpoints = points.to_vec();
+    let mut left = vec![Vec2::default(); count];
+    let mut right = vec![Vec2::default(); count];
+
+    for i in 0..count {
+        left[i] = midpoints[0];
+        right[count - i - 1] = midpoints[count - i - 1];
+        for j in 0..count - i - 1 {
+            midpoints[j] = (midpoints[j] + midpoints[j + 1]) * 0.5;
+        }
+    }
+
+    (left, right)
+}
+
+fn bezier_approximate(points: &[Vec2], output: &mut Vec<Vec2>) {
+    let count = points.len();
+    let (left, right) = bezier_subdivide(points);
+    let combined: Vec<Vec2> = left.iter().chain(right.iter().skip(1)).copied().collect();
+
+    output.push(points[0]);
+    for i in 1..count - 1 {
+        let index = 2 * i;
+        output.push((combined[index - 1] + combined[index] * 2.0 + combined[index + 1]) * 0.25);
+    }
+}
+
+pub fn bezier(points: &[Vec2]) -> Vec<Vec2> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+
+    let mut output = Vec::new();
+    let mut stack = vec![points.to_vec()];
+    let mut subdivisions = 0;
+
+    while let Some(parent) = stack.pop() {
+        if bezier_is_flat(&parent) || subdivisions >= MAX_BEZIER_SUBDIVISIONS {
+            bezier_approximate(&parent, &mut output);
+            continue;
+        }
+
+        let (left, right) = bezier_subdivide(&parent);
+        stack.push(right);
+        stack.push(left);
+        subdivisions += 1;
+    }
+
+    output.push(points[points.len() - 1]);
+    output
+}
+
+pub fn perfect_circle(points: &[Vec2]) -> Vec<Vec2> {
+    if points.len() != 3 {
+        return bezier(points);
+    }
+
+    let (a, b, c) = (points[0], points[1], points[2]);
+    let d = 2.0 * (a.x * (b.y - c.y) + b.x * (c.y - a.y) + c.x * (a.y - b.y));
+    if d.abs() < 1e-3 {
+        return bezier(points);
+    }
+
+    let a_sq = a.length_squared();
+    let b_sq = b.length_squared();
+    let c_sq = c.length_squared();
+    let center = Vec2::new(
+        (a_sq * (b.y - c.y) + b_sq * (c.y - a.y) + c_sq * (a.y - b.y)) / d,
+        (a_sq * (c.x - b.x) + b_sq * (a.x - c.x) + c_sq * (b.x - a.x)) / d,
+    );
+
+    let d_a = a - center;
+    let d_c = c - center;
+    let radius = d_a.length();
+
+    let theta_start = d_a.y.atan2(d_a.x);
+    let mut theta_end = d_c.y.atan2(d_c.x);
+    while theta_end < theta_start {
+        theta_end += 2.0 * PI;
+    }
+
+    let mut direction = 1.0;
+    let mut theta_range = theta_end - theta_start;
+
+    let a_to_c = c - a;
+    let ortho_a_to_c = Vec2::new(a_to_c.y, -a_to_c.x);
+    if ortho_a_to_c.dot(b - a) < 0.0 {
+        direction = -1.0;
+        theta_range = 2.0 * PI - theta_range;
+    }
+
+    let amount = if 2.0 * radius <= CIRCULAR_ARC_TOLERANCE {
+        2
+    } else {
+        let step = 2.0 * (1.0 - CIRCULAR_ARC_TOLERANCE / radius).acos();
+        ((theta_range / step).ceil() as usize).max(2)
+    };
+
+    (0..amount)
+        .map(|i| {
+            let fraction = i as f64 / (amount - 1) as f64;
+            let theta = theta_start + direction * fraction * theta_range;
+            center + Vec2::new(theta.cos(), theta.sin()) * radius
+        })
+        .collect()
+}
+
+fn catmull_point(v1: Vec2, v2: Vec2, v3: Vec2, v4: Vec2, t: f64) -> Vec2 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+
+    let axis = |p1: f64, p2: f64, p3: f64, p4: f64| {
+        0.5 * (2.0 * p2
+            + (-p1 + p3) * t
+            + (2.0 * p1 - 5.0 * p2 + 4.0 * p3 - p4) * t2
+            + (-p1 + 3.0 * p2 - 3.0 * p3 + p4) * t3)
+    };
+
+    Vec2::new(axis(v1.x, v2.x, v3.x, v4.x), axis(v1.y, v2.y, v3.y, v4.y))
+}
+
+pub fn catmull(points: &[Vec2]) -> Vec<Vec2> {
+    let count = points.len();
+    let mut output = Vec::new();
+
+    for i in 0..count.saturating_sub(1) {
+        let v1 = if i > 0 { points[i - 1] } else { points[i] };
+        let v2 = points[i];
+        let v3 = if i + 1 < count {
+            points[i + 1]
+        } else {
+            v2 * 2.0 - v1
+        };
+        let v4 = if i + 2 < count {
+            points[i + 2]
+        } else {
+            v3 * 2.0 - v2
+        };
+
+        for c in 0..CATMULL_DETAIL {
+            output.push(catmull_point(
+                v1,
+                v2,
+                v3,
+                v4,
+                c as f64 / CATMULL_DETAIL as f64,
+            ));
+            output.push(catmull_point(
+                v1,
+                v2,
+                v3,
+                v4,
+                (c + 1) as f64 / CATMULL_DETAIL as f64,
+            ));
+        }
+    }
+
+    output
+}
+
+// Red anchors are written as two identical consecutive points and start a new segment.
+fn split_segments(points: &[Vec2]) -> Vec<&[Vec2]> {
+    let mut segments = Vec::new();
+    let mut start = 0;
+
+    for i in 1..points.len() {
+        if points[i] == points[i - 1] {
+            segments.push(&points[start..i]);
+            start = i;
+        }
+    }
+    segments.push(&points[start..]);
+
+    segments
+}
+
+pub fn approximate(curve_type: CurveType, points: &[Vec2]) -> Vec<Vec2> {
+    if points.len() < 2 {
+        return points.to_vec();
+    }
+
+    let mut path: Vec<Vec2> = Vec::new();
+    let mut push_segment = |segment: Vec<Vec2>| {
+        for point in segment {
+            if path.last() != Some(&point) {
+                path.push(point);
+            }
+        }
+    };
+
+    match curve_type {
+        CurveType::Linear => push_segment(points.to_vec()),
+        CurveType::Catmull => push_segment(catmull(points)),
+        CurveType::PerfectCircle if points.len() == 3 => push_segment(perfect_circle(points)),
+        CurveType::Bezier | CurveType::PerfectCircle => {
+            for segment in split_segments(points) {
+                push_segment(bezier(segment));
+            }
+        }
+    }
+
+    path
+}
+
+pub fn path_length(path: &[Vec2]) -> f64 {
+    path.windows(2).map(|w| w[0].distance(w[1])).sum()
+}
+
+// Cuts the approximated path down (or extends its last segment) to the length
+// declared in the .osu file, which is what osu! actually uses for gameplay.
+pub fn fit_to_length(path: &[Vec2], length: f64) -> Vec<Vec2> {
+    if path.len() < 2 || length <= 0.0 {
+        return path.to_vec();
+    }
+
+    let mut output = vec![path[0]];
+    let mut travelled = 0.0;
+
+    for w in path.windows(2) {
+        let segment = w[0].distance(w[1]);
+        if travelled + segment >= length {
+            let t = if segment > 0.0 {
+                (length - travelled) / segment
+            } else {
+                0.0
+            };
+            output.push(w[0].lerp(w[1], t));
+            return output;
+        }
+        travelled += segment;
+        output.push(w[1]);
+    }
+
+    let last = path[path.len() - 1];
+    let previous = path[path.len() - 2];
+    let direction = (last - previous).normalized();
+    if let Some(end) = output.last_mut() {
+        *end = last + direction * (length - travelled);
+    }
+
+    output
+}
+
+pub fn point_at_distance(path: &[Vec2], distance: f64) -> Vec2 {
+    let Some(first) = path.first() else {
+        return Vec2::default();
+    };
+
+    let mut travelled = 0.0;
+    for w in path.windows(2) {
+        let segment = w[0].distance(w[1]);
+        if travelled + segment >= distance {
+            let t = if segment > 0.0 {
+                (distance - travelled) / segment
+            } else {
+                0.0
+            };
+            return w[0].lerp(w[1], t.max(0.0));
+        }
+        travelled += segment;
+    }
+
+    *path.last().unwrap_or(first)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(actual: Vec2, expected: Vec2) {
+        assert!(
+            actual.distance(expected) < 0.01,
+            "expected {:?}, got {:?}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn linear_keeps_control_points() {
+        let points = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(100.0, 0.0),
+            Vec2::new(100.0, 50.0),
+        ];
+        let path = approximate(CurveType::Linear, &points);
+        assert_eq!(path, points.to_vec());
+        assert_eq!(path_length(&path), 150.0);
+    }
+
+    #[test]
+    fn perfect_circle_follows_the_arc() {
+        let points = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(50.0, 50.0),
+            Vec2::new(100.0, 0.0),
+        ];
+        let path = approximate(CurveType::PerfectCircle, &points);
+        assert_near(path[0], points[0]);
+        assert_near(path[path.len() - 1], points[2]);
+        assert!((path_length(&path) - 50.0 * PI).abs() < 0.5);
+        for point in &path {
+            assert!((point.distance(Vec2::new(50.0, 0.0)) - 50.0).abs() < 1e-9);
+            assert!(point.y >= -1e-9);
+        }
+    }
+
+    #[test]
+    fn collinear_perfect_circle_is_straight() {
+        let points = [
+            Vec2::new(0.0, 100.0),
+            Vec2::new(50.0, 100.0),
+            Vec2::new(200.0, 100.0),
+        ];
+        let path = approximate(CurveType::PerfectCircle, &points);
+        assert!((path_length(&path) - 200.0).abs() < 0.01);
+        assert!(path.iter().all(|p| (p.y - 100.0).abs() < 1e-9));
+        assert_near(path[path.len() - 1], points[2]);
+    }
+
+    #[test]
+    fn red_anchor_splits_bezier() {
+        let points = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(100.0, 0.0),
+            Vec2::new(100.0, 0.0),
+            Vec2::new(100.0, 100.0),
+        ];
+        let path = approximate(CurveType::Bezier, &points);
+        assert_eq!(
+            path,
+            vec![
+                Vec2::new(0.0, 0.0),
+                Vec2::new(100.0, 0.0),
+                Vec2::new(100.0, 100.0)
+            ]
+        );
+        assert_near(point_at_distance(&path, 150.0), Vec2::new(100.0, 50.0));
+    }
+
+    #[test]
+    fn bezier_ends_on_last_control_point() {
+        let points = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(100.0, 0.0),
+            Vec2::new(100.0, 100.0),
+        ];
+        let path = approximate(CurveType::Bezier, &points);
+        assert_eq!(path[0], points[0]);
+        assert_eq!(path[path.len() - 1], points[2]);
+        // A quadratic curve sits between its chord (141.4) and its control polygon (200).
+        let length = path_length(&path);
+        assert!(length > 155.0 && length < 165.0, "{}", length);
+    }
+
+    #[test]
+    fn catmull_passes_through_control_points() {
+        let points = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(100.0, 50.0),
+            Vec2::new(200.0, 0.0),
+        ];
+        let path = approximate(CurveType::Catmull, &points);
+        assert_eq!(path[0], points[0]);
+        assert!(path.contains(&points[1]));
+        assert_near(path[path.len() - 1], points[2]);
+    }
+
+    #[test]
+    fn fit_to_length_cuts_and_extends() {
+        let path = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(100.0, 0.0),
+            Vec2::new(100.0, 100.0),
+        ];
+
+        let cut = fit_to_length(&path, 150.0);
+        assert_eq!(cut.len(), 3);
+        assert_near(cut[2], Vec2::new(100.0, 50.0));
+
+        let extended = fit_to_length(&path, 250.0);
+        assert_near(extended[extended.len() - 1], Vec2::new(100.0, 150.0));
+        assert!((path_length(&extended) - 250.0).abs() < 1e-9);
+    }
+}
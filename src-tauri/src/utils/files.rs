@@ -0,0 +1,76 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+
+pub fn app_data_path(app: &tauri::AppHandle, name: &str) -> Result<PathBuf, String> {
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data directory: {}", e))?;
+    Ok(data_dir.join(name))
+}
+
+// Characters Windows doesn't allow in file and folder names.
+pub fn sanitize_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if matches!(c, '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*') || c.is_control() {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect::<String>()
+        .trim_end_matches(['.', ' '])
+        .trim()
+        .to_string()
+}
+
+// osu!stable keeps its databases in the osu! folder, one level above Songs.
+pub fn osu_data_path(songs_folder: &Path, name: &str) -> Result<PathBuf, String> {
+    songs_folder
+        .parent()
+        .map(|osu_dir| osu_dir.join(name))
+        .ok_or_else(|| format!("Invalid Songs folder: {}", songs_folder.display()))
+}
+
+// Every file below `dir`, sorted so callers get a stable order.
+pub fn list_files(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    fn visit(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), String> {
+        let entries = fs::read_dir(dir).map_err(|e| format!("Failed to read directory: {}", e))?;
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if path.is_dir() {
+                visit(&path, files)?;
+            } else {
+                files.push(path);
+            }
+        }
+        Ok(())
+    }
+
+    let mut files = Vec::new();
+    visit(dir, &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> Result<(), String> {
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| format!("Invalid file path: {}", path.display()))?;
+    let temp_path = path.with_file_name(format!(".{}.tmp", file_name));
+
+    fs::write(&temp_path, contents.as_ref())
+        .map_err(|e| format!("Failed to write {}: {}", temp_path.display(), e))?;
+
+    fs::rename(&temp_path, path).map_err(|e| {
+        let _ = fs::remove_file(&temp_path);
+        format!("Failed to replace {}: {}", path.display(), e)
+    })
+}
+
+pub fn content_md5(data: impl AsRef<[u8]>) -> String {
+    format!("{:x}", md5::compute(data))
+}
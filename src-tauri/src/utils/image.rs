@@ -0,0 +1,56 @@
+use std::fs;
+use std::path::Path;
+
+fn be16(data: &[u8], at: usize) -> Option<u32> {
+    Some(u16::from_be_bytes(data.get(at..at + 2)?.try_into().ok()?) as u32)
+}
+
+fn be32(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn le32(data: &[u8], at: usize) -> Option<i32> {
+    Some(i32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+// JPEG stores the size in the first start-of-frame segment, which can come after
+// EXIF data and thumbnails, so segments are walked until one is found.
+fn jpeg_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    let mut at = 2;
+    while at + 4 <= data.len() {
+        if data[at] != 0xff {
+            return None;
+        }
+        let marker = data[at + 1];
+        match marker {
+            0xff => at += 1,
+            0x01 | 0xd0..=0xd8 => at += 2,
+            0xc0..=0xcf if !matches!(marker, 0xc4 | 0xc8 | 0xcc) => {
+                return Some((be16(data, at + 7)?, be16(data, at + 5)?));
+            }
+            _ => at += 2 + be16(data, at + 2)? as usize,
+        }
+    }
+    None
+}
+
+pub fn parse_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return Some((be32(data, 16)?, be32(data, 20)?));
+    }
+    if data.starts_with(&[0xff, 0xd8]) {
+        return jpeg_dimensions(data);
+    }
+    if data.starts_with(b"BM") {
+        return Some((
+            le32(data, 18)?.unsigned_abs(),
+            le32(data, 22)?.unsigned_abs(),
+        ));
+    }
+    None
+}
+
+// Width and height of a PNG, JPEG or BMP file without decoding it.
+pub fn image_dimensions(path: &Path) -> Option<(u32, u32)> {
+    parse_dimensions(&fs::read(path).ok()?)
+}
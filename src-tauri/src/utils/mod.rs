@@ -1 +1,9 @@
+pub mod backup;
+pub mod binary;
+pub mod curve;
+pub mod diff;
+pub mod files;
+pub mod image;
 pub mod parser;
+pub mod plan;
+pub mod references;
@@ -1,12 +1,4 @@
-pub fn scrape(data: &str, start: &str, end: &str) -> String {
-    if let Some(start_idx) = data.find(start) {
-        let value_start = start_idx + start.len();
-        if let Some(end_idx) = data[value_start..].find(end) {
-            return data[value_start..value_start + end_idx].trim().to_string();
-        }
-    }
-    String::new()
-}
+use std::collections::HashMap;
 
 pub fn quote(s: &str) -> String {
     if s.chars()
@@ -17,3 +9,243 @@ pub fn quote(s: &str) -> String {
         s.to_string()
     }
 }
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawLine {
+    pub text: String,
+    pub eol: String,
+}
+
+impl RawLine {
+    pub fn new(text: impl Into<String>, eol: &str) -> Self {
+        RawLine {
+            text: text.into(),
+            eol: eol.to_string(),
+        }
+    }
+}
+
+pub fn split_lines(data: &str) -> Vec<RawLine> {
+    data.split_inclusive('\n')
+        .map(|line| {
+            if let Some(text) = line.strip_suffix("\r\n") {
+                RawLine::new(text, "\r\n")
+            } else if let Some(text) = line.strip_suffix('\n') {
+                RawLine::new(text, "\n")
+            } else {
+                RawLine::new(line, "")
+            }
+        })
+        .collect()
+}
+
+pub fn join_lines(lines: &[RawLine], eol: &str) -> String {
+    let mut out = String::new();
+    for (i, line) in lines.iter().enumerate() {
+        out.push_str(&line.text);
+        if line.eol.is_empty() && i + 1 < lines.len() {
+            out.push_str(eol);
+        } else {
+            out.push_str(&line.eol);
+        }
+    }
+    out
+}
+
+pub fn detect_eol(data: &str) -> &'static str {
+    if data.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    }
+}
+
+pub fn is_skippable(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.is_empty() || trimmed.starts_with("//")
+}
+
+pub fn section_name(line: &str) -> Option<&str> {
+    let trimmed = line.trim();
+    if trimmed.starts_with('[') && trimmed.ends_with(']') && trimmed.len() > 2 {
+        Some(&trimmed[1..trimmed.len() - 1])
+    } else {
+        None
+    }
+}
+
+// Returns (key, separator, value) so the separator can be written back untouched.
+pub fn split_key_value(line: &str) -> Option<(&str, &str, &str)> {
+    if is_skippable(line) {
+        return None;
+    }
+
+    let colon = line.find(':')?;
+    let key = line[..colon].trim();
+    if key.is_empty() {
+        return None;
+    }
+
+    let key_start = line.find(key)?;
+    let key_end = key_start + key.len();
+    let rest = &line[colon + 1..];
+    let value_offset = rest.len() - rest.trim_start().len();
+    let sep = &line[key_end..colon + 1 + value_offset];
+    let value = rest.trim();
+
+    Some((key, sep, value))
+}
+
+pub fn parse_pairs(lines: &[RawLine]) -> Vec<(String, String)> {
+    lines
+        .iter()
+        .filter_map(|line| split_key_value(&line.text))
+        .map(|(key, _, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+fn insert_before_trailing_blanks(lines: &mut Vec<RawLine>, extra: Vec<RawLine>) {
+    let mut at = lines.len();
+    while at > 0 && lines[at - 1].text.trim().is_empty() {
+        at -= 1;
+    }
+    lines.splice(at..at, extra);
+}
+
+// Rewrites only the key/value lines whose value changed since `snapshot` was parsed,
+// drops keys that disappeared and appends new ones at the end of the section.
+pub fn merge_pairs(
+    lines: &[RawLine],
+    current: &[(String, String)],
+    snapshot: &[(String, String)],
+    eol: &str,
+) -> Vec<RawLine> {
+    let current_map: HashMap<&str, &str> = current
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .collect();
+    let snapshot_map: HashMap<&str, &str> = snapshot
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .collect();
+
+    let mut output = Vec::new();
+    let mut seen = Vec::new();
+
+    for line in lines {
+        let Some((key, sep, _)) = split_key_value(&line.text) else {
+            output.push(line.clone());
+            continue;
+        };
+
+        match current_map.get(key) {
+            Some(value) if !seen.contains(&key) => {
+                seen.push(key);
+                if snapshot_map.get(key) == Some(value) {
+                    output.push(line.clone());
+                } else {
+                    output.push(RawLine::new(format!("{}{}{}", key, sep, value), &line.eol));
+                }
+            }
+            Some(_) => output.push(line.clone()),
+            None if snapshot_map.contains_key(key) => {}
+            None => output.push(line.clone()),
+        }
+    }
+
+    let appended: Vec<RawLine> = current
+        .iter()
+        .filter(|(k, v)| {
+            !seen.contains(&k.as_str()) && snapshot_map.get(k.as_str()) != Some(&v.as_str())
+        })
+        .map(|(k, v)| RawLine::new(format!("{}:{}", k, v), eol))
+        .collect();
+
+    insert_before_trailing_blanks(&mut output, appended);
+    output
+}
+
+fn take(counts: &mut HashMap<String, usize>, key: &str) {
+    if let Some(count) = counts.get_mut(key) {
+        *count = count.saturating_sub(1);
+    }
+}
+
+fn remaining(counts: &HashMap<String, usize>, key: &str) -> bool {
+    counts.get(key).copied().unwrap_or(0) > 0
+}
+
+// Lines in list sections are matched against the current items so that untouched
+// lines keep their original formatting, inserted items are placed where they fit
+// and removed items are dropped.
+pub fn merge_items(
+    lines: &[RawLine],
+    current: &[String],
+    normalize: impl Fn(&str) -> Option<String>,
+    eol: &str,
+) -> Vec<RawLine> {
+    let snapshots: Vec<Option<String>> = lines
+        .iter()
+        .map(|line| {
+            if is_skippable(&line.text) {
+                None
+            } else {
+                normalize(&line.text)
+            }
+        })
+        .collect();
+
+    let mut current_left: HashMap<String, usize> = HashMap::new();
+    for item in current {
+        *current_left.entry(item.clone()).or_default() += 1;
+    }
+    let mut snapshot_left: HashMap<String, usize> = HashMap::new();
+    for snap in snapshots.iter().flatten() {
+        *snapshot_left.entry(snap.clone()).or_default() += 1;
+    }
+
+    let mut output = Vec::new();
+    let mut index = 0;
+
+    for (line, snap) in lines.iter().zip(&snapshots) {
+        let Some(snap) = snap else {
+            output.push(line.clone());
+            continue;
+        };
+        take(&mut snapshot_left, snap);
+
+        while index < current.len() {
+            let item = &current[index];
+
+            if item == snap {
+                output.push(line.clone());
+                take(&mut current_left, item);
+                index += 1;
+                break;
+            }
+
+            let snap_kept = remaining(&current_left, snap);
+            let item_existed = remaining(&snapshot_left, item);
+
+            if !snap_kept && item_existed {
+                break;
+            }
+
+            output.push(RawLine::new(item.clone(), &line.eol));
+            take(&mut current_left, item);
+            index += 1;
+
+            if !snap_kept || item_existed {
+                break;
+            }
+        }
+    }
+
+    let appended: Vec<RawLine> = current[index..]
+        .iter()
+        .map(|item| RawLine::new(item.clone(), eol))
+        .collect();
+
+    insert_before_trailing_blanks(&mut output, appended);
+    output
+}
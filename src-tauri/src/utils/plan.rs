@@ -0,0 +1,400 @@
+use crate::utils::backup::{BackupStore, Snapshot};
+use crate::utils::diff::unified_diff;
+use crate::utils::files::{content_md5, write_atomic};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FileAction {
+    CreateDir,
+    Copy,
+    Create,
+    Modify,
+    Rename,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct FileChange {
+    pub action: FileAction,
+    pub path: String,
+    pub source: Option<String>,
+    pub diff: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    NotReferenced,
+    SkinFile,
+}
+
+// A source file the command deliberately did not carry over.
+#[derive(Serialize, Debug, Clone)]
+pub struct SkippedFile {
+    pub path: String,
+    pub reason: SkipReason,
+}
+
+#[derive(Debug, Clone)]
+enum Operation {
+    CreateDir(PathBuf),
+    Copy(PathBuf, PathBuf),
+    Write(PathBuf, String),
+    Rename(PathBuf, PathBuf),
+}
+
+#[derive(Debug, Clone)]
+struct BackupTarget {
+    store: BackupStore,
+    set_folder: PathBuf,
+    operation: String,
+}
+
+// Every file-writing command records its work here first, so the same code path
+// can either return a preview (dry run) or actually touch the Songs folder.
+#[derive(Debug, Clone, Default)]
+pub struct ChangePlan {
+    operations: Vec<Operation>,
+    changes: Vec<FileChange>,
+    skipped: Vec<SkippedFile>,
+    backup: Option<BackupTarget>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct PlanResult {
+    pub dry_run: bool,
+    pub message: String,
+    pub changes: Vec<FileChange>,
+    pub skipped: Vec<SkippedFile>,
+    pub backup_id: Option<String>,
+}
+
+impl ChangePlan {
+    pub fn new() -> Self {
+        ChangePlan::default()
+    }
+
+    pub fn with_backup(mut self, store: BackupStore, set_folder: &Path, operation: &str) -> Self {
+        self.backup = Some(BackupTarget {
+            store,
+            set_folder: set_folder.to_path_buf(),
+            operation: operation.to_string(),
+        });
+        self
+    }
+
+    pub fn create_dir(&mut self, path: &Path) {
+        self.operations
+            .push(Operation::CreateDir(path.to_path_buf()));
+        self.changes.push(FileChange {
+            action: FileAction::CreateDir,
+            path: path.display().to_string(),
+            source: None,
+            diff: None,
+        });
+    }
+
+    pub fn copy(&mut self, from: &Path, to: &Path) {
+        self.operations
+            .push(Operation::Copy(from.to_path_buf(), to.to_path_buf()));
+        self.changes.push(FileChange {
+            action: FileAction::Copy,
+            path: to.display().to_string(),
+            source: Some(from.display().to_string()),
+            diff: None,
+        });
+    }
+
+    // Runs after any write planned for `from`, so a file can be rewritten and renamed.
+    pub fn rename(&mut self, from: &Path, to: &Path) {
+        self.operations
+            .push(Operation::Rename(from.to_path_buf(), to.to_path_buf()));
+        self.changes.push(FileChange {
+            action: FileAction::Rename,
+            path: to.display().to_string(),
+            source: Some(from.display().to_string()),
+            diff: None,
+        });
+    }
+
+    pub fn skip(&mut self, path: &Path, reason: SkipReason) {
+        self.skipped.push(SkippedFile {
+            path: path.display().to_string(),
+            reason,
+        });
+    }
+
+    pub fn skipped(&self) -> &[SkippedFile] {
+        &self.skipped
+    }
+
+    pub fn write(&mut self, path: &Path, contents: String) -> Result<(), String> {
+        let existing = if path.exists() {
+            Some(
+                fs::read_to_string(path)
+                    .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?,
+            )
+        } else {
+            None
+        };
+
+        if existing.as_deref() == Some(contents.as_str()) {
+            return Ok(());
+        }
+
+        let name = path.display().to_string();
+        let (action, diff) = match &existing {
+            Some(old) => (
+                FileAction::Modify,
+                unified_diff(
+                    old,
+                    &contents,
+                    &format!("a/{}", name),
+                    &format!("b/{}", name),
+                ),
+            ),
+            None => (
+                FileAction::Create,
+                unified_diff("", &contents, "/dev/null", &format!("b/{}", name)),
+            ),
+        };
+
+        self.changes.push(FileChange {
+            action,
+            path: name,
+            source: None,
+            diff: Some(diff),
+        });
+        self.operations
+            .push(Operation::Write(path.to_path_buf(), contents));
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    pub fn count(&self, action: FileAction) -> usize {
+        self.changes.iter().filter(|c| c.action == action).count()
+    }
+
+    fn affected_files(&self) -> Vec<PathBuf> {
+        self.operations
+            .iter()
+            .flat_map(|operation| match operation {
+                Operation::CreateDir(_) => vec![],
+                Operation::Copy(_, to) => vec![to.clone()],
+                Operation::Write(path, _) => vec![path.clone()],
+                Operation::Rename(from, to) => vec![from.clone(), to.clone()],
+            })
+            .collect()
+    }
+
+    // Current and planned MD5 of every .osu file the plan writes, as used by collection.db.
+    pub fn osu_hashes(&self) -> Vec<(Option<String>, String)> {
+        self.operations
+            .iter()
+            .filter_map(|operation| match operation {
+                Operation::Write(path, contents)
+                    if path.extension().is_some_and(|ext| ext == "osu") =>
+                {
+                    Some((fs::read(path).ok().map(content_md5), content_md5(contents)))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn execute(&self) -> Result<Option<Snapshot>, String> {
+        let snapshot = match &self.backup {
+            Some(target) if !self.is_empty() => Some(target.store.snapshot(
+                &target.set_folder,
+                &target.operation,
+                &self.affected_files(),
+            )?),
+            _ => None,
+        };
+
+        for operation in &self.operations {
+            match operation {
+                Operation::CreateDir(path) => fs::create_dir_all(path)
+                    .map_err(|e| format!("Failed to create folder: {}", e))?,
+                Operation::Copy(from, to) => {
+                    fs::copy(from, to).map_err(|e| format!("Failed to copy file: {}", e))?;
+                }
+                Operation::Write(path, contents) => write_atomic(path, contents)?,
+                Operation::Rename(from, to) => {
+                    fs::rename(from, to).map_err(|e| format!("Failed to rename file: {}", e))?;
+                }
+            }
+        }
+        Ok(snapshot)
+    }
+
+    pub fn finish(self, dry_run: bool, message: String) -> Result<PlanResult, String> {
+        let snapshot = if dry_run { None } else { self.execute()? };
+
+        Ok(PlanResult {
+            dry_run,
+            message,
+            changes: self.changes,
+            skipped: self.skipped,
+            backup_id: snapshot.map(|s| s.id),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set_folder() -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let folder = dir.path().join("set");
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join("map.osu"), "a\nb\nc\n").unwrap();
+        fs::write(folder.join("bg.jpg"), "image").unwrap();
+        (dir, folder)
+    }
+
+    fn listing(folder: &Path) -> Vec<(String, String)> {
+        let mut files: Vec<(String, String)> = fs::read_dir(folder)
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .map(|path| {
+                let name = path.file_name().unwrap().to_string_lossy().into_owned();
+                let contents = fs::read_to_string(&path).unwrap_or_default();
+                (name, contents)
+            })
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn dry_run_leaves_disk_untouched() {
+        let (dir, folder) = set_folder();
+        let before = listing(&folder);
+        let store = BackupStore::new(dir.path().join("backups"));
+
+        let mut plan = ChangePlan::new().with_backup(store.clone(), &folder, "edit");
+        plan.write(&folder.join("map.osu"), "a\nB\nc\n".to_string())
+            .unwrap();
+        plan.write(&folder.join("new.osu"), "new\n".to_string())
+            .unwrap();
+        plan.copy(&folder.join("bg.jpg"), &folder.join("bg2.jpg"));
+        plan.create_dir(&folder.join("sb"));
+        plan.rename(&folder.join("map.osu"), &folder.join("renamed.osu"));
+
+        let result = plan.finish(true, "Done".to_string()).unwrap();
+        assert!(result.dry_run);
+        assert_eq!(result.changes.len(), 5);
+        assert_eq!(result.backup_id, None);
+        assert_eq!(listing(&folder), before);
+        assert!(store.list(&folder).unwrap().is_empty());
+    }
+
+    #[test]
+    fn diffs_show_the_changed_lines() {
+        let (_dir, folder) = set_folder();
+        let path = folder.join("map.osu");
+        let name = path.display().to_string();
+
+        let mut plan = ChangePlan::new();
+        plan.write(&path, "a\nB\nc\n".to_string()).unwrap();
+        plan.write(&folder.join("new.osu"), "new\n".to_string())
+            .unwrap();
+
+        let modify = &plan.changes[0];
+        assert_eq!(modify.action, FileAction::Modify);
+        assert_eq!(
+            modify.diff.as_deref(),
+            Some(
+                format!(
+                    "--- a/{0}\n+++ b/{0}\n@@ -1,3 +1,3 @@\n a\n-b\n+B\n c\n",
+                    name
+                )
+                .as_str()
+            )
+        );
+
+        let create = &plan.changes[1];
+        assert_eq!(create.action, FileAction::Create);
+        assert!(create
+            .diff
+            .as_deref()
+            .unwrap()
+            .starts_with("--- /dev/null\n"));
+        assert!(create.diff.as_deref().unwrap().ends_with("+new\n"));
+        assert_eq!(plan.count(FileAction::Modify), 1);
+        assert_eq!(plan.count(FileAction::Create), 1);
+    }
+
+    #[test]
+    fn unchanged_writes_are_dropped() {
+        let (_dir, folder) = set_folder();
+        let mut plan = ChangePlan::new();
+        plan.write(&folder.join("map.osu"), "a\nb\nc\n".to_string())
+            .unwrap();
+        assert!(plan.is_empty());
+        assert!(plan.changes.is_empty());
+    }
+
+    #[test]
+    fn write_then_rename_applies_in_order() {
+        let (dir, folder) = set_folder();
+        let store = BackupStore::new(dir.path().join("backups"));
+        let old = folder.join("map.osu");
+        let new = folder.join("renamed.osu");
+
+        let mut plan = ChangePlan::new().with_backup(store.clone(), &folder, "edit");
+        plan.write(&old, "edited\n".to_string()).unwrap();
+        plan.rename(&old, &new);
+        let result = plan.finish(false, "Done".to_string()).unwrap();
+
+        assert!(!old.exists());
+        assert_eq!(fs::read_to_string(&new).unwrap(), "edited\n");
+
+        // The backup covers both names, so restoring undoes the edit and the rename.
+        let id = result.backup_id.unwrap();
+        store.restore(&folder, &id).unwrap();
+        assert_eq!(fs::read_to_string(&old).unwrap(), "a\nb\nc\n");
+        assert!(!new.exists());
+    }
+
+    #[test]
+    fn operations_run_in_the_order_planned() {
+        let (_dir, folder) = set_folder();
+        let sb = folder.join("sb");
+        let mut plan = ChangePlan::new();
+        plan.create_dir(&sb);
+        plan.copy(&folder.join("bg.jpg"), &sb.join("bg.jpg"));
+        plan.write(&sb.join("bg.jpg"), "replaced".to_string())
+            .unwrap();
+        plan.execute().unwrap();
+
+        assert_eq!(fs::read_to_string(sb.join("bg.jpg")).unwrap(), "replaced");
+        assert_eq!(fs::read_to_string(folder.join("bg.jpg")).unwrap(), "image");
+    }
+
+    #[test]
+    fn hashes_of_written_osu_files() {
+        let (_dir, folder) = set_folder();
+        let mut plan = ChangePlan::new();
+        plan.write(&folder.join("map.osu"), "edited\n".to_string())
+            .unwrap();
+        plan.write(&folder.join("new.osu"), "new\n".to_string())
+            .unwrap();
+        plan.write(&folder.join("storyboard.osb"), "sb\n".to_string())
+            .unwrap();
+        assert_eq!(
+            plan.osu_hashes(),
+            [
+                (Some(content_md5("a\nb\nc\n")), content_md5("edited\n")),
+                (None, content_md5("new\n")),
+            ]
+        );
+    }
+}
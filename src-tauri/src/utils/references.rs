@@ -0,0 +1,333 @@
+use crate::difficulty::slider::{SliderEventKind, SliderTiming};
+use crate::models::beatmap::{Beatmap, Event};
+use crate::models::hitobject::{
+    HitObject, HitObjectKind, HITSOUND_CLAP, HITSOUND_FINISH, HITSOUND_WHISTLE,
+};
+use crate::models::storyboard::{Element, Storyboard};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+pub const SAMPLE_EXTENSIONS: [&str; 3] = ["wav", "ogg", "mp3"];
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ReferenceKind {
+    Beatmap,
+    Audio,
+    Background,
+    Video,
+    Storyboard,
+    StoryboardSample,
+    HitSound,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct FileReference {
+    pub path: String,
+    pub kind: ReferenceKind,
+    pub sources: Vec<String>,
+}
+
+// Files a beatmap set actually uses, keyed by normalized relative path. Hitsound
+// samples may have any of the supported extensions, so they're keyed without one.
+#[derive(Debug, Clone, Default)]
+pub struct SetReferences {
+    files: HashMap<String, FileReference>,
+    hitsounds: HashMap<String, FileReference>,
+    source: String,
+}
+
+pub fn clean_path(path: &str) -> String {
+    path.trim()
+        .trim_matches('"')
+        .replace('\\', "/")
+        .trim_start_matches("./")
+        .to_string()
+}
+
+pub fn normalize_path(path: &str) -> String {
+    clean_path(path).to_lowercase()
+}
+
+fn add_reference(
+    references: &mut HashMap<String, FileReference>,
+    key: String,
+    path: String,
+    kind: ReferenceKind,
+    source: &str,
+) {
+    let reference = references.entry(key).or_insert_with(|| FileReference {
+        path,
+        kind,
+        sources: Vec::new(),
+    });
+    if !reference.sources.iter().any(|s| s == source) {
+        reference.sources.push(source.to_string());
+    }
+}
+
+fn sample_set_name(set: u8) -> Option<&'static str> {
+    match set {
+        1 => Some("normal"),
+        2 => Some("soft"),
+        3 => Some("drum"),
+        _ => None,
+    }
+}
+
+fn default_sample_set(beatmap: &Beatmap) -> u8 {
+    match beatmap.general.sample_set.to_lowercase().as_str() {
+        "soft" => 2,
+        "drum" => 3,
+        _ => 1,
+    }
+}
+
+impl SetReferences {
+    pub fn scan(folder: &Path) -> Result<Self, String> {
+        let mut references = SetReferences::default();
+        let entries =
+            fs::read_dir(folder).map_err(|e| format!("Failed to read directory: {}", e))?;
+
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            let extension = path
+                .extension()
+                .and_then(|e| e.to_str())
+                .map(|e| e.to_lowercase());
+
+            match extension.as_deref() {
+                Some("osu") => {
+                    let content = fs::read_to_string(&path)
+                        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+                    references.add_osu(name, &content)?;
+                }
+                Some("osb") => {
+                    let content = fs::read_to_string(&path)
+                        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+                    references.add_osb(name, &content);
+                }
+                _ => {}
+            }
+        }
+
+        Ok(references)
+    }
+
+    // `name` is the file's path relative to the set folder, whether or not it exists yet.
+    pub fn add_osu(&mut self, name: &str, content: &str) -> Result<(), String> {
+        self.source = name.to_string();
+        self.add(name, ReferenceKind::Beatmap);
+        let beatmap = Beatmap::parse(content)?;
+        self.add_beatmap(&beatmap);
+        self.add_storyboard(content);
+        Ok(())
+    }
+
+    pub fn add_osb(&mut self, name: &str, content: &str) {
+        self.source = name.to_string();
+        self.add(name, ReferenceKind::Beatmap);
+        self.add_storyboard(content);
+    }
+
+    fn add(&mut self, path: &str, kind: ReferenceKind) {
+        let path = clean_path(path);
+        if !path.is_empty() {
+            add_reference(
+                &mut self.files,
+                path.to_lowercase(),
+                path,
+                kind,
+                &self.source,
+            );
+        }
+    }
+
+    // Index 0 means the skin's samples are used; index 1 omits the number.
+    fn add_hitsound(&mut self, set: u8, name: &str, index: i32) {
+        let Some(set) = sample_set_name(set) else {
+            return;
+        };
+        if index <= 0 {
+            return;
+        }
+        let suffix = if index == 1 {
+            String::new()
+        } else {
+            index.to_string()
+        };
+        let stem = format!("{}-{}{}", set, name, suffix);
+        add_reference(
+            &mut self.hitsounds,
+            stem.clone(),
+            stem,
+            ReferenceKind::HitSound,
+            &self.source,
+        );
+    }
+
+    fn add_beatmap(&mut self, beatmap: &Beatmap) {
+        self.add(&beatmap.general.audio_filename, ReferenceKind::Audio);
+
+        for event in &beatmap.events {
+            match event {
+                Event::Background { filename, .. } => self.add(filename, ReferenceKind::Background),
+                Event::Video { filename, .. } => self.add(filename, ReferenceKind::Video),
+                _ => {}
+            }
+        }
+
+        let default_set = default_sample_set(beatmap);
+        for object in &beatmap.hit_objects {
+            self.add_object_hitsounds(beatmap, object, default_set);
+        }
+    }
+
+    fn add_object_hitsounds(&mut self, beatmap: &Beatmap, object: &HitObject, default_set: u8) {
+        let sample = object.hit_sample.clone().unwrap_or_default();
+        if !sample.filename.is_empty() {
+            self.add(&sample.filename, ReferenceKind::HitSound);
+        }
+
+        let resolve = |time: f64, normal_set: u8, addition_set: u8| {
+            let point = beatmap.timing_points.point_at(time);
+            let timing_set = point
+                .map(|p| p.sample_set)
+                .filter(|set| *set != 0)
+                .unwrap_or(default_set);
+            let normal = if normal_set != 0 {
+                normal_set
+            } else {
+                timing_set
+            };
+            let addition = if addition_set != 0 {
+                addition_set
+            } else {
+                normal
+            };
+            let index = if sample.index != 0 {
+                sample.index
+            } else {
+                point.map(|p| p.sample_index).unwrap_or(0)
+            };
+            (normal, addition, index)
+        };
+
+        let add_edge = |this: &mut Self, time: f64, sound: u8, normal_set: u8, addition_set: u8| {
+            let (normal, addition, index) = resolve(time, normal_set, addition_set);
+            this.add_hitsound(normal, "hitnormal", index);
+            for (flag, name) in [
+                (HITSOUND_WHISTLE, "hitwhistle"),
+                (HITSOUND_FINISH, "hitfinish"),
+                (HITSOUND_CLAP, "hitclap"),
+            ] {
+                if sound & flag != 0 {
+                    this.add_hitsound(addition, name, index);
+                }
+            }
+        };
+
+        match &object.kind {
+            HitObjectKind::Slider(slider) => {
+                let span = beatmap.slider_duration(object) / slider.slides.max(1) as f64;
+                for edge in 0..=slider.slides as usize {
+                    let sound = slider
+                        .edge_sounds
+                        .get(edge)
+                        .copied()
+                        .unwrap_or(object.hit_sound);
+                    let set = slider.edge_sets.get(edge).copied().unwrap_or_default();
+                    add_edge(
+                        self,
+                        object.time + span * edge as f64,
+                        sound,
+                        set.normal_set,
+                        set.addition_set,
+                    );
+                }
+
+                let (normal, addition, index) =
+                    resolve(object.time, sample.normal_set, sample.addition_set);
+                self.add_hitsound(normal, "sliderslide", index);
+                let timing = SliderTiming::new(
+                    beatmap,
+                    &beatmap.difficulty,
+                    object.time,
+                    object.position,
+                    slider,
+                );
+                if timing
+                    .events(object.time)
+                    .iter()
+                    .any(|event| event.kind == SliderEventKind::Tick)
+                {
+                    self.add_hitsound(normal, "slidertick", index);
+                }
+                if object.hit_sound & HITSOUND_WHISTLE != 0 {
+                    self.add_hitsound(addition, "sliderwhistle", index);
+                }
+            }
+            HitObjectKind::Spinner { end_time } => add_edge(
+                self,
+                *end_time,
+                object.hit_sound,
+                sample.normal_set,
+                sample.addition_set,
+            ),
+            HitObjectKind::Circle | HitObjectKind::Hold { .. } => add_edge(
+                self,
+                object.time,
+                object.hit_sound,
+                sample.normal_set,
+                sample.addition_set,
+            ),
+        }
+    }
+
+    fn add_storyboard(&mut self, content: &str) {
+        for element in Storyboard::parse(content).elements {
+            let kind = match element {
+                Element::Sample { .. } => ReferenceKind::StoryboardSample,
+                Element::Video { .. } => ReferenceKind::Video,
+                _ => ReferenceKind::Storyboard,
+            };
+            for file in element.files() {
+                self.add(&file, kind);
+            }
+        }
+    }
+
+    pub fn kind_of(&self, relative_path: &str) -> Option<ReferenceKind> {
+        let path = normalize_path(relative_path);
+        if let Some(reference) = self.files.get(&path) {
+            return Some(reference.kind);
+        }
+
+        // Custom hitsounds are only looked up in the set's root folder.
+        let (stem, extension) = path.rsplit_once('.')?;
+        if stem.contains('/') || !SAMPLE_EXTENSIONS.contains(&extension) {
+            return None;
+        }
+        self.hitsounds.get(stem).map(|reference| reference.kind)
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = &FileReference> {
+        self.files.values()
+    }
+
+    // Samples picked by index rather than named by a hit object. Paths here have no
+    // extension; any of the supported sample formats satisfies them, and without one
+    // osu! plays the skin's sample instead.
+    pub fn hitsounds(&self) -> impl Iterator<Item = &FileReference> {
+        self.hitsounds.values()
+    }
+
+    pub fn is_referenced(&self, relative_path: &str) -> bool {
+        self.kind_of(relative_path).is_some()
+    }
+}
@@ -0,0 +1,106 @@
+import { useCallback, useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { FiRotateCcw, FiTrash2 } from "react-icons/fi";
+
+import { Button } from "./common/Button";
+import { Card } from "./common/Card";
+
+export interface Snapshot {
+    id: string;
+    operation: string;
+    created_at: number;
+    beatmap_folder: string;
+    folder_existed: boolean;
+    files: { path: string; existed: boolean }[];
+}
+
+interface BackupListProps {
+    beatmapFolder: string;
+    refreshKey?: unknown;
+    onRestored?: (message: string) => void;
+}
+
+export function BackupList({ beatmapFolder, refreshKey, onRestored }: BackupListProps) {
+    const [snapshots, setSnapshots] = useState<Snapshot[]>([]);
+    const [busy, setBusy] = useState<string | null>(null);
+
+    const load = useCallback(async () => {
+        try {
+            setSnapshots(await invoke<Snapshot[]>("list_backups", { beatmapFolder }));
+        } catch (err) {
+            console.error("Failed to list backups:", err);
+            setSnapshots([]);
+        }
+    }, [beatmapFolder]);
+
+    useEffect(() => {
+        load();
+    }, [load, refreshKey]);
+
+    const onRestore = async (backupId: string) => {
+        setBusy(backupId);
+        try {
+            const message = await invoke<string>("restore_backup", { beatmapFolder, backupId });
+            onRestored?.(message);
+        } catch (err) {
+            onRestored?.(`Error: ${err}`);
+        } finally {
+            setBusy(null);
+            load();
+        }
+    };
+
+    const onDelete = async (backupId: string) => {
+        setBusy(backupId);
+        try {
+            await invoke("delete_backup", { beatmapFolder, backupId });
+        } catch (err) {
+            console.error("Failed to delete backup:", err);
+        } finally {
+            setBusy(null);
+            load();
+        }
+    };
+
+    if (snapshots.length === 0) return null;
+
+    return (
+        <Card className="overflow-hidden">
+            <div className="p-3">
+                <h3 className="font-semibold text-sm">Backups</h3>
+            </div>
+            <div className="border-t border-[#2a2a2a]">
+                {snapshots.map((snapshot) => (
+                    <div
+                        key={snapshot.id}
+                        className="flex items-center gap-2 px-3 py-1.5 text-xs border-t border-[#2a2a2a] first:border-t-0"
+                    >
+                        <span className="w-20 flex-shrink-0 text-[#7b7b7b]">{snapshot.operation}</span>
+                        <span className="flex-1">{new Date(snapshot.created_at).toLocaleString()}</span>
+                        <span className="text-[#7b7b7b]">
+                            {snapshot.files.length} file{snapshot.files.length !== 1 ? "s" : ""}
+                        </span>
+                        <Button
+                            variant="ghost"
+                            size="sm"
+                            icon={<FiRotateCcw />}
+                            disabled={busy !== null}
+                            onClick={() => onRestore(snapshot.id)}
+                        >
+                            Restore
+                        </Button>
+                        <Button
+                            variant="ghost"
+                            size="sm"
+                            icon={<FiTrash2 />}
+                            disabled={busy !== null}
+                            onClick={() => onDelete(snapshot.id)}
+                        >
+                            Delete
+                        </Button>
+                    </div>
+                ))}
+            </div>
+        </Card>
+    );
+}
@@ -0,0 +1,111 @@
+import { useState } from "react";
+import { FiChevronDown, FiChevronUp } from "react-icons/fi";
+
+import { Card } from "./common/Card";
+
+export interface FileChange {
+    action: "create_dir" | "copy" | "create" | "modify" | "rename";
+    path: string;
+    source: string | null;
+    diff: string | null;
+}
+
+export interface SkippedFile {
+    path: string;
+    reason: "not_referenced" | "skin_file";
+}
+
+export interface PlanResult {
+    dry_run: boolean;
+    message: string;
+    changes: FileChange[];
+    skipped: SkippedFile[];
+    backup_id: string | null;
+}
+
+const SKIP_REASONS: Record<SkippedFile["reason"], string> = {
+    not_referenced: "not referenced",
+    skin_file: "skin file",
+};
+
+const ACTION_LABELS: Record<FileChange["action"], string> = {
+    create_dir: "mkdir",
+    copy: "copy",
+    create: "create",
+    modify: "modify",
+    rename: "rename",
+};
+
+function diffLineClass(line: string): string {
+    if (line.startsWith("+++") || line.startsWith("---")) return "text-[#7b7b7b]";
+    if (line.startsWith("+")) return "text-green-400";
+    if (line.startsWith("-")) return "text-red-400";
+    if (line.startsWith("@@")) return "text-[#2563eb]";
+    return "text-[#e0e0e0]";
+}
+
+function ChangeRow({ change }: { change: FileChange }) {
+    const [expanded, setExpanded] = useState(false);
+    const baseName = (path: string) => path.split(/[\\/]/).pop() ?? path;
+    const fileName =
+        change.action === "rename" && change.source
+            ? `${baseName(change.source)} → ${baseName(change.path)}`
+            : baseName(change.path);
+
+    return (
+        <div className="border-t border-[#2a2a2a] first:border-t-0">
+            <button
+                onClick={() => change.diff && setExpanded(!expanded)}
+                className="w-full flex items-center gap-2 px-3 py-1.5 text-left text-xs hover:bg-[#222] transition-colors"
+            >
+                <span className="w-14 flex-shrink-0 text-[#7b7b7b]">{ACTION_LABELS[change.action]}</span>
+                <span className="flex-1 truncate" title={change.path}>{fileName}</span>
+                {change.diff && (expanded
+                    ? <FiChevronUp className="text-[#7b7b7b]" />
+                    : <FiChevronDown className="text-[#7b7b7b]" />
+                )}
+            </button>
+
+            {expanded && change.diff && (
+                <pre className="px-3 pb-2 text-[11px] leading-4 overflow-x-auto max-h-80">
+                    {change.diff.split("\n").map((line, i) => (
+                        <div key={i} className={diffLineClass(line)}>{line.replace(/\r$/, "") || " "}</div>
+                    ))}
+                </pre>
+            )}
+        </div>
+    );
+}
+
+export function ChangePreview({ plan }: { plan: PlanResult }) {
+    return (
+        <Card className="overflow-hidden">
+            <div className="flex items-center justify-between p-3">
+                <h3 className="font-semibold text-sm">Dry Run</h3>
+                <span className="text-xs text-[#7b7b7b]">{plan.message}</span>
+            </div>
+            {plan.changes.length === 0 ? (
+                <div className="px-3 pb-3 text-xs text-[#7b7b7b]">No files would change</div>
+            ) : (
+                <div className="border-t border-[#2a2a2a]">
+                    {plan.changes.map((change, i) => (
+                        <ChangeRow key={`${change.action}-${change.path}-${i}`} change={change} />
+                    ))}
+                </div>
+            )}
+            {plan.skipped.length > 0 && (
+                <div className="border-t border-[#2a2a2a]">
+                    {plan.skipped.map((file) => (
+                        <div key={file.path} className="flex items-center gap-2 px-3 py-1.5 text-xs text-[#7b7b7b]">
+                            <span className="w-14 flex-shrink-0">skip</span>
+                            <span className="flex-1 truncate" title={file.path}>
+                                {file.path.split(/[\\/]/).pop() ?? file.path}
+                            </span>
+                            <span>{SKIP_REASONS[file.reason]}</span>
+                        </div>
+                    ))}
+                </div>
+            )}
+        </Card>
+    );
+}
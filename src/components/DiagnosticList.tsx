@@ -0,0 +1,36 @@
+export interface Diagnostic {
+    severity: "problem" | "warning" | "minor";
+    check: string;
+    message: string;
+    file: string;
+    time: number | null;
+    timestamp: string | null;
+}
+
+const SEVERITY_CLASSES: Record<Diagnostic["severity"], string> = {
+    problem: "text-red-400",
+    warning: "text-yellow-400",
+    minor: "text-[#7b7b7b]",
+};
+
+export function DiagnosticList({ diagnostics }: { diagnostics: Diagnostic[] }) {
+    if (diagnostics.length === 0) {
+        return <p className="text-xs text-[#7b7b7b]">No issues found.</p>;
+    }
+
+    return (
+        <div className="space-y-1 text-xs">
+            {diagnostics.map((diagnostic, i) => (
+                <div key={`${diagnostic.check}-${diagnostic.file}-${i}`} className="flex gap-2">
+                    <span className={`w-14 flex-shrink-0 ${SEVERITY_CLASSES[diagnostic.severity]}`}>
+                        {diagnostic.severity}
+                    </span>
+                    <span className="w-28 flex-shrink-0 text-[#7b7b7b] select-text">{diagnostic.timestamp ?? ""}</span>
+                    <span className="flex-1 text-[#bbbbbb]" title={diagnostic.file}>
+                        {diagnostic.message}
+                    </span>
+                </div>
+            ))}
+        </div>
+    );
+}
@@ -0,0 +1,195 @@
+import { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { FiStar } from "react-icons/fi";
+
+import { Button } from "./common/Button";
+import { Card } from "./common/Card";
+import { Select } from "./common/Select";
+import { Switch } from "./common/Switch";
+
+type DifficultyAttributes =
+    | {
+          mode: "osu";
+          star_rating: number;
+          aim: number;
+          speed: number;
+          flashlight: number;
+          approach_rate: number;
+          overall_difficulty: number;
+          max_combo: number;
+      }
+    | {
+          mode: "taiko";
+          star_rating: number;
+          rhythm: number;
+          colour: number;
+          stamina: number;
+          max_combo: number;
+          converted: boolean;
+      }
+    | {
+          mode: "catch";
+          star_rating: number;
+          approach_rate: number;
+          max_combo: number;
+          hyper_dash_count: number;
+          converted: boolean;
+      }
+    | {
+          mode: "mania";
+          star_rating: number;
+          key_count: number;
+          max_combo: number;
+          hold_note_count: number;
+      };
+
+interface DifficultyResult {
+    attributes: DifficultyAttributes;
+    pp: { accuracy: number; pp: number }[];
+}
+
+interface DifficultyPanelProps {
+    beatmapFolder: string;
+    osuFiles: string[];
+}
+
+// Bit flags as stored in osu! replays and scores.
+const MODS = [
+    { name: "EZ", bit: 1 << 1, excludes: 1 << 4 },
+    { name: "HD", bit: 1 << 3, excludes: 0 },
+    { name: "HR", bit: 1 << 4, excludes: 1 << 1 },
+    { name: "DT", bit: 1 << 6, excludes: 1 << 8 },
+    { name: "HT", bit: 1 << 8, excludes: 1 << 6 },
+    { name: "FL", bit: 1 << 10, excludes: 0 },
+];
+
+const MODES = [
+    { label: "Beatmap's mode", value: "" },
+    { label: "osu!standard", value: "0" },
+    { label: "osu!taiko", value: "1" },
+    { label: "osu!catch", value: "2" },
+    { label: "osu!mania", value: "3" },
+];
+
+function AttributeDetails({ attributes }: { attributes: DifficultyAttributes }) {
+    switch (attributes.mode) {
+        case "osu":
+            return (
+                <>
+                    <div className="text-[#7b7b7b]">
+                        Aim {attributes.aim.toFixed(2)} · Speed {attributes.speed.toFixed(2)}
+                        {attributes.flashlight > 0 && ` · Flashlight ${attributes.flashlight.toFixed(2)}`}
+                    </div>
+                    <div className="text-[#7b7b7b]">
+                        AR {attributes.approach_rate.toFixed(1)} · OD {attributes.overall_difficulty.toFixed(1)} ·{" "}
+                        {attributes.max_combo}x
+                    </div>
+                </>
+            );
+        case "taiko":
+            return (
+                <div className="text-[#7b7b7b]">
+                    Rhythm {attributes.rhythm.toFixed(2)} · Colour {attributes.colour.toFixed(2)} · Stamina{" "}
+                    {attributes.stamina.toFixed(2)} · {attributes.max_combo}x{attributes.converted && " · Converted"}
+                </div>
+            );
+        case "catch":
+            return (
+                <div className="text-[#7b7b7b]">
+                    AR {attributes.approach_rate.toFixed(1)} · {attributes.hyper_dash_count} hyperdashes ·{" "}
+                    {attributes.max_combo}x{attributes.converted && " · Converted"}
+                </div>
+            );
+        case "mania":
+            return (
+                <div className="text-[#7b7b7b]">
+                    {attributes.key_count}K · {attributes.hold_note_count} holds · {attributes.max_combo}x
+                </div>
+            );
+    }
+}
+
+export function DifficultyPanel({ beatmapFolder, osuFiles }: DifficultyPanelProps) {
+    const [file, setFile] = useState(osuFiles[0] ?? "");
+    const [mods, setMods] = useState(0);
+    const [mode, setMode] = useState("");
+    const [result, setResult] = useState<DifficultyResult | null>(null);
+    const [loading, setLoading] = useState(false);
+    const [error, setError] = useState<string | null>(null);
+
+    const toggleMod = (bit: number, excludes: number, enabled: boolean) => {
+        setMods((current) => (enabled ? (current | bit) & ~excludes : current & ~bit));
+    };
+
+    const onCalculate = async () => {
+        const selected = file || osuFiles[0];
+        if (!selected) return;
+        setLoading(true);
+        setError(null);
+        try {
+            setResult(
+                await invoke<DifficultyResult>("calculate_difficulty", {
+                    filePath: `${beatmapFolder}\\${selected}`,
+                    mods,
+                    mode: mode === "" ? null : Number(mode),
+                })
+            );
+        } catch (err) {
+            setResult(null);
+            setError(`Error: ${err}`);
+        } finally {
+            setLoading(false);
+        }
+    };
+
+    const attributes = result?.attributes;
+
+    return (
+        <Card className="p-3 space-y-2">
+            <div className="flex items-center justify-between">
+                <h3 className="font-semibold text-sm">Difficulty</h3>
+                <Button size="sm" icon={<FiStar />} onClick={onCalculate} disabled={loading || osuFiles.length === 0}>
+                    {loading ? "Calculating..." : "Calculate"}
+                </Button>
+            </div>
+            <Select value={file} onChange={(e) => setFile(e.target.value)}>
+                {osuFiles.map((name) => (
+                    <option key={name} value={name}>
+                        {name}
+                    </option>
+                ))}
+            </Select>
+            <Select value={mode} onChange={(e) => setMode(e.target.value)}>
+                {MODES.map(({ label, value }) => (
+                    <option key={value} value={value}>
+                        {label}
+                    </option>
+                ))}
+            </Select>
+            <div className="flex flex-wrap gap-2">
+                {MODS.map(({ name, bit, excludes }) => (
+                    <Switch
+                        key={name}
+                        checked={(mods & bit) !== 0}
+                        onChange={(enabled) => toggleMod(bit, excludes, enabled)}
+                        label={name}
+                    />
+                ))}
+            </div>
+            {error && <p className="text-xs text-red-400">{error}</p>}
+            {attributes && result && (
+                <div className="text-xs space-y-1">
+                    <div className="text-sm font-semibold">{attributes.star_rating.toFixed(2)}★</div>
+                    <AttributeDetails attributes={attributes} />
+                    <div className="flex flex-wrap gap-3">
+                        {result.pp.map(({ accuracy, pp }) => (
+                            <span key={accuracy}>
+                                {(accuracy * 100).toFixed(0)}%: <span className="font-semibold">{pp.toFixed(0)}pp</span>
+                            </span>
+                        ))}
+                    </div>
+                </div>
+            )}
+        </Card>
+    );
+}
@@ -0,0 +1,86 @@
+import { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { save } from "@tauri-apps/plugin-dialog";
+import { FiPackage } from "react-icons/fi";
+
+import { Button } from "./common/Button";
+import { Card } from "./common/Card";
+import { Switch } from "./common/Switch";
+
+interface ExportResult {
+    path: string;
+    size: number;
+    file_count: number;
+    excluded: string[];
+}
+
+interface ExportPanelProps {
+    beatmapFolder: string;
+    folderName: string;
+}
+
+function formatSize(bytes: number): string {
+    if (bytes >= 1024 * 1024) return `${(bytes / (1024 * 1024)).toFixed(1)} MB`;
+    if (bytes >= 1024) return `${(bytes / 1024).toFixed(1)} KB`;
+    return `${bytes} B`;
+}
+
+export function ExportPanel({ beatmapFolder, folderName }: ExportPanelProps) {
+    const [excludeSkinFiles, setExcludeSkinFiles] = useState(false);
+    const [excludeUnusedFiles, setExcludeUnusedFiles] = useState(false);
+    const [excludeBackups, setExcludeBackups] = useState(true);
+    const [exporting, setExporting] = useState(false);
+    const [message, setMessage] = useState<string | null>(null);
+
+    const onExport = async () => {
+        const outputPath = await save({
+            title: "Export beatmap as .osz",
+            defaultPath: `${folderName}.osz`,
+            filters: [{ name: "osu! beatmap archive", extensions: ["osz"] }],
+        });
+        if (!outputPath) return;
+
+        setExporting(true);
+        setMessage(null);
+        try {
+            const result = await invoke<ExportResult>("export_osz", {
+                beatmapFolder,
+                outputPath,
+                options: {
+                    exclude_skin_files: excludeSkinFiles,
+                    exclude_unused_files: excludeUnusedFiles,
+                    exclude_backups: excludeBackups,
+                },
+            });
+            setMessage(
+                `Exported ${result.file_count} files (${formatSize(result.size)})` +
+                    (result.excluded.length > 0 ? `, ${result.excluded.length} excluded` : "")
+            );
+        } catch (err) {
+            setMessage(`Error: ${err}`);
+        } finally {
+            setExporting(false);
+        }
+    };
+
+    return (
+        <Card className="p-3 space-y-2">
+            <h3 className="font-semibold text-sm">Export .osz</h3>
+            <div className="flex flex-wrap gap-2">
+                <Switch checked={excludeSkinFiles} onChange={setExcludeSkinFiles} label="Exclude skin files" />
+                <Switch checked={excludeUnusedFiles} onChange={setExcludeUnusedFiles} label="Exclude unused files" />
+                <Switch checked={excludeBackups} onChange={setExcludeBackups} label="Exclude backups" />
+            </div>
+            <div className="flex items-center gap-3">
+                <Button size="sm" icon={<FiPackage />} onClick={onExport} disabled={exporting}>
+                    {exporting ? "Exporting..." : "Export"}
+                </Button>
+                {message && (
+                    <span className={`text-xs ${message.startsWith("Error") ? "text-red-400" : "text-[#7b7b7b]"}`}>
+                        {message}
+                    </span>
+                )}
+            </div>
+        </Card>
+    );
+}
@@ -0,0 +1,101 @@
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+
+import { Card } from "./common/Card";
+
+export interface LocalScore {
+    mode: number;
+    player_name: string;
+    count_300: number;
+    count_100: number;
+    count_50: number;
+    count_geki: number;
+    count_katu: number;
+    count_miss: number;
+    score: number;
+    max_combo: number;
+    perfect: boolean;
+    mods: number;
+    mod_names: string[];
+    accuracy: number;
+    timestamp: number;
+}
+
+interface DifficultyScores {
+    version: string;
+    scores: LocalScore[];
+}
+
+interface LocalScoresProps {
+    songsFolder: string;
+    folderName: string;
+}
+
+export function LocalScores({ songsFolder, folderName }: LocalScoresProps) {
+    const [difficulties, setDifficulties] = useState<DifficultyScores[]>([]);
+
+    useEffect(() => {
+        let cancelled = false;
+
+        (async () => {
+            try {
+                const set = await invoke<{ difficulties: { version: string; md5: string }[] } | null>(
+                    "get_beatmapset_details",
+                    { basePath: songsFolder, folderName }
+                );
+                if (!set) return;
+
+                const scores = await invoke<Record<string, LocalScore[]>>("get_local_scores", {
+                    basePath: songsFolder,
+                    beatmapMd5s: set.difficulties.map((d) => d.md5),
+                });
+
+                if (!cancelled) {
+                    setDifficulties(
+                        set.difficulties
+                            .map((d) => ({ version: d.version, scores: scores[d.md5] ?? [] }))
+                            .filter((d) => d.scores.length > 0)
+                    );
+                }
+            } catch (err) {
+                console.error("Failed to load local scores:", err);
+                if (!cancelled) setDifficulties([]);
+            }
+        })();
+
+        return () => {
+            cancelled = true;
+        };
+    }, [songsFolder, folderName]);
+
+    if (difficulties.length === 0) return null;
+
+    return (
+        <Card className="overflow-hidden">
+            <div className="p-3">
+                <h3 className="font-semibold text-sm">Local scores</h3>
+            </div>
+            {difficulties.map((difficulty) => (
+                <div key={difficulty.version} className="border-t border-[#2a2a2a]">
+                    <div className="px-3 py-1.5 text-xs font-medium">[{difficulty.version}]</div>
+                    {difficulty.scores.map((score, i) => (
+                        <div
+                            key={`${score.timestamp}-${i}`}
+                            className="flex items-center gap-2 px-3 py-1 text-xs text-[#bbbbbb]"
+                        >
+                            <span className="w-16 flex-shrink-0 text-right">{(score.accuracy * 100).toFixed(2)}%</span>
+                            <span className="w-14 flex-shrink-0">{score.max_combo}x</span>
+                            <span className="w-24 flex-shrink-0 text-[#7b7b7b]" title="300 / 100 / 50 / miss">
+                                {score.count_300}/{score.count_100}/{score.count_50}/{score.count_miss}
+                            </span>
+                            <span className="w-20 flex-shrink-0">{score.mod_names.join("") || "NM"}</span>
+                            <span className="flex-1 text-right text-[#7b7b7b]">
+                                {new Date(score.timestamp).toLocaleString()}
+                            </span>
+                        </div>
+                    ))}
+                </div>
+            ))}
+        </Card>
+    );
+}
@@ -0,0 +1,86 @@
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { FiCheckSquare } from "react-icons/fi";
+
+import { Button } from "./common/Button";
+import { Card } from "./common/Card";
+import { Switch } from "./common/Switch";
+import { Diagnostic, DiagnosticList } from "./DiagnosticList";
+
+interface CheckInfo {
+    id: string;
+    description: string;
+}
+
+const DISABLED_KEY = "disabledMapChecks";
+
+function loadDisabled(): string[] {
+    try {
+        return JSON.parse(localStorage.getItem(DISABLED_KEY) ?? "[]");
+    } catch {
+        return [];
+    }
+}
+
+export function MapChecks({ beatmapFolder }: { beatmapFolder: string }) {
+    const [checks, setChecks] = useState<CheckInfo[]>([]);
+    const [disabled, setDisabled] = useState<string[]>(loadDisabled);
+    const [diagnostics, setDiagnostics] = useState<Diagnostic[] | null>(null);
+    const [loading, setLoading] = useState(false);
+    const [error, setError] = useState<string | null>(null);
+
+    useEffect(() => {
+        invoke<CheckInfo[]>("list_map_checks").then(setChecks).catch(console.error);
+    }, []);
+
+    const toggleCheck = (id: string, enabled: boolean) => {
+        const next = enabled ? disabled.filter((d) => d !== id) : [...disabled, id];
+        setDisabled(next);
+        localStorage.setItem(DISABLED_KEY, JSON.stringify(next));
+    };
+
+    const onCheck = async () => {
+        setLoading(true);
+        setError(null);
+        try {
+            setDiagnostics(
+                await invoke<Diagnostic[]>("run_map_checks", { beatmapFolder, disabledChecks: disabled })
+            );
+        } catch (err) {
+            setError(`Error: ${err}`);
+        } finally {
+            setLoading(false);
+        }
+    };
+
+    return (
+        <Card className="p-3 space-y-2">
+            <div className="flex items-center justify-between">
+                <h3 className="font-semibold text-sm">Map checks</h3>
+                <Button size="sm" icon={<FiCheckSquare />} onClick={onCheck} disabled={loading}>
+                    {loading ? "Checking..." : "Check"}
+                </Button>
+            </div>
+            <div className="flex flex-wrap gap-2">
+                {checks.map(({ id, description }) => (
+                    <span key={id} title={description}>
+                        <Switch
+                            checked={!disabled.includes(id)}
+                            onChange={(enabled) => toggleCheck(id, enabled)}
+                            label={id.replace(/_/g, " ")}
+                        />
+                    </span>
+                ))}
+            </div>
+            {error && <p className="text-xs text-red-400">{error}</p>}
+            {diagnostics && diagnostics.length === 0 && <DiagnosticList diagnostics={diagnostics} />}
+            {diagnostics &&
+                [...new Set(diagnostics.map((d) => d.file))].sort().map((file) => (
+                    <div key={file} className="space-y-1">
+                        <h4 className="text-xs font-semibold text-[#bbbbbb]">{file}</h4>
+                        <DiagnosticList diagnostics={diagnostics.filter((d) => d.file === file)} />
+                    </div>
+                ))}
+        </Card>
+    );
+}
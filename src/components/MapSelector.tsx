@@ -1,8 +1,9 @@
 import { useState, useEffect, useRef } from "react";
-import { MdRefresh, MdFolder, MdSearch } from "react-icons/md";
+import { MdRefresh, MdFolder, MdSearch, MdUnarchive } from "react-icons/md";
 import { open } from "@tauri-apps/plugin-dialog";
 import { invoke } from "@tauri-apps/api/core";
 import { convertFileSrc } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { Button } from "./common/Button";
 
 interface Beatmapset {
@@ -80,8 +81,15 @@ export function MapSelector({ onSelect, selectedBeatmap, className = "" }: MapSe
     const [detectStatus, setDetectStatus] = useState<string>("");
     const [currentIndex, setCurrentIndex] = useState(0);
     const [hasMore, setHasMore] = useState(true);
+    const [collections, setCollections] = useState<{ name: string; beatmap_count: number }[]>([]);
+    const [collection, setCollection] = useState("");
+    const [importConflict, setImportConflict] = useState<"rename" | "merge">("rename");
 
     const searchTimerRef = useRef<number | null>(null);
+    const searchRef = useRef(search);
+    searchRef.current = search;
+    const collectionRef = useRef(collection);
+    collectionRef.current = collection;
 
     const autoDetectOsuFolder = async (): Promise<string | null> => {
         setDetectStatus("Detecting osu! installation...");
@@ -130,6 +138,7 @@ export function MapSelector({ onSelect, selectedBeatmap, className = "" }: MapSe
                 startIndex,
                 stepSize: STEP_SIZE,
                 searchQuery,
+                collection: collectionRef.current || null,
             });
 
             const [newBeatmaps, nextIndex, more] = result;
@@ -159,6 +168,70 @@ export function MapSelector({ onSelect, selectedBeatmap, className = "" }: MapSe
         await loadStep(songsFolder, searchQuery, 0, false);
     };
 
+    const loadCollections = async (folder: string) => {
+        try {
+            setCollections(await invoke<{ name: string; beatmap_count: number }[]>("list_collections", { basePath: folder }));
+        } catch (err) {
+            console.error("[collections] Failed to read collection.db:", err);
+            setCollections([]);
+        }
+    };
+
+    const handleCollectionChange = (value: string) => {
+        setCollection(value);
+        collectionRef.current = value;
+        reloadSearch(search);
+    };
+
+    const refreshIndex = async () => {
+        if (!songsFolder) return;
+
+        setIsScanning(true);
+        try {
+            const stats = await invoke<{ total: number; added: number; updated: number; removed: number }>(
+                "refresh_songs_index",
+                { basePath: songsFolder, full: false }
+            );
+            setDetectStatus(`${stats.total} sets indexed (+${stats.added} ~${stats.updated} -${stats.removed})`);
+        } catch (err) {
+            console.error("[refreshIndex] Error:", err);
+            setDetectStatus(`Error: ${err}`);
+        } finally {
+            setIsScanning(false);
+        }
+
+        await loadCollections(songsFolder);
+        await reloadSearch(search);
+    };
+
+    const importOsz = async () => {
+        if (!songsFolder) return;
+
+        const selected = await open({
+            multiple: false,
+            title: "Import .osz",
+            filters: [{ name: "osu! beatmap archive", extensions: ["osz"] }],
+        });
+        if (!selected || typeof selected !== "string") return;
+
+        setIsScanning(true);
+        try {
+            const result = await invoke<{ folder_name: string; file_count: number; merged: boolean }>("import_osz", {
+                basePath: songsFolder,
+                oszPath: selected,
+                onConflict: importConflict,
+            });
+            setDetectStatus(`${result.merged ? "Merged into" : "Imported"} ${result.folder_name} (${result.file_count} files)`);
+        } catch (err) {
+            console.error("[import] Error:", err);
+            setDetectStatus(`Error: ${err}`);
+        } finally {
+            setIsScanning(false);
+        }
+
+        await reloadSearch(search);
+    };
+
     const handleSearchChange = (value: string) => {
         setSearch(value);
 
@@ -199,6 +272,29 @@ export function MapSelector({ onSelect, selectedBeatmap, className = "" }: MapSe
         })();
     }, []);
 
+    useEffect(() => {
+        if (!songsFolder) return;
+
+        loadCollections(songsFolder);
+        invoke("watch_songs_folder", { basePath: songsFolder }).catch((err) =>
+            console.error("[watch] Failed to watch Songs folder:", err)
+        );
+
+        const unlisten = listen<{ added: string[]; updated: string[]; removed: string[] }>(
+            "songs-index-changed",
+            (event) => {
+                const { added, updated, removed } = event.payload;
+                setDetectStatus(`Library updated (+${added.length} ~${updated.length} -${removed.length})`);
+                loadStep(songsFolder, searchRef.current, 0, false);
+            }
+        );
+
+        return () => {
+            unlisten.then((fn) => fn());
+            invoke("unwatch_songs_folder").catch(() => {});
+        };
+    }, [songsFolder]);
+
     return (
         <aside
             className={`h-full w-64 shrink-0 bg-[#191919] text-[#eeeeee] border-r border-[#2a2a2a] flex flex-col ${className}`}
@@ -206,7 +302,7 @@ export function MapSelector({ onSelect, selectedBeatmap, className = "" }: MapSe
             <div className="px-3 py-3 border-b border-[#2a2a2a]">
                 <div className="space-y-2">
                     <div className="flex gap-2">
-                        <Button variant="secondary" size="sm" className="flex-1" onClick={() => reloadSearch(search)} disabled={isScanning} title="Reload beatmaps" icon={<MdRefresh className={`text-base ${isScanning ? "animate-spin" : ""}`} />}>
+                        <Button variant="secondary" size="sm" className="flex-1" onClick={refreshIndex} disabled={isScanning} title="Reload beatmaps" icon={<MdRefresh className={`text-base ${isScanning ? "animate-spin" : ""}`} />}>
                             Reload
                         </Button>
                         <Button
@@ -220,17 +316,47 @@ export function MapSelector({ onSelect, selectedBeatmap, className = "" }: MapSe
                         </Button>
                     </div>
 
+                    <div className="flex gap-2">
+                        <Button variant="secondary" size="sm" className="flex-1" onClick={importOsz} disabled={isScanning || !songsFolder} title="Import .osz" icon={<MdUnarchive className="text-base" />}>
+                            Import
+                        </Button>
+                        <select
+                            value={importConflict}
+                            onChange={(e) => setImportConflict(e.target.value as "rename" | "merge")}
+                            title="When the set already exists"
+                            className="h-8 px-2 rounded-lg bg-[#2a2a2a] border border-[#3a3a3a] text-xs focus:outline-none focus:border-[#4a4a4a] transition-colors"
+                        >
+                            <option value="rename">Rename</option>
+                            <option value="merge">Merge</option>
+                        </select>
+                    </div>
+
                     <div className="relative">
                         <MdSearch className="absolute left-3 top-1/2 -translate-y-1/2 text-[#7b7b7b] text-base" />
                         <input
                             type="text"
-                            placeholder="Search beatmaps..."
+                            placeholder="Search (artist= bpm>180 mode=mania)"
                             value={search}
                             onChange={(e) => handleSearchChange(e.target.value)}
                             className="w-full h-8 pl-9 pr-3 rounded-lg bg-[#2a2a2a] border border-[#3a3a3a] text-sm placeholder-[#7b7b7b] focus:outline-none focus:border-[#4a4a4a] transition-colors"
                         />
                     </div>
 
+                    {collections.length > 0 && (
+                        <select
+                            value={collection}
+                            onChange={(e) => handleCollectionChange(e.target.value)}
+                            className="w-full h-8 px-3 rounded-lg bg-[#2a2a2a] border border-[#3a3a3a] text-sm focus:outline-none focus:border-[#4a4a4a] transition-colors"
+                        >
+                            <option value="">All beatmaps</option>
+                            {collections.map((c) => (
+                                <option key={c.name} value={c.name}>
+                                    {c.name} ({c.beatmap_count})
+                                </option>
+                            ))}
+                        </select>
+                    )}
+
                     {detectStatus && (
                         <div className="text-xs text-[#7b7b7b] truncate" title={detectStatus}>
                             {detectStatus}
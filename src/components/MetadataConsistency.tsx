@@ -0,0 +1,156 @@
+import { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { FiTag } from "react-icons/fi";
+
+import { Button } from "./common/Button";
+import { Card } from "./common/Card";
+import { Input } from "./common/Input";
+import { Switch } from "./common/Switch";
+import { ChangePreview, PlanResult } from "./ChangePreview";
+
+interface BeatmapMetadata {
+    title: string;
+    title_unicode: string;
+    artist: string;
+    artist_unicode: string;
+    creator: string;
+    source: string;
+    tags: string;
+}
+
+interface MetadataReport {
+    canonical: BeatmapMetadata;
+    inconsistencies: { field: string; values: { value: string; files: string[] }[] }[];
+    renames: { from: string; to: string }[];
+}
+
+const FIELDS: { key: keyof BeatmapMetadata; label: string }[] = [
+    { key: "title", label: "Title" },
+    { key: "title_unicode", label: "Title (Unicode)" },
+    { key: "artist", label: "Artist" },
+    { key: "artist_unicode", label: "Artist (Unicode)" },
+    { key: "creator", label: "Creator" },
+    { key: "source", label: "Source" },
+    { key: "tags", label: "Tags" },
+];
+
+interface MetadataConsistencyProps {
+    beatmapFolder: string;
+    onChanged?: () => void;
+}
+
+export function MetadataConsistency({ beatmapFolder, onChanged }: MetadataConsistencyProps) {
+    const [report, setReport] = useState<MetadataReport | null>(null);
+    const [metadata, setMetadata] = useState<BeatmapMetadata | null>(null);
+    const [renameFiles, setRenameFiles] = useState(true);
+    const [preview, setPreview] = useState<PlanResult | null>(null);
+    const [message, setMessage] = useState<string | null>(null);
+    const [loading, setLoading] = useState(false);
+    const [error, setError] = useState<string | null>(null);
+
+    const onCheck = async () => {
+        setLoading(true);
+        setError(null);
+        setPreview(null);
+        setMessage(null);
+        try {
+            const next = await invoke<MetadataReport>("check_metadata_consistency", { beatmapFolder });
+            setReport(next);
+            setMetadata(next.canonical);
+        } catch (err) {
+            setError(`Error: ${err}`);
+        } finally {
+            setLoading(false);
+        }
+    };
+
+    const onApply = async (dryRun: boolean) => {
+        if (!metadata) return;
+        setLoading(true);
+        setError(null);
+        setPreview(null);
+        setMessage(null);
+        try {
+            const plan = await invoke<PlanResult>("apply_metadata", {
+                beatmapFolder,
+                metadata,
+                renameFiles,
+                dryRun,
+            });
+            if (dryRun) {
+                setPreview(plan);
+            } else {
+                setMessage(plan.message);
+                setReport(null);
+                onChanged?.();
+            }
+        } catch (err) {
+            setError(`Error: ${err}`);
+        } finally {
+            setLoading(false);
+        }
+    };
+
+    return (
+        <Card className="p-3 space-y-2">
+            <div className="flex items-center justify-between">
+                <h3 className="font-semibold text-sm">Metadata</h3>
+                <Button size="sm" icon={<FiTag />} onClick={onCheck} disabled={loading}>
+                    {loading ? "Checking..." : "Check"}
+                </Button>
+            </div>
+            {error && <p className="text-xs text-red-400">{error}</p>}
+            {message && <p className="text-xs text-green-400">{message}</p>}
+            {report && metadata && (
+                <div className="space-y-2 text-xs">
+                    {report.inconsistencies.length === 0 ? (
+                        <p className="text-[#7b7b7b]">All difficulties share the same metadata.</p>
+                    ) : (
+                        report.inconsistencies.map(({ field, values }) => (
+                            <div key={field}>
+                                <div className="font-semibold">{field}</div>
+                                {values.map(({ value, files }) => (
+                                    <div key={value} className="flex gap-2 text-[#bbbbbb]">
+                                        <span className="flex-1 truncate">{value || <em>(empty)</em>}</span>
+                                        <span className="text-[#7b7b7b]" title={files.join("\n")}>
+                                            {files.length} file{files.length !== 1 && "s"}
+                                        </span>
+                                    </div>
+                                ))}
+                            </div>
+                        ))
+                    )}
+                    {report.renames.length > 0 && (
+                        <p className="text-[#7b7b7b]">
+                            {report.renames.length} file{report.renames.length !== 1 && "s"} named differently from
+                            the metadata
+                        </p>
+                    )}
+                    <div className="grid grid-cols-2 gap-2">
+                        {FIELDS.map(({ key, label }) => (
+                            <Input
+                                key={key}
+                                placeholder={label}
+                                title={label}
+                                value={metadata[key]}
+                                onChange={(e) => setMetadata({ ...metadata, [key]: e.target.value })}
+                            />
+                        ))}
+                    </div>
+                    <div className="flex items-center justify-between">
+                        <Switch checked={renameFiles} onChange={setRenameFiles} label="Rename files" />
+                        <div className="flex gap-2">
+                            <Button size="sm" onClick={() => onApply(true)} disabled={loading}>
+                                Preview
+                            </Button>
+                            <Button size="sm" variant="primary" onClick={() => onApply(false)} disabled={loading}>
+                                Apply to all difficulties
+                            </Button>
+                        </div>
+                    </div>
+                </div>
+            )}
+            {preview && <ChangePreview plan={preview} />}
+        </Card>
+    );
+}
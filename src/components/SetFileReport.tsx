@@ -0,0 +1,140 @@
+import { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { FiSearch, FiTrash2 } from "react-icons/fi";
+
+import { Button } from "./common/Button";
+import { Card } from "./common/Card";
+
+interface UnusedFile {
+    path: string;
+    size: number;
+    skin_element: boolean;
+}
+
+interface MissingFile {
+    path: string;
+    kind: string;
+    sources: string[];
+}
+
+interface CaseMismatch {
+    referenced: string;
+    actual: string;
+    sources: string[];
+}
+
+interface SetFileReportResult {
+    unused: UnusedFile[];
+    missing: MissingFile[];
+    skin_fallback: MissingFile[];
+    case_mismatches: CaseMismatch[];
+    deleted: string[];
+    backup_id: string | null;
+}
+
+interface SetFileReportProps {
+    beatmapFolder: string;
+    onDeleted?: (message: string) => void;
+}
+
+export function SetFileReport({ beatmapFolder, onDeleted }: SetFileReportProps) {
+    const [report, setReport] = useState<SetFileReportResult | null>(null);
+    const [loading, setLoading] = useState(false);
+    const [error, setError] = useState<string | null>(null);
+
+    const analyze = async (deleteUnused: boolean) => {
+        setLoading(true);
+        setError(null);
+        try {
+            const result = await invoke<SetFileReportResult>("analyze_set_files", {
+                beatmapFolder,
+                deleteUnused,
+            });
+            setReport(result);
+            if (deleteUnused && result.deleted.length > 0) {
+                onDeleted?.(`Deleted ${result.deleted.length} unused file${result.deleted.length !== 1 ? "s" : ""}`);
+            }
+        } catch (err) {
+            setError(`Error: ${err}`);
+        } finally {
+            setLoading(false);
+        }
+    };
+
+    const deletable = report?.unused.filter((file) => !file.skin_element).length ?? 0;
+
+    return (
+        <Card className="p-3 space-y-2">
+            <div className="flex items-center justify-between">
+                <h3 className="font-semibold text-sm">Set files</h3>
+                <div className="flex gap-2">
+                    <Button size="sm" icon={<FiSearch />} onClick={() => analyze(false)} disabled={loading}>
+                        {loading ? "Checking..." : "Check"}
+                    </Button>
+                    {deletable > 0 && (
+                        <Button size="sm" icon={<FiTrash2 />} onClick={() => analyze(true)} disabled={loading}>
+                            Delete {deletable} unused
+                        </Button>
+                    )}
+                </div>
+            </div>
+
+            {error && <p className="text-xs text-red-400">{error}</p>}
+
+            {report && (
+                <div className="space-y-2 text-xs">
+                    {report.missing.length === 0 &&
+                        report.unused.length === 0 &&
+                        report.case_mismatches.length === 0 && (
+                            <p className="text-[#7b7b7b]">Every file is referenced and present.</p>
+                        )}
+
+                    {report.missing.length > 0 && (
+                        <div>
+                            <div className="font-medium text-red-400">Missing</div>
+                            {report.missing.map((file) => (
+                                <div key={file.path} className="text-[#bbbbbb]" title={file.sources.join("\n")}>
+                                    {file.path} <span className="text-[#7b7b7b]">({file.kind.replace("_", " ")})</span>
+                                </div>
+                            ))}
+                        </div>
+                    )}
+
+                    {report.skin_fallback.length > 0 && (
+                        <div>
+                            <div className="font-medium text-[#7b7b7b]">Using skin samples</div>
+                            {report.skin_fallback.map((file) => (
+                                <div key={file.path} className="text-[#7b7b7b]" title={file.sources.join("\n")}>
+                                    {file.path}
+                                </div>
+                            ))}
+                        </div>
+                    )}
+
+                    {report.case_mismatches.length > 0 && (
+                        <div>
+                            <div className="font-medium text-yellow-400">Case mismatches</div>
+                            {report.case_mismatches.map((file) => (
+                                <div key={file.referenced} className="text-[#bbbbbb]" title={file.sources.join("\n")}>
+                                    {file.referenced} <span className="text-[#7b7b7b]">on disk as</span> {file.actual}
+                                </div>
+                            ))}
+                        </div>
+                    )}
+
+                    {report.unused.length > 0 && (
+                        <div>
+                            <div className="font-medium">Unused</div>
+                            {report.unused.map((file) => (
+                                <div key={file.path} className="text-[#bbbbbb]">
+                                    {file.path}
+                                    {file.skin_element && <span className="text-[#7b7b7b]"> (skin element)</span>}
+                                </div>
+                            ))}
+                        </div>
+                    )}
+                </div>
+            )}
+        </Card>
+    );
+}
@@ -0,0 +1,99 @@
+import { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { FiBarChart2 } from "react-icons/fi";
+
+import { Button } from "./common/Button";
+import { Card } from "./common/Card";
+import { Diagnostic, DiagnosticList } from "./DiagnosticList";
+
+interface SpreadDifficulty {
+    file: string;
+    version: string;
+    mode: number;
+    star_rating: number;
+    tier: number;
+    tier_name: string;
+    drain_time: number;
+    circle_count: number;
+    slider_count: number;
+    spinner_count: number;
+    hold_count: number;
+    approach_rate: number;
+    overall_difficulty: number;
+    circle_size: number;
+    hp_drain_rate: number;
+    note_density: number;
+}
+
+interface SpreadReport {
+    difficulties: SpreadDifficulty[];
+    diagnostics: Diagnostic[];
+}
+
+// Roughly the colours osu! uses for each difficulty tier.
+const TIER_COLOURS = ["#4fc0ff", "#7cff4f", "#f6f05c", "#ff4e6f", "#c645b8", "#6563de"];
+
+function formatDrain(seconds: number): string {
+    const rounded = Math.round(seconds);
+    return `${Math.floor(rounded / 60)}:${String(rounded % 60).padStart(2, "0")}`;
+}
+
+export function SpreadChart({ beatmapFolder }: { beatmapFolder: string }) {
+    const [report, setReport] = useState<SpreadReport | null>(null);
+    const [loading, setLoading] = useState(false);
+    const [error, setError] = useState<string | null>(null);
+
+    const onAnalyze = async () => {
+        setLoading(true);
+        setError(null);
+        try {
+            setReport(await invoke<SpreadReport>("analyze_difficulty_spread", { beatmapFolder }));
+        } catch (err) {
+            setError(`Error: ${err}`);
+        } finally {
+            setLoading(false);
+        }
+    };
+
+    const maxStars = Math.max(7, ...(report?.difficulties.map((d) => d.star_rating) ?? []));
+
+    return (
+        <Card className="p-3 space-y-2">
+            <div className="flex items-center justify-between">
+                <h3 className="font-semibold text-sm">Spread</h3>
+                <Button size="sm" icon={<FiBarChart2 />} onClick={onAnalyze} disabled={loading}>
+                    {loading ? "Analyzing..." : "Analyze"}
+                </Button>
+            </div>
+            {error && <p className="text-xs text-red-400">{error}</p>}
+            {report && (
+                <>
+                    <div className="space-y-1 text-xs">
+                        {report.difficulties.map((difficulty) => (
+                            <div key={difficulty.file} className="flex items-center gap-2" title={difficulty.file}>
+                                <span className="w-32 flex-shrink-0 truncate">{difficulty.version}</span>
+                                <div className="flex-1 h-2 bg-[#2a2a2a] rounded">
+                                    <div
+                                        className="h-2 rounded"
+                                        style={{
+                                            width: `${(difficulty.star_rating / maxStars) * 100}%`,
+                                            backgroundColor: TIER_COLOURS[difficulty.tier],
+                                        }}
+                                    />
+                                </div>
+                                <span className="w-12 flex-shrink-0 text-right font-semibold">
+                                    {difficulty.star_rating.toFixed(2)}★
+                                </span>
+                                <span className="w-44 flex-shrink-0 text-[#7b7b7b]">
+                                    {difficulty.tier_name} · {formatDrain(difficulty.drain_time)} ·{" "}
+                                    {difficulty.note_density.toFixed(1)}/s · OD {difficulty.overall_difficulty}
+                                </span>
+                            </div>
+                        ))}
+                    </div>
+                    <DiagnosticList diagnostics={report.diagnostics} />
+                </>
+            )}
+        </Card>
+    );
+}
@@ -0,0 +1,38 @@
+import { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { FiFilm } from "react-icons/fi";
+
+import { Button } from "./common/Button";
+import { Card } from "./common/Card";
+import { Diagnostic, DiagnosticList } from "./DiagnosticList";
+
+export function StoryboardChecks({ beatmapFolder }: { beatmapFolder: string }) {
+    const [diagnostics, setDiagnostics] = useState<Diagnostic[] | null>(null);
+    const [loading, setLoading] = useState(false);
+    const [error, setError] = useState<string | null>(null);
+
+    const onCheck = async () => {
+        setLoading(true);
+        setError(null);
+        try {
+            setDiagnostics(await invoke<Diagnostic[]>("check_storyboard", { beatmapFolder }));
+        } catch (err) {
+            setError(`Error: ${err}`);
+        } finally {
+            setLoading(false);
+        }
+    };
+
+    return (
+        <Card className="p-3 space-y-2">
+            <div className="flex items-center justify-between">
+                <h3 className="font-semibold text-sm">Storyboard</h3>
+                <Button size="sm" icon={<FiFilm />} onClick={onCheck} disabled={loading}>
+                    {loading ? "Checking..." : "Check"}
+                </Button>
+            </div>
+            {error && <p className="text-xs text-red-400">{error}</p>}
+            {diagnostics && <DiagnosticList diagnostics={diagnostics} />}
+        </Card>
+    );
+}
@@ -6,6 +6,7 @@ import { Button } from "../components/common/Button";
 import { Card } from "../components/common/Card";
 import { Input } from "../components/common/Input";
 import { Select } from "../components/common/Select";
+import { ChangePreview, PlanResult } from "../components/ChangePreview";
 
 interface BeatmapCloneProps {
     selectedBeatmap?: {
@@ -35,6 +36,7 @@ const MODE_VALUES: Record<GameMode, number> = {
 export function BeatmapClone({ selectedBeatmap }: BeatmapCloneProps) {
     const [processing, setProcessing] = useState(false);
     const [result, setResult] = useState<{ success: boolean; message: string } | null>(null);
+    const [preview, setPreview] = useState<PlanResult | null>(null);
     const [loading, setLoading] = useState(false);
 
     const [gameMode, setGameMode] = useState<GameMode>("taiko");
@@ -53,6 +55,7 @@ export function BeatmapClone({ selectedBeatmap }: BeatmapCloneProps) {
     const [resetSampleSet, setResetSampleSet] = useState(true);
     const [resetDifficulty, setResetDifficulty] = useState(true);
     const [removeColours, setRemoveColours] = useState(true);
+    const [collection, setCollection] = useState("");
 
     useEffect(() => {
         if (!selectedBeatmap) return;
@@ -157,11 +160,12 @@ export function BeatmapClone({ selectedBeatmap }: BeatmapCloneProps) {
         }
     };
 
-    const onClone = useCallback(async () => {
+    const onClone = useCallback(async (dryRun: boolean) => {
         if (!selectedBeatmap || selectedDifficulties.size === 0) return;
 
         setProcessing(true);
         setResult(null);
+        setPreview(null);
 
         try {
             const songsFolder = localStorage.getItem("songsFolder");
@@ -169,9 +173,16 @@ export function BeatmapClone({ selectedBeatmap }: BeatmapCloneProps) {
                 throw new Error("Songs folder not found");
             }
 
-            const result = await invoke<string>("clone_beatmap", {
+            const pipeline = [
+                { name: "set_mode", params: { mode: MODE_VALUES[gameMode] } },
+                { name: keepTimingPoints ? "simplify_timing_points" : "strip_timing_points" },
+                ...(resetSampleSet ? [{ name: "reset_sample_set" }] : []),
+                ...(resetDifficulty ? [{ name: "reset_difficulty" }] : []),
+                ...(removeColours ? [{ name: "strip_colours" }] : []),
+            ];
+
+            const plan = await invoke<PlanResult>("clone_beatmap", {
                 sourceBeatmap: selectedBeatmap.folder_name,
-                gameMode: MODE_VALUES[gameMode],
                 difficulties: Array.from(selectedDifficulties),
                 metadata: {
                     title: title || selectedBeatmap.title,
@@ -182,18 +193,21 @@ export function BeatmapClone({ selectedBeatmap }: BeatmapCloneProps) {
                     source: source || "",
                     tags: tags || "",
                 },
-                keepTimingPoints,
+                pipeline,
                 removeSkinFiles,
-                resetSampleSet,
-                resetDifficulty,
-                removeColours,
                 songsFolder,
+                collection: collection.trim() || null,
+                dryRun,
             });
 
-            setResult({
-                success: true,
-                message: result,
-            });
+            if (dryRun) {
+                setPreview(plan);
+            } else {
+                setResult({
+                    success: true,
+                    message: plan.message,
+                });
+            }
         } catch (err) {
             console.error("Clone failed:", err);
             setResult({
@@ -203,7 +217,7 @@ export function BeatmapClone({ selectedBeatmap }: BeatmapCloneProps) {
         } finally {
             setProcessing(false);
         }
-    }, [selectedBeatmap, selectedDifficulties, gameMode, title, titleUnicode, artist, artistUnicode, creator, source, tags, keepTimingPoints, removeSkinFiles, resetSampleSet, resetDifficulty, removeColours]);
+    }, [selectedBeatmap, selectedDifficulties, gameMode, title, titleUnicode, artist, artistUnicode, creator, source, tags, keepTimingPoints, removeSkinFiles, resetSampleSet, resetDifficulty, removeColours, collection]);
 
     if (!selectedBeatmap) {
         return (
@@ -358,6 +372,15 @@ export function BeatmapClone({ selectedBeatmap }: BeatmapCloneProps) {
                                     className="w-full px-3 py-2 rounded-lg bg-[#101010] border border-[#2a2a2a] text-sm text-white placeholder-[#7b7b7b] focus:outline-none focus:border-[#4a4a4a] transition-colors resize-none"
                                 />
                             </div>
+
+                            <div>
+                                <label className="block text-xs text-[#7b7b7b] mb-1">Add to collection</label>
+                                <Input
+                                    value={collection}
+                                    onChange={(e) => setCollection(e.target.value)}
+                                    placeholder="Collection name (optional)"
+                                />
+                            </div>
                         </div>
                     </Card>
 
@@ -445,6 +468,8 @@ export function BeatmapClone({ selectedBeatmap }: BeatmapCloneProps) {
                         </div>
                     </Card>
 
+                    {preview && <ChangePreview plan={preview} />}
+
                     {result && (
                         <Card
                             className={`flex items-center gap-2.5 px-3 py-2.5 ${result.success
@@ -464,14 +489,22 @@ export function BeatmapClone({ selectedBeatmap }: BeatmapCloneProps) {
             </div>
 
             <div className="absolute bottom-0 left-0 right-0 p-3 pt-6">
-                <div className="max-w-5xl mx-auto">
+                <div className="max-w-5xl mx-auto flex items-center gap-3">
+                    <Button
+                        size="lg"
+                        onClick={() => onClone(true)}
+                        disabled={processing || selectedDifficulties.size === 0}
+                    >
+                        Preview
+                    </Button>
+
                     <Button
                         variant="primary"
                         size="lg"
                         icon={processing ? <FiRefreshCw className="animate-spin" /> : <FiCopy />}
-                        onClick={onClone}
+                        onClick={() => onClone(false)}
                         disabled={processing || selectedDifficulties.size === 0}
-                        className="w-full"
+                        className="flex-1"
                     >
                         {processing
                             ? "Creating..."
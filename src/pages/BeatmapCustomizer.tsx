@@ -1,11 +1,21 @@
 import { useCallback, useEffect, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { FiCheckCircle, FiAlertCircle, FiRefreshCw, FiChevronDown, FiChevronUp } from "react-icons/fi";
-import { readTextFile, writeTextFile, copyFile } from "@tauri-apps/plugin-fs";
 
 import { Button } from "../components/common/Button";
 import { Card } from "../components/common/Card";
+import { Input } from "../components/common/Input";
 import { Switch } from "../components/common/Switch";
+import { BackupList } from "../components/BackupList";
+import { LocalScores } from "../components/LocalScores";
+import { MapChecks } from "../components/MapChecks";
+import { MetadataConsistency } from "../components/MetadataConsistency";
+import { DifficultyPanel } from "../components/DifficultyPanel";
+import { ExportPanel } from "../components/ExportPanel";
+import { SetFileReport } from "../components/SetFileReport";
+import { SpreadChart } from "../components/SpreadChart";
+import { StoryboardChecks } from "../components/StoryboardChecks";
+import { ChangePreview, PlanResult } from "../components/ChangePreview";
 
 interface BeatmapCustomizerProps {
     selectedBeatmap?: {
@@ -16,117 +26,6 @@ interface BeatmapCustomizerProps {
     };
 }
 
-function rewriteCenter(osuText: string, x = 256, y = 192): string {
-    const lines = osuText.split(/\r?\n/);
-    let inHit = false;
-    for (let i = 0; i < lines.length; i++) {
-        const raw = lines[i];
-        if (/^\s*\[HitObjects\]\s*$/i.test(raw)) {
-            inHit = true;
-            continue;
-        }
-        if (/^\s*\[[A-Za-z]+\]\s*$/.test(raw)) {
-            inHit = false;
-            continue;
-        }
-        if (!inHit) continue;
-        const line = raw.trim();
-        if (line === "" || line.startsWith("//")) continue;
-        const a = line.split(",");
-        if (a.length < 5) continue;
-        a[0] = String(x);
-        a[1] = String(y);
-        lines[i] = a.join(",");
-    }
-    const eol = /\r\n/.test(osuText) ? "\r\n" : "\n";
-    return lines.join(eol);
-}
-
-function removeEditorBookmarks(osuText: string): string {
-    const lines = osuText.split(/\r?\n/);
-    let inEditor = false;
-    const out: string[] = [];
-    for (const raw of lines) {
-        if (/^\s*\[Editor\]\s*$/i.test(raw)) {
-            inEditor = true;
-            out.push(raw);
-            continue;
-        }
-        if (/^\s*\[[A-Za-z]+\]\s*$/.test(raw)) {
-            inEditor = false;
-            out.push(raw);
-            continue;
-        }
-        if (inEditor && /^\s*Bookmarks\s*:/i.test(raw)) continue;
-        out.push(raw);
-    }
-    const eol = /\r\n/.test(osuText) ? "\r\n" : "\n";
-    return out.join(eol);
-}
-
-function removeNewComboExceptFirst(osuText: string): string {
-    const lines = osuText.split(/\r?\n/);
-    let inHit = false;
-    let seenFirst = false;
-    for (let i = 0; i < lines.length; i++) {
-        const raw = lines[i];
-        if (/^\s*\[HitObjects\]\s*$/i.test(raw)) {
-            inHit = true;
-            continue;
-        }
-        if (/^\s*\[[A-Za-z]+\]\s*$/.test(raw)) {
-            inHit = false;
-            continue;
-        }
-        if (!inHit) continue;
-        const line = raw.trim();
-        if (line === "" || line.startsWith("//")) continue;
-        const a = line.split(",");
-        if (a.length < 4) continue;
-        if (!seenFirst) {
-            seenFirst = true;
-            continue;
-        }
-        if (a[3] === "5") {
-            a[3] = "1";
-            lines[i] = a.join(",");
-        }
-    }
-    const eol = /\r\n/.test(osuText) ? "\r\n" : "\n";
-    return lines.join(eol);
-}
-
-function whistleToClap_2to8(osuText: string): string {
-    const lines = osuText.split(/\r?\n/);
-    let inHit = false;
-    for (let i = 0; i < lines.length; i++) {
-        const raw = lines[i];
-        if (/^\s*\[HitObjects\]\s*$/i.test(raw)) {
-            inHit = true;
-            continue;
-        }
-        if (/^\s*\[[A-Za-z]+\]\s*$/.test(raw)) {
-            inHit = false;
-            continue;
-        }
-        if (!inHit) continue;
-        const line = raw.trim();
-        if (line === "" || line.startsWith("//")) continue;
-        const a = line.split(",");
-        if (a.length < 5) continue;
-        if (a[4] === "2") {
-            a[4] = "8";
-            lines[i] = a.join(",");
-        }
-        if (a[4] === "6") {
-            a[4] = "12";
-            lines[i] = a.join(",");
-        }
-    }
-    const eol = /\r\n/.test(osuText) ? "\r\n" : "\n";
-    return lines.join(eol);
-}
-
 function extractDifficultyName(fileName: string): string {
     const match = fileName.match(/\[(.+)\]\.osu$/);
     return match ? match[1] : fileName.replace('.osu', '');
@@ -137,6 +36,8 @@ export function BeatmapCustomizer({ selectedBeatmap }: BeatmapCustomizerProps) {
     const [selectedFiles, setSelectedFiles] = useState<Set<string>>(new Set());
     const [processing, setProcessing] = useState(false);
     const [result, setResult] = useState<{ success: boolean; message: string } | null>(null);
+    const [preview, setPreview] = useState<PlanResult | null>(null);
+    const [filesVersion, setFilesVersion] = useState(0);
     const [isDiffExpanded, setIsDiffExpanded] = useState(false);
 
     const [centerOn, setCenterOn] = useState(false);
@@ -144,6 +45,7 @@ export function BeatmapCustomizer({ selectedBeatmap }: BeatmapCustomizerProps) {
     const [rmNewCombo, setRmNewCombo] = useState(false);
     const [w2cOn, setW2cOn] = useState(false);
     const [createBackup, setCreateBackup] = useState(true);
+    const [collection, setCollection] = useState("");
 
     useEffect(() => {
         if (!selectedBeatmap) {
@@ -172,7 +74,7 @@ export function BeatmapCustomizer({ selectedBeatmap }: BeatmapCustomizerProps) {
                 setSelectedFiles(new Set());
             }
         })();
-    }, [selectedBeatmap]);
+    }, [selectedBeatmap, filesVersion]);
 
     const toggleFile = (fileName: string) => {
         setSelectedFiles(prev => {
@@ -194,56 +96,48 @@ export function BeatmapCustomizer({ selectedBeatmap }: BeatmapCustomizerProps) {
         }
     };
 
-    const onApply = useCallback(async () => {
+    const onApply = useCallback(async (dryRun: boolean) => {
         if (!selectedBeatmap || selectedFiles.size === 0) return;
 
         setProcessing(true);
         setResult(null);
+        setPreview(null);
 
         try {
             const songsFolder = localStorage.getItem("songsFolder");
             if (!songsFolder) throw new Error("Songs folder not found");
 
             const beatmapPath = `${songsFolder}\\${selectedBeatmap.folder_name}`;
-            let successCount = 0;
-
-            for (const fileName of selectedFiles) {
-                const filePath = `${beatmapPath}\\${fileName}`;
-
-                if (createBackup) {
-                    const backupPath = `${filePath}.backup`;
-                    try {
-                        await copyFile(filePath, backupPath);
-                    } catch (err) {
-                        console.warn(`Failed to create backup for ${fileName}:`, err);
-                    }
-                }
-
-                let text = await readTextFile(filePath);
 
-                if (centerOn) text = rewriteCenter(text, 256, 192);
-                if (rmBookmarks) text = removeEditorBookmarks(text);
-                if (rmNewCombo) text = removeNewComboExceptFirst(text);
-                if (w2cOn) text = whistleToClap_2to8(text);
+            const plan = await invoke<PlanResult>("customize_beatmap", {
+                beatmapFolder: beatmapPath,
+                osuFiles: Array.from(selectedFiles),
+                options: {
+                    center_objects: centerOn,
+                    remove_bookmarks: rmBookmarks,
+                    remove_new_combos: rmNewCombo,
+                    whistle_to_clap: w2cOn,
+                    create_backup: createBackup,
+                },
+                collection: collection.trim() || null,
+                dryRun,
+            });
 
-                await writeTextFile(filePath, text);
-                successCount++;
+            if (dryRun) {
+                setPreview(plan);
+            } else {
+                setResult({ success: true, message: plan.message });
             }
-
-            setResult({
-                success: true,
-                message: `Successfully processed ${successCount} file${successCount !== 1 ? 's' : ''}`,
-            });
         } catch (err) {
             console.error("Processing failed:", err);
             setResult({
                 success: false,
-                message: `Error: ${err instanceof Error ? err.message : 'Unknown error'}`,
+                message: `Error: ${err instanceof Error ? err.message : String(err)}`,
             });
         } finally {
             setProcessing(false);
         }
-    }, [selectedBeatmap, selectedFiles, centerOn, rmBookmarks, rmNewCombo, w2cOn, createBackup]);
+    }, [selectedBeatmap, selectedFiles, centerOn, rmBookmarks, rmNewCombo, w2cOn, createBackup, collection]);
 
     if (!selectedBeatmap) {
         return (
@@ -369,6 +263,64 @@ export function BeatmapCustomizer({ selectedBeatmap }: BeatmapCustomizerProps) {
                         ))}
                     </div>
 
+                    <Card className="p-3">
+                        <label className="block text-xs text-[#7b7b7b] mb-1">Add to collection</label>
+                        <Input
+                            value={collection}
+                            onChange={(e) => setCollection(e.target.value)}
+                            placeholder="Collection name (optional)"
+                        />
+                    </Card>
+
+                    {preview && <ChangePreview plan={preview} />}
+
+                    <BackupList
+                        beatmapFolder={`${localStorage.getItem("songsFolder")}\\${selectedBeatmap.folder_name}`}
+                        refreshKey={result}
+                        onRestored={(message) => setResult({ success: !message.startsWith("Error"), message })}
+                    />
+
+                    <SetFileReport
+                        beatmapFolder={`${localStorage.getItem("songsFolder")}\\${selectedBeatmap.folder_name}`}
+                        onDeleted={(message) => setResult({ success: true, message })}
+                    />
+
+                    <DifficultyPanel
+                        key={`${selectedBeatmap.folder_name}-${filesVersion}`}
+                        beatmapFolder={`${localStorage.getItem("songsFolder")}\\${selectedBeatmap.folder_name}`}
+                        osuFiles={osuFiles}
+                    />
+
+                    <SpreadChart
+                        key={selectedBeatmap.folder_name}
+                        beatmapFolder={`${localStorage.getItem("songsFolder")}\\${selectedBeatmap.folder_name}`}
+                    />
+
+                    <MapChecks
+                        key={selectedBeatmap.folder_name}
+                        beatmapFolder={`${localStorage.getItem("songsFolder")}\\${selectedBeatmap.folder_name}`}
+                    />
+
+                    <MetadataConsistency
+                        key={selectedBeatmap.folder_name}
+                        beatmapFolder={`${localStorage.getItem("songsFolder")}\\${selectedBeatmap.folder_name}`}
+                        onChanged={() => setFilesVersion((v) => v + 1)}
+                    />
+
+                    <StoryboardChecks
+                        beatmapFolder={`${localStorage.getItem("songsFolder")}\\${selectedBeatmap.folder_name}`}
+                    />
+
+                    <ExportPanel
+                        beatmapFolder={`${localStorage.getItem("songsFolder")}\\${selectedBeatmap.folder_name}`}
+                        folderName={selectedBeatmap.folder_name}
+                    />
+
+                    <LocalScores
+                        songsFolder={localStorage.getItem("songsFolder") ?? ""}
+                        folderName={selectedBeatmap.folder_name}
+                    />
+
                     {result && (
                         <Card
                             className={`flex items-center gap-2.5 px-3 py-2.5 ${result.success
@@ -396,11 +348,19 @@ export function BeatmapCustomizer({ selectedBeatmap }: BeatmapCustomizerProps) {
                         label="Create Backup"
                     />
 
+                    <Button
+                        size="lg"
+                        onClick={() => onApply(true)}
+                        disabled={!hasChanges || processing || selectedFiles.size === 0}
+                    >
+                        Preview
+                    </Button>
+
                     <Button
                         variant="primary"
                         size="lg"
                         icon={processing ? <FiRefreshCw className="animate-spin" /> : undefined}
-                        onClick={onApply}
+                        onClick={() => onApply(false)}
                         disabled={!hasChanges || processing || selectedFiles.size === 0}
                         className="flex-1"
                     >